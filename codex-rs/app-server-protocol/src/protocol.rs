@@ -102,6 +102,16 @@ client_request_definitions! {
         params: ArchiveConversationParams,
         response: ArchiveConversationResponse,
     },
+    /// Render a recorded Codex conversation as a Markdown, HTML or JSON transcript.
+    ExportConversation {
+        params: ExportConversationParams,
+        response: ExportConversationResponse,
+    },
+    /// Set the title, tags or pin state of a recorded Codex conversation.
+    UpdateConversationMetadata {
+        params: UpdateConversationMetadataParams,
+        response: UpdateConversationMetadataResponse,
+    },
     SendUserMessage {
         params: SendUserMessageParams,
         response: SendUserMessageResponse,
@@ -171,6 +181,26 @@ client_request_definitions! {
         params: ExecOneOffCommandParams,
         response: ExecOneOffCommandResponse,
     },
+    /// List custom modes visible from a conversation (or the server cwd).
+    ListModes {
+        params: ListModesParams,
+        response: ListModesResponse,
+    },
+    /// Replace the set of enabled persistent modes for a conversation.
+    SetEnabledModes {
+        params: SetEnabledModesParams,
+        response: SetEnabledModesResponse,
+    },
+    /// Set variable values for one mode of a conversation.
+    SetModeVariables {
+        params: SetModeVariablesParams,
+        response: SetModeVariablesResponse,
+    },
+    /// Return the `<user_instructions>` currently rendered for a conversation.
+    GetRenderedInstructions {
+        params: GetRenderedInstructionsParams,
+        response: GetRenderedInstructionsResponse,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
@@ -266,6 +296,13 @@ pub struct ListConversationsParams {
     /// Opaque pagination cursor returned by a previous call.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+    /// Only list conversations whose messages, commands or touched file paths
+    /// contain every whitespace-separated term (case-insensitive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Only list conversations carrying this tag (case-insensitive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -277,6 +314,14 @@ pub struct ConversationSummary {
     /// RFC3339 timestamp string for the session start, if available.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<String>,
+    /// Title set with `updateConversationMetadata` or generated from the first
+    /// message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -317,6 +362,65 @@ pub struct ArchiveConversationParams {
 #[serde(rename_all = "camelCase")]
 pub struct ArchiveConversationResponse {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    #[default]
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConversationParams {
+    pub conversation_id: ConversationId,
+    /// Defaults to Markdown.
+    #[serde(default)]
+    pub format: TranscriptFormat,
+    /// Replace API keys, tokens, passwords and private keys with `[REDACTED]`.
+    #[serde(default)]
+    pub redact_secrets: bool,
+    /// Leave out the model's reasoning summaries.
+    #[serde(default)]
+    pub omit_reasoning: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConversationResponse {
+    /// The rendered transcript.
+    pub content: String,
+}
+
+/// Fields that are left out are not changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateConversationMetadataParams {
+    pub conversation_id: ConversationId,
+    /// New title; an empty string clears it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Tags to add. A leading `#` is dropped and tags are lowercased.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<bool>,
+}
+
+/// The conversation's metadata after the update.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateConversationMetadataResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub pinned: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveConversationSubscriptionResponse {}
@@ -405,6 +509,129 @@ pub struct ExecOneOffCommandResponse {
     pub stderr: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModesParams {
+    /// When set, report enabled state and variable values for this conversation
+    /// and scan modes from its working directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<ConversationId>,
+    /// Directory to scan when no conversation is given. Defaults to the server cwd.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModesResponse {
+    pub modes: Vec<ModeInfo>,
+    /// Mode files that failed to load; the other modes are still listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<ModeDiagnostic>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ModeDiagnostic {
+    /// Id of the mode that failed to load, e.g. `/review`.
+    pub id: String,
+    pub path: PathBuf,
+    /// Stable error code, e.g. `E2001`.
+    pub code: String,
+    /// Message including the code, e.g. `E2001 Frontmatter: ...`.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ModeInfo {
+    /// Mode id, e.g. `/review` or `/team:qa`.
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    /// `persistent` or `instant`.
+    pub kind: String,
+    /// `global` or `project:<dir>`.
+    pub scope: String,
+    pub path: PathBuf,
+    pub default_enabled: bool,
+    /// Whether the mode is enabled in the requested conversation.
+    pub enabled: bool,
+    pub priority: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts_with: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    pub variables: Vec<ModeVariableInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ModeVariableInfo {
+    pub name: String,
+    /// `text`, `enum`, `boolean`, `number`, `path`, `list`, `multiline` or `secret`.
+    #[serde(rename = "type")]
+    pub var_type: String,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+    /// Explicit value set for the requested conversation, if any. Secret
+    /// values (and defaults) are reported as `••••`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SetEnabledModesParams {
+    pub conversation_id: ConversationId,
+    /// Mode ids in enable order; replaces the previously enabled set.
+    pub mode_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SetEnabledModesResponse {
+    /// Enabled mode ids in render order.
+    pub enabled_modes: Vec<String>,
+    pub user_instructions: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SetModeVariablesParams {
+    pub conversation_id: ConversationId,
+    pub mode_id: String,
+    /// Variable values by name; `null` clears the explicit value so the default applies.
+    pub variables: HashMap<String, Option<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SetModeVariablesResponse {
+    pub user_instructions: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRenderedInstructionsParams {
+    pub conversation_id: ConversationId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRenderedInstructionsResponse {
+    /// Enabled mode ids in render order.
+    pub enabled_modes: Vec<String>,
+    pub user_instructions: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct GetAuthStatusResponse {
@@ -525,6 +752,8 @@ pub struct SandboxSettings {
     pub writable_roots: Vec<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network_access: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_allowlist: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude_tmpdir_env_var: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -697,6 +926,9 @@ pub struct ExecCommandApprovalParams {
     pub cwd: PathBuf,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// Exec policy rule that matched the command, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -773,6 +1005,18 @@ pub struct SessionConfiguredNotification {
     pub rollout_path: PathBuf,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ModesChangedNotification {
+    /// Conversation whose modes are affected.
+    pub conversation_id: ConversationId,
+    /// Working directory whose mode files (or `$CODEX_HOME/modes`) changed.
+    pub cwd: PathBuf,
+    /// Mode files that no longer load after the change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<ModeDiagnostic>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthStatusChangeNotification {
@@ -794,6 +1038,9 @@ pub enum ServerNotification {
 
     /// The special session configured event for a new or resumed conversation.
     SessionConfigured(SessionConfiguredNotification),
+
+    /// Mode definition files changed on disk; clients should call `listModes` again.
+    ModesChanged(ModesChangedNotification),
 }
 
 impl ServerNotification {
@@ -802,6 +1049,7 @@ impl ServerNotification {
             ServerNotification::AuthStatusChange(params) => serde_json::to_value(params),
             ServerNotification::LoginChatGptComplete(params) => serde_json::to_value(params),
             ServerNotification::SessionConfigured(params) => serde_json::to_value(params),
+            ServerNotification::ModesChanged(params) => serde_json::to_value(params),
         }
     }
 }
@@ -904,6 +1152,7 @@ mod tests {
             command: vec!["echo".to_string(), "hello".to_string()],
             cwd: PathBuf::from("/tmp"),
             reason: Some("because tests".to_string()),
+            matched_rule: None,
         };
         let request = ServerRequest::ExecCommandApproval {
             request_id: RequestId::Integer(7),
@@ -929,4 +1178,30 @@ mod tests {
         assert_eq!(payload.request_with_id(RequestId::Integer(7)), request);
         Ok(())
     }
+
+    #[test]
+    fn serialize_set_mode_variables() -> Result<()> {
+        let conversation_id = ConversationId::from_string("67e55044-10b1-426f-9247-bb680e5fe0c8")?;
+        let request = ClientRequest::SetModeVariables {
+            request_id: RequestId::Integer(3),
+            params: SetModeVariablesParams {
+                conversation_id,
+                mode_id: "/review".to_string(),
+                variables: HashMap::from([("ticket".to_string(), None)]),
+            },
+        };
+        assert_eq!(
+            json!({
+                "method": "setModeVariables",
+                "id": 3,
+                "params": {
+                    "conversationId": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                    "modeId": "/review",
+                    "variables": { "ticket": null },
+                }
+            }),
+            serde_json::to_value(&request)?,
+        );
+        Ok(())
+    }
 }
@@ -1,6 +1,10 @@
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::fuzzy_file_search::run_fuzzy_file_search;
+use crate::modes::ConversationModes;
+use crate::modes::PreparedModes;
+use crate::modes::mode_diagnostics;
+use crate::modes::mode_infos;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotification;
 use codex_app_server_protocol::AddConversationListenerParams;
@@ -16,8 +20,12 @@ use codex_app_server_protocol::ExecCommandApprovalParams;
 use codex_app_server_protocol::ExecCommandApprovalResponse;
 use codex_app_server_protocol::ExecOneOffCommandParams;
 use codex_app_server_protocol::ExecOneOffCommandResponse;
+use codex_app_server_protocol::ExportConversationParams;
+use codex_app_server_protocol::ExportConversationResponse;
 use codex_app_server_protocol::FuzzyFileSearchParams;
 use codex_app_server_protocol::FuzzyFileSearchResponse;
+use codex_app_server_protocol::GetRenderedInstructionsParams;
+use codex_app_server_protocol::GetRenderedInstructionsResponse;
 use codex_app_server_protocol::GetUserAgentResponse;
 use codex_app_server_protocol::GetUserSavedConfigResponse;
 use codex_app_server_protocol::GitDiffToRemoteResponse;
@@ -27,6 +35,8 @@ use codex_app_server_protocol::InterruptConversationResponse;
 use codex_app_server_protocol::JSONRPCErrorError;
 use codex_app_server_protocol::ListConversationsParams;
 use codex_app_server_protocol::ListConversationsResponse;
+use codex_app_server_protocol::ListModesParams;
+use codex_app_server_protocol::ListModesResponse;
 use codex_app_server_protocol::LoginApiKeyParams;
 use codex_app_server_protocol::LoginApiKeyResponse;
 use codex_app_server_protocol::LoginChatGptCompleteNotification;
@@ -47,12 +57,21 @@ use codex_app_server_protocol::ServerRequestPayload;
 use codex_app_server_protocol::SessionConfiguredNotification;
 use codex_app_server_protocol::SetDefaultModelParams;
 use codex_app_server_protocol::SetDefaultModelResponse;
+use codex_app_server_protocol::SetEnabledModesParams;
+use codex_app_server_protocol::SetEnabledModesResponse;
+use codex_app_server_protocol::SetModeVariablesParams;
+use codex_app_server_protocol::SetModeVariablesResponse;
+use codex_app_server_protocol::TranscriptFormat;
+use codex_app_server_protocol::UpdateConversationMetadataParams;
+use codex_app_server_protocol::UpdateConversationMetadataResponse;
 use codex_app_server_protocol::UserInfoResponse;
 use codex_app_server_protocol::UserSavedConfig;
 use codex_core::AuthManager;
 use codex_core::CodexConversation;
 use codex_core::ConversationManager;
 use codex_core::Cursor as RolloutCursor;
+use codex_core::ExportFormat;
+use codex_core::ExportOptions;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::NewConversation;
 use codex_core::RolloutRecorder;
@@ -71,15 +90,21 @@ use codex_core::config_edit::persist_overrides_and_clear_if_none;
 use codex_core::default_client::get_codex_user_agent;
 use codex_core::exec::ExecParams;
 use codex_core::exec_env::create_env;
+use codex_core::export_session;
+use codex_core::find_conversation_path_by_id_str;
 use codex_core::get_platform_sandbox;
 use codex_core::git_info::git_diff_to_remote;
+use codex_core::load_session_metadata;
+use codex_core::normalize_tag;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem as CoreInputItem;
+use codex_core::protocol::ModeStateItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
+use codex_core::update_session_metadata;
 use codex_login::ServerOptions as LoginServerOptions;
 use codex_login::ShutdownHandle;
 use codex_login::run_login_server;
@@ -130,6 +155,8 @@ pub(crate) struct CodexMessageProcessor {
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
     pending_fuzzy_searches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    // Enabled modes and variable values per conversation.
+    conversation_modes: HashMap<ConversationId, ConversationModes>,
 }
 
 impl CodexMessageProcessor {
@@ -150,6 +177,7 @@ impl CodexMessageProcessor {
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
+            conversation_modes: HashMap::new(),
         }
     }
 
@@ -173,6 +201,12 @@ impl CodexMessageProcessor {
             ClientRequest::ArchiveConversation { request_id, params } => {
                 self.archive_conversation(request_id, params).await;
             }
+            ClientRequest::ExportConversation { request_id, params } => {
+                self.export_conversation(request_id, params).await;
+            }
+            ClientRequest::UpdateConversationMetadata { request_id, params } => {
+                self.update_conversation_metadata(request_id, params).await;
+            }
             ClientRequest::SendUserMessage { request_id, params } => {
                 self.send_user_message(request_id, params).await;
             }
@@ -239,6 +273,18 @@ impl CodexMessageProcessor {
             ClientRequest::ExecOneOffCommand { request_id, params } => {
                 self.exec_one_off_command(request_id, params).await;
             }
+            ClientRequest::ListModes { request_id, params } => {
+                self.list_modes(request_id, params).await;
+            }
+            ClientRequest::SetEnabledModes { request_id, params } => {
+                self.set_enabled_modes(request_id, params).await;
+            }
+            ClientRequest::SetModeVariables { request_id, params } => {
+                self.set_mode_variables(request_id, params).await;
+            }
+            ClientRequest::GetRenderedInstructions { request_id, params } => {
+                self.get_rendered_instructions(request_id, params).await;
+            }
         }
     }
 
@@ -620,6 +666,7 @@ impl CodexMessageProcessor {
         let outgoing = self.outgoing.clone();
         let req_id = request_id;
         let sandbox_cwd = self.config.cwd.clone();
+        let resource_limits = self.config.resource_limits;
 
         tokio::spawn(async move {
             match codex_core::exec::process_exec_tool_call(
@@ -628,6 +675,7 @@ impl CodexMessageProcessor {
                 &effective_policy,
                 sandbox_cwd.as_path(),
                 &codex_linux_sandbox_exe,
+                &resource_limits,
                 None,
             )
             .await
@@ -652,7 +700,214 @@ impl CodexMessageProcessor {
         });
     }
 
-    async fn process_new_conversation(&self, request_id: RequestId, params: NewConversationParams) {
+    fn track_conversation_modes(
+        &mut self,
+        conversation_id: ConversationId,
+        mut modes: ConversationModes,
+    ) {
+        modes.watch(conversation_id, self.outgoing.clone());
+        self.conversation_modes.insert(conversation_id, modes);
+    }
+
+    async fn list_modes(&mut self, request_id: RequestId, params: ListModesParams) {
+        let ListModesParams {
+            conversation_id,
+            cwd,
+        } = params;
+        let state = match conversation_id {
+            Some(id) => match self.conversation_modes.get(&id) {
+                Some(state) => Some(state),
+                None => {
+                    self.send_conversation_not_found(request_id, id).await;
+                    return;
+                }
+            },
+            None => None,
+        };
+        let cwd = match (state, cwd) {
+            (Some(state), _) => state.cwd().to_path_buf(),
+            (None, Some(cwd)) => cwd,
+            (None, None) => self.config.cwd.clone(),
+        };
+        let scan = codex_modes::scan_modes(&cwd, Some(&self.config.codex_home));
+        let response = ListModesResponse {
+            modes: mode_infos(&scan.defs, state),
+            diagnostics: mode_diagnostics(&scan),
+        };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    async fn set_enabled_modes(&mut self, request_id: RequestId, params: SetEnabledModesParams) {
+        let SetEnabledModesParams {
+            conversation_id,
+            mode_ids,
+        } = params;
+        let prepared = match self.conversation_modes.get(&conversation_id) {
+            Some(state) => state.prepare_enabled(&state.scan(), mode_ids),
+            None => {
+                self.send_conversation_not_found(request_id, conversation_id)
+                    .await;
+                return;
+            }
+        };
+        match prepared {
+            Ok(prepared) => {
+                let response = SetEnabledModesResponse {
+                    enabled_modes: prepared.enabled_modes.clone(),
+                    user_instructions: prepared.user_instructions.clone(),
+                };
+                if self.apply_modes(conversation_id, prepared).await {
+                    self.outgoing.send_response(request_id, response).await;
+                } else {
+                    self.send_conversation_not_found(request_id, conversation_id)
+                        .await;
+                }
+            }
+            Err(errors) => self.send_modes_errors(request_id, errors).await,
+        }
+    }
+
+    async fn set_mode_variables(&mut self, request_id: RequestId, params: SetModeVariablesParams) {
+        let SetModeVariablesParams {
+            conversation_id,
+            mode_id,
+            variables,
+        } = params;
+        let (prepared, enabled) = match self.conversation_modes.get(&conversation_id) {
+            Some(state) => (
+                state.prepare_variables(&state.scan(), &mode_id, variables),
+                state.is_enabled(&mode_id),
+            ),
+            None => {
+                self.send_conversation_not_found(request_id, conversation_id)
+                    .await;
+                return;
+            }
+        };
+        let prepared = match prepared {
+            Ok(prepared) => prepared,
+            Err(errors) => {
+                self.send_modes_errors(request_id, errors).await;
+                return;
+            }
+        };
+        if enabled {
+            let user_instructions = prepared.user_instructions.clone();
+            if self.apply_modes(conversation_id, prepared).await {
+                self.outgoing
+                    .send_response(request_id, SetModeVariablesResponse { user_instructions })
+                    .await;
+            } else {
+                self.send_conversation_not_found(request_id, conversation_id)
+                    .await;
+            }
+            return;
+        }
+        let Some(state) = self.conversation_modes.get_mut(&conversation_id) else {
+            return;
+        };
+        state.store_variables(prepared);
+        let user_instructions = state.rendered();
+        self.outgoing
+            .send_response(request_id, SetModeVariablesResponse { user_instructions })
+            .await;
+    }
+
+    async fn get_rendered_instructions(
+        &self,
+        request_id: RequestId,
+        params: GetRenderedInstructionsParams,
+    ) {
+        let GetRenderedInstructionsParams { conversation_id } = params;
+        let Some(state) = self.conversation_modes.get(&conversation_id) else {
+            self.send_conversation_not_found(request_id, conversation_id)
+                .await;
+            return;
+        };
+        let response = GetRenderedInstructionsResponse {
+            enabled_modes: state.enabled_modes().to_vec(),
+            user_instructions: state.rendered(),
+        };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    /// Commit `prepared` for the conversation and submit the override; false if it is gone.
+    async fn apply_modes(
+        &mut self,
+        conversation_id: ConversationId,
+        prepared: PreparedModes,
+    ) -> bool {
+        let Ok(conversation) = self
+            .conversation_manager
+            .get_conversation(conversation_id)
+            .await
+        else {
+            return false;
+        };
+        let Some(state) = self.conversation_modes.get_mut(&conversation_id) else {
+            return false;
+        };
+        let mode_state = prepared.mode_state.clone();
+        let op = state.apply(prepared);
+        if let Err(err) = conversation.submit(op).await {
+            warn!("failed to apply modes to conversation {conversation_id}: {err}");
+        }
+        if let Err(err) = conversation
+            .submit(Op::RecordModeState { mode_state })
+            .await
+        {
+            warn!("failed to record modes for conversation {conversation_id}: {err}");
+        }
+        true
+    }
+
+    /// Re-apply the modes recorded in a resumed rollout. Failures are logged
+    /// and leave the conversation without modes rather than failing the resume.
+    async fn restore_modes(&mut self, conversation_id: ConversationId, mode_state: ModeStateItem) {
+        let Some(state) = self.conversation_modes.get(&conversation_id) else {
+            return;
+        };
+        match state.prepare_restore(&state.scan().defs, mode_state) {
+            Ok(prepared) => {
+                self.apply_modes(conversation_id, prepared).await;
+            }
+            Err(errors) => {
+                warn!(
+                    "failed to restore modes for conversation {conversation_id}: {}",
+                    errors.join("; ")
+                );
+            }
+        }
+    }
+
+    async fn send_conversation_not_found(
+        &self,
+        request_id: RequestId,
+        conversation_id: ConversationId,
+    ) {
+        let error = JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message: format!("conversation not found: {conversation_id}"),
+            data: None,
+        };
+        self.outgoing.send_error(request_id, error).await;
+    }
+
+    /// Mode errors carry their stable codes (e.g. `E3101 RequiredMissing: ...`) in `data.errors`.
+    async fn send_modes_errors(&self, request_id: RequestId, errors: Vec<String>) {
+        let error = JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message: errors.join("; "),
+            data: Some(serde_json::json!({ "errors": errors })),
+        };
+        self.outgoing.send_error(request_id, error).await;
+    }
+
+    async fn process_new_conversation(
+        &mut self,
+        request_id: RequestId,
+        params: NewConversationParams,
+    ) {
         let config =
             match derive_config_from_params(params, self.codex_linux_sandbox_exe.clone()).await {
                 Ok(config) => config,
@@ -667,6 +922,7 @@ impl CodexMessageProcessor {
                 }
             };
 
+        let modes = ConversationModes::from_config(&config).await;
         match self.conversation_manager.new_conversation(config).await {
             Ok(conversation_id) => {
                 let NewConversation {
@@ -674,6 +930,7 @@ impl CodexMessageProcessor {
                     session_configured,
                     ..
                 } = conversation_id;
+                self.track_conversation_modes(conversation_id, modes);
                 let response = NewConversationResponse {
                     conversation_id,
                     model: session_configured.model,
@@ -706,14 +963,44 @@ impl CodexMessageProcessor {
         };
         let cursor_ref = cursor_obj.as_ref();
 
-        let page = match RolloutRecorder::list_conversations(
-            &self.config.codex_home,
-            page_size,
-            cursor_ref,
-            INTERACTIVE_SESSION_SOURCES,
-        )
-        .await
-        {
+        let tag = params
+            .tag
+            .as_deref()
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty());
+        let page = match (params.search.as_deref().map(str::trim), tag) {
+            (Some(query), tag) if !query.is_empty() => {
+                RolloutRecorder::search_conversations(
+                    &self.config.codex_home,
+                    query,
+                    tag,
+                    page_size,
+                    cursor_ref,
+                    INTERACTIVE_SESSION_SOURCES,
+                )
+                .await
+            }
+            (_, Some(tag)) => {
+                RolloutRecorder::list_tagged_conversations(
+                    &self.config.codex_home,
+                    tag,
+                    page_size,
+                    cursor_ref,
+                    INTERACTIVE_SESSION_SOURCES,
+                )
+                .await
+            }
+            _ => {
+                RolloutRecorder::list_conversations(
+                    &self.config.codex_home,
+                    page_size,
+                    cursor_ref,
+                    INTERACTIVE_SESSION_SOURCES,
+                )
+                .await
+            }
+        };
+        let page = match page {
             Ok(p) => p,
             Err(err) => {
                 let error = JSONRPCErrorError {
@@ -726,10 +1013,26 @@ impl CodexMessageProcessor {
             }
         };
 
+        // Metadata only decorates the summaries, so a broken file should not
+        // prevent listing.
+        let metadata = load_session_metadata(&self.config.codex_home)
+            .await
+            .unwrap_or_else(|err| {
+                warn!("failed to load session metadata: {err}");
+                HashMap::new()
+            });
         let items = page
             .items
             .into_iter()
             .filter_map(|it| extract_conversation_summary(it.path, &it.head))
+            .map(|mut summary| {
+                if let Some(metadata) = metadata.get(&summary.conversation_id) {
+                    summary.title = metadata.title.clone();
+                    summary.tags = metadata.tags.clone();
+                    summary.pinned = metadata.pinned;
+                }
+                summary
+            })
             .collect();
 
         // Encode next_cursor as a plain string
@@ -746,7 +1049,7 @@ impl CodexMessageProcessor {
     }
 
     async fn handle_resume_conversation(
-        &self,
+        &mut self,
         request_id: RequestId,
         params: ResumeConversationParams,
     ) {
@@ -770,6 +1073,7 @@ impl CodexMessageProcessor {
             }
         };
 
+        let modes = ConversationModes::from_config(&config).await;
         match self
             .conversation_manager
             .resume_conversation_from_rollout(
@@ -784,6 +1088,14 @@ impl CodexMessageProcessor {
                 session_configured,
                 ..
             }) => {
+                self.track_conversation_modes(conversation_id, modes);
+                if let Some(mode_state) = session_configured
+                    .mode_state
+                    .clone()
+                    .filter(|state| !state.enabled.is_empty())
+                {
+                    self.restore_modes(conversation_id, mode_state).await;
+                }
                 self.outgoing
                     .send_server_notification(ServerNotification::SessionConfigured(
                         SessionConfiguredNotification {
@@ -829,7 +1141,160 @@ impl CodexMessageProcessor {
         }
     }
 
-    async fn archive_conversation(&self, request_id: RequestId, params: ArchiveConversationParams) {
+    async fn export_conversation(&self, request_id: RequestId, params: ExportConversationParams) {
+        let ExportConversationParams {
+            conversation_id,
+            format,
+            redact_secrets,
+            omit_reasoning,
+        } = params;
+
+        let path = match find_conversation_path_by_id_str(
+            &self.config.codex_home,
+            &conversation_id.to_string(),
+        )
+        .await
+        {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                let error = JSONRPCErrorError {
+                    code: INVALID_REQUEST_ERROR_CODE,
+                    message: format!("no rollout found for conversation id {conversation_id}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to locate conversation id {conversation_id}: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let options = ExportOptions {
+            format: match format {
+                TranscriptFormat::Markdown => ExportFormat::Markdown,
+                TranscriptFormat::Html => ExportFormat::Html,
+                TranscriptFormat::Json => ExportFormat::Json,
+            },
+            redact_secrets,
+            include_reasoning: !omit_reasoning,
+        };
+        match export_session(&path, &options).await {
+            Ok(content) => {
+                self.outgoing
+                    .send_response(request_id, ExportConversationResponse { content })
+                    .await;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to export conversation: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    async fn update_conversation_metadata(
+        &self,
+        request_id: RequestId,
+        params: UpdateConversationMetadataParams,
+    ) {
+        let UpdateConversationMetadataParams {
+            conversation_id,
+            title,
+            add_tags,
+            remove_tags,
+            pinned,
+        } = params;
+
+        if let Some(tag) = add_tags.iter().find(|tag| normalize_tag(tag).is_none()) {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!(
+                    "invalid tag `{tag}`: tags must be non-empty and contain no whitespace"
+                ),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        }
+
+        match find_conversation_path_by_id_str(
+            &self.config.codex_home,
+            &conversation_id.to_string(),
+        )
+        .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                let error = JSONRPCErrorError {
+                    code: INVALID_REQUEST_ERROR_CODE,
+                    message: format!("no rollout found for conversation id {conversation_id}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to locate conversation id {conversation_id}: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        }
+
+        let result =
+            update_session_metadata(&self.config.codex_home, conversation_id, |metadata| {
+                if let Some(title) = &title {
+                    metadata.set_title(title);
+                }
+                for tag in &add_tags {
+                    metadata.add_tag(tag);
+                }
+                for tag in &remove_tags {
+                    metadata.remove_tag(tag);
+                }
+                if let Some(pinned) = pinned {
+                    metadata.pinned = pinned;
+                }
+            })
+            .await;
+        match result {
+            Ok(metadata) => {
+                let response = UpdateConversationMetadataResponse {
+                    title: metadata.title,
+                    tags: metadata.tags,
+                    pinned: metadata.pinned,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to update conversation metadata: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    async fn archive_conversation(
+        &mut self,
+        request_id: RequestId,
+        params: ArchiveConversationParams,
+    ) {
         let ArchiveConversationParams {
             conversation_id,
             rollout_path,
@@ -870,9 +1335,10 @@ impl CodexMessageProcessor {
             return;
         };
 
-        if !file_name
-            .to_string_lossy()
-            .ends_with(required_suffix.as_str())
+        // Rollouts compressed by `codex sessions gc` end in `.jsonl.gz`.
+        let name = file_name.to_string_lossy();
+        if !name.ends_with(required_suffix.as_str())
+            && !name.ends_with(&format!("{required_suffix}.gz"))
         {
             let error = JSONRPCErrorError {
                 code: INVALID_REQUEST_ERROR_CODE,
@@ -886,6 +1352,8 @@ impl CodexMessageProcessor {
             return;
         }
 
+        // dropping the mode state also stops its `modesChanged` watcher
+        self.conversation_modes.remove(&conversation_id);
         let removed_conversation = self
             .conversation_manager
             .remove_conversation(&conversation_id)
@@ -1284,6 +1752,7 @@ async fn apply_bespoke_event_handling(
             command,
             cwd,
             reason,
+            matched_rule,
         }) => {
             let params = ExecCommandApprovalParams {
                 conversation_id,
@@ -1291,6 +1760,7 @@ async fn apply_bespoke_event_handling(
                 command,
                 cwd,
                 reason,
+                matched_rule,
             };
             let rx = outgoing
                 .send_request(ServerRequestPayload::ExecCommandApproval(params))
@@ -1483,6 +1953,9 @@ fn extract_conversation_summary(
         timestamp,
         path,
         preview: preview.to_string(),
+        title: None,
+        tags: Vec::new(),
+        pinned: false,
     })
 }
 
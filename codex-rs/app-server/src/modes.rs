@@ -0,0 +1,463 @@
+//! Custom modes for app-server clients. Each conversation keeps its own
+//! enabled set and variable values; changes are rendered into
+//! `<user_instructions>` and applied through `Op::OverrideTurnContext`.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_app_server_protocol::ModeDiagnostic;
+use codex_app_server_protocol::ModeInfo;
+use codex_app_server_protocol::ModeVariableInfo;
+use codex_app_server_protocol::ModesChangedNotification;
+use codex_app_server_protocol::ServerNotification;
+use codex_core::config::Config;
+use codex_core::project_doc::get_user_instructions;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ModeStateItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol_config_types::ReasoningEffort;
+use codex_core::protocol_config_types::ToolFilter;
+use codex_modes::EnabledMode;
+use codex_modes::IndexMap;
+use codex_modes::ModeDefinition;
+use codex_modes::ModeKind;
+use codex_modes::ModeOverrides;
+use codex_modes::ModeScope;
+use codex_modes::ModesScan;
+use codex_modes::VarType;
+use codex_protocol::ConversationId;
+use tracing::warn;
+
+use crate::outgoing_message::OutgoingMessageSender;
+
+/// Mode state of one conversation.
+pub(crate) struct ConversationModes {
+    cwd: PathBuf,
+    codex_home: PathBuf,
+    /// `<user_instructions>` content without any `<mode_instructions>`.
+    base_user_instructions: String,
+    /// Session settings that mode `overrides` fall back to when reverted.
+    model: String,
+    effort: Option<ReasoningEffort>,
+    sandbox_policy: SandboxPolicy,
+    approval_policy: AskForApproval,
+    tool_filter: ToolFilter,
+    enable_order: Vec<String>,
+    var_values: HashMap<String, IndexMap<String, Option<String>>>,
+    applied_overrides: ModeOverrides,
+    rendered: Option<String>,
+    /// Sends `modesChanged` for `cwd`; stopped when the conversation is removed.
+    watcher: Option<ModesWatcher>,
+}
+
+/// Result of rendering a candidate enabled set; applied only when valid.
+pub(crate) struct PreparedModes {
+    enable_order: Vec<String>,
+    var_values: HashMap<String, IndexMap<String, Option<String>>>,
+    pub(crate) enabled_modes: Vec<String>,
+    pub(crate) user_instructions: String,
+    overrides: ModeOverrides,
+    /// Snapshot recorded in the rollout so resume/fork can restore the modes.
+    /// Secret variables are left out and have to be supplied again.
+    pub(crate) mode_state: ModeStateItem,
+}
+
+impl ConversationModes {
+    pub(crate) async fn from_config(config: &Config) -> Self {
+        Self {
+            cwd: config.cwd.clone(),
+            codex_home: config.codex_home.clone(),
+            base_user_instructions: get_user_instructions(config).await.unwrap_or_default(),
+            model: config.model.clone(),
+            effort: config.model_reasoning_effort,
+            sandbox_policy: config.sandbox_policy.clone(),
+            approval_policy: config.approval_policy,
+            tool_filter: config.tool_filter.clone(),
+            enable_order: Vec::new(),
+            var_values: HashMap::new(),
+            applied_overrides: ModeOverrides::default(),
+            rendered: None,
+            watcher: None,
+        }
+    }
+
+    /// Start sending `modesChanged` for this conversation's mode directories.
+    pub(crate) fn watch(
+        &mut self,
+        conversation_id: ConversationId,
+        outgoing: Arc<OutgoingMessageSender>,
+    ) {
+        self.watcher = Some(spawn_modes_watcher(
+            conversation_id,
+            self.cwd.clone(),
+            self.codex_home.clone(),
+            outgoing,
+        ));
+    }
+
+    pub(crate) fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    pub(crate) fn scan(&self) -> ModesScan {
+        codex_modes::scan_modes(&self.cwd, Some(&self.codex_home))
+    }
+
+    pub(crate) fn enabled_modes(&self) -> &[String] {
+        &self.enable_order
+    }
+
+    /// The `<user_instructions>` last applied, or the mode-free baseline.
+    pub(crate) fn rendered(&self) -> String {
+        self.rendered.clone().unwrap_or_else(|| {
+            codex_modes::render_user_instructions(&self.base_user_instructions, &[], &[])
+                .unwrap_or_default()
+        })
+    }
+
+    /// Validate and render `enable_order` with the current variable values.
+    pub(crate) fn prepare_enabled(
+        &self,
+        scan: &ModesScan,
+        mode_ids: Vec<String>,
+    ) -> Result<PreparedModes, Vec<String>> {
+        let defs = &scan.defs;
+        let mut enable_order: Vec<String> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+        for id in mode_ids {
+            match defs.iter().find(|d| d.id == id) {
+                Some(def) if def.kind == ModeKind::Persistent => {
+                    if !enable_order.contains(&id) {
+                        enable_order.push(id);
+                    }
+                }
+                Some(_) => errors.push(format!("{id} is not a persistent mode")),
+                None => errors.push(codex_modes::format_modes_error(&scan.missing(&id))),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.prepare(defs, enable_order, self.var_values.clone())
+    }
+
+    /// Validate and merge `values` into `mode_id`'s variables, re-rendering if it is enabled.
+    pub(crate) fn prepare_variables(
+        &self,
+        scan: &ModesScan,
+        mode_id: &str,
+        values: HashMap<String, Option<String>>,
+    ) -> Result<PreparedModes, Vec<String>> {
+        let defs = &scan.defs;
+        let Some(def) = defs.iter().find(|d| d.id == mode_id) else {
+            return Err(vec![codex_modes::format_modes_error(
+                &scan.missing(mode_id),
+            )]);
+        };
+        let mut errors: Vec<String> = Vec::new();
+        let mut var_values = self.var_values.clone();
+        let entry = var_values.entry(def.id.clone()).or_default();
+        // apply in declaration order so the stored map stays ordered
+        for v in &def.variables {
+            let Some(value) = values.get(&v.name) else {
+                continue;
+            };
+            if let Some(val) = value.as_deref()
+                && let Some(err) = codex_modes::validate_var_value(&def.id, v, val)
+                && let Some(msg) = codex_modes::format_validation_error(&err)
+            {
+                errors.push(msg);
+            }
+            entry.insert(v.name.clone(), value.clone());
+        }
+        for name in values.keys() {
+            if !def.variables.iter().any(|v| &v.name == name) {
+                errors.push(format!("unknown variable {mode_id}/{name}"));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.prepare(defs, self.enable_order.clone(), var_values)
+    }
+
+    fn prepare(
+        &self,
+        defs: &[ModeDefinition],
+        mut enable_order: Vec<String>,
+        var_values: HashMap<String, IndexMap<String, Option<String>>>,
+    ) -> Result<PreparedModes, Vec<String>> {
+        // drop modes that disappeared from disk since they were enabled
+        enable_order.retain(|id| defs.iter().any(|d| &d.id == id));
+        let mut enabled: Vec<EnabledMode> = enable_order
+            .iter()
+            .filter_map(|id| defs.iter().find(|d| &d.id == id))
+            .map(|def| {
+                let values = var_values.get(&def.id);
+                EnabledMode {
+                    id: &def.id,
+                    display_name: def.display_name.as_deref(),
+                    scope: &def.scope,
+                    variables: def
+                        .variables
+                        .iter()
+                        .map(|v| {
+                            let val = values.and_then(|m| m.get(&v.name).cloned()).flatten();
+                            (v.name.as_str(), val)
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        let errors =
+            codex_modes::format_validation_errors(&codex_modes::validate_enabled(defs, &enabled));
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        codex_modes::sort_by_priority(defs, &mut enabled);
+        let user_instructions =
+            codex_modes::render_user_instructions(&self.base_user_instructions, &enabled, defs)
+                .map_err(|e| vec![codex_modes::format_modes_error(&e)])?;
+        let enabled_modes = enabled.iter().map(|e| e.id.to_string()).collect();
+        let overrides = codex_modes::resolve_overrides(defs, &enable_order).overrides;
+        let mode_state = ModeStateItem {
+            enabled: enable_order.clone(),
+            variables: var_values
+                .iter()
+                .map(|(id, vars)| {
+                    let vars: BTreeMap<String, Option<String>> = vars
+                        .iter()
+                        .filter(|(k, _)| codex_modes::is_recordable_var(defs, id, k))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect();
+                    (id.clone(), vars)
+                })
+                .collect(),
+        };
+        Ok(PreparedModes {
+            enable_order,
+            var_values,
+            enabled_modes,
+            user_instructions,
+            overrides,
+            mode_state,
+        })
+    }
+
+    /// Commit `prepared` and return the op that applies it to the session.
+    pub(crate) fn apply(&mut self, prepared: PreparedModes) -> Op {
+        let PreparedModes {
+            enable_order,
+            var_values,
+            user_instructions,
+            overrides,
+            ..
+        } = prepared;
+        let prev = std::mem::replace(&mut self.applied_overrides, overrides.clone());
+        let op = Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: (prev.approval_policy != overrides.approval_policy).then(|| {
+                overrides
+                    .approval_policy
+                    .as_deref()
+                    .and_then(codex_modes::parse_approval)
+                    .unwrap_or(self.approval_policy)
+            }),
+            sandbox_policy: (prev.sandbox != overrides.sandbox).then(|| {
+                overrides
+                    .sandbox
+                    .as_deref()
+                    .and_then(|s| codex_modes::parse_sandbox(s, &self.sandbox_policy))
+                    .unwrap_or_else(|| self.sandbox_policy.clone())
+            }),
+            model: (prev.model != overrides.model).then(|| {
+                overrides
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| self.model.clone())
+            }),
+            effort: (prev.effort != overrides.effort).then(|| match overrides.effort.as_deref() {
+                Some(e) => codex_modes::parse_effort(e).or(self.effort),
+                None => self.effort,
+            }),
+            summary: None,
+            user_instructions: Some(user_instructions.clone()),
+            tool_filter: (prev.tools != overrides.tools).then(|| {
+                overrides
+                    .tools
+                    .clone()
+                    .unwrap_or_else(|| self.tool_filter.clone())
+            }),
+        };
+        self.enable_order = enable_order;
+        self.var_values = var_values;
+        self.rendered = Some(user_instructions);
+        op
+    }
+
+    /// Store variable values for a mode that is not enabled; nothing to send.
+    pub(crate) fn store_variables(&mut self, prepared: PreparedModes) {
+        self.var_values = prepared.var_values;
+    }
+
+    pub(crate) fn is_enabled(&self, mode_id: &str) -> bool {
+        self.enable_order.iter().any(|id| id == mode_id)
+    }
+
+    /// Re-render the modes recorded in a resumed rollout against the current
+    /// definitions. Modes that no longer exist are dropped.
+    pub(crate) fn prepare_restore(
+        &self,
+        defs: &[ModeDefinition],
+        mode_state: ModeStateItem,
+    ) -> Result<PreparedModes, Vec<String>> {
+        let var_values = mode_state
+            .variables
+            .into_iter()
+            .map(|(id, vars)| (id, vars.into_iter().collect()))
+            .collect();
+        self.prepare(defs, mode_state.enabled, var_values)
+    }
+}
+
+/// Mode files under `cwd` (and `codex_home`) that failed to load.
+pub(crate) fn mode_diagnostics(scan: &ModesScan) -> Vec<ModeDiagnostic> {
+    scan.diagnostics
+        .iter()
+        .map(|d| ModeDiagnostic {
+            id: d.id.clone(),
+            path: d.path.clone(),
+            code: d.error.code().to_string(),
+            message: d.message(),
+        })
+        .collect()
+}
+
+/// E31xx messages; missing required variables are aggregated into one E3101 like the TUI does.
+pub(crate) fn mode_infos(
+    defs: &[ModeDefinition],
+    state: Option<&ConversationModes>,
+) -> Vec<ModeInfo> {
+    defs.iter()
+        .map(|def| {
+            let values = state.and_then(|s| s.var_values.get(&def.id));
+            ModeInfo {
+                id: def.id.clone(),
+                display_name: def.display_name.clone(),
+                description: def.description.clone(),
+                argument_hint: def.argument_hint.clone(),
+                kind: match def.kind {
+                    ModeKind::Persistent => "persistent",
+                    ModeKind::Instant => "instant",
+                }
+                .to_string(),
+                scope: match &def.scope {
+                    ModeScope::Global => "global".to_string(),
+                    ModeScope::Project(dir) => format!("project:{dir}"),
+                },
+                path: def.path.clone(),
+                default_enabled: def.default_enabled,
+                enabled: state.is_some_and(|s| s.is_enabled(&def.id)),
+                priority: def.priority,
+                conflicts_with: def.conflicts_with.clone(),
+                requires: def.requires.clone(),
+                variables: def
+                    .variables
+                    .iter()
+                    .map(|v| {
+                        // secrets are reported as set/unset only
+                        let reveal = |value: String| {
+                            if v.is_secret() {
+                                codex_modes::SECRET_MASK.to_string()
+                            } else {
+                                value
+                            }
+                        };
+                        ModeVariableInfo {
+                            name: v.name.clone(),
+                            var_type: var_type_name(v).to_string(),
+                            required: v.required,
+                            default: v.default_value().map(reveal),
+                            enum_values: v.r#enum.clone(),
+                            value: values
+                                .and_then(|m| m.get(&v.name).cloned())
+                                .flatten()
+                                .map(reveal),
+                        }
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn var_type_name(v: &codex_modes::ModeVariableDefinition) -> &'static str {
+    // a list keeps its type when `enum` restricts its items
+    if v.r#enum.is_some() && v.var_type != Some(VarType::List) {
+        return "enum";
+    }
+    match v.var_type.unwrap_or(VarType::Text) {
+        VarType::Text => "text",
+        VarType::Enum => "enum",
+        VarType::Boolean => "boolean",
+        VarType::Number => "number",
+        VarType::Path => "path",
+        VarType::List => "list",
+        VarType::Multiline => "multiline",
+        VarType::Secret => "secret",
+    }
+}
+
+/// Polling task for one conversation's mode directories; stops when dropped.
+struct ModesWatcher(tokio::task::JoinHandle<()>);
+
+impl Drop for ModesWatcher {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Poll the mode directories of the conversation's `cwd` and send
+/// `modesChanged` whenever they change.
+fn spawn_modes_watcher(
+    conversation_id: ConversationId,
+    cwd: PathBuf,
+    codex_home: PathBuf,
+    outgoing: Arc<OutgoingMessageSender>,
+) -> ModesWatcher {
+    ModesWatcher(tokio::spawn(async move {
+        let mut last: Option<u64> = None;
+        loop {
+            let (dir, home) = (cwd.clone(), codex_home.clone());
+            let fingerprint = match tokio::task::spawn_blocking(move || {
+                codex_modes::modes_fingerprint(&dir, Some(&home))
+            })
+            .await
+            {
+                Ok(fp) => fp,
+                Err(err) => {
+                    warn!("modes watcher for {} stopped: {err}", cwd.display());
+                    return;
+                }
+            };
+            if last.is_some_and(|prev| prev != fingerprint) {
+                let scan = codex_modes::scan_modes(&cwd, Some(&codex_home));
+                outgoing
+                    .send_server_notification(ServerNotification::ModesChanged(
+                        ModesChangedNotification {
+                            conversation_id,
+                            cwd: cwd.clone(),
+                            diagnostics: mode_diagnostics(&scan),
+                        },
+                    ))
+                    .await;
+            }
+            last = Some(fingerprint);
+            tokio::time::sleep(codex_modes::MODES_POLL_INTERVAL).await;
+        }
+    }))
+}
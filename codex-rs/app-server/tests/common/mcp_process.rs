@@ -17,10 +17,13 @@ use codex_app_server_protocol::ArchiveConversationParams;
 use codex_app_server_protocol::CancelLoginChatGptParams;
 use codex_app_server_protocol::ClientInfo;
 use codex_app_server_protocol::ClientNotification;
+use codex_app_server_protocol::ExportConversationParams;
 use codex_app_server_protocol::GetAuthStatusParams;
+use codex_app_server_protocol::GetRenderedInstructionsParams;
 use codex_app_server_protocol::InitializeParams;
 use codex_app_server_protocol::InterruptConversationParams;
 use codex_app_server_protocol::ListConversationsParams;
+use codex_app_server_protocol::ListModesParams;
 use codex_app_server_protocol::LoginApiKeyParams;
 use codex_app_server_protocol::NewConversationParams;
 use codex_app_server_protocol::RemoveConversationListenerParams;
@@ -29,6 +32,9 @@ use codex_app_server_protocol::SendUserMessageParams;
 use codex_app_server_protocol::SendUserTurnParams;
 use codex_app_server_protocol::ServerRequest;
 use codex_app_server_protocol::SetDefaultModelParams;
+use codex_app_server_protocol::SetEnabledModesParams;
+use codex_app_server_protocol::SetModeVariablesParams;
+use codex_app_server_protocol::UpdateConversationMetadataParams;
 
 use codex_app_server_protocol::JSONRPCError;
 use codex_app_server_protocol::JSONRPCMessage;
@@ -170,6 +176,25 @@ impl McpProcess {
         self.send_request("archiveConversation", params).await
     }
 
+    /// Send an `exportConversation` JSON-RPC request.
+    pub async fn send_export_conversation_request(
+        &mut self,
+        params: ExportConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("exportConversation", params).await
+    }
+
+    /// Send an `updateConversationMetadata` JSON-RPC request.
+    pub async fn send_update_conversation_metadata_request(
+        &mut self,
+        params: UpdateConversationMetadataParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("updateConversationMetadata", params)
+            .await
+    }
+
     /// Send an `addConversationListener` JSON-RPC request.
     pub async fn send_add_conversation_listener_request(
         &mut self,
@@ -313,6 +338,42 @@ impl McpProcess {
         self.send_request("fuzzyFileSearch", Some(params)).await
     }
 
+    /// Send a `listModes` JSON-RPC request.
+    pub async fn send_list_modes_request(
+        &mut self,
+        params: ListModesParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("listModes", params).await
+    }
+
+    /// Send a `setEnabledModes` JSON-RPC request.
+    pub async fn send_set_enabled_modes_request(
+        &mut self,
+        params: SetEnabledModesParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("setEnabledModes", params).await
+    }
+
+    /// Send a `setModeVariables` JSON-RPC request.
+    pub async fn send_set_mode_variables_request(
+        &mut self,
+        params: SetModeVariablesParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("setModeVariables", params).await
+    }
+
+    /// Send a `getRenderedInstructions` JSON-RPC request.
+    pub async fn send_get_rendered_instructions_request(
+        &mut self,
+        params: GetRenderedInstructionsParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("getRenderedInstructions", params).await
+    }
+
     async fn send_request(
         &mut self,
         method: &str,
@@ -311,6 +311,7 @@ async fn test_send_user_turn_changes_approval_policy_behavior() {
             ],
             cwd: working_directory.clone(),
             reason: None,
+            matched_rule: None,
         },
         params
     );
@@ -477,8 +478,11 @@ async fn test_send_user_turn_updates_sandbox_and_cwd_between_turns() {
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![first_cwd.clone()],
                 network_access: false,
+                network_allowlist: vec![],
                 exclude_tmpdir_env_var: false,
                 exclude_slash_tmp: false,
+                readable_roots: vec![],
+                deny_read_paths: vec![],
             },
             model: model.clone(),
             effort: Some(ReasoningEffort::Medium),
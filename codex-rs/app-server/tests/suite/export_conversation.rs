@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+
+use app_test_support::McpProcess;
+use app_test_support::to_response;
+use codex_app_server_protocol::ExportConversationParams;
+use codex_app_server_protocol::ExportConversationResponse;
+use codex_app_server_protocol::JSONRPCError;
+use codex_app_server_protocol::JSONRPCResponse;
+use codex_app_server_protocol::RequestId;
+use codex_app_server_protocol::TranscriptFormat;
+use codex_protocol::ConversationId;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const CONVERSATION_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn export_conversation_renders_transcript() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    write_rollout(codex_home.path());
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let conversation_id = ConversationId::from_string(CONVERSATION_ID).expect("valid id");
+    let request_id = mcp
+        .send_export_conversation_request(ExportConversationParams {
+            conversation_id,
+            format: TranscriptFormat::Json,
+            redact_secrets: true,
+            omit_reasoning: true,
+        })
+        .await
+        .expect("send exportConversation");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("exportConversation timeout")
+    .expect("exportConversation response");
+    let ExportConversationResponse { content } =
+        to_response::<ExportConversationResponse>(response).expect("deserialize response");
+
+    let transcript: serde_json::Value = serde_json::from_str(&content).expect("json transcript");
+    assert_eq!(transcript["session"]["id"], CONVERSATION_ID);
+    assert_eq!(
+        transcript["entries"],
+        json!([
+            { "type": "user_message", "text": "Use password=[REDACTED] to log in" },
+            { "type": "agent_message", "text": "Logged in." },
+        ])
+    );
+
+    let missing_request_id = mcp
+        .send_export_conversation_request(ExportConversationParams {
+            conversation_id: ConversationId::new(),
+            format: TranscriptFormat::Markdown,
+            redact_secrets: false,
+            omit_reasoning: false,
+        })
+        .await
+        .expect("send exportConversation");
+    let error: JSONRPCError = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(missing_request_id)),
+    )
+    .await
+    .expect("exportConversation timeout")
+    .expect("exportConversation error");
+    assert!(error.error.message.contains("no rollout found"));
+}
+
+fn write_rollout(codex_home: &Path) {
+    let dir = codex_home
+        .join("sessions")
+        .join("2025")
+        .join("01")
+        .join("02");
+    fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("create sessions dir: {e}"));
+    let lines = [
+        json!({
+            "timestamp": "2025-01-02T12:00:00Z",
+            "type": "session_meta",
+            "payload": {
+                "id": CONVERSATION_ID,
+                "timestamp": "2025-01-02T12:00:00Z",
+                "cwd": "/",
+                "originator": "codex",
+                "cli_version": "0.0.0",
+                "instructions": null
+            }
+        }),
+        json!({
+            "timestamp": "2025-01-02T12:00:00Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "user_message",
+                "message": "Use password=correcthorse to log in",
+                "kind": "plain"
+            }
+        }),
+        json!({
+            "timestamp": "2025-01-02T12:00:01Z",
+            "type": "event_msg",
+            "payload": { "type": "agent_reasoning", "text": "Logging in" }
+        }),
+        json!({
+            "timestamp": "2025-01-02T12:00:02Z",
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "Logged in." }
+        }),
+    ];
+    let contents: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    fs::write(
+        dir.join(format!(
+            "rollout-2025-01-02T12-00-00-{CONVERSATION_ID}.jsonl"
+        )),
+        contents,
+    )
+    .unwrap_or_else(|e| panic!("write rollout file: {e}"));
+}
@@ -3,6 +3,7 @@ use std::path::Path;
 
 use app_test_support::McpProcess;
 use app_test_support::to_response;
+use codex_app_server_protocol::JSONRPCError;
 use codex_app_server_protocol::JSONRPCNotification;
 use codex_app_server_protocol::JSONRPCResponse;
 use codex_app_server_protocol::ListConversationsParams;
@@ -13,6 +14,9 @@ use codex_app_server_protocol::ResumeConversationParams;
 use codex_app_server_protocol::ResumeConversationResponse;
 use codex_app_server_protocol::ServerNotification;
 use codex_app_server_protocol::SessionConfiguredNotification;
+use codex_app_server_protocol::UpdateConversationMetadataParams;
+use codex_app_server_protocol::UpdateConversationMetadataResponse;
+use codex_protocol::ConversationId;
 use pretty_assertions::assert_eq;
 use serde_json::json;
 use tempfile::TempDir;
@@ -57,6 +61,8 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: None,
+            search: None,
+            tag: None,
         })
         .await
         .expect("send listConversations");
@@ -82,6 +88,8 @@ async fn test_list_and_resume_conversations() {
         .send_list_conversations_request(ListConversationsParams {
             page_size: Some(2),
             cursor: next_cursor,
+            search: None,
+            tag: None,
         })
         .await
         .expect("send listConversations page 2");
@@ -152,7 +160,166 @@ async fn test_list_and_resume_conversations() {
     assert!(!conversation_id.to_string().is_empty());
 }
 
-fn create_fake_rollout(codex_home: &Path, filename_ts: &str, meta_rfc3339: &str, preview: &str) {
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_list_conversations_with_search() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_fake_rollout(
+        codex_home.path(),
+        "2025-01-02T12-00-00",
+        "2025-01-02T12:00:00Z",
+        "Fix the flaky login test",
+    );
+    create_fake_rollout(
+        codex_home.path(),
+        "2025-01-01T13-00-00",
+        "2025-01-01T13:00:00Z",
+        "Add a changelog entry",
+    );
+    create_fake_rollout(
+        codex_home.path(),
+        "2025-01-01T12-00-00",
+        "2025-01-01T12:00:00Z",
+        "Why does LOGIN fail on CI?",
+    );
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let req_id = mcp
+        .send_list_conversations_request(ListConversationsParams {
+            page_size: Some(10),
+            cursor: None,
+            search: Some("login".to_string()),
+            tag: None,
+        })
+        .await
+        .expect("send listConversations");
+    let resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(req_id)),
+    )
+    .await
+    .expect("listConversations timeout")
+    .expect("listConversations resp");
+    let ListConversationsResponse { items, next_cursor } =
+        to_response::<ListConversationsResponse>(resp).expect("deserialize response");
+
+    let previews: Vec<_> = items.iter().map(|item| item.preview.as_str()).collect();
+    assert_eq!(
+        previews,
+        vec!["Fix the flaky login test", "Why does LOGIN fail on CI?"]
+    );
+    assert_eq!(next_cursor, None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_update_metadata_and_list_by_tag() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let tagged = create_fake_rollout(
+        codex_home.path(),
+        "2025-01-02T12-00-00",
+        "2025-01-02T12:00:00Z",
+        "Fix the flaky login test",
+    );
+    create_fake_rollout(
+        codex_home.path(),
+        "2025-01-01T12-00-00",
+        "2025-01-01T12:00:00Z",
+        "Add a changelog entry",
+    );
+    let conversation_id =
+        ConversationId::from_string(&tagged.to_string()).expect("valid conversation id");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let update_id = mcp
+        .send_update_conversation_metadata_request(UpdateConversationMetadataParams {
+            conversation_id,
+            title: Some("Login flake".to_string()),
+            add_tags: vec!["#CI".to_string(), "auth".to_string()],
+            remove_tags: Vec::new(),
+            pinned: Some(true),
+        })
+        .await
+        .expect("send updateConversationMetadata");
+    let update_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(update_id)),
+    )
+    .await
+    .expect("updateConversationMetadata timeout")
+    .expect("updateConversationMetadata resp");
+    let updated = to_response::<UpdateConversationMetadataResponse>(update_resp)
+        .expect("deserialize updateConversationMetadata response");
+    assert_eq!(
+        updated,
+        UpdateConversationMetadataResponse {
+            title: Some("Login flake".to_string()),
+            tags: vec!["auth".to_string(), "ci".to_string()],
+            pinned: true,
+        }
+    );
+
+    let invalid_id = mcp
+        .send_update_conversation_metadata_request(UpdateConversationMetadataParams {
+            conversation_id,
+            add_tags: vec!["two words".to_string()],
+            ..Default::default()
+        })
+        .await
+        .expect("send updateConversationMetadata");
+    let invalid: JSONRPCError = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(invalid_id)),
+    )
+    .await
+    .expect("updateConversationMetadata timeout")
+    .expect("updateConversationMetadata error");
+    assert!(invalid.error.message.contains("invalid tag"));
+
+    let list_id = mcp
+        .send_list_conversations_request(ListConversationsParams {
+            page_size: Some(10),
+            cursor: None,
+            search: None,
+            tag: Some("CI".to_string()),
+        })
+        .await
+        .expect("send listConversations");
+    let list_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(list_id)),
+    )
+    .await
+    .expect("listConversations timeout")
+    .expect("listConversations resp");
+    let ListConversationsResponse { items, next_cursor } =
+        to_response::<ListConversationsResponse>(list_resp).expect("deserialize response");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].conversation_id, conversation_id);
+    assert_eq!(items[0].title.as_deref(), Some("Login flake"));
+    assert_eq!(items[0].tags, vec!["auth".to_string(), "ci".to_string()]);
+    assert!(items[0].pinned);
+    assert_eq!(next_cursor, None);
+}
+
+fn create_fake_rollout(
+    codex_home: &Path,
+    filename_ts: &str,
+    meta_rfc3339: &str,
+    preview: &str,
+) -> Uuid {
     let uuid = Uuid::new_v4();
     // sessions/YYYY/MM/DD/ derived from filename_ts (YYYY-MM-DDThh-mm-ss)
     let year = &filename_ts[0..4];
@@ -207,4 +374,5 @@ fn create_fake_rollout(codex_home: &Path, filename_ts: &str, meta_rfc3339: &str,
     );
     fs::write(file_path, lines.join("\n") + "\n")
         .unwrap_or_else(|e| panic!("write rollout file: {e}"));
+    uuid
 }
@@ -3,10 +3,12 @@ mod auth;
 mod codex_message_processor_flow;
 mod config;
 mod create_conversation;
+mod export_conversation;
 mod fuzzy_file_search;
 mod interrupt;
 mod list_resume;
 mod login;
+mod modes;
 mod send_message;
 mod set_default_model;
 mod user_agent;
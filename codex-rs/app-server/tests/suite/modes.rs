@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use app_test_support::McpProcess;
+use app_test_support::create_mock_chat_completions_server;
+use app_test_support::to_response;
+use codex_app_server_protocol::GetRenderedInstructionsParams;
+use codex_app_server_protocol::GetRenderedInstructionsResponse;
+use codex_app_server_protocol::JSONRPCError;
+use codex_app_server_protocol::JSONRPCResponse;
+use codex_app_server_protocol::ListModesParams;
+use codex_app_server_protocol::ListModesResponse;
+use codex_app_server_protocol::ModesChangedNotification;
+use codex_app_server_protocol::NewConversationParams;
+use codex_app_server_protocol::NewConversationResponse;
+use codex_app_server_protocol::RequestId;
+use codex_app_server_protocol::SetEnabledModesParams;
+use codex_app_server_protocol::SetEnabledModesResponse;
+use codex_app_server_protocol::SetModeVariablesParams;
+use codex_app_server_protocol::SetModeVariablesResponse;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn modes_enable_and_render_for_conversation() {
+    let server = create_mock_chat_completions_server(vec![]).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri()).expect("write config.toml");
+    let modes_dir = codex_home.path().join("modes");
+    std::fs::create_dir_all(&modes_dir).expect("create modes dir");
+    std::fs::write(
+        modes_dir.join("review.md"),
+        "---\nkind: persistent\nvariables:\n  - name: focus\n    enum: [tests, docs]\n    default: tests\n---\nReview with focus on {{focus}}.\n",
+    )
+    .expect("write mode file");
+    std::fs::write(modes_dir.join("broken.md"), "---\nkind: [\n---\nB\n").expect("write mode file");
+    let workspace = TempDir::new().expect("create workspace");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let new_conv_id = mcp
+        .send_new_conversation_request(NewConversationParams {
+            cwd: Some(workspace.path().to_string_lossy().to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect("send newConversation");
+    let new_conv_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(new_conv_id)),
+    )
+    .await
+    .expect("newConversation timeout")
+    .expect("newConversation resp");
+    let NewConversationResponse {
+        conversation_id, ..
+    } = to_response::<NewConversationResponse>(new_conv_resp)
+        .expect("deserialize newConversation response");
+
+    // listModes reports the mode and its variables, not yet enabled, and the
+    // file that failed to load.
+    let list_id = mcp
+        .send_list_modes_request(ListModesParams {
+            conversation_id: Some(conversation_id),
+            cwd: None,
+        })
+        .await
+        .expect("send listModes");
+    let ListModesResponse { modes, diagnostics } = to_response::<ListModesResponse>(
+        timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp.read_stream_until_response_message(RequestId::Integer(list_id)),
+        )
+        .await
+        .expect("listModes timeout")
+        .expect("listModes resp"),
+    )
+    .expect("deserialize listModes response");
+    assert_eq!(modes.len(), 1);
+    assert_eq!(modes[0].id, "/review");
+    assert!(!modes[0].enabled);
+    assert_eq!(modes[0].variables[0].name, "focus");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].id, "/broken");
+    assert_eq!(diagnostics[0].code, "E2001");
+
+    // Enabling the mode renders its body with the default value.
+    let enable_id = mcp
+        .send_set_enabled_modes_request(SetEnabledModesParams {
+            conversation_id,
+            mode_ids: vec!["/review".to_string()],
+        })
+        .await
+        .expect("send setEnabledModes");
+    let SetEnabledModesResponse {
+        enabled_modes,
+        user_instructions,
+    } = to_response::<SetEnabledModesResponse>(
+        timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp.read_stream_until_response_message(RequestId::Integer(enable_id)),
+        )
+        .await
+        .expect("setEnabledModes timeout")
+        .expect("setEnabledModes resp"),
+    )
+    .expect("deserialize setEnabledModes response");
+    assert_eq!(enabled_modes, vec!["/review".to_string()]);
+    assert!(user_instructions.contains("Review with focus on tests."));
+
+    // Variable updates re-render the enabled mode.
+    let vars_id = mcp
+        .send_set_mode_variables_request(SetModeVariablesParams {
+            conversation_id,
+            mode_id: "/review".to_string(),
+            variables: [("focus".to_string(), Some("docs".to_string()))]
+                .into_iter()
+                .collect(),
+        })
+        .await
+        .expect("send setModeVariables");
+    let SetModeVariablesResponse { user_instructions } = to_response::<SetModeVariablesResponse>(
+        timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp.read_stream_until_response_message(RequestId::Integer(vars_id)),
+        )
+        .await
+        .expect("setModeVariables timeout")
+        .expect("setModeVariables resp"),
+    )
+    .expect("deserialize setModeVariables response");
+    assert!(user_instructions.contains("Review with focus on docs."));
+
+    // Invalid enum values are rejected with their error code.
+    let bad_id = mcp
+        .send_set_mode_variables_request(SetModeVariablesParams {
+            conversation_id,
+            mode_id: "/review".to_string(),
+            variables: [("focus".to_string(), Some("perf".to_string()))]
+                .into_iter()
+                .collect(),
+        })
+        .await
+        .expect("send setModeVariables");
+    let err: JSONRPCError = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(bad_id)),
+    )
+    .await
+    .expect("setModeVariables timeout")
+    .expect("setModeVariables error");
+    assert!(
+        err.error.message.starts_with("E3102"),
+        "{}",
+        err.error.message
+    );
+
+    // The rendered text reflects the last successful update.
+    let get_id = mcp
+        .send_get_rendered_instructions_request(GetRenderedInstructionsParams { conversation_id })
+        .await
+        .expect("send getRenderedInstructions");
+    let rendered = to_response::<GetRenderedInstructionsResponse>(
+        timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp.read_stream_until_response_message(RequestId::Integer(get_id)),
+        )
+        .await
+        .expect("getRenderedInstructions timeout")
+        .expect("getRenderedInstructions resp"),
+    )
+    .expect("deserialize getRenderedInstructions response");
+    assert_eq!(rendered.enabled_modes, vec!["/review".to_string()]);
+    assert!(
+        rendered
+            .user_instructions
+            .contains("Review with focus on docs.")
+    );
+
+    // Editing a mode file notifies the conversation that watches it.
+    std::fs::write(modes_dir.join("notes.md"), "Take notes.\n").expect("write mode file");
+    let notification = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("modesChanged"),
+    )
+    .await
+    .expect("modesChanged timeout")
+    .expect("modesChanged notification");
+    let changed: ModesChangedNotification =
+        serde_json::from_value(notification.params.expect("modesChanged params"))
+            .expect("deserialize modesChanged");
+    assert_eq!(changed.conversation_id, conversation_id);
+    assert_eq!(changed.diagnostics.len(), 1);
+
+    drop(server);
+}
+
+// Helper to create a config.toml pointing at the mock model server.
+fn create_config_toml(codex_home: &Path, server_uri: &str) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "never"
+sandbox_mode = "danger-full-access"
+
+model_provider = "mock_provider"
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+"#
+        ),
+    )
+}
@@ -0,0 +1,183 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::approval_rules;
+use codex_core::approval_rules::RulePattern;
+use codex_core::config::find_codex_home;
+
+/// Manage persistent command approval rules.
+///
+/// Rules are created from the approval prompt ("always allow ... in this
+/// project" / "everywhere") and stored in `~/.codex/approval_rules.toml`.
+///
+/// Subcommands:
+/// - `list`   — list saved rules (with `--json`)
+/// - `revoke` — delete a rule by id, or every rule with `--all`
+#[derive(Debug, clap::Parser)]
+pub struct ApprovalsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ApprovalsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ApprovalsSubcommand {
+    /// List saved approval rules.
+    List(ListArgs),
+
+    /// Delete saved approval rules.
+    Revoke(RevokeArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Output the rules as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RevokeArgs {
+    /// Id of the rule to delete, as shown by `codex approvals list`.
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub id: Option<String>,
+
+    /// Delete every saved rule.
+    #[arg(long)]
+    pub all: bool,
+}
+
+impl ApprovalsCli {
+    pub async fn run(self) -> Result<()> {
+        let ApprovalsCli {
+            config_overrides,
+            subcommand,
+        } = self;
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        match subcommand {
+            ApprovalsSubcommand::List(args) => run_list(args)?,
+            ApprovalsSubcommand::Revoke(args) => run_revoke(args)?,
+        }
+
+        Ok(())
+    }
+}
+
+fn load_rules() -> Result<(std::path::PathBuf, Vec<approval_rules::ApprovalRule>)> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let rules = approval_rules::load_rules(&codex_home).with_context(|| {
+        format!(
+            "failed to load approval rules from {}",
+            approval_rules::rules_path(&codex_home).display()
+        )
+    })?;
+    Ok((codex_home, rules))
+}
+
+fn run_list(list_args: ListArgs) -> Result<()> {
+    let (_, rules) = load_rules()?;
+
+    if list_args.json {
+        let json_entries: Vec<_> = rules
+            .iter()
+            .map(|rule| {
+                let (kind, pattern) = match &rule.pattern {
+                    RulePattern::Exact(argv) => ("exact", serde_json::json!(argv)),
+                    RulePattern::Prefix(prefix) => ("prefix", serde_json::json!(prefix)),
+                    RulePattern::Glob(glob) => ("glob", serde_json::json!(glob)),
+                };
+                serde_json::json!({
+                    "id": rule.id,
+                    "kind": kind,
+                    "pattern": pattern,
+                    "cwd": rule.cwd,
+                    "created_at": rule.created_at.to_rfc3339(),
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if rules.is_empty() {
+        println!(
+            "No approval rules saved. Choose \"always allow\" in an approval prompt to add one."
+        );
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 3]> = rules
+        .iter()
+        .map(|rule| {
+            [
+                rule.id.clone(),
+                rule.describe_pattern(),
+                rule.cwd
+                    .as_ref()
+                    .map(|cwd| cwd.display().to_string())
+                    .unwrap_or_else(|| "(everywhere)".to_string()),
+            ]
+        })
+        .collect();
+    let mut widths = ["Id".len(), "Command".len()];
+    for row in &rows {
+        widths[0] = widths[0].max(row[0].len());
+        widths[1] = widths[1].max(row[1].len());
+    }
+
+    println!(
+        "{:<id_w$}  {:<cmd_w$}  Directory",
+        "Id",
+        "Command",
+        id_w = widths[0],
+        cmd_w = widths[1],
+    );
+    for row in &rows {
+        println!(
+            "{:<id_w$}  {:<cmd_w$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            id_w = widths[0],
+            cmd_w = widths[1],
+        );
+    }
+
+    Ok(())
+}
+
+fn run_revoke(revoke_args: RevokeArgs) -> Result<()> {
+    let (codex_home, mut rules) = load_rules()?;
+    let before = rules.len();
+
+    // clap guarantees exactly one of `id` and `--all`.
+    match &revoke_args.id {
+        Some(id) => rules.retain(|rule| &rule.id != id),
+        None => rules.clear(),
+    }
+    let removed = before - rules.len();
+
+    if removed > 0 {
+        approval_rules::save_rules(&codex_home, &rules).with_context(|| {
+            format!(
+                "failed to write approval rules to {}",
+                approval_rules::rules_path(&codex_home).display()
+            )
+        })?;
+    }
+
+    match (&revoke_args.id, removed) {
+        (Some(id), 0) => bail!("No approval rule with id '{id}' found."),
+        (Some(id), _) => println!("Revoked approval rule '{id}'."),
+        (None, 1) => println!("Revoked 1 approval rule."),
+        (None, n) => println!("Revoked {n} approval rules."),
+    }
+
+    Ok(())
+}
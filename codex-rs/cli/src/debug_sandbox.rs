@@ -94,6 +94,7 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                &config.resource_limits,
                 stdio_policy,
                 env,
             )
@@ -110,8 +111,10 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                &config.resource_limits,
                 stdio_policy,
                 env,
+                None,
             )
             .await?
         }
@@ -23,9 +23,15 @@ use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod approvals_cmd;
 mod mcp_cmd;
+mod modes_cmd;
+mod sessions_cmd;
 
+use crate::approvals_cmd::ApprovalsCli;
 use crate::mcp_cmd::McpCli;
+use crate::modes_cmd::ModesCli;
+use crate::sessions_cmd::SessionsCli;
 
 /// Codex CLI
 ///
@@ -67,6 +73,15 @@ enum Subcommand {
     /// [experimental] Run Codex as an MCP server and manage MCP servers.
     Mcp(McpCli),
 
+    /// Inspect custom modes: list, lint and render.
+    Modes(ModesCli),
+
+    /// Manage persistent command approval rules.
+    Approvals(ApprovalsCli),
+
+    /// Search, export, compare, organize and clean up recorded sessions.
+    Sessions(SessionsCli),
+
     /// [experimental] Run the Codex MCP server (stdio transport).
     McpServer,
 
@@ -276,6 +291,27 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
+        Some(Subcommand::Modes(mut modes_cli)) => {
+            prepend_config_flags(
+                &mut modes_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            modes_cli.run().await?;
+        }
+        Some(Subcommand::Approvals(mut approvals_cli)) => {
+            prepend_config_flags(
+                &mut approvals_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            approvals_cli.run().await?;
+        }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            sessions_cli.run().await?;
+        }
         Some(Subcommand::AppServer) => {
             codex_app_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
@@ -0,0 +1,323 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_modes::LintSeverity;
+use codex_modes::ModeKind;
+use codex_modes::ModeScope;
+
+/// Inspect custom modes without starting a session.
+///
+/// Subcommands:
+/// - `list`   — list visible modes with their source files (with `--json`)
+/// - `lint`   — check every mode file; exits non-zero on errors (with `--json`)
+/// - `render` — print the `<user_instructions>` block for a set of modes
+#[derive(Debug, clap::Parser)]
+pub struct ModesCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ModesSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ModesSubcommand {
+    /// List the modes visible from a directory, including shadowed definitions.
+    List(ListArgs),
+
+    /// Check mode files for errors and suspicious placeholders.
+    Lint(LintArgs),
+
+    /// Print the `<user_instructions>` block a session would receive.
+    Render(RenderArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Directory to resolve project modes from (defaults to the current directory).
+    #[arg(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Output the modes as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LintArgs {
+    /// Directory to resolve project modes from (defaults to the current directory).
+    #[arg(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Exit non-zero on warnings as well as errors.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Output the findings as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RenderArgs {
+    /// Directory to resolve project modes from (defaults to the current directory).
+    #[arg(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Persistent mode to enable (repeatable), e.g. `--mode /review`.
+    #[arg(long = "mode", value_name = "ID")]
+    pub modes: Vec<String>,
+
+    /// Variable value for an enabled mode (repeatable), e.g. `--mode-var /review:focus=tests`.
+    #[arg(long = "mode-var", value_name = "ID:NAME=VALUE")]
+    pub mode_vars: Vec<String>,
+
+    /// Output the enabled modes, overrides and rendered text as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl ModesCli {
+    pub async fn run(self) -> Result<()> {
+        let ModesCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        match subcommand {
+            ModesSubcommand::List(args) => {
+                run_list(&config_overrides, args).await?;
+            }
+            ModesSubcommand::Lint(args) => {
+                run_lint(&config_overrides, args).await?;
+            }
+            ModesSubcommand::Render(args) => {
+                run_render(&config_overrides, args).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn load_config(
+    config_overrides: &CliConfigOverrides,
+    cwd: Option<PathBuf>,
+) -> Result<Config> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    Config::load_with_cli_overrides(
+        overrides,
+        ConfigOverrides {
+            cwd,
+            ..ConfigOverrides::default()
+        },
+    )
+    .await
+    .context("failed to load configuration")
+}
+
+fn kind_label(kind: ModeKind) -> &'static str {
+    match kind {
+        ModeKind::Persistent => "persistent",
+        ModeKind::Instant => "instant",
+    }
+}
+
+fn scope_label(scope: &ModeScope) -> String {
+    match scope {
+        ModeScope::Global => "global".to_string(),
+        ModeScope::Project(dir) => format!("project:{dir}"),
+    }
+}
+
+async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
+    let config = load_config(config_overrides, list_args.cwd).await?;
+    let scan = codex_modes::scan_modes(&config.cwd, Some(&config.codex_home));
+    for diagnostic in &scan.diagnostics {
+        eprintln!(
+            "warning: skipped {} ({}): {}",
+            diagnostic.id,
+            diagnostic.path.display(),
+            diagnostic.message()
+        );
+    }
+    if !scan.diagnostics.is_empty() {
+        eprintln!("run `codex modes lint` for all problems");
+    }
+    let defs = scan.defs;
+    let sources = codex_modes::mode_sources(&config.cwd, Some(&config.codex_home));
+    let shadowed = |id: &str, path: &std::path::Path| -> Vec<PathBuf> {
+        sources
+            .iter()
+            .filter(|s| s.id == id && s.path != path)
+            .map(|s| s.path.clone())
+            .collect()
+    };
+
+    if list_args.json {
+        let json_entries: Vec<_> = defs
+            .iter()
+            .map(|def| {
+                serde_json::json!({
+                    "id": def.id,
+                    "kind": kind_label(def.kind),
+                    "scope": scope_label(&def.scope),
+                    "path": def.path,
+                    "shadows": shadowed(&def.id, &def.path),
+                    "default_enabled": def.default_enabled,
+                    "priority": def.priority,
+                    "variables": def
+                        .variables
+                        .iter()
+                        .map(|v| v.name.as_str())
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if defs.is_empty() {
+        println!("No modes found. Add Markdown files under .codex/modes/ or ~/.codex/modes/.");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = defs
+        .iter()
+        .map(|def| {
+            [
+                def.id.clone(),
+                kind_label(def.kind).to_string(),
+                scope_label(&def.scope),
+                def.path.display().to_string(),
+            ]
+        })
+        .collect();
+    let mut widths = ["Id".len(), "Kind".len(), "Scope".len(), "Source".len()];
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+
+    println!(
+        "{:<id_w$}  {:<kind_w$}  {:<scope_w$}  Source",
+        "Id",
+        "Kind",
+        "Scope",
+        id_w = widths[0],
+        kind_w = widths[1],
+        scope_w = widths[2],
+    );
+    for (row, def) in rows.iter().zip(&defs) {
+        println!(
+            "{:<id_w$}  {:<kind_w$}  {:<scope_w$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            id_w = widths[0],
+            kind_w = widths[1],
+            scope_w = widths[2],
+        );
+        for path in shadowed(&def.id, &def.path) {
+            println!(
+                "{:<pad$}  shadows {}",
+                "",
+                path.display(),
+                pad = widths[0] + widths[1] + widths[2] + 4,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_lint(config_overrides: &CliConfigOverrides, lint_args: LintArgs) -> Result<()> {
+    let config = load_config(config_overrides, lint_args.cwd).await?;
+    let issues = codex_modes::lint_modes(&config.cwd, Some(&config.codex_home));
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == LintSeverity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    if lint_args.json {
+        let json_entries: Vec<_> = issues
+            .iter()
+            .map(|issue| {
+                serde_json::json!({
+                    "severity": issue.severity.as_str(),
+                    "code": issue.code,
+                    "message": issue.message,
+                    "file": issue.path,
+                    "id": issue.id,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+    } else {
+        for issue in &issues {
+            let id = issue
+                .id
+                .as_deref()
+                .map(|id| format!(" {id}:"))
+                .unwrap_or_default();
+            println!(
+                "{}: {}[{}]{id} {}",
+                issue.path.display(),
+                issue.severity.as_str(),
+                issue.code,
+                issue.message
+            );
+        }
+        if issues.is_empty() {
+            println!("No problems found.");
+        } else {
+            println!("{errors} error(s), {warnings} warning(s)");
+        }
+    }
+
+    if errors > 0 || (lint_args.strict && warnings > 0) {
+        bail!("mode lint failed: {errors} error(s), {warnings} warning(s)");
+    }
+    Ok(())
+}
+
+async fn run_render(config_overrides: &CliConfigOverrides, render_args: RenderArgs) -> Result<()> {
+    let config = load_config(config_overrides, render_args.cwd).await?;
+    let resolved = codex_exec::resolve_modes(&config, &render_args.modes, &render_args.mode_vars)
+        .await
+        .map_err(|errors| anyhow!(errors.join("\n")))?;
+
+    if render_args.json {
+        let mut overrides: serde_json::Map<String, serde_json::Value> = resolved
+            .overrides
+            .fields()
+            .iter()
+            .filter_map(|(name, value)| value.map(|v| (name.to_string(), v.into())))
+            .collect();
+        if let Some(tools) = &resolved.overrides.tools {
+            overrides.insert("tools".to_string(), serde_json::to_value(tools)?);
+        }
+        let output = serde_json::to_string_pretty(&serde_json::json!({
+            "enabled_modes": resolved.enabled,
+            "overrides": overrides,
+            "user_instructions": resolved.user_instructions,
+        }))?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    print!("{}", resolved.user_instructions);
+    Ok(())
+}
@@ -0,0 +1,751 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_common::elapsed::format_duration;
+use codex_core::Divergence;
+use codex_core::ExportFormat;
+use codex_core::ExportOptions;
+use codex_core::GcActionKind;
+use codex_core::IndexEntryKind;
+use codex_core::ListChange;
+use codex_core::RolloutRecorder;
+use codex_core::SearchHit;
+use codex_core::SessionDiff;
+use codex_core::SessionMeta;
+use codex_core::SessionMetadata;
+use codex_core::SessionSummary;
+use codex_core::TurnSummary;
+use codex_core::collect_garbage;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::find_codex_home;
+use codex_core::diff_lists;
+use codex_core::diff_sessions;
+use codex_core::export_session;
+use codex_core::find_conversation_path_by_id_str;
+use codex_core::load_session_metadata;
+use codex_core::normalize_tag;
+use codex_core::search_sessions;
+use codex_core::update_session_metadata;
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::num_format::format_with_separators;
+use codex_protocol::protocol::InputMessageKind;
+
+/// Work with recorded sessions.
+///
+/// Subcommands:
+/// - `search` — full-text search over messages, commands and touched file paths
+/// - `export` — render a session as a Markdown, HTML or JSON transcript
+/// - `diff` — compare two sessions turn by turn
+/// - `gc` — apply the `[sessions]` retention settings from config.toml
+/// - `list`, `title`, `tag`, `untag`, `pin`, `unpin` — organize sessions
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// Search recorded sessions, newest first.
+    Search(SearchArgs),
+
+    /// Export a session as a readable transcript.
+    Export(ExportArgs),
+
+    /// Compare two sessions turn by turn.
+    Diff(DiffArgs),
+
+    /// Delete or compress old sessions according to the `[sessions]` settings.
+    Gc(GcArgs),
+
+    /// List recorded sessions, newest first.
+    List(ListArgs),
+
+    /// Name a session. Without a title, the current title is removed.
+    Title(TitleArgs),
+
+    /// Add tags to a session.
+    Tag(TagArgs),
+
+    /// Remove tags from a session.
+    Untag(TagArgs),
+
+    /// Keep a session when old sessions are garbage collected.
+    Pin(SessionIdArg),
+
+    /// Undo `pin`.
+    Unpin(SessionIdArg),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SearchArgs {
+    /// Terms that must all appear in a session (case-insensitive).
+    #[arg(required = true, value_name = "TERM")]
+    pub query: Vec<String>,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Output the matching sessions as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ExportArgs {
+    /// Id of the session to export.
+    #[arg(value_name = "SESSION_ID")]
+    pub id: String,
+
+    /// Transcript format.
+    #[arg(long, value_enum, default_value_t = ExportFormatArg::Markdown)]
+    pub format: ExportFormatArg,
+
+    /// Write the transcript to this file instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Replace API keys, tokens, passwords and private keys with `[REDACTED]`.
+    #[arg(long)]
+    pub redact: bool,
+
+    /// Leave out the model's reasoning summaries.
+    #[arg(long)]
+    pub no_reasoning: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct DiffArgs {
+    /// Id or rollout file of the first session.
+    #[arg(value_name = "A")]
+    pub left: String,
+
+    /// Id or rollout file of the second session.
+    #[arg(value_name = "B")]
+    pub right: String,
+
+    /// Output the comparison as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct GcArgs {
+    /// Show what would be deleted or compressed without changing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Only list sessions carrying this tag.
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Output the sessions as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SessionIdArg {
+    #[arg(value_name = "SESSION_ID")]
+    pub id: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct TitleArgs {
+    #[arg(value_name = "SESSION_ID")]
+    pub id: String,
+
+    #[arg(value_name = "TITLE", trailing_var_arg = true)]
+    pub title: Vec<String>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct TagArgs {
+    #[arg(value_name = "SESSION_ID")]
+    pub id: String,
+
+    /// Tags are lowercased; a leading `#` is optional.
+    #[arg(required = true, value_name = "TAG")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormatArg {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Markdown => ExportFormat::Markdown,
+            ExportFormatArg::Html => ExportFormat::Html,
+            ExportFormatArg::Json => ExportFormat::Json,
+        }
+    }
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let SessionsCli {
+            config_overrides,
+            subcommand,
+        } = self;
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        match subcommand {
+            SessionsSubcommand::Search(args) => run_search(args).await?,
+            SessionsSubcommand::Export(args) => run_export(args).await?,
+            SessionsSubcommand::Diff(args) => run_diff(args).await?,
+            SessionsSubcommand::Gc(args) => run_gc(&config_overrides, args).await?,
+            SessionsSubcommand::List(args) => run_list(args).await?,
+            SessionsSubcommand::Title(TitleArgs { id, title }) => {
+                let title = title.join(" ");
+                update_metadata(&id, |metadata| metadata.set_title(&title)).await?;
+            }
+            SessionsSubcommand::Tag(TagArgs { id, tags }) => {
+                if let Some(tag) = tags.iter().find(|tag| normalize_tag(tag).is_none()) {
+                    bail!("Invalid tag \"{tag}\": tags must not be empty or contain spaces.");
+                }
+                update_metadata(&id, |metadata| {
+                    for tag in &tags {
+                        metadata.add_tag(tag);
+                    }
+                })
+                .await?;
+            }
+            SessionsSubcommand::Untag(TagArgs { id, tags }) => {
+                update_metadata(&id, |metadata| {
+                    for tag in &tags {
+                        metadata.remove_tag(tag);
+                    }
+                })
+                .await?;
+            }
+            SessionsSubcommand::Pin(SessionIdArg { id }) => {
+                update_metadata(&id, |metadata| metadata.pinned = true).await?;
+            }
+            SessionsSubcommand::Unpin(SessionIdArg { id }) => {
+                update_metadata(&id, |metadata| metadata.pinned = false).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn run_search(search_args: SearchArgs) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let query = search_args.query.join(" ");
+    let mut hits = search_sessions(&codex_home, &query, &[])
+        .await
+        .context("failed to search sessions")?;
+    hits.truncate(search_args.limit);
+
+    if search_args.json {
+        let json_entries: Vec<_> = hits
+            .iter()
+            .map(|hit| {
+                serde_json::json!({
+                    "id": hit.conversation_id.map(|id| id.to_string()),
+                    "path": hit.path,
+                    "started_at": format_started_at(hit),
+                    "preview": hit.preview,
+                    "matches": hit.matches,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        println!("No sessions match \"{query}\".");
+        return Ok(());
+    }
+
+    for hit in &hits {
+        let id = hit
+            .conversation_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let preview = hit.preview.as_deref().unwrap_or("(no message yet)");
+        println!("{}  {id}  {}", format_started_at(hit), first_line(preview));
+        for entry in &hit.matches {
+            let label = match entry.kind {
+                IndexEntryKind::UserMessage => "user",
+                IndexEntryKind::AgentMessage => "agent",
+                IndexEntryKind::Command => "command",
+                IndexEntryKind::FilePath => "file",
+            };
+            println!("    {label:<7}  {}", first_line(&entry.text));
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_export(export_args: ExportArgs) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let Some(path) = find_conversation_path_by_id_str(&codex_home, &export_args.id)
+        .await
+        .context("failed to look up session")?
+    else {
+        bail!("No session found with id {}.", export_args.id);
+    };
+
+    let options = ExportOptions {
+        format: export_args.format.into(),
+        redact_secrets: export_args.redact,
+        include_reasoning: !export_args.no_reasoning,
+    };
+    let transcript = export_session(&path, &options)
+        .await
+        .with_context(|| format!("failed to export {}", path.display()))?;
+
+    match export_args.output {
+        Some(output) => {
+            std::fs::write(&output, transcript)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            println!(
+                "Exported session {} to {}.",
+                export_args.id,
+                output.display()
+            );
+        }
+        None => print!("{transcript}"),
+    }
+
+    Ok(())
+}
+
+async fn run_diff(diff_args: DiffArgs) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let left = resolve_session(&codex_home, &diff_args.left).await?;
+    let right = resolve_session(&codex_home, &diff_args.right).await?;
+    let diff = diff_sessions(&left, &right)
+        .await
+        .context("failed to compare sessions")?;
+
+    if diff_args.json {
+        let output = serde_json::to_string_pretty(&diff)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    print_session_diff(&diff);
+    Ok(())
+}
+
+/// Resolves a session id, or the path of a rollout file, to a rollout file.
+async fn resolve_session(codex_home: &Path, id_or_path: &str) -> Result<PathBuf> {
+    let path = Path::new(id_or_path);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    find_conversation_path_by_id_str(codex_home, id_or_path)
+        .await
+        .context("failed to look up session")?
+        .ok_or_else(|| anyhow!("No session found with id {id_or_path}."))
+}
+
+fn print_session_diff(diff: &SessionDiff) {
+    for (label, summary) in [("A", &diff.left), ("B", &diff.right)] {
+        let id = summary.id.as_deref().unwrap_or("-");
+        println!("{label}  {id}  {}", summary.path.display());
+        println!("   {}", describe_session(summary));
+    }
+    let same = diff.turns.iter().filter(|turn| turn.is_same()).count();
+    println!();
+    println!(
+        "{} turn(s), {same} the same. Lines starting with - are only in A, + only in B.",
+        diff.turns.len()
+    );
+
+    for (index, turn) in diff.turns.iter().enumerate() {
+        println!();
+        let status = match (&turn.left, &turn.right) {
+            (Some(_), None) => "only in A".to_string(),
+            (None, Some(_)) => "only in B".to_string(),
+            _ if turn.divergences.is_empty() => "same".to_string(),
+            _ => {
+                let names: Vec<&str> = turn
+                    .divergences
+                    .iter()
+                    .map(|divergence| match divergence {
+                        Divergence::UserMessage => "user message",
+                        Divergence::Model => "model",
+                        Divergence::Commands => "commands",
+                        Divergence::ToolCalls => "tool calls",
+                        Divergence::Patches => "patches",
+                        Divergence::FinalMessage => "final message",
+                    })
+                    .collect();
+                format!("differs in {}", names.join(", "))
+            }
+        };
+        println!("Turn {}: {status}", index + 1);
+
+        let (left, right) = match (&turn.left, &turn.right) {
+            (Some(left), Some(right)) => (left, right),
+            (Some(only), None) | (None, Some(only)) => {
+                println!("  user: {}", first_line(&only.user_message));
+                let label = if turn.left.is_some() { "A" } else { "B" };
+                println!("  {label}: {}", describe_turn(only));
+                continue;
+            }
+            (None, None) => continue,
+        };
+        if turn.divergences.contains(&Divergence::UserMessage) {
+            println!("  user:");
+            println!("    A: {}", first_line(&left.user_message));
+            println!("    B: {}", first_line(&right.user_message));
+        } else {
+            println!("  user: {}", first_line(&left.user_message));
+        }
+        if turn.divergences.contains(&Divergence::Model) {
+            println!("  model:");
+            println!("    A: {}", describe_model(left));
+            println!("    B: {}", describe_model(right));
+        }
+        if turn.divergences.contains(&Divergence::Commands) {
+            print_list_diff("commands", &left.commands, &right.commands, |entry| {
+                first_line(entry).to_string()
+            });
+        }
+        if turn.divergences.contains(&Divergence::ToolCalls) {
+            print_list_diff("tool calls", &left.tool_calls, &right.tool_calls, |entry| {
+                first_line(entry).to_string()
+            });
+        }
+        if turn.divergences.contains(&Divergence::Patches) {
+            print_list_diff("patches", &left.patches, &right.patches, describe_patch);
+        }
+        if turn.divergences.contains(&Divergence::FinalMessage) {
+            println!("  final message:");
+            print_labeled_text("A", left.final_message.as_deref());
+            print_labeled_text("B", right.final_message.as_deref());
+        }
+        println!("  A: {}", describe_turn(left));
+        println!("  B: {}", describe_turn(right));
+    }
+}
+
+fn print_list_diff(
+    heading: &str,
+    left: &[String],
+    right: &[String],
+    describe: impl Fn(&str) -> String,
+) {
+    println!("  {heading}:");
+    for change in diff_lists(left, right) {
+        let (marker, entry) = match change {
+            ListChange::Both(entry) => (' ', entry),
+            ListChange::Left(entry) => ('-', entry),
+            ListChange::Right(entry) => ('+', entry),
+        };
+        println!("    {marker} {}", describe(entry));
+    }
+}
+
+/// Prints `text` after `label`, indenting continuation lines to match.
+fn print_labeled_text(label: &str, text: Option<&str>) {
+    let text = text.map(str::trim).unwrap_or("(none)");
+    let mut lines = text.lines();
+    println!("    {label}: {}", lines.next().unwrap_or_default());
+    for line in lines {
+        println!("       {line}");
+    }
+}
+
+fn describe_session(summary: &SessionSummary) -> String {
+    let mut parts = Vec::new();
+    if !summary.models.is_empty() {
+        parts.push(summary.models.join(", "));
+    }
+    parts.push(format!("{} turn(s)", summary.turns));
+    if let Some(duration_ms) = summary.duration_ms {
+        parts.push(format_duration(Duration::from_millis(duration_ms)));
+    }
+    if let Some(usage) = &summary.token_usage {
+        parts.push(format!(
+            "{} tokens ({} input, {} cached, {} output, {} reasoning)",
+            format_with_separators(usage.total_tokens),
+            format_with_separators(usage.input_tokens),
+            format_with_separators(usage.cached_input_tokens),
+            format_with_separators(usage.output_tokens),
+            format_with_separators(usage.reasoning_output_tokens)
+        ));
+    }
+    parts.join(" · ")
+}
+
+fn describe_turn(turn: &TurnSummary) -> String {
+    let mut parts = vec![
+        format!("{} command(s)", turn.commands.len()),
+        format!("{} patch(es)", turn.patches.len()),
+    ];
+    if let Some(duration_ms) = turn.duration_ms {
+        parts.push(format_duration(Duration::from_millis(duration_ms)));
+    }
+    if let Some(total_tokens) = turn.total_tokens {
+        parts.push(format!("{} tokens", format_with_separators(total_tokens)));
+    }
+    parts.join(", ")
+}
+
+fn describe_model(turn: &TurnSummary) -> String {
+    match (&turn.model, turn.effort) {
+        (Some(model), Some(effort)) => format!("{model} ({effort})"),
+        (Some(model), None) => model.clone(),
+        (None, _) => "(unknown)".to_string(),
+    }
+}
+
+/// The files a patch touches and its added and removed line counts.
+fn describe_patch(diff: &str) -> String {
+    let mut files: Vec<&str> = Vec::new();
+    let (mut added, mut removed) = (0, 0);
+    for line in diff.lines() {
+        if let Some(file) = line
+            .strip_prefix("+++ b/")
+            .or_else(|| line.strip_prefix("--- a/"))
+        {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        } else if line.starts_with("+++ ") || line.starts_with("--- ") {
+            // `/dev/null` side of an added or deleted file.
+        } else if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    format!("{} (+{added} -{removed})", files.join(", "))
+}
+
+async fn run_gc(config_overrides: &CliConfigOverrides, gc_args: GcArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .await
+        .context("failed to load configuration")?;
+    let sessions = &config.sessions;
+    if sessions.max_age_days.is_none()
+        && sessions.max_total_size_mb.is_none()
+        && sessions.compress_after_days.is_none()
+    {
+        println!("No retention settings configured. Set them under [sessions] in config.toml.");
+        return Ok(());
+    }
+
+    let report = collect_garbage(&config.codex_home, sessions, gc_args.dry_run)
+        .await
+        .context("failed to collect garbage")?;
+
+    let (delete_verb, compress_verb) = if gc_args.dry_run {
+        ("Would delete", "Would compress")
+    } else {
+        ("Deleted", "Compressed")
+    };
+    for action in &report.actions {
+        let (verb, reason) = match action.kind {
+            GcActionKind::Expired => (delete_verb, "expired"),
+            GcActionKind::OverSizeLimit => (delete_verb, "over size limit"),
+            GcActionKind::Compressed => (compress_verb, "old"),
+        };
+        println!(
+            "{verb} {} ({reason}, {})",
+            action.path.display(),
+            format_bytes(action.bytes)
+        );
+    }
+    let deleted = report
+        .actions
+        .iter()
+        .filter(|action| action.kind != GcActionKind::Compressed)
+        .count();
+    let compressed = report.actions.len() - deleted;
+    println!(
+        "{delete_verb} {deleted} session(s) ({}), {} {compressed} session(s).",
+        format_bytes(report.deleted_bytes()),
+        compress_verb.to_lowercase()
+    );
+    if report.kept_pinned > 0 {
+        println!("Kept {} pinned session(s).", report.kept_pinned);
+    }
+
+    Ok(())
+}
+
+async fn run_list(list_args: ListArgs) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let page = match &list_args.tag {
+        Some(tag) => {
+            RolloutRecorder::list_tagged_conversations(&codex_home, tag, list_args.limit, None, &[])
+                .await
+        }
+        None => RolloutRecorder::list_conversations(&codex_home, list_args.limit, None, &[]).await,
+    }
+    .context("failed to list sessions")?;
+    let metadata = load_session_metadata(&codex_home)
+        .await
+        .context("failed to read session metadata")?;
+
+    let sessions: Vec<_> = page
+        .items
+        .iter()
+        .filter_map(|item| {
+            let meta = serde_json::from_value::<SessionMeta>(item.head.first()?.clone()).ok()?;
+            let metadata = metadata.get(&meta.id).cloned().unwrap_or_default();
+            Some((meta, metadata, first_user_message(&item.head)))
+        })
+        .collect();
+
+    if list_args.json {
+        let json_entries: Vec<_> = sessions
+            .iter()
+            .map(|(meta, metadata, preview)| {
+                serde_json::json!({
+                    "id": meta.id,
+                    "started_at": meta.timestamp,
+                    "title": metadata.title,
+                    "tags": metadata.tags,
+                    "pinned": metadata.pinned,
+                    "preview": preview,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!("No sessions found.");
+        return Ok(());
+    }
+
+    for (meta, metadata, preview) in &sessions {
+        let started_at: String = meta
+            .timestamp
+            .replacen('T', " ", 1)
+            .chars()
+            .take(19)
+            .collect();
+        let pin = if metadata.pinned { "*" } else { " " };
+        let name = metadata
+            .title
+            .as_deref()
+            .or(preview.as_deref())
+            .unwrap_or("(no message yet)");
+        let mut line = format!("{started_at}  {} {pin} {}", meta.id, first_line(name));
+        for tag in &metadata.tags {
+            line.push_str(&format!("  #{tag}"));
+        }
+        println!("{line}");
+    }
+
+    Ok(())
+}
+
+/// The first plain user message among the head records of a listed session.
+fn first_user_message(head: &[serde_json::Value]) -> Option<String> {
+    head.iter()
+        .filter_map(|value| serde_json::from_value::<ResponseItem>(value.clone()).ok())
+        .find_map(|item| match item {
+            ResponseItem::Message { role, content, .. } if role == "user" => {
+                content.into_iter().find_map(|content| match content {
+                    ContentItem::InputText { text }
+                        if matches!(
+                            InputMessageKind::from(("user", &text)),
+                            InputMessageKind::Plain
+                        ) =>
+                    {
+                        Some(text)
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        })
+}
+
+async fn update_metadata(id: &str, update: impl FnOnce(&mut SessionMetadata)) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let conversation_id = match ConversationId::from_string(id) {
+        Ok(conversation_id) => conversation_id,
+        Err(_) => bail!("No session found with id {id}."),
+    };
+    if find_conversation_path_by_id_str(&codex_home, id)
+        .await
+        .context("failed to look up session")?
+        .is_none()
+    {
+        bail!("No session found with id {id}.");
+    }
+
+    let metadata = update_session_metadata(&codex_home, conversation_id, update)
+        .await
+        .context("failed to update session metadata")?;
+    let title = metadata.title.as_deref().unwrap_or("(none)");
+    let tags = if metadata.tags.is_empty() {
+        "(none)".to_string()
+    } else {
+        metadata.tags.join(", ")
+    };
+    let pinned = if metadata.pinned { "yes" } else { "no" };
+    println!("Session {id}\n  title:  {title}\n  tags:   {tags}\n  pinned: {pinned}");
+    Ok(())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+fn format_started_at(hit: &SearchHit) -> String {
+    let ts = hit.started_at;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        ts.year(),
+        u8::from(ts.month()),
+        ts.day(),
+        ts.hour(),
+        ts.minute(),
+        ts.second()
+    )
+}
+
+fn first_line(text: &str) -> &str {
+    text.trim().lines().next().unwrap_or_default()
+}
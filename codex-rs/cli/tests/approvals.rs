@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::approval_rules::ApprovalRule;
+use codex_core::approval_rules::load_rules;
+use codex_core::approval_rules::save_rules;
+use codex_core::protocol::ApprovalRuleScope;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use serde_json::Value as JsonValue;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn prefix(args: &[&str]) -> Vec<String> {
+    args.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn list_and_revoke_rules() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let project = TempDir::new()?;
+
+    let mut empty_cmd = codex_command(codex_home.path())?;
+    empty_cmd
+        .args(["approvals", "list"])
+        .assert()
+        .success()
+        .stdout(contains("No approval rules saved."));
+
+    let cargo_test = ApprovalRule::new_prefix(
+        prefix(&["cargo", "test"]),
+        ApprovalRuleScope::Project,
+        project.path(),
+    );
+    let git_status = ApprovalRule::new_prefix(
+        prefix(&["git", "status"]),
+        ApprovalRuleScope::Global,
+        project.path(),
+    );
+    save_rules(codex_home.path(), &[cargo_test.clone(), git_status.clone()])?;
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["approvals", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    let entries = parsed.as_array().expect("array");
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["id"], cargo_test.id.as_str());
+    assert_eq!(entries[0]["kind"], "prefix");
+    assert_eq!(entries[0]["pattern"], serde_json::json!(["cargo", "test"]));
+    assert_eq!(
+        entries[0]["cwd"],
+        serde_json::json!(project.path().to_string_lossy())
+    );
+    assert_eq!(entries[1]["cwd"], JsonValue::Null);
+
+    let mut table_cmd = codex_command(codex_home.path())?;
+    table_cmd
+        .args(["approvals", "list"])
+        .assert()
+        .success()
+        .stdout(contains("git status *").and(contains("(everywhere)")));
+
+    let mut revoke_cmd = codex_command(codex_home.path())?;
+    revoke_cmd
+        .args(["approvals", "revoke", &cargo_test.id])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "Revoked approval rule '{}'.",
+            cargo_test.id
+        )));
+    assert_eq!(load_rules(codex_home.path())?, vec![git_status]);
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["approvals", "revoke", &cargo_test.id])
+        .assert()
+        .failure()
+        .stderr(contains("No approval rule with id"));
+
+    let mut all_cmd = codex_command(codex_home.path())?;
+    all_cmd
+        .args(["approvals", "revoke", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("Revoked 1 approval rule."));
+    assert!(load_rules(codex_home.path())?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use serde_json::Value as JsonValue;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_mode(dir: &Path, name: &str, text: &str) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(name), text)?;
+    Ok(())
+}
+
+#[test]
+fn list_json_reports_scope_and_shadowing() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let project = TempDir::new()?;
+    write_mode(&codex_home.path().join("modes"), "review.md", "global")?;
+    write_mode(
+        &project.path().join(".codex/modes"),
+        "review.md",
+        "---\nvariables:\n  - name: focus\n---\nreview {{focus}}",
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["modes", "list", "--json", "-C"])
+        .arg(project.path())
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    let entries = parsed.as_array().expect("array");
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry["id"], "/review");
+    assert_eq!(entry["kind"], "persistent");
+    assert!(
+        entry["scope"]
+            .as_str()
+            .unwrap_or_default()
+            .starts_with("project:"),
+        "{entry}"
+    );
+    assert_eq!(entry["variables"], serde_json::json!(["focus"]));
+    let shadows = entry["shadows"].as_array().expect("shadows");
+    assert_eq!(shadows.len(), 1);
+    assert!(
+        shadows[0]
+            .as_str()
+            .unwrap_or_default()
+            .ends_with("review.md"),
+        "{entry}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lint_json_reports_errors_and_fails() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let project = TempDir::new()?;
+    let modes_dir = project.path().join(".codex/modes");
+    write_mode(
+        &modes_dir,
+        "a.md",
+        "---\nvariables:\n  - name: x\n  - name: x\n---\nhi {{x}}",
+    )?;
+    write_mode(&modes_dir, "b.md", "hello {{who}}")?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["modes", "lint", "--json", "-C"])
+        .arg(project.path())
+        .output()?;
+    assert!(!output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    let codes: Vec<(&str, &str)> = parsed
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|issue| {
+            (
+                issue["code"].as_str().unwrap_or_default(),
+                issue["severity"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(codes, vec![("E2101", "error"), ("E3204", "warning")]);
+
+    Ok(())
+}
+
+#[test]
+fn lint_passes_clean_tree_unless_strict_with_warnings() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let project = TempDir::new()?;
+    write_mode(
+        &project.path().join(".codex/modes"),
+        "a.md",
+        "---\nvariables:\n  - name: unused\n---\nhello",
+    )?;
+
+    let mut lint = codex_command(codex_home.path())?;
+    lint.args(["modes", "lint", "-C"])
+        .arg(project.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "warning[E3203] /a: unused variable: unused",
+        ));
+
+    let mut strict = codex_command(codex_home.path())?;
+    strict
+        .args(["modes", "lint", "--strict", "-C"])
+        .arg(project.path())
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn render_prints_mode_instructions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let project = TempDir::new()?;
+    write_mode(
+        &project.path().join(".codex/modes"),
+        "review.md",
+        "---\nvariables:\n  - name: focus\n---\nreview {{focus}}",
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args([
+            "modes",
+            "render",
+            "--mode",
+            "/review",
+            "--mode-var",
+            "/review:focus=tests",
+            "--json",
+            "-C",
+        ])
+        .arg(project.path())
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed["enabled_modes"], serde_json::json!(["/review"]));
+    let text = parsed["user_instructions"].as_str().unwrap_or_default();
+    assert!(text.contains("review tests"), "{text}");
+
+    let mut unknown = codex_command(codex_home.path())?;
+    unknown
+        .args(["modes", "render", "--mode", "/missing", "-C"])
+        .arg(project.path())
+        .assert()
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,472 @@
+use std::path::Path;
+
+use anyhow::Result;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_rollout(
+    codex_home: &Path,
+    filename_ts: &str,
+    id: &str,
+    lines: &[JsonValue],
+) -> Result<()> {
+    let dir = codex_home
+        .join("sessions")
+        .join(&filename_ts[0..4])
+        .join(&filename_ts[5..7])
+        .join(&filename_ts[8..10]);
+    std::fs::create_dir_all(&dir)?;
+    let meta = json!({
+        "timestamp": "2025-01-01T00:00:00.000Z",
+        "type": "session_meta",
+        "payload": {
+            "id": id,
+            "timestamp": "2025-01-01T00:00:00.000Z",
+            "cwd": "/",
+            "originator": "codex",
+            "cli_version": "0.0.0",
+            "instructions": null,
+            "source": "cli"
+        }
+    });
+    let mut contents = format!("{meta}\n");
+    for line in lines {
+        contents.push_str(&format!("{line}\n"));
+    }
+    std::fs::write(
+        dir.join(format!("rollout-{filename_ts}-{id}.jsonl")),
+        contents,
+    )?;
+    Ok(())
+}
+
+fn user_message(text: &str) -> JsonValue {
+    json!({
+        "timestamp": "2025-01-01T00:00:00.000Z",
+        "type": "event_msg",
+        "payload": { "type": "user_message", "message": text, "kind": "plain" }
+    })
+}
+
+#[test]
+fn search_lists_matching_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_rollout(
+        codex_home.path(),
+        "2025-01-02T12-00-00",
+        "00000000-0000-0000-0000-000000000002",
+        &[
+            user_message("Why is the login test flaky?"),
+            json!({
+                "timestamp": "2025-01-02T12:00:01.000Z",
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": json!({ "command": ["cargo", "test", "login"] }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+        ],
+    )?;
+    write_rollout(
+        codex_home.path(),
+        "2025-01-01T12-00-00",
+        "00000000-0000-0000-0000-000000000001",
+        &[user_message("Update the changelog")],
+    )?;
+
+    let mut search_cmd = codex_command(codex_home.path())?;
+    search_cmd
+        .args(["sessions", "search", "cargo", "LOGIN"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "2025-01-02 12:00:00  00000000-0000-0000-0000-000000000002  Why is the login test flaky?",
+        ))
+        .stdout(contains("command  cargo test login"));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd
+        .args(["sessions", "search", "the", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    let ids: Vec<_> = parsed
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|hit| hit["id"].clone())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            json!("00000000-0000-0000-0000-000000000002"),
+            json!("00000000-0000-0000-0000-000000000001"),
+        ]
+    );
+    assert_eq!(
+        parsed[1]["matches"],
+        json!([{ "kind": "user_message", "text": "Update the changelog" }])
+    );
+
+    let mut empty_cmd = codex_command(codex_home.path())?;
+    empty_cmd
+        .args(["sessions", "search", "nothing-like-this"])
+        .assert()
+        .success()
+        .stdout(contains("No sessions match \"nothing-like-this\"."));
+
+    Ok(())
+}
+
+#[test]
+fn export_renders_transcript() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let id = "00000000-0000-0000-0000-000000000003";
+    write_rollout(
+        codex_home.path(),
+        "2025-01-03T12-00-00",
+        id,
+        &[
+            user_message("Deploy with token=supersecretvalue"),
+            json!({
+                "timestamp": "2025-01-03T12:00:01.000Z",
+                "type": "event_msg",
+                "payload": { "type": "agent_reasoning", "text": "Checking the deploy script" }
+            }),
+            json!({
+                "timestamp": "2025-01-03T12:00:02.000Z",
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": json!({ "command": ["./deploy.sh"] }).to_string(),
+                    "call_id": "call-1"
+                }
+            }),
+            json!({
+                "timestamp": "2025-01-03T12:00:03.000Z",
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call_output",
+                    "call_id": "call-1",
+                    "output": json!({
+                        "output": "deployed",
+                        "metadata": { "exit_code": 0, "duration_seconds": 0.5 }
+                    }).to_string()
+                }
+            }),
+        ],
+    )?;
+
+    let mut markdown_cmd = codex_command(codex_home.path())?;
+    markdown_cmd
+        .args(["sessions", "export", id])
+        .assert()
+        .success()
+        .stdout(contains(format!("# Codex session {id}")))
+        .stdout(contains("Deploy with token=supersecretvalue"))
+        .stdout(contains("> Checking the deploy script"))
+        .stdout(contains("$ ./deploy.sh"))
+        .stdout(contains("Exit code: 0"));
+
+    let output_path = codex_home.path().join("transcript.json");
+    let mut json_cmd = codex_command(codex_home.path())?;
+    json_cmd
+        .args([
+            "sessions",
+            "export",
+            id,
+            "--format",
+            "json",
+            "--redact",
+            "--no-reasoning",
+            "-o",
+        ])
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(contains("Exported session"));
+    let parsed: JsonValue = serde_json::from_str(&std::fs::read_to_string(&output_path)?)?;
+    assert_eq!(
+        parsed["entries"],
+        json!([
+            { "type": "user_message", "text": "Deploy with token=[REDACTED]" },
+            { "type": "command", "command": "./deploy.sh", "exit_code": 0, "output": "deployed" },
+        ])
+    );
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["sessions", "export", "00000000-0000-0000-0000-0000000000ff"])
+        .assert()
+        .failure()
+        .stderr(contains("No session found"));
+
+    Ok(())
+}
+
+#[test]
+fn gc_dry_run_lists_expired_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let id = "00000000-0000-0000-0000-000000000004";
+    write_rollout(
+        codex_home.path(),
+        "2025-01-04T12-00-00",
+        id,
+        &[user_message("An old session")],
+    )?;
+    let path = codex_home.path().join(format!(
+        "sessions/2025/01/04/rollout-2025-01-04T12-00-00-{id}.jsonl"
+    ));
+    let ninety_days = std::time::Duration::from_secs(90 * 24 * 60 * 60);
+    std::fs::File::options()
+        .append(true)
+        .open(&path)?
+        .set_modified(std::time::SystemTime::now() - ninety_days)?;
+
+    let mut unconfigured_cmd = codex_command(codex_home.path())?;
+    unconfigured_cmd
+        .args(["sessions", "gc"])
+        .assert()
+        .success()
+        .stdout(contains("No retention settings configured."));
+
+    let mut dry_run_cmd = codex_command(codex_home.path())?;
+    dry_run_cmd
+        .args([
+            "sessions",
+            "gc",
+            "--dry-run",
+            "-c",
+            "sessions.max_age_days=30",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(format!("Would delete {}", path.display())))
+        .stdout(contains("Would delete 1 session(s)"));
+    assert!(path.exists());
+
+    let mut gc_cmd = codex_command(codex_home.path())?;
+    gc_cmd
+        .args(["sessions", "gc", "-c", "sessions.max_age_days=30"])
+        .assert()
+        .success()
+        .stdout(contains("Deleted 1 session(s)"));
+    assert!(!path.exists());
+
+    Ok(())
+}
+
+#[test]
+fn title_tag_and_pin_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let tagged = "00000000-0000-0000-0000-000000000005";
+    let other = "00000000-0000-0000-0000-000000000006";
+    write_rollout(
+        codex_home.path(),
+        "2025-01-05T12-00-00",
+        tagged,
+        &[user_message("Fix the login flow")],
+    )?;
+    write_rollout(
+        codex_home.path(),
+        "2025-01-06T12-00-00",
+        other,
+        &[user_message("Something else")],
+    )?;
+
+    let mut title_cmd = codex_command(codex_home.path())?;
+    title_cmd
+        .args(["sessions", "title", tagged, "Login", "rework"])
+        .assert()
+        .success()
+        .stdout(contains("title:  Login rework"));
+
+    let mut tag_cmd = codex_command(codex_home.path())?;
+    tag_cmd
+        .args(["sessions", "tag", tagged, "#Auth", "wip"])
+        .assert()
+        .success()
+        .stdout(contains("tags:   auth, wip"));
+
+    let mut pin_cmd = codex_command(codex_home.path())?;
+    pin_cmd
+        .args(["sessions", "pin", tagged])
+        .assert()
+        .success()
+        .stdout(contains("pinned: yes"));
+
+    let mut untag_cmd = codex_command(codex_home.path())?;
+    untag_cmd
+        .args(["sessions", "untag", tagged, "wip"])
+        .assert()
+        .success()
+        .stdout(contains("tags:   auth"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["sessions", "list", "--tag", "auth"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "2025-01-01 00:00:00  {tagged} * Login rework  #auth"
+        )));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd.args(["sessions", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        parsed,
+        json!([
+            {
+                "id": other,
+                "started_at": "2025-01-01T00:00:00.000Z",
+                "title": null,
+                "tags": [],
+                "pinned": false,
+                "preview": null,
+            },
+            {
+                "id": tagged,
+                "started_at": "2025-01-01T00:00:00.000Z",
+                "title": "Login rework",
+                "tags": ["auth"],
+                "pinned": true,
+                "preview": null,
+            },
+        ])
+    );
+
+    let mut invalid_cmd = codex_command(codex_home.path())?;
+    invalid_cmd
+        .args(["sessions", "tag", tagged, "two words"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid tag"));
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["sessions", "pin", "00000000-0000-0000-0000-0000000000ff"])
+        .assert()
+        .failure()
+        .stderr(contains("No session found"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_aligns_turns_of_two_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let shell_call = |command: &[&str]| {
+        json!({
+            "timestamp": "2025-01-01T00:00:01.000Z",
+            "type": "response_item",
+            "payload": {
+                "type": "function_call",
+                "name": "shell",
+                "arguments": json!({ "command": command }).to_string(),
+                "call_id": "call-1"
+            }
+        })
+    };
+    let agent_message = |text: &str| {
+        json!({
+            "timestamp": "2025-01-01T00:00:02.000Z",
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": text }
+        })
+    };
+    write_rollout(
+        codex_home.path(),
+        "2025-01-01T12-00-00",
+        "00000000-0000-0000-0000-000000000001",
+        &[
+            user_message("Fix the test"),
+            shell_call(&["cargo", "test"]),
+            agent_message("Fixed."),
+            user_message("Commit it"),
+            agent_message("Committed."),
+        ],
+    )?;
+    write_rollout(
+        codex_home.path(),
+        "2025-01-02T12-00-00",
+        "00000000-0000-0000-0000-000000000002",
+        &[
+            user_message("Fix the test"),
+            shell_call(&["cargo", "test", "-p", "core"]),
+            agent_message("Fixed."),
+            user_message("Commit it"),
+            agent_message("Committed."),
+            user_message("Push it"),
+        ],
+    )?;
+    // Either session can be given by id or by its rollout file.
+    let right_path = codex_home
+        .path()
+        .join("sessions/2025/01/02/rollout-2025-01-02T12-00-00-00000000-0000-0000-0000-000000000002.jsonl");
+    let right_arg = right_path.to_string_lossy().to_string();
+
+    let mut diff_cmd = codex_command(codex_home.path())?;
+    diff_cmd
+        .args([
+            "sessions",
+            "diff",
+            "00000000-0000-0000-0000-000000000001",
+            &right_arg,
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Turn 1: differs in commands"))
+        .stdout(contains("    - cargo test\n    + cargo test -p core\n"))
+        .stdout(contains("Turn 2: same"))
+        .stdout(contains("Turn 3: only in B\n  user: Push it"));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd
+        .args([
+            "sessions",
+            "diff",
+            "00000000-0000-0000-0000-000000000001",
+            "00000000-0000-0000-0000-000000000002",
+            "--json",
+        ])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    let divergences: Vec<_> = parsed["turns"]
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|turn| turn["divergences"].clone())
+        .collect();
+    assert_eq!(divergences, vec![json!(["commands"]), json!([]), json!([])]);
+    assert_eq!(parsed["right"]["turns"], json!(3));
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args([
+            "sessions",
+            "diff",
+            "00000000-0000-0000-0000-000000000001",
+            "00000000-0000-0000-0000-000000000009",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "No session found with id 00000000-0000-0000-0000-000000000009.",
+        ));
+    Ok(())
+}
@@ -21,6 +21,8 @@ pub use config_override::CliConfigOverrides;
 
 mod sandbox_summary;
 
+#[cfg(feature = "sandbox_summary")]
+pub use sandbox_summary::summarize_read_access;
 #[cfg(feature = "sandbox_summary")]
 pub use sandbox_summary::summarize_sandbox_policy;
 
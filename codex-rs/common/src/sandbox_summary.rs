@@ -3,12 +3,21 @@ use codex_core::protocol::SandboxPolicy;
 pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
     match sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-        SandboxPolicy::ReadOnly => "read-only".to_string(),
+        SandboxPolicy::ReadOnly { .. } => {
+            let mut summary = "read-only".to_string();
+            if let Some(read_access) = summarize_read_access(sandbox_policy) {
+                summary.push_str(&format!(" ({read_access})"));
+            }
+            summary
+        }
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            network_allowlist,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
+            readable_roots: _,
+            deny_read_paths: _,
         } => {
             let mut summary = "workspace-write".to_string();
 
@@ -29,8 +38,55 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !network_allowlist.is_empty() {
+                summary.push_str(&format!(" (network: {})", network_allowlist.join(", ")));
+            }
+            if let Some(read_access) = summarize_read_access(sandbox_policy) {
+                summary.push_str(&format!(" ({read_access})"));
             }
             summary
         }
     }
 }
+
+/// Describes how file reads are restricted, e.g.
+/// `read: workdir, /srv/data; deny read: /home/me/.ssh`. Returns `None` when
+/// the entire file-system is readable.
+pub fn summarize_read_access(sandbox_policy: &SandboxPolicy) -> Option<String> {
+    let (readable_roots, deny_read_paths) = match sandbox_policy {
+        SandboxPolicy::DangerFullAccess => return None,
+        SandboxPolicy::ReadOnly {
+            readable_roots,
+            deny_read_paths,
+        }
+        | SandboxPolicy::WorkspaceWrite {
+            readable_roots,
+            deny_read_paths,
+            ..
+        } => (readable_roots, deny_read_paths),
+    };
+
+    let mut parts = Vec::<String>::new();
+    if !readable_roots.is_empty() {
+        let mut entries = vec!["workdir".to_string()];
+        entries.extend(
+            readable_roots
+                .iter()
+                .map(|p| p.to_string_lossy().to_string()),
+        );
+        parts.push(format!("read: {}", entries.join(", ")));
+    }
+    if !deny_read_paths.is_empty() {
+        let entries: Vec<String> = deny_read_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        parts.push(format!("deny read: {}", entries.join(", ")));
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("; "))
+    }
+}
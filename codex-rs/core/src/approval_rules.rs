@@ -0,0 +1,373 @@
+//! Persistent approval rules.
+//!
+//! When the user answers an exec approval prompt with "always allow", a rule is
+//! written to `$CODEX_HOME/approval_rules.toml`. A rule matches an exact argv,
+//! an argv prefix or a glob over the shell-quoted command line, optionally
+//! limited to commands whose working directory is inside a given directory.
+//! Matching commands run without a prompt, but still inside the sandbox.
+
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_protocol::protocol::ApprovalRuleScope;
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use wildmatch::WildMatch;
+
+use crate::bash::parse_bash_lc_plain_commands;
+use crate::command_safety::is_dangerous_command::command_might_be_dangerous;
+use crate::git_info::get_git_repo_root;
+
+pub const APPROVAL_RULES_FILE: &str = "approval_rules.toml";
+
+/// Maximum number of subcommand-like tokens kept after the program name when
+/// deriving a prefix from an approved command, e.g. `cargo test` or
+/// `git remote show`.
+const MAX_PREFIX_SUBCOMMANDS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    /// Short identifier used by `codex approvals revoke`.
+    pub id: String,
+    #[serde(flatten)]
+    pub pattern: RulePattern,
+    /// When set, the rule only applies to commands whose working directory is
+    /// this directory or one of its descendants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulePattern {
+    /// Matches commands whose argv is exactly these tokens.
+    Exact(Vec<String>),
+    /// Matches commands whose argv starts with these tokens.
+    Prefix(Vec<String>),
+    /// Matches the shell-quoted command line against a `*`/`?` glob.
+    Glob(String),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ApprovalRulesFile {
+    #[serde(default)]
+    rules: Vec<ApprovalRule>,
+}
+
+impl RulePattern {
+    /// Human-readable form of the pattern, e.g. `cargo test *`.
+    pub fn describe(&self) -> String {
+        match self {
+            RulePattern::Exact(argv) => describe_prefix(argv),
+            RulePattern::Prefix(prefix) => format!("{} *", describe_prefix(prefix)),
+            RulePattern::Glob(glob) => glob.clone(),
+        }
+    }
+}
+
+impl ApprovalRule {
+    /// Creates a prefix rule. See [`ApprovalRule::new`] for `scope`.
+    pub fn new_prefix(prefix: Vec<String>, scope: ApprovalRuleScope, cwd: &Path) -> Self {
+        Self::new(RulePattern::Prefix(prefix), scope, cwd)
+    }
+
+    /// Creates a rule for `pattern`. Project-scoped rules are limited to the
+    /// git repository containing `cwd`, or to `cwd` itself outside a
+    /// repository.
+    pub fn new(pattern: RulePattern, scope: ApprovalRuleScope, cwd: &Path) -> Self {
+        let cwd = match scope {
+            ApprovalRuleScope::Project => {
+                Some(get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf()))
+            }
+            ApprovalRuleScope::Global => None,
+        };
+        let mut id = uuid::Uuid::new_v4().simple().to_string();
+        id.truncate(8);
+        Self {
+            id,
+            pattern,
+            cwd,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Human-readable form of the pattern, e.g. `cargo test *`.
+    pub fn describe_pattern(&self) -> String {
+        self.pattern.describe()
+    }
+
+    fn matches_argv(&self, argv: &[String], cwd: &Path) -> bool {
+        if let Some(rule_cwd) = &self.cwd
+            && !cwd.starts_with(rule_cwd)
+        {
+            return false;
+        }
+        match &self.pattern {
+            RulePattern::Exact(exact) => !exact.is_empty() && argv == exact.as_slice(),
+            RulePattern::Prefix(prefix) => !prefix.is_empty() && argv.starts_with(prefix),
+            RulePattern::Glob(glob) => WildMatch::new(glob).matches(&describe_prefix(argv)),
+        }
+    }
+
+    /// Whether a same-shaped rule already exists, ignoring id and timestamp.
+    fn same_pattern(&self, other: &ApprovalRule) -> bool {
+        self.pattern == other.pattern && self.cwd == other.cwd
+    }
+}
+
+/// Returns true when `command` is covered by `rules`. For `bash -lc` scripts
+/// made of plain commands, every command in the script must be covered.
+pub fn command_matches_rules(rules: &[ApprovalRule], command: &[String], cwd: &Path) -> bool {
+    if rules.is_empty() || command.is_empty() {
+        return false;
+    }
+    let commands = parse_bash_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+    commands
+        .iter()
+        .all(|argv| !argv.is_empty() && rules.iter().any(|rule| rule.matches_argv(argv, cwd)))
+}
+
+/// Derives the pattern a persistent rule would use for `command`: the program
+/// followed by up to two subcommand-like words, e.g. `cargo test`. Commands
+/// without a subcommand, such as `rm -rf out`, and commands that might be
+/// dangerous only match their exact argv. Returns `None` when no safe pattern
+/// exists, e.g. for `bash -lc` scripts with several commands.
+pub fn rule_pattern_for_command(command: &[String]) -> Option<RulePattern> {
+    let argv = match parse_bash_lc_plain_commands(command) {
+        Some(mut commands) if commands.len() == 1 => commands.pop()?,
+        Some(_) => return None,
+        None if is_shell_invocation(command) => return None,
+        None => command.to_vec(),
+    };
+    let (program, rest) = argv.split_first()?;
+    if program.is_empty() {
+        return None;
+    }
+    let subcommands: Vec<String> = rest
+        .iter()
+        .take_while(|token| is_subcommand_like(token))
+        .take(MAX_PREFIX_SUBCOMMANDS)
+        .cloned()
+        .collect();
+    if subcommands.is_empty()
+        || subcommands.len() == rest.len()
+        || command_might_be_dangerous(&argv)
+    {
+        return Some(RulePattern::Exact(argv));
+    }
+    let mut prefix = vec![program.clone()];
+    prefix.extend(subcommands);
+    Some(RulePattern::Prefix(prefix))
+}
+
+/// Shell-quoted form of an argv prefix, e.g. `git remote show`.
+pub fn describe_prefix(prefix: &[String]) -> String {
+    shlex::try_join(prefix.iter().map(String::as_str)).unwrap_or_else(|_| prefix.join(" "))
+}
+
+pub fn rules_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(APPROVAL_RULES_FILE)
+}
+
+/// Loads rules from `$CODEX_HOME/approval_rules.toml`. A missing file yields no
+/// rules.
+pub fn load_rules(codex_home: &Path) -> std::io::Result<Vec<ApprovalRule>> {
+    let contents = match std::fs::read_to_string(rules_path(codex_home)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let file: ApprovalRulesFile =
+        toml::from_str(&contents).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+    Ok(file.rules)
+}
+
+/// Atomically replaces the rules file with `rules`.
+pub fn save_rules(codex_home: &Path, rules: &[ApprovalRule]) -> std::io::Result<()> {
+    let file = ApprovalRulesFile {
+        rules: rules.to_vec(),
+    };
+    let contents = toml::to_string_pretty(&file)
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+    std::fs::create_dir_all(codex_home)?;
+    let tmp_file = NamedTempFile::new_in(codex_home)?;
+    std::fs::write(tmp_file.path(), contents)?;
+    tmp_file
+        .persist(rules_path(codex_home))
+        .map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Adds `rule` unless an equivalent rule is already present. Returns whether
+/// the list changed.
+pub fn add_rule(rules: &mut Vec<ApprovalRule>, rule: ApprovalRule) -> bool {
+    if rules.iter().any(|existing| existing.same_pattern(&rule)) {
+        return false;
+    }
+    rules.push(rule);
+    true
+}
+
+fn is_shell_invocation(command: &[String]) -> bool {
+    matches!(
+        command,
+        [shell, flag, _] if matches!(shell.as_str(), "bash" | "sh" | "zsh")
+            && matches!(flag.as_str(), "-c" | "-lc")
+    )
+}
+
+fn is_subcommand_like(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn prefix_keeps_program_and_subcommands() {
+        assert_eq!(
+            rule_pattern_for_command(&vec_str(&["cargo", "test", "-p", "codex-core"])),
+            Some(RulePattern::Prefix(vec_str(&["cargo", "test"])))
+        );
+        assert_eq!(
+            rule_pattern_for_command(&vec_str(&["git", "remote", "show", "origin"])),
+            Some(RulePattern::Prefix(vec_str(&["git", "remote", "show"])))
+        );
+        assert_eq!(
+            rule_pattern_for_command(&vec_str(&["bash", "-lc", "npm run build --watch"])),
+            Some(RulePattern::Prefix(vec_str(&["npm", "run", "build"])))
+        );
+        assert_eq!(
+            rule_pattern_for_command(&vec_str(&["bash", "-lc", "ls && rm -rf out"])),
+            None
+        );
+        assert_eq!(
+            rule_pattern_for_command(&vec_str(&["bash", "-lc", "echo $(whoami)"])),
+            None
+        );
+    }
+
+    #[test]
+    fn commands_without_subcommands_match_exactly() {
+        for command in [
+            vec_str(&["rm", "-rf", "out"]),
+            vec_str(&["touch", "notes.txt"]),
+            vec_str(&["git", "reset", "--hard", "HEAD~1"]),
+            vec_str(&["npm", "run", "build"]),
+        ] {
+            assert_eq!(
+                rule_pattern_for_command(&command),
+                Some(RulePattern::Exact(command.clone()))
+            );
+        }
+
+        let rule = ApprovalRule::new(
+            RulePattern::Exact(vec_str(&["rm", "-rf", "out"])),
+            ApprovalRuleScope::Global,
+            Path::new("/work"),
+        );
+        assert_eq!(rule.describe_pattern(), "rm -rf out");
+        let rules = vec![rule];
+        let cwd = Path::new("/work");
+        assert!(command_matches_rules(
+            &rules,
+            &vec_str(&["rm", "-rf", "out"]),
+            cwd
+        ));
+        assert!(!command_matches_rules(
+            &rules,
+            &vec_str(&["rm", "-rf", "out", "/"]),
+            cwd
+        ));
+        assert!(!command_matches_rules(
+            &rules,
+            &vec_str(&["rm", "-rf"]),
+            cwd
+        ));
+    }
+
+    #[test]
+    fn rules_match_prefix_glob_and_cwd() {
+        let project = Path::new("/work/project");
+        let rules = vec![
+            ApprovalRule {
+                id: "a".to_string(),
+                pattern: RulePattern::Prefix(vec_str(&["cargo", "test"])),
+                cwd: Some(project.to_path_buf()),
+                created_at: Utc::now(),
+            },
+            ApprovalRule {
+                id: "b".to_string(),
+                pattern: RulePattern::Glob("git log *".to_string()),
+                cwd: None,
+                created_at: Utc::now(),
+            },
+        ];
+
+        let cargo_test = vec_str(&["cargo", "test", "--all"]);
+        assert!(command_matches_rules(
+            &rules,
+            &cargo_test,
+            &project.join("core")
+        ));
+        assert!(!command_matches_rules(
+            &rules,
+            &cargo_test,
+            Path::new("/tmp")
+        ));
+        assert!(command_matches_rules(
+            &rules,
+            &vec_str(&["git", "log", "--oneline"]),
+            Path::new("/tmp")
+        ));
+        assert!(command_matches_rules(
+            &rules,
+            &vec_str(&["bash", "-lc", "cargo test && git log -1"]),
+            project
+        ));
+        assert!(!command_matches_rules(
+            &rules,
+            &vec_str(&["bash", "-lc", "cargo test && rm -rf target"]),
+            project
+        ));
+    }
+
+    #[test]
+    fn save_and_load_round_trip_without_duplicates() {
+        let home = TempDir::new().expect("home");
+        assert_eq!(load_rules(home.path()).expect("load"), Vec::new());
+
+        let mut rules = Vec::new();
+        let rule = ApprovalRule::new_prefix(
+            vec_str(&["cargo", "check"]),
+            ApprovalRuleScope::Global,
+            home.path(),
+        );
+        assert!(add_rule(&mut rules, rule.clone()));
+        assert!(!add_rule(
+            &mut rules,
+            ApprovalRule::new_prefix(
+                vec_str(&["cargo", "check"]),
+                ApprovalRuleScope::Global,
+                home.path()
+            )
+        ));
+        save_rules(home.path(), &rules).expect("save");
+
+        assert_eq!(load_rules(home.path()).expect("load"), vec![rule]);
+    }
+}
@@ -222,13 +222,18 @@ impl ModelClient {
         // For Azure, we send `store: true` and preserve reasoning item IDs.
         let azure_workaround = self.provider.is_azure_responses_endpoint();
 
+        // Parallel-safe calls run concurrently, and the rest still run one at
+        // a time in order, so several calls per response are always handled.
+        let parallel_tool_calls = self.config.parallel_tool_calls
+            && self.config.model_family.supports_parallel_tool_calls;
+
         let payload = ResponsesApiRequest {
             model: &self.config.model,
             instructions: &full_instructions,
             input: &input_with_instructions,
             tools: &tools_json,
             tool_choice: "auto",
-            parallel_tool_calls: false,
+            parallel_tool_calls,
             reasoning,
             store: azure_workaround,
             stream: true,
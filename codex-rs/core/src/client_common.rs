@@ -182,6 +182,18 @@ pub(crate) mod tools {
         Freeform(FreeformTool),
     }
 
+    impl ToolSpec {
+        /// Name the model uses to call the tool.
+        pub(crate) fn name(&self) -> &str {
+            match self {
+                ToolSpec::Function(ResponsesApiTool { name, .. }) => name,
+                ToolSpec::LocalShell {} => "local_shell",
+                ToolSpec::WebSearch {} => "web_search",
+                ToolSpec::Freeform(FreeformTool { name, .. }) => name,
+            }
+        }
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     pub struct FreeformTool {
         pub(crate) name: String,
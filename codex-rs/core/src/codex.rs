@@ -23,6 +23,7 @@ use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::TaskStartedEvent;
 use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnContextItem;
+use futures::future::join_all;
 use futures::prelude::*;
 use mcp_types::CallToolResult;
 use serde_json;
@@ -37,11 +38,13 @@ use tracing::warn;
 
 use crate::ModelProviderInfo;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::approval_rules;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::container::ContainerSession;
 use crate::conversation_history::ConversationHistory;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
@@ -52,9 +55,11 @@ use crate::exec::StreamOutput;
 use crate::exec_command::ExecCommandParams;
 use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WriteStdinParams;
+use crate::exec_policy::ExecPolicy;
 use crate::executor::Executor;
 use crate::executor::ExecutorConfig;
 use crate::executor::normalize_exec_result;
+use crate::hooks::ToolHooks;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
@@ -93,6 +98,7 @@ use crate::protocol::TurnDiffEvent;
 use crate::protocol::WebSearchBeginEvent;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
+use crate::script_tools::load_script_tools;
 use crate::shell;
 use crate::state::ActiveTurn;
 use crate::state::SessionServices;
@@ -101,6 +107,7 @@ use crate::tasks::RegularTask;
 use crate::tasks::ReviewTask;
 use crate::tools::ToolRouter;
 use crate::tools::format_exec_output_str;
+use crate::tools::router::ToolCall;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_instructions::UserInstructions;
@@ -404,6 +411,45 @@ impl Session {
             }
         }
 
+        // Load the exec policy; unreadable or invalid policy files are skipped
+        // and reported.
+        let (exec_policy, exec_policy_errors) = ExecPolicy::load(&config.codex_home, &cwd);
+        for message in exec_policy_errors {
+            error!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
+        // Load user-defined tools; invalid tools are skipped and reported.
+        let (script_tools, script_tool_errors) = load_script_tools(&config.script_tools, &cwd);
+        for message in script_tool_errors {
+            error!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
+        // Load persistent approval rules; a broken rules file is reported and
+        // treated as empty until a new rule overwrites it.
+        let approval_rules = match approval_rules::load_rules(&config.codex_home) {
+            Ok(rules) => rules,
+            Err(e) => {
+                let message = format!(
+                    "failed to load approval rules from {}: {e}",
+                    approval_rules::rules_path(&config.codex_home).display()
+                );
+                error!("{message}");
+                post_session_configured_error_events.push(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Error(ErrorEvent { message }),
+                });
+                Vec::new()
+            }
+        };
+
         let otel_event_manager = OtelEventManager::new(
             conversation_id,
             config.model.as_str(),
@@ -448,7 +494,8 @@ impl Session {
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                 include_view_image_tool: config.include_view_image_tool,
                 experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
-            }),
+            })
+            .with_tool_filter(config.tool_filter.clone()),
             user_instructions: user_instructions.clone(),
             base_instructions,
             base_user_instructions: user_instructions,
@@ -459,19 +506,40 @@ impl Session {
             is_review_mode: false,
             final_output_json_schema: None,
         };
+        let container = config.container.clone().map(|container| {
+            Arc::new(
+                ContainerSession::new(
+                    container,
+                    turn_context.cwd.clone(),
+                    &turn_context.sandbox_policy,
+                )
+                .with_resource_limits(config.resource_limits),
+            )
+        });
         let services = SessionServices {
             mcp_connection_manager,
             session_manager: ExecSessionManager::default(),
-            unified_exec_manager: UnifiedExecSessionManager::default(),
+            unified_exec_manager: UnifiedExecSessionManager::with_resource_limits(
+                config.resource_limits,
+            )
+            .with_container(container.clone()),
             notifier: notify,
             rollout: Mutex::new(Some(rollout_recorder)),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
-            executor: Executor::new(ExecutorConfig::new(
-                turn_context.sandbox_policy.clone(),
-                turn_context.cwd.clone(),
-                config.codex_linux_sandbox_exe.clone(),
-            )),
+            executor: Executor::new(
+                ExecutorConfig::new(
+                    turn_context.sandbox_policy.clone(),
+                    turn_context.cwd.clone(),
+                    config.codex_linux_sandbox_exe.clone(),
+                )
+                .with_exec_policy(exec_policy)
+                .with_resource_limits(config.resource_limits),
+            )
+            .with_approval_rules(config.codex_home.clone(), approval_rules)
+            .with_container(container),
+            hooks: ToolHooks::new(config.hooks.clone()),
+            script_tools,
         };
 
         let sess = Arc::new(Session {
@@ -486,6 +554,7 @@ impl Session {
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
         let initial_messages = initial_history.get_event_msgs();
+        let mode_state = initial_history.get_mode_state();
         sess.record_initial_history(&turn_context, initial_history)
             .await;
 
@@ -498,6 +567,7 @@ impl Session {
                 history_log_id,
                 history_entry_count,
                 initial_messages,
+                mode_state,
                 rollout_path,
             }),
         })
@@ -572,6 +642,7 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        matched_rule: Option<String>,
     ) -> ReviewDecision {
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -597,6 +668,7 @@ impl Session {
                 command,
                 cwd,
                 reason,
+                matched_rule,
             }),
         };
         self.send_event(event).await;
@@ -866,6 +938,7 @@ impl Session {
             duration,
             exit_code,
             timed_out: _,
+            resource_limit_exceeded,
         } = output;
         // Send full stdout/stderr to clients; do not truncate.
         let stdout = stdout.text.clone();
@@ -889,6 +962,7 @@ impl Session {
                 exit_code: *exit_code,
                 duration: *duration,
                 formatted_output,
+                limit_exceeded: *resource_limit_exceeded,
             })
         };
 
@@ -1057,8 +1131,12 @@ impl Session {
 
     pub(crate) async fn run_unified_exec_request(
         &self,
+        turn_context: &TurnContext,
         request: crate::unified_exec::UnifiedExecRequest<'_>,
     ) -> Result<crate::unified_exec::UnifiedExecResult, crate::unified_exec::UnifiedExecError> {
+        self.services
+            .unified_exec_manager
+            .update_environment(turn_context.cwd.clone(), &turn_context.sandbox_policy);
         self.services
             .unified_exec_manager
             .handle_request(request)
@@ -1125,6 +1203,7 @@ async fn submission_loop(
                 effort,
                 summary,
                 user_instructions,
+                tool_filter,
             } => {
                 // Recalculate the persistent turn context with provided overrides.
                 let prev = Arc::clone(&turn_context);
@@ -1182,7 +1261,10 @@ async fn submission_loop(
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                     include_view_image_tool: config.include_view_image_tool,
                     experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
-                });
+                })
+                .with_tool_filter(
+                    tool_filter.unwrap_or_else(|| prev.tools_config.tool_filter.clone()),
+                );
 
                 let new_turn_context = TurnContext {
                     client,
@@ -1330,7 +1412,8 @@ async fn submission_loop(
                             include_view_image_tool: config.include_view_image_tool,
                             experimental_unified_exec_tool: config
                                 .use_experimental_unified_exec_tool,
-                        }),
+                        })
+                        .with_tool_filter(turn_context.tools_config.tool_filter.clone()),
                         user_instructions: turn_context.user_instructions.clone(),
                         base_instructions: turn_context.base_instructions.clone(),
                         base_user_instructions: turn_context.base_user_instructions.clone(),
@@ -1430,10 +1513,19 @@ async fn submission_loop(
 
                 // This is a cheap lookup from the connection manager's cache.
                 let tools = sess.services.mcp_connection_manager.list_all_tools();
+                let mut hidden_tools: Vec<String> = tools
+                    .keys()
+                    .filter(|name| !turn_context.tools_config.exposes_tool(name))
+                    .cloned()
+                    .collect();
+                hidden_tools.sort();
                 let event = Event {
                     id: sub_id,
                     msg: EventMsg::McpListToolsResponse(
-                        crate::protocol::McpListToolsResponseEvent { tools },
+                        crate::protocol::McpListToolsResponseEvent {
+                            tools,
+                            hidden_tools,
+                        },
                     ),
                 };
                 sess.send_event(event).await;
@@ -1535,6 +1627,10 @@ async fn submission_loop(
                 )
                 .await;
             }
+            Op::RecordModeState { mode_state } => {
+                sess.persist_rollout_items(&[RolloutItem::ModeState(mode_state)])
+                    .await;
+            }
             _ => {
                 // Ignore unknown ops; enum is non_exhaustive to allow extensions.
             }
@@ -1562,7 +1658,8 @@ async fn spawn_review_thread(
         use_streamable_shell_tool: false,
         include_view_image_tool: false,
         experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
-    });
+    })
+    .with_tool_filter(parent_turn_context.tools_config.tool_filter.clone());
 
     let base_instructions = REVIEW_PROMPT.to_string();
     let review_prompt = review_request.prompt.clone();
@@ -1963,7 +2060,11 @@ async fn run_turn(
     input: Vec<ResponseItem>,
 ) -> CodexResult<TurnRunResult> {
     let mcp_tools = sess.services.mcp_connection_manager.list_all_tools();
-    let router = ToolRouter::from_config(&turn_context.tools_config, Some(mcp_tools));
+    let router = ToolRouter::from_config(
+        &turn_context.tools_config,
+        Some(mcp_tools),
+        &sess.services.script_tools,
+    );
 
     let prompt = Prompt {
         input,
@@ -2120,6 +2221,10 @@ async fn try_run_turn(
     let mut stream = turn_context.client.clone().stream(&prompt).await?;
 
     let mut output = Vec::new();
+    // Parallel-safe tool calls wait here, with the index of their slot in
+    // `output`, until the next call that must run on its own or the end of the
+    // response, and then run concurrently.
+    let mut parallel_calls: Vec<(usize, ToolCall)> = Vec::new();
 
     loop {
         // Poll the next item from the model stream. We must inspect *both* Ok and Err
@@ -2147,6 +2252,31 @@ async fn try_run_turn(
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
+                match ToolRouter::build_tool_call(sess, item.clone()) {
+                    Ok(Some(call)) if router.supports_parallel_calls(&call) => {
+                        debug!(?item, "Output item");
+                        let payload_preview = call.payload.log_payload().into_owned();
+                        tracing::info!("ToolCall: {} {}", call.tool_name, payload_preview);
+                        parallel_calls.push((output.len(), call));
+                        output.push(ProcessedResponseItem {
+                            item,
+                            response: None,
+                        });
+                        continue;
+                    }
+                    Ok(Some(_)) | Err(_) => {
+                        run_parallel_tool_calls(
+                            router,
+                            sess,
+                            turn_context,
+                            sub_id,
+                            std::mem::take(&mut parallel_calls),
+                            &mut output,
+                        )
+                        .await?;
+                    }
+                    Ok(None) => {}
+                }
                 let response = handle_response_item(
                     router,
                     sess,
@@ -2176,6 +2306,15 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
+                run_parallel_tool_calls(
+                    router,
+                    sess,
+                    turn_context,
+                    sub_id,
+                    std::mem::take(&mut parallel_calls),
+                    &mut output,
+                )
+                .await?;
                 sess.update_token_usage_info(sub_id, turn_context, token_usage.as_ref())
                     .await;
 
@@ -2238,6 +2377,39 @@ async fn try_run_turn(
     }
 }
 
+/// Runs `calls` concurrently and stores each response in its slot in
+/// `output`.
+async fn run_parallel_tool_calls(
+    router: &crate::tools::ToolRouter,
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    calls: Vec<(usize, ToolCall)>,
+    output: &mut [ProcessedResponseItem],
+) -> CodexResult<()> {
+    if calls.is_empty() {
+        return Ok(());
+    }
+    let results = join_all(calls.into_iter().map(|(index, call)| async move {
+        // Parallel-safe tools never edit files, so nothing is recorded in
+        // the turn's diff tracker.
+        let mut tracker = TurnDiffTracker::new();
+        let result = router
+            .dispatch_tool_call(sess, turn_context, &mut tracker, sub_id, call)
+            .await;
+        (index, result)
+    }))
+    .await;
+    for (index, result) in results {
+        match result {
+            Ok(response) => output[index].response = Some(response),
+            Err(FunctionCallError::Fatal(message)) => return Err(CodexErr::Fatal(message)),
+            Err(other) => unreachable!("non-fatal tool error returned: {other:?}"),
+        }
+    }
+    Ok(())
+}
+
 async fn handle_response_item(
     router: &crate::tools::ToolRouter,
     sess: &Session,
@@ -2466,6 +2638,7 @@ mod tests {
     use pretty_assertions::assert_eq;
     use serde::Deserialize;
     use serde_json::json;
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
     use std::sync::Arc;
     use std::time::Duration as StdDuration;
@@ -2551,6 +2724,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -2594,6 +2768,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -2625,6 +2800,7 @@ mod tests {
             aggregated_output: StreamOutput::new("Command output".to_string()),
             duration: StdDuration::from_secs(1),
             timed_out: true,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -2763,6 +2939,8 @@ mod tests {
                 turn_context.cwd.clone(),
                 None,
             )),
+            hooks: ToolHooks::default(),
+            script_tools: BTreeMap::new(),
         };
         let session = Session {
             conversation_id,
@@ -2837,6 +3015,8 @@ mod tests {
                 config.cwd.clone(),
                 None,
             )),
+            hooks: ToolHooks::default(),
+            script_tools: BTreeMap::new(),
         };
         let session = Arc::new(Session {
             conversation_id,
@@ -2958,6 +3138,7 @@ mod tests {
         let router = ToolRouter::from_config(
             &turn_context_ref.tools_config,
             Some(session_ref.services.mcp_connection_manager.list_all_tools()),
+            &session_ref.services.script_tools,
         );
         let mut tracker = TurnDiffTracker::new();
         let item = ResponseItem::CustomToolCall {
@@ -3,8 +3,10 @@ pub use crate::config_loader::load_config_as_toml;
 use crate::config_loader::load_config_layers_with_overrides;
 use crate::config_loader::merge_toml_values;
 use crate::config_profile::ConfigProfile;
+use crate::config_types::ContainerConfig;
 use crate::config_types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config_types::History;
+use crate::config_types::HooksConfig;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
 use crate::config_types::Notifications;
@@ -12,7 +14,10 @@ use crate::config_types::OtelConfig;
 use crate::config_types::OtelConfigToml;
 use crate::config_types::OtelExporterKind;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::SandboxRead;
 use crate::config_types::SandboxWorkspaceWrite;
+use crate::config_types::ScriptToolConfig;
+use crate::config_types::SessionsConfig;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::Tui;
@@ -26,12 +31,14 @@ use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
 use anyhow::Context;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::ToolFilter;
 use codex_protocol::config_types::Verbosity;
 use dirs::home_dir;
 use serde::Deserialize;
@@ -93,6 +100,24 @@ pub struct Config {
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
+    /// Resource limits applied to every command run for the model.
+    pub resource_limits: ResourceLimits,
+
+    /// When set, shell commands run inside this container instead of on the
+    /// host.
+    pub container: Option<ContainerConfig>,
+
+    /// Commands run before and after tool calls.
+    pub hooks: HooksConfig,
+
+    /// Function tools backed by local commands, from `[tools.scripts.<name>]`.
+    /// Project tools in `.codex/tools` are added when a session starts.
+    pub script_tools: BTreeMap<String, ScriptToolConfig>,
+
+    /// Which tools are exposed to the model. Can be replaced for the rest of a
+    /// session with `Op::OverrideTurnContext`.
+    pub tool_filter: ToolFilter,
+
     /// When `true`, `AgentReasoning` events emitted by the backend will be
     /// suppressed from the frontend output. This can reduce visual noise when
     /// users are only interested in the final agent responses.
@@ -158,6 +183,9 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Settings for recorded sessions under `~/.codex/sessions`.
+    pub sessions: SessionsConfig,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -206,6 +234,10 @@ pub struct Config {
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
 
+    /// Let the model request several tool calls in one response when the
+    /// model family supports it.
+    pub parallel_tool_calls: bool,
+
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
@@ -657,6 +689,24 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Read restrictions to apply if `sandbox` is `ReadOnly` or
+    /// `WorkspaceWrite`.
+    pub sandbox_read: Option<SandboxRead>,
+
+    /// Limits on CPU time, memory, processes, file size and output size for
+    /// commands run on behalf of the model.
+    pub resource_limits: Option<ResourceLimits>,
+
+    /// Run shell commands inside a long-lived OCI container.
+    pub container: Option<ContainerConfig>,
+
+    /// Commands that can allow, deny or rewrite tool calls before they run and
+    /// observe their results afterwards.
+    pub hooks: Option<HooksConfig>,
+
+    /// Include/exclude patterns limiting which tools are exposed to the model.
+    pub tool_filter: Option<ToolFilter>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -689,6 +739,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Settings for recorded sessions under `~/.codex/sessions`.
+    #[serde(default)]
+    pub sessions: Option<SessionsConfig>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
@@ -775,6 +829,15 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Let the model request several tool calls in one response.
+    #[serde(default)]
+    pub parallel_tool_calls: Option<bool>,
+
+    /// `[tools.scripts.<name>]` tables declaring function tools backed by
+    /// local commands.
+    #[serde(default)]
+    pub scripts: BTreeMap<String, ScriptToolConfig>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -792,21 +855,46 @@ impl ConfigToml {
         let resolved_sandbox_mode = sandbox_mode_override
             .or(self.sandbox_mode)
             .unwrap_or_default();
+        let (readable_roots, deny_read_paths) = match self.sandbox_read.as_ref() {
+            Some(SandboxRead {
+                readable_roots,
+                deny_read_paths,
+            }) => (
+                readable_roots.iter().map(|p| expand_home(p)).collect(),
+                deny_read_paths.iter().map(|p| expand_home(p)).collect(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
         match resolved_sandbox_mode {
-            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::ReadOnly => SandboxPolicy::ReadOnly {
+                readable_roots,
+                deny_read_paths,
+            },
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    network_allowlist: network_allowlist.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    readable_roots,
+                    deny_read_paths,
+                },
+                None => SandboxPolicy::WorkspaceWrite {
+                    writable_roots: vec![],
+                    network_access: false,
+                    network_allowlist: vec![],
+                    exclude_tmpdir_env_var: false,
+                    exclude_slash_tmp: false,
+                    readable_roots,
+                    deny_read_paths,
                 },
-                None => SandboxPolicy::new_workspace_write_policy(),
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
         }
@@ -969,6 +1057,7 @@ impl Config {
         };
 
         let history = cfg.history.unwrap_or_default();
+        let sessions = cfg.sessions.unwrap_or_default();
 
         let tools_web_search_request = override_tools_web_search_request
             .or(cfg.tools.as_ref().and_then(|t| t.web_search))
@@ -978,6 +1067,12 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
+        let parallel_tool_calls = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.parallel_tool_calls)
+            .unwrap_or(false);
+
         let model = model
             .or(config_profile.model)
             .or(cfg.model)
@@ -1040,6 +1135,18 @@ impl Config {
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
             shell_environment_policy,
+            resource_limits: cfg.resource_limits.unwrap_or_default(),
+            container: cfg.container.map(expand_container_mounts),
+            hooks: cfg.hooks.unwrap_or_default(),
+            script_tools: cfg
+                .tools
+                .as_ref()
+                .map(|tools| tools.scripts.clone())
+                .unwrap_or_default(),
+            tool_filter: config_profile
+                .tool_filter
+                .or(cfg.tool_filter)
+                .unwrap_or_default(),
             notify: cfg.notify,
             user_instructions,
             base_instructions,
@@ -1061,6 +1168,7 @@ impl Config {
                 .collect(),
             codex_home,
             history,
+            sessions,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
 
@@ -1092,6 +1200,7 @@ impl Config {
                 .unwrap_or(false),
             use_experimental_use_rmcp_client: cfg.experimental_use_rmcp_client.unwrap_or(false),
             include_view_image_tool,
+            parallel_tool_calls,
             active_profile: active_profile_name,
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
             tui_notifications: cfg
@@ -1211,6 +1320,29 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Replaces a leading `~` in `path` with the home directory, if known.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Expands a leading `~/` in the source of each `source:target` mount.
+fn expand_container_mounts(mut container: ContainerConfig) -> ContainerConfig {
+    container.mounts = container
+        .mounts
+        .into_iter()
+        .map(|mount| match mount.split_once(':') {
+            Some((source, rest)) => {
+                format!("{}:{rest}", expand_home(Path::new(source)).display())
+            }
+            None => mount,
+        })
+        .collect();
+    container
+}
+
 /// Returns the path to the folder where Codex logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -1223,6 +1355,7 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 mod tests {
     use crate::config_types::HistoryPersistence;
     use crate::config_types::Notifications;
+    use crate::config_types::ToolHookConfig;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1302,7 +1435,7 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::new_read_only_policy(),
             sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
 
@@ -1324,11 +1457,203 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                network_allowlist: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                readable_roots: vec![],
+                deny_read_paths: vec![],
             },
             sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
+
+        let sandbox_read = r#"
+sandbox_mode = "read-only"
+
+[sandbox_read]
+readable_roots = ["/srv/data"]
+deny_read_paths = ["~/.ssh", "/etc/secrets"]
+"#;
+
+        let sandbox_read_cfg = toml::from_str::<ConfigToml>(sandbox_read)
+            .expect("TOML deserialization should succeed");
+        let home = home_dir().expect("home dir");
+        assert_eq!(
+            SandboxPolicy::ReadOnly {
+                readable_roots: vec![PathBuf::from("/srv/data")],
+                deny_read_paths: vec![home.join(".ssh"), PathBuf::from("/etc/secrets")],
+            },
+            sandbox_read_cfg.derive_sandbox_policy(None)
+        );
+    }
+
+    #[test]
+    fn test_container_config_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[container]
+image = "ghcr.io/acme/toolchain:1.2"
+mounts = ["~/.cargo/registry:/root/.cargo/registry:ro", "/opt/cache:/cache"]
+env = { CARGO_TERM_COLOR = "always" }
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let container = expand_container_mounts(cfg.container.expect("container config"));
+        let home = home_dir().expect("home dir");
+        assert_eq!(
+            ContainerConfig {
+                runtime: "podman".to_string(),
+                image: "ghcr.io/acme/toolchain:1.2".to_string(),
+                mounts: vec![
+                    format!(
+                        "{}:/root/.cargo/registry:ro",
+                        home.join(".cargo/registry").display()
+                    ),
+                    "/opt/cache:/cache".to_string(),
+                ],
+                env: HashMap::from([("CARGO_TERM_COLOR".to_string(), "always".to_string())]),
+                run_args: Vec::new(),
+            },
+            container
+        );
+    }
+
+    #[test]
+    fn test_hooks_config_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[[hooks.pre_tool_use]]
+command = ["./scripts/check-command.sh"]
+tools = ["shell", "local_shell"]
+timeout_ms = 2000
+
+[[hooks.post_tool_use]]
+command = ["cargo", "fmt"]
+tools = ["apply_patch"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            Some(HooksConfig {
+                pre_tool_use: vec![ToolHookConfig {
+                    command: vec!["./scripts/check-command.sh".to_string()],
+                    tools: vec!["shell".to_string(), "local_shell".to_string()],
+                    timeout_ms: Some(2000),
+                }],
+                post_tool_use: vec![ToolHookConfig {
+                    command: vec!["cargo".to_string(), "fmt".to_string()],
+                    tools: vec!["apply_patch".to_string()],
+                    timeout_ms: None,
+                }],
+            }),
+            cfg.hooks
+        );
+    }
+
+    #[test]
+    fn test_script_tools_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tools]
+web_search = true
+# Toggles this version does not know about are ignored.
+some_future_toggle = true
+
+[tools.scripts.query_schema]
+description = "Look up a schema"
+command = ["schema-registry", "get"]
+
+[tools.scripts.query_schema.parameters]
+type = "object"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let tools = cfg.tools.expect("tools table");
+        assert_eq!(Some(true), tools.web_search);
+        assert_eq!(
+            BTreeMap::from([(
+                "query_schema".to_string(),
+                ScriptToolConfig {
+                    description: "Look up a schema".to_string(),
+                    command: vec!["schema-registry".to_string(), "get".to_string()],
+                    parameters: serde_json::json!({ "type": "object" }),
+                    timeout_ms: None,
+                },
+            )]),
+            tools.scripts
+        );
+    }
+
+    #[test]
+    fn test_profile_tool_filter_replaces_top_level_filter() -> std::io::Result<()> {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tool_filter]
+exclude = ["apply_patch"]
+
+[profiles.readonly.tool_filter]
+include = ["shell", "read_*"]
+exclude = ["github/delete_*"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg.clone(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            ToolFilter {
+                include: Vec::new(),
+                exclude: vec!["apply_patch".to_string()],
+            },
+            config.tool_filter
+        );
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                config_profile: Some("readonly".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(
+            ToolFilter {
+                include: vec!["shell".to_string(), "read_*".to_string()],
+                exclude: vec!["github/delete_*".to_string()],
+            },
+            config.tool_filter
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resource_limits_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[resource_limits]
+cpu_time_secs = 60
+max_memory_mb = 2048
+max_output_mb = 16
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            Some(ResourceLimits {
+                cpu_time_secs: Some(60),
+                max_memory_mb: Some(2048),
+                max_processes: None,
+                max_file_size_mb: None,
+                max_output_mb: Some(16),
+            }),
+            cfg.resource_limits
+        );
     }
 
     #[tokio::test]
@@ -1859,6 +2184,11 @@ model_verbosity = "high"
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
+                resource_limits: ResourceLimits::default(),
+                container: None,
+                hooks: HooksConfig::default(),
+                script_tools: BTreeMap::new(),
+                tool_filter: ToolFilter::default(),
                 user_instructions: None,
                 notify: None,
                 cwd: fixture.cwd(),
@@ -1868,6 +2198,7 @@ model_verbosity = "high"
                 project_doc_fallback_filenames: Vec::new(),
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                sessions: SessionsConfig::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
@@ -1884,6 +2215,7 @@ model_verbosity = "high"
                 use_experimental_unified_exec_tool: false,
                 use_experimental_use_rmcp_client: false,
                 include_view_image_tool: true,
+                parallel_tool_calls: false,
                 active_profile: Some("o3".to_string()),
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
@@ -1920,6 +2252,11 @@ model_verbosity = "high"
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            container: None,
+            hooks: HooksConfig::default(),
+            script_tools: BTreeMap::new(),
+            tool_filter: ToolFilter::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
@@ -1929,6 +2266,7 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            sessions: SessionsConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -1945,6 +2283,7 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            parallel_tool_calls: false,
             active_profile: Some("gpt3".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -1996,6 +2335,11 @@ model_verbosity = "high"
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            container: None,
+            hooks: HooksConfig::default(),
+            script_tools: BTreeMap::new(),
+            tool_filter: ToolFilter::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
@@ -2005,6 +2349,7 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            sessions: SessionsConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -2021,6 +2366,7 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            parallel_tool_calls: false,
             active_profile: Some("zdr".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -2058,6 +2404,11 @@ model_verbosity = "high"
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
+            container: None,
+            hooks: HooksConfig::default(),
+            script_tools: BTreeMap::new(),
+            tool_filter: ToolFilter::default(),
             user_instructions: None,
             notify: None,
             cwd: fixture.cwd(),
@@ -2067,6 +2418,7 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             codex_home: fixture.codex_home(),
             history: History::default(),
+            sessions: SessionsConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -2083,6 +2435,7 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            parallel_tool_calls: false,
             active_profile: Some("gpt5".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -221,6 +221,47 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Settings for recorded sessions under `~/.codex/sessions`. The retention
+/// settings are only applied when `codex sessions gc` runs, never in the
+/// background.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionsConfig {
+    /// Delete sessions that were last written more than this many days ago.
+    pub max_age_days: Option<u64>,
+
+    /// Delete the oldest sessions until all rollout files together take up
+    /// at most this many megabytes.
+    pub max_total_size_mb: Option<u64>,
+
+    /// Gzip sessions that were last written more than this many days ago.
+    /// Compressed sessions can still be listed, searched and resumed.
+    pub compress_after_days: Option<u64>,
+
+    /// Never delete pinned sessions.
+    #[serde(default = "default_keep_pinned")]
+    pub keep_pinned: bool,
+
+    /// Title new sessions after their first message.
+    #[serde(default)]
+    pub auto_title: bool,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_total_size_mb: None,
+            compress_after_days: None,
+            keep_pinned: true,
+            auto_title: false,
+        }
+    }
+}
+
+const fn default_keep_pinned() -> bool {
+    true
+}
+
 // ===== OTEL configuration =====
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -308,6 +349,8 @@ pub struct SandboxWorkspaceWrite {
     #[serde(default)]
     pub network_access: bool,
     #[serde(default)]
+    pub network_allowlist: Vec<String>,
+    #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
     pub exclude_slash_tmp: bool,
@@ -318,12 +361,96 @@ impl From<SandboxWorkspaceWrite> for codex_app_server_protocol::SandboxSettings
         Self {
             writable_roots: sandbox_workspace_write.writable_roots,
             network_access: Some(sandbox_workspace_write.network_access),
+            network_allowlist: sandbox_workspace_write.network_allowlist,
             exclude_tmpdir_env_var: Some(sandbox_workspace_write.exclude_tmpdir_env_var),
             exclude_slash_tmp: Some(sandbox_workspace_write.exclude_slash_tmp),
         }
     }
 }
 
+/// Read restrictions applied to the `read-only` and `workspace-write`
+/// sandboxes. A leading `~/` in a path refers to the home directory.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SandboxRead {
+    /// When non-empty, only these folders (plus the workspace and system
+    /// folders) are readable instead of the entire file-system.
+    #[serde(default)]
+    pub readable_roots: Vec<PathBuf>,
+    /// Paths that are never readable, e.g. `~/.ssh`.
+    #[serde(default)]
+    pub deny_read_paths: Vec<PathBuf>,
+}
+
+/// Settings for running shell commands inside a long-lived OCI container
+/// instead of on the host.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ContainerConfig {
+    /// Container CLI used to manage the container, e.g. `podman` or `docker`.
+    #[serde(default = "default_container_runtime")]
+    pub runtime: String,
+    /// Image the container is created from.
+    pub image: String,
+    /// Extra bind mounts in `source:target[:options]` form. A leading `~/` in
+    /// the source refers to the home directory.
+    #[serde(default)]
+    pub mounts: Vec<String>,
+    /// Environment variables set inside the container.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Extra arguments passed to `<runtime> run`, e.g. `["--cpus=4"]`.
+    #[serde(default)]
+    pub run_args: Vec<String>,
+}
+
+fn default_container_runtime() -> String {
+    "podman".to_string()
+}
+
+/// Commands run before and after tool calls, from the `[hooks]` table.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HooksConfig {
+    /// Run before a tool call; may allow, deny or rewrite it.
+    #[serde(default)]
+    pub pre_tool_use: Vec<ToolHookConfig>,
+    /// Run after a tool call with its output; cannot change the result.
+    #[serde(default)]
+    pub post_tool_use: Vec<ToolHookConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolHookConfig {
+    /// Program and arguments. The hook input is written to its stdin as JSON.
+    pub command: Vec<String>,
+    /// Tool names the hook applies to, e.g. `["shell", "apply_patch"]`. Empty
+    /// means every tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// How long the hook may run before it is killed. Defaults to 10 seconds.
+    pub timeout_ms: Option<u64>,
+}
+
+/// A function tool backed by a local command, declared as
+/// `[tools.scripts.<name>]` in `config.toml` or in `.codex/tools/<name>.toml`
+/// at the project root.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ScriptToolConfig {
+    /// Shown to the model in the tool definition.
+    pub description: String,
+    /// Program and arguments. The JSON arguments from the model are appended
+    /// as the last argument.
+    pub command: Vec<String>,
+    /// JSON schema of the arguments. Defaults to an object without
+    /// properties.
+    #[serde(default = "default_script_tool_parameters")]
+    pub parameters: serde_json::Value,
+    /// How long the command may run. Defaults to the shell tool's timeout.
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_script_tool_parameters() -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
@@ -0,0 +1,557 @@
+//! Runs model commands inside a long-lived OCI container.
+//!
+//! When `[container]` is configured, a container is started from the
+//! configured image the first time a command needs it, with the workspace
+//! bind-mounted at the same path it has on the host. Each command then runs
+//! through `<runtime> exec`, so paths in commands and tool output are the same
+//! inside and outside the container. When the working directory or sandbox
+//! policy changes, the container is recreated under the same name with the new
+//! mounts before the next command runs. The container is removed when the
+//! session ends.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use tokio::process::Command;
+
+use crate::config_types::ContainerConfig;
+use crate::protocol::ResourceLimitKind;
+use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
+use crate::resource_limits::exceeded_limit_for_signal;
+use crate::resource_limits::signal_from_exit_code;
+
+/// Memory cgroup events of the container, as seen from inside it (cgroup v2).
+const MEMORY_EVENTS_PATH: &str = "/sys/fs/cgroup/memory.events";
+
+const SIGKILL_CODE: i32 = 9;
+
+/// Mounts and network access a container is started with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ContainerEnvironment {
+    workspace: PathBuf,
+    read_only_workspace: bool,
+    writable_roots: Vec<PathBuf>,
+    network_access: bool,
+}
+
+impl ContainerEnvironment {
+    /// The workspace is mounted read-only and networking is disabled when
+    /// `sandbox_policy` does not allow writes or network access. Extra
+    /// writable roots of the policy are mounted read-write at the same path.
+    fn new(workspace: PathBuf, sandbox_policy: &SandboxPolicy) -> Self {
+        let writable_roots = match sandbox_policy {
+            SandboxPolicy::WorkspaceWrite { writable_roots, .. } => writable_roots
+                .iter()
+                .filter(|root| **root != workspace)
+                .cloned()
+                .collect(),
+            SandboxPolicy::ReadOnly { .. } | SandboxPolicy::DangerFullAccess => Vec::new(),
+        };
+        Self {
+            read_only_workspace: matches!(sandbox_policy, SandboxPolicy::ReadOnly { .. }),
+            network_access: sandbox_policy.has_full_network_access(),
+            writable_roots,
+            workspace,
+        }
+    }
+}
+
+/// A container shared by every command of one session.
+#[derive(Debug)]
+pub(crate) struct ContainerSession {
+    config: ContainerConfig,
+    name: String,
+    resource_limits: ResourceLimits,
+
+    /// Environment for the current turn.
+    environment: std::sync::Mutex<ContainerEnvironment>,
+
+    /// Environment the running container was started with, if any.
+    running: tokio::sync::Mutex<Option<ContainerEnvironment>>,
+
+    /// OOM kills in the running container already attributed to a command.
+    oom_kills: AtomicU64,
+}
+
+impl ContainerSession {
+    pub(crate) fn new(
+        config: ContainerConfig,
+        workspace: PathBuf,
+        sandbox_policy: &SandboxPolicy,
+    ) -> Self {
+        Self {
+            config,
+            name: format!("codex-{}", uuid::Uuid::new_v4()),
+            resource_limits: ResourceLimits::default(),
+            environment: std::sync::Mutex::new(ContainerEnvironment::new(
+                workspace,
+                sandbox_policy,
+            )),
+            running: tokio::sync::Mutex::new(None),
+            oom_kills: AtomicU64::new(0),
+        }
+    }
+
+    /// Applies the process limits of `resource_limits` to the container
+    /// through the runtime instead of to the local `<runtime> exec` client.
+    pub(crate) fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    /// Records the working directory and sandbox policy of the current turn.
+    /// A running container with different mounts is recreated by the next
+    /// [`Self::ensure_started`], which stops commands still running in it.
+    pub(crate) fn update_environment(&self, workspace: PathBuf, sandbox_policy: &SandboxPolicy) {
+        if let Ok(mut environment) = self.environment.lock() {
+            *environment = ContainerEnvironment::new(workspace, sandbox_policy);
+        }
+    }
+
+    /// Starts the container unless it is already running with the current
+    /// environment. A failed start is retried by the next command.
+    pub(crate) async fn ensure_started(&self) -> std::io::Result<()> {
+        let mut running = self.running.lock().await;
+        let environment = self.environment();
+        if running.as_ref() == Some(&environment) {
+            return Ok(());
+        }
+        if running.take().is_some() {
+            self.remove().await?;
+        }
+
+        let output = Command::new(&self.config.runtime)
+            .args(self.run_args(&environment))
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|err| {
+                std::io::Error::new(
+                    err.kind(),
+                    format!("failed to run `{}`: {err}", self.config.runtime),
+                )
+            })?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "`{} run` failed for image {}: {}",
+                self.config.runtime,
+                self.config.image,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        self.oom_kills.store(0, Ordering::SeqCst);
+        *running = Some(environment);
+        Ok(())
+    }
+
+    /// Removes the running container so it can be started again.
+    async fn remove(&self) -> std::io::Result<()> {
+        let output = Command::new(&self.config.runtime)
+            .args(["rm", "--force", &self.name])
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "`{} rm` failed for container {}: {}",
+                self.config.runtime,
+                self.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn environment(&self) -> ContainerEnvironment {
+        match self.environment.lock() {
+            Ok(environment) => environment.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Arguments for `<runtime> run` that start the long-lived container.
+    fn run_args(&self, environment: &ContainerEnvironment) -> Vec<String> {
+        let workspace = environment.workspace.to_string_lossy();
+        let mut workspace_mount = format!("{workspace}:{workspace}");
+        if environment.read_only_workspace {
+            workspace_mount.push_str(":ro");
+        }
+
+        let mut args = vec![
+            "run".to_string(),
+            "--detach".to_string(),
+            "--rm".to_string(),
+            "--init".to_string(),
+            "--name".to_string(),
+            self.name.clone(),
+            "--volume".to_string(),
+            workspace_mount,
+            "--workdir".to_string(),
+            workspace.to_string(),
+        ];
+        for root in &environment.writable_roots {
+            let root = root.to_string_lossy();
+            args.push("--volume".to_string());
+            args.push(format!("{root}:{root}"));
+        }
+        if !environment.network_access {
+            args.push("--network=none".to_string());
+        }
+        for mount in &self.config.mounts {
+            args.push("--volume".to_string());
+            args.push(mount.clone());
+        }
+        args.extend(self.resource_limits.container_run_args());
+        let mut env: Vec<_> = self.config.env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            args.push("--env".to_string());
+            args.push(format!("{key}={value}"));
+        }
+        args.extend(self.config.run_args.iter().cloned());
+        args.push(self.config.image.clone());
+        args.extend(["sleep".to_string(), "infinity".to_string()]);
+        args
+    }
+
+    /// Wraps `command` so it runs inside the container in `cwd`. `tty`
+    /// allocates a terminal for interactive sessions.
+    pub(crate) fn wrap_command(&self, command: Vec<String>, cwd: &Path, tty: bool) -> Vec<String> {
+        let mut wrapped = vec![
+            self.config.runtime.clone(),
+            "exec".to_string(),
+            "--interactive".to_string(),
+        ];
+        if tty {
+            wrapped.push("--tty".to_string());
+        }
+        wrapped.extend([
+            "--workdir".to_string(),
+            cwd.to_string_lossy().to_string(),
+            self.name.clone(),
+        ]);
+        wrapped.extend(command);
+        wrapped
+    }
+
+    /// Works out whether a command that `<runtime> exec` reported `exit_code`
+    /// for was stopped by one of the container's limits. The ulimits deliver
+    /// `SIGXCPU` and `SIGXFSZ`; for a `SIGKILL` the memory limit only counts
+    /// when the container's memory cgroup recorded an OOM kill since the last
+    /// one attributed to a command, so `kill -9` is not mistaken for it.
+    pub(crate) async fn exceeded_limit(&self, exit_code: i32) -> Option<ResourceLimitKind> {
+        let signal = signal_from_exit_code(exit_code)?;
+        if signal == SIGKILL_CODE {
+            self.resource_limits.max_memory_mb?;
+            let oom_kills = self.oom_kills().await?;
+            let seen = self.oom_kills.fetch_max(oom_kills, Ordering::SeqCst);
+            return (oom_kills > seen).then_some(ResourceLimitKind::Memory);
+        }
+        exceeded_limit_for_signal(&self.resource_limits, signal, None)
+    }
+
+    /// Number of processes the kernel's OOM killer stopped in the container,
+    /// read through the runtime.
+    async fn oom_kills(&self) -> Option<u64> {
+        let output = Command::new(&self.config.runtime)
+            .args(["exec", &self.name, "cat", MEMORY_EVENTS_PATH])
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .ok()
+            .filter(|output| output.status.success())?;
+        parse_oom_kills(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Working directory for commands that do not choose one.
+    pub(crate) fn workspace(&self) -> PathBuf {
+        self.environment().workspace
+    }
+}
+
+/// The `oom_kill` counter of a cgroup v2 `memory.events` file.
+fn parse_oom_kills(memory_events: &str) -> Option<u64> {
+    memory_events.lines().find_map(|line| {
+        line.strip_prefix("oom_kill ")
+            .and_then(|count| count.trim().parse().ok())
+    })
+}
+
+impl Drop for ContainerSession {
+    fn drop(&mut self) {
+        if self.running.get_mut().is_none() {
+            return;
+        }
+        // Best effort: the container was started with `--rm`, so stopping it
+        // also removes it.
+        let _ = std::process::Command::new(&self.config.runtime)
+            .args(["rm", "--force", &self.name])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn container_config(runtime: &str) -> ContainerConfig {
+        ContainerConfig {
+            runtime: runtime.to_string(),
+            image: "toolchain:1.0".to_string(),
+            mounts: vec!["/opt/cache:/cache:ro".to_string()],
+            env: HashMap::from([("CARGO_TERM_COLOR".to_string(), "always".to_string())]),
+            run_args: vec!["--cpus=2".to_string()],
+        }
+    }
+
+    #[test]
+    fn run_args_follow_the_sandbox_policy() {
+        let session = ContainerSession::new(
+            container_config("podman"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::new_read_only_policy(),
+        );
+
+        let name = session.name.clone();
+        assert_eq!(
+            session.run_args(&session.environment()),
+            vec![
+                "run",
+                "--detach",
+                "--rm",
+                "--init",
+                "--name",
+                name.as_str(),
+                "--volume",
+                "/work/repo:/work/repo:ro",
+                "--workdir",
+                "/work/repo",
+                "--network=none",
+                "--volume",
+                "/opt/cache:/cache:ro",
+                "--env",
+                "CARGO_TERM_COLOR=always",
+                "--cpus=2",
+                "toolchain:1.0",
+                "sleep",
+                "infinity",
+            ]
+        );
+
+        let full_access = ContainerSession::new(
+            container_config("podman"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        );
+        let args = full_access.run_args(&full_access.environment());
+        assert!(args.contains(&"/work/repo:/work/repo".to_string()));
+        assert!(!args.contains(&"--network=none".to_string()));
+
+        let workspace_write = ContainerSession::new(
+            container_config("podman"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![PathBuf::from("/work/repo"), PathBuf::from("/work/out")],
+                network_access: false,
+                network_allowlist: vec![],
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+                readable_roots: vec![],
+                deny_read_paths: vec![],
+            },
+        );
+        let args = workspace_write.run_args(&workspace_write.environment());
+        assert!(args.contains(&"/work/out:/work/out".to_string()));
+        assert_eq!(
+            args.iter()
+                .filter(|arg| arg.starts_with("/work/repo:"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn resource_limits_are_passed_to_the_runtime() {
+        let session = ContainerSession::new(
+            container_config("docker"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        )
+        .with_resource_limits(ResourceLimits {
+            cpu_time_secs: Some(60),
+            max_memory_mb: Some(512),
+            max_processes: Some(100),
+            max_file_size_mb: Some(1),
+            max_output_mb: Some(2),
+        });
+
+        let args = session.run_args(&session.environment());
+        let limits_start = args
+            .iter()
+            .position(|arg| arg == "--ulimit")
+            .expect("limits are passed");
+        assert_eq!(
+            args[limits_start..limits_start + 8],
+            [
+                "--ulimit",
+                "cpu=60:61",
+                "--memory",
+                "512m",
+                "--pids-limit",
+                "100",
+                "--ulimit",
+                "fsize=1048576:1048576",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_command_runs_in_the_container() {
+        let session = ContainerSession::new(
+            container_config("docker"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        );
+
+        let name = session.name.clone();
+        assert_eq!(
+            session.wrap_command(
+                vec!["cargo".to_string(), "build".to_string()],
+                Path::new("/work/repo/core"),
+                false,
+            ),
+            vec![
+                "docker",
+                "exec",
+                "--interactive",
+                "--workdir",
+                "/work/repo/core",
+                name.as_str(),
+                "cargo",
+                "build",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn memory_limit_is_detected_from_new_oom_kills() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let runtime = dir.path().join("runtime");
+        std::fs::write(
+            &runtime,
+            "#!/bin/sh\nif [ \"$1\" = exec ]; then printf 'oom 2\\noom_kill 1\\n'; fi\n",
+        )
+        .expect("write runtime");
+        std::fs::set_permissions(
+            &runtime,
+            std::os::unix::fs::PermissionsExt::from_mode(0o755),
+        )
+        .expect("chmod runtime");
+        let session = ContainerSession::new(
+            container_config(&runtime.to_string_lossy()),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        )
+        .with_resource_limits(ResourceLimits {
+            cpu_time_secs: Some(60),
+            max_memory_mb: Some(512),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            session.exceeded_limit(137).await,
+            Some(ResourceLimitKind::Memory)
+        );
+        // No new OOM kill: another `SIGKILL` was sent by someone else.
+        assert_eq!(session.exceeded_limit(137).await, None);
+        assert_eq!(
+            session.exceeded_limit(128 + libc::SIGXCPU).await,
+            Some(ResourceLimitKind::CpuTime)
+        );
+        assert_eq!(session.exceeded_limit(1).await, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_start_reports_runtime_stderr_and_is_retried() {
+        let session = ContainerSession::new(
+            container_config("false"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        );
+
+        let err = session
+            .ensure_started()
+            .await
+            .expect_err("`false` never starts a container");
+        assert!(
+            err.to_string()
+                .contains("`false run` failed for image toolchain:1.0"),
+            "{err}"
+        );
+        assert!(session.running.lock().await.is_none());
+
+        let missing = ContainerSession::new(
+            container_config("/nonexistent/podman"),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::DangerFullAccess,
+        );
+        let err = missing.ensure_started().await.expect_err("missing runtime");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn changed_environment_recreates_the_container() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let log = dir.path().join("runtime.log");
+        let runtime = dir.path().join("runtime");
+        std::fs::write(
+            &runtime,
+            format!("#!/bin/sh\necho \"$*\" >> \"{}\"\n", log.display()),
+        )
+        .expect("write runtime");
+        std::fs::set_permissions(&runtime, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod runtime");
+
+        let session = ContainerSession::new(
+            container_config(&runtime.to_string_lossy()),
+            PathBuf::from("/work/repo"),
+            &SandboxPolicy::new_read_only_policy(),
+        );
+        session.ensure_started().await.expect("start");
+        session.ensure_started().await.expect("already running");
+        session.update_environment(
+            PathBuf::from("/work/other"),
+            &SandboxPolicy::DangerFullAccess,
+        );
+        session.ensure_started().await.expect("restart");
+
+        let log = std::fs::read_to_string(&log).expect("read log");
+        let invocations: Vec<&str> = log.lines().collect();
+        assert_eq!(invocations.len(), 3, "{log}");
+        assert!(invocations[0].starts_with("run "), "{log}");
+        assert!(invocations[0].contains("--network=none"), "{log}");
+        assert_eq!(invocations[1], format!("rm --force {}", session.name));
+        assert!(invocations[2].starts_with("run "), "{log}");
+        assert!(invocations[2].contains("/work/other:/work/other "), "{log}");
+        assert!(!invocations[2].contains("--network=none"), "{log}");
+        assert_eq!(session.workspace(), PathBuf::from("/work/other"));
+    }
+}
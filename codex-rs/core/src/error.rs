@@ -3,6 +3,7 @@ use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::ResourceLimitKind;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
@@ -21,6 +22,17 @@ pub enum SandboxErr {
     )]
     Denied { output: Box<ExecToolCallOutput> },
 
+    /// The Linux sandbox's network proxy refused connections to hosts that
+    /// are not in the policy's network allowlist.
+    #[error(
+        "sandbox blocked network access to {} (not in the network allowlist)",
+        .hosts.join(", ")
+    )]
+    NetworkDenied {
+        hosts: Vec<String>,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Error from linux seccomp filter setup
     #[cfg(target_os = "linux")]
     #[error("seccomp setup error")]
@@ -35,6 +47,13 @@ pub enum SandboxErr {
     #[error("command timed out")]
     Timeout { output: Box<ExecToolCallOutput> },
 
+    /// Command was stopped by one of the configured resource limits
+    #[error("command exceeded the {limit} limit")]
+    ResourceLimit {
+        limit: ResourceLimitKind,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Command was killed by a signal
     #[error("command was killed by a signal")]
     Signal(i32),
@@ -301,11 +320,17 @@ impl CodexErr {
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
         CodexErr::Sandbox(SandboxErr::Denied { output }) => output.stderr.text.clone(),
+        CodexErr::Sandbox(err @ SandboxErr::NetworkDenied { output, .. }) => {
+            format!("{err}\n{}", output.stderr.text)
+        }
         // Timeouts are not sandbox errors from a UX perspective; present them plainly
         CodexErr::Sandbox(SandboxErr::Timeout { output }) => format!(
             "error: command timed out after {} ms",
             output.duration.as_millis()
         ),
+        CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, .. }) => {
+            format!("error: command exceeded the {limit} limit")
+        }
         _ => e.to_string(),
     }
 }
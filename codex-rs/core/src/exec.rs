@@ -6,6 +6,9 @@ use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -14,16 +17,22 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
+use tokio::sync::Notify;
 
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::landlock::read_network_denied_hosts;
 use crate::landlock::spawn_command_under_linux_sandbox;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
+use crate::protocol::ResourceLimitKind;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
+use crate::resource_limits::cpu_time_at_exit;
+use crate::resource_limits::exceeded_limit;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -41,6 +50,10 @@ const EXEC_TIMEOUT_EXIT_CODE: i32 = 124; // conventional timeout exit code
 const READ_CHUNK_SIZE: usize = 8192; // bytes per read
 const AGGREGATE_BUFFER_INITIAL_CAPACITY: usize = 8 * 1024; // 8 KiB
 
+/// File, inside a private temporary directory, that the Linux sandbox's network
+/// proxy appends refused `host:port` targets to.
+const NETWORK_DENIED_REPORT_FILE: &str = "network-denied";
+
 /// Limit the number of ExecCommandOutputDelta events emitted per exec call.
 /// Aggregation still collects full output; only the live event stream is capped.
 pub(crate) const MAX_EXEC_OUTPUT_DELTAS_PER_CALL: usize = 10_000;
@@ -85,15 +98,28 @@ pub async fn process_exec_tool_call(
     sandbox_policy: &SandboxPolicy,
     sandbox_cwd: &Path,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    resource_limits: &ResourceLimits,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
 
     let timeout_duration = params.timeout_duration();
 
+    // Private directory the Linux sandbox's network proxy reports refused
+    // connections into; removed when dropped.
+    let mut network_denied_report: Option<tempfile::TempDir> = None;
+
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
-        SandboxType::None => exec(params, sandbox_policy, stdout_stream.clone()).await,
+        SandboxType::None => {
+            exec(
+                params,
+                sandbox_policy,
+                resource_limits,
+                stdout_stream.clone(),
+            )
+            .await
+        }
         SandboxType::MacosSeatbelt => {
             let ExecParams {
                 command,
@@ -106,11 +132,18 @@ pub async fn process_exec_tool_call(
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
             .await?;
-            consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
+            consume_truncated_output(
+                child,
+                timeout_duration,
+                resource_limits,
+                stdout_stream.clone(),
+            )
+            .await
         }
         SandboxType::LinuxSeccomp => {
             let ExecParams {
@@ -123,18 +156,30 @@ pub async fn process_exec_tool_call(
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            if !sandbox_policy.network_allowlist().is_empty() {
+                network_denied_report = Some(
+                    tempfile::Builder::new()
+                        .prefix("codex-network-denied-")
+                        .tempdir()?,
+                );
+            }
+            let report_path = network_denied_report
+                .as_ref()
+                .map(|dir| dir.path().join(NETWORK_DENIED_REPORT_FILE));
             let child = spawn_command_under_linux_sandbox(
                 codex_linux_sandbox_exe,
                 command,
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                report_path.as_deref(),
             )
             .await?;
 
-            consume_truncated_output(child, timeout_duration, stdout_stream).await
+            consume_truncated_output(child, timeout_duration, resource_limits, stdout_stream).await
         }
     };
     let duration = start.elapsed();
@@ -143,24 +188,34 @@ pub async fn process_exec_tool_call(
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
 
+            let stderr = raw_output.stderr.from_utf8_lossy();
+            let resource_limit_exceeded = if raw_output.output_limit_exceeded {
+                Some(ResourceLimitKind::OutputSize)
+            } else {
+                exceeded_limit(resource_limits, raw_output.exit_status, raw_output.cpu_time)
+            };
+
+            #[allow(unused_mut)]
+            let mut exit_code = raw_output.exit_status.code().unwrap_or(-1);
+
             #[cfg(target_family = "unix")]
             {
                 if let Some(signal) = raw_output.exit_status.signal() {
                     if signal == TIMEOUT_CODE {
                         timed_out = true;
+                    } else if resource_limit_exceeded.is_some() {
+                        exit_code = EXIT_CODE_SIGNAL_BASE + signal;
                     } else {
                         return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                     }
                 }
             }
 
-            let mut exit_code = raw_output.exit_status.code().unwrap_or(-1);
             if timed_out {
                 exit_code = EXEC_TIMEOUT_EXIT_CODE;
             }
 
             let stdout = raw_output.stdout.from_utf8_lossy();
-            let stderr = raw_output.stderr.from_utf8_lossy();
             let aggregated_output = raw_output.aggregated_output.from_utf8_lossy();
             let exec_output = ExecToolCallOutput {
                 exit_code,
@@ -169,6 +224,7 @@ pub async fn process_exec_tool_call(
                 aggregated_output,
                 duration,
                 timed_out,
+                resource_limit_exceeded,
             };
 
             if timed_out {
@@ -177,6 +233,24 @@ pub async fn process_exec_tool_call(
                 }));
             }
 
+            if let Some(limit) = resource_limit_exceeded {
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
+            if exit_code != 0
+                && let Some(report) = &network_denied_report
+                && let Some(hosts) =
+                    read_network_denied_hosts(&report.path().join(NETWORK_DENIED_REPORT_FILE))
+            {
+                return Err(CodexErr::Sandbox(SandboxErr::NetworkDenied {
+                    hosts,
+                    output: Box::new(exec_output),
+                }));
+            }
+
             if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied {
                     output: Box::new(exec_output),
@@ -224,6 +298,10 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    pub output_limit_exceeded: bool,
+    /// CPU time the command used, measured when a CPU time limit is set and
+    /// the command exited on its own.
+    pub cpu_time: Option<Duration>,
 }
 
 impl StreamOutput<String> {
@@ -257,11 +335,15 @@ pub struct ExecToolCallOutput {
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
     pub timed_out: bool,
+    /// Set when the command was stopped by one of the configured
+    /// [`ResourceLimits`].
+    pub resource_limit_exceeded: Option<ResourceLimitKind>,
 }
 
 async fn exec(
     params: ExecParams,
     sandbox_policy: &SandboxPolicy,
+    resource_limits: &ResourceLimits,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
@@ -282,20 +364,81 @@ async fn exec(
         arg0,
         cwd,
         sandbox_policy,
+        resource_limits,
         StdioPolicy::RedirectForShellTool,
         env,
     )
     .await?;
-    consume_truncated_output(child, timeout, stdout_stream).await
+    consume_truncated_output(child, timeout, resource_limits, stdout_stream).await
+}
+
+/// Shared between the stdout and stderr readers to enforce
+/// [`ResourceLimits::max_output_mb`] on their combined output.
+struct OutputLimit {
+    max_bytes: usize,
+    total_bytes: AtomicUsize,
+    exceeded: Notify,
+}
+
+impl OutputLimit {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            total_bytes: AtomicUsize::new(0),
+            exceeded: Notify::new(),
+        }
+    }
+
+    /// Records `n` more bytes of output. Returns `false` once the limit has
+    /// been exceeded, in which case the bytes should be dropped.
+    fn record(&self, n: usize) -> bool {
+        let before = self.total_bytes.fetch_add(n, Ordering::SeqCst);
+        let after = before.saturating_add(n);
+        if after > self.max_bytes {
+            if before <= self.max_bytes {
+                // Stores a permit, so the waiter is woken even if it is not
+                // polling yet.
+                self.exceeded.notify_one();
+            }
+            return false;
+        }
+        true
+    }
+}
+
+async fn wait_for_output_limit(output_limit: Option<&OutputLimit>) {
+    match output_limit {
+        Some(output_limit) => output_limit.exceeded.notified().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for `child` to exit. With `measure_cpu_time`, also returns the CPU
+/// time it used, read before it is reaped.
+async fn wait_for_exit(
+    child: &mut Child,
+    measure_cpu_time: bool,
+) -> io::Result<(ExitStatus, Option<Duration>)> {
+    let cpu_time = match child.id() {
+        Some(pid) if measure_cpu_time => tokio::task::spawn_blocking(move || cpu_time_at_exit(pid))
+            .await
+            .ok()
+            .flatten(),
+        _ => None,
+    };
+    Ok((child.wait().await?, cpu_time))
 }
 
 /// Consumes the output of a child process, truncating it so it is suitable for
-/// use as the output of a `shell` tool call. Also enforces specified timeout.
+/// use as the output of a `shell` tool call. Also enforces specified timeout
+/// and output size limit.
 async fn consume_truncated_output(
     mut child: Child,
     timeout: Duration,
+    resource_limits: &ResourceLimits,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
+    let max_output_bytes = resource_limits.max_output_bytes();
     // Both stdout and stderr were configured with `Stdio::piped()`
     // above, therefore `take()` should normally return `Some`.  If it doesn't
     // we treat it as an exceptional I/O error
@@ -312,38 +455,48 @@ async fn consume_truncated_output(
     })?;
 
     let (agg_tx, agg_rx) = async_channel::unbounded::<Vec<u8>>();
+    let output_limit = max_output_bytes.map(|max_bytes| Arc::new(OutputLimit::new(max_bytes)));
 
     let stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
         stdout_stream.clone(),
         false,
         Some(agg_tx.clone()),
+        output_limit.clone(),
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
         Some(agg_tx.clone()),
+        output_limit.clone(),
     ));
 
-    let (exit_status, timed_out) = tokio::select! {
-        result = tokio::time::timeout(timeout, child.wait()) => {
+    let measure_cpu_time = resource_limits.cpu_time_secs.is_some();
+    let (exit_status, cpu_time, timed_out, output_limit_exceeded) = tokio::select! {
+        result = tokio::time::timeout(timeout, wait_for_exit(&mut child, measure_cpu_time)) => {
             match result {
                 Ok(status_result) => {
-                    let exit_status = status_result?;
-                    (exit_status, false)
+                    let (exit_status, cpu_time) = status_result?;
+                    (exit_status, cpu_time, false, false)
                 }
                 Err(_) => {
                     // timeout
                     child.start_kill()?;
                     // Debatable whether `child.wait().await` should be called here.
-                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true)
+                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), None, true, false)
                 }
             }
         }
+        _ = wait_for_output_limit(output_limit.as_deref()) => {
+            child.start_kill()?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), None, false, true)
+        }
         _ = tokio::signal::ctrl_c() => {
             child.start_kill()?;
-            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false)
+            // Without a measured CPU time this `SIGKILL` is never taken for
+            // the CPU time limit.
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), None, false, false)
         }
     };
 
@@ -367,6 +520,8 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        output_limit_exceeded,
+        cpu_time,
     })
 }
 
@@ -375,6 +530,7 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     stream: Option<StdoutStream>,
     is_stderr: bool,
     aggregate_tx: Option<Sender<Vec<u8>>>,
+    output_limit: Option<Arc<OutputLimit>>,
 ) -> io::Result<StreamOutput<Vec<u8>>> {
     let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
     let mut tmp = [0u8; READ_CHUNK_SIZE];
@@ -388,6 +544,13 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
             break;
         }
 
+        if let Some(output_limit) = &output_limit
+            && !output_limit.record(n)
+        {
+            // Keep draining so the child is not blocked before it is killed.
+            continue;
+        }
+
         if let Some(stream) = &stream
             && emitted_deltas < MAX_EXEC_OUTPUT_DELTAS_PER_CALL
         {
@@ -0,0 +1,423 @@
+//! Exec policy used by the command approval path.
+//!
+//! Rules come from the built-in `codex-execpolicy` default policy, followed by
+//! `$CODEX_HOME/policy/*.policy` and then `.codex/policy/*.policy` at the
+//! project root (the git repository root, or the cwd outside a repository).
+//! Files within a directory are loaded in name order. Project files come from
+//! the repository, so their rules can forbid commands or send them through
+//! the sandbox but never auto-approve them.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+
+use crate::bash::parse_bash_lc_plain_commands;
+use crate::git_info::get_git_repo_root;
+use crate::protocol::SandboxPolicy;
+
+const POLICY_DIR: &str = "policy";
+const POLICY_EXTENSION: &str = "policy";
+
+/// Result of checking a command against the exec policy. For `bash -lc`
+/// scripts made of plain commands, every command in the script is checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PolicyVerdict {
+    /// Every program matched a default or user rule, none of them may write
+    /// files, and every file they read is inside the readable roots.
+    Safe { rule: String },
+    /// Every program matched a rule, but at least one may write files, reads
+    /// outside the readable roots or only matched a project rule, so the
+    /// command goes through the regular sandbox and approval flow.
+    RequiresSandbox { rule: String },
+    /// A program or argument is forbidden by the policy.
+    Forbidden { reason: String },
+    /// The policy has no rule for at least one program.
+    Unmatched,
+}
+
+impl PolicyVerdict {
+    /// Human-readable description of the matched rule, shown to approvers.
+    pub(crate) fn matched_rule(&self) -> Option<String> {
+        match self {
+            PolicyVerdict::Safe { rule } | PolicyVerdict::RequiresSandbox { rule } => {
+                Some(rule.clone())
+            }
+            PolicyVerdict::Forbidden { reason } => Some(reason.clone()),
+            PolicyVerdict::Unmatched => None,
+        }
+    }
+}
+
+/// Parsed exec policy for a session. The default value has no rules, so every
+/// command is [`PolicyVerdict::Unmatched`].
+#[derive(Clone, Default)]
+pub(crate) struct ExecPolicy {
+    /// Default and user rules, the only ones that can auto-approve a command.
+    checker: Option<Arc<ExecvChecker>>,
+
+    /// Default, user and project rules, present when the project has policy
+    /// files.
+    project_policy: Option<Arc<Policy>>,
+}
+
+impl fmt::Debug for ExecPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecPolicy")
+            .field("loaded", &self.checker.is_some())
+            .field("project_rules", &self.project_policy.is_some())
+            .finish()
+    }
+}
+
+impl ExecPolicy {
+    /// Loads the default policy plus user and project policy files. Files that
+    /// cannot be read or parsed are skipped and described in the returned
+    /// messages so the caller can surface them.
+    pub(crate) fn load(codex_home: &Path, cwd: &Path) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut parsers =
+            parse_policy_files(&policy_files_in(&codex_home.join(POLICY_DIR)), &mut errors);
+        let project_parsers = parse_policy_files(&project_policy_files(cwd), &mut errors);
+
+        let checker = match codex_execpolicy::get_default_policy_with(&parsers) {
+            Ok(policy) => ExecvChecker::new(policy),
+            Err(e) => {
+                errors.push(format!("failed to load exec policy: {e}"));
+                return (Self::default(), errors);
+            }
+        };
+        let project_policy = if project_parsers.is_empty() {
+            None
+        } else {
+            parsers.extend(project_parsers);
+            match codex_execpolicy::get_default_policy_with(&parsers) {
+                Ok(policy) => Some(Arc::new(policy)),
+                Err(e) => {
+                    errors.push(format!("failed to load project exec policy: {e}"));
+                    None
+                }
+            }
+        };
+
+        (
+            Self {
+                checker: Some(Arc::new(checker)),
+                project_policy,
+            },
+            errors,
+        )
+    }
+
+    /// Checks `command`, run from `cwd`, against the policy. Files read by a
+    /// matched command must be inside the readable roots of `sandbox_policy`
+    /// for it to be [`PolicyVerdict::Safe`].
+    pub(crate) fn evaluate(
+        &self,
+        command: &[String],
+        cwd: &Path,
+        sandbox_policy: &SandboxPolicy,
+    ) -> PolicyVerdict {
+        let Some(checker) = &self.checker else {
+            return PolicyVerdict::Unmatched;
+        };
+        let commands =
+            parse_bash_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+        let readable_folders = sandbox_policy
+            .get_readable_roots_with_cwd(cwd)
+            .map(|roots| {
+                roots
+                    .into_iter()
+                    .map(|root| root.canonicalize().unwrap_or(root))
+                    .collect::<Vec<_>>()
+            });
+        let cwd = Some(OsString::from(cwd));
+
+        let mut rules = Vec::new();
+        let mut requires_sandbox = false;
+        let mut unmatched = false;
+        for argv in commands {
+            let Some((program, args)) = argv.split_first() else {
+                unmatched = true;
+                continue;
+            };
+            let exec_call = ExecCall {
+                program: program.clone(),
+                args: args.to_vec(),
+            };
+            let project_match = self
+                .project_policy
+                .as_ref()
+                .map(|policy| policy.check(&exec_call));
+            if let Some(Ok(MatchedExec::Forbidden { reason, .. })) = &project_match {
+                return PolicyVerdict::Forbidden {
+                    reason: format!("`{exec_call}` is forbidden by exec policy: {reason}"),
+                };
+            }
+            match checker.r#match(&exec_call) {
+                Ok(MatchedExec::Forbidden { reason, .. }) => {
+                    return PolicyVerdict::Forbidden {
+                        reason: format!("`{exec_call}` is forbidden by exec policy: {reason}"),
+                    };
+                }
+                Ok(MatchedExec::Match { exec }) => {
+                    let written: Vec<&str> = exec
+                        .args
+                        .iter()
+                        .map(|arg| (&arg.r#type, arg.value.as_str()))
+                        .chain(
+                            exec.opts
+                                .iter()
+                                .map(|opt| (&opt.r#type, opt.value.as_str())),
+                        )
+                        .filter(|(arg_type, _)| arg_type.might_write_file())
+                        .map(|(_, value)| value)
+                        .collect();
+                    if !written.is_empty() {
+                        requires_sandbox = true;
+                        rules.push(format!(
+                            "`{exec_call}` matches exec policy but may write {}",
+                            written.join(", ")
+                        ));
+                        continue;
+                    }
+                    let Some(readable_folders) = &readable_folders else {
+                        rules.push(format!("`{exec_call}` is allowed by exec policy"));
+                        continue;
+                    };
+                    match checker.check(exec, &cwd, readable_folders, &[]) {
+                        Ok(_) => rules.push(format!("`{exec_call}` is allowed by exec policy")),
+                        Err(codex_execpolicy::Error::ReadablePathNotInReadableFolders {
+                            file,
+                            ..
+                        }) => {
+                            requires_sandbox = true;
+                            rules.push(format!(
+                                "`{exec_call}` matches exec policy but reads {} outside the readable roots",
+                                file.display()
+                            ));
+                        }
+                        Err(_) => unmatched = true,
+                    }
+                }
+                Err(_) => match project_match {
+                    Some(Ok(MatchedExec::Match { .. })) => {
+                        requires_sandbox = true;
+                        rules.push(format!(
+                            "`{exec_call}` matches project exec policy, which cannot auto-approve"
+                        ));
+                    }
+                    _ => unmatched = true,
+                },
+            }
+        }
+
+        if unmatched {
+            PolicyVerdict::Unmatched
+        } else if requires_sandbox {
+            PolicyVerdict::RequiresSandbox {
+                rule: rules.join("; "),
+            }
+        } else {
+            PolicyVerdict::Safe {
+                rule: rules.join("; "),
+            }
+        }
+    }
+}
+
+/// Reads and parses `paths`, skipping files that fail and recording why.
+fn parse_policy_files(paths: &[PathBuf], errors: &mut Vec<String>) -> Vec<PolicyParser> {
+    let mut parsers = Vec::new();
+    for path in paths {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                errors.push(format!(
+                    "failed to read exec policy {}: {e}",
+                    path.display()
+                ));
+                continue;
+            }
+        };
+        let parser = PolicyParser::new(&path.to_string_lossy(), &contents);
+        if let Err(e) = parser.parse() {
+            errors.push(format!(
+                "failed to parse exec policy {}: {e}",
+                path.display()
+            ));
+            continue;
+        }
+        parsers.push(parser);
+    }
+    parsers
+}
+
+fn project_policy_files(cwd: &Path) -> Vec<PathBuf> {
+    let project_root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    policy_files_in(&project_root.join(".codex").join(POLICY_DIR))
+}
+
+fn policy_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().is_some_and(|ext| ext == POLICY_EXTENSION)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn evaluate(policy: &ExecPolicy, command: &[&str]) -> PolicyVerdict {
+        policy.evaluate(
+            &vec_str(command),
+            Path::new("/"),
+            &SandboxPolicy::DangerFullAccess,
+        )
+    }
+
+    fn write_policy(dir: &Path, name: &str, contents: &str) {
+        std::fs::create_dir_all(dir).expect("create policy dir");
+        std::fs::write(dir.join(name), contents).expect("write policy");
+    }
+
+    #[test]
+    fn default_policy_classifies_readers_and_writers() {
+        let home = TempDir::new().expect("home");
+        let cwd = TempDir::new().expect("cwd");
+        let (policy, errors) = ExecPolicy::load(home.path(), cwd.path());
+        assert!(errors.is_empty(), "{errors:?}");
+
+        assert_eq!(
+            evaluate(&policy, &["ls", "-l"]),
+            PolicyVerdict::Safe {
+                rule: "`ls -l` is allowed by exec policy".to_string(),
+            }
+        );
+        assert_eq!(
+            evaluate(&policy, &["cp", "a.txt", "b.txt"]),
+            PolicyVerdict::RequiresSandbox {
+                rule: "`cp a.txt b.txt` matches exec policy but may write b.txt".to_string(),
+            }
+        );
+        assert_eq!(
+            evaluate(&policy, &["bash", "-lc", "ls && pwd"]),
+            PolicyVerdict::Safe {
+                rule: "`ls` is allowed by exec policy; `pwd` is allowed by exec policy".to_string(),
+            }
+        );
+        assert_eq!(
+            evaluate(&policy, &["bash", "-lc", "ls && cargo build"]),
+            PolicyVerdict::Unmatched
+        );
+        assert_eq!(
+            evaluate(&ExecPolicy::default(), &["ls"]),
+            PolicyVerdict::Unmatched
+        );
+    }
+
+    #[test]
+    fn user_and_project_policies_extend_default() {
+        let home = TempDir::new().expect("home");
+        let cwd = TempDir::new().expect("cwd");
+        write_policy(
+            &home.path().join("policy"),
+            "cargo.policy",
+            "define_program(program=\"cargo\", args=[\"check\"])\n",
+        );
+        write_policy(
+            &cwd.path().join(".codex/policy"),
+            "make.policy",
+            "define_program(program=\"make\", args=[\"lint\"])\n",
+        );
+        write_policy(
+            &cwd.path().join(".codex/policy"),
+            "no-head.policy",
+            "forbid_program_regex(regex=\"^head$\", reason=\"use sed instead\")\n",
+        );
+        write_policy(&cwd.path().join(".codex/policy"), "broken.policy", "oops(");
+
+        let (policy, errors) = ExecPolicy::load(home.path(), cwd.path());
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].contains("broken.policy"), "{errors:?}");
+
+        assert!(matches!(
+            evaluate(&policy, &["cargo", "check"]),
+            PolicyVerdict::Safe { .. }
+        ));
+        assert_eq!(
+            evaluate(&policy, &["bash", "-lc", "ls && head -n 1 a.txt"]),
+            PolicyVerdict::Forbidden {
+                reason: "`head -n 1 a.txt` is forbidden by exec policy: use sed instead"
+                    .to_string(),
+            }
+        );
+        // Project rules come from the repository and never auto-approve.
+        assert_eq!(
+            evaluate(&policy, &["make", "lint"]),
+            PolicyVerdict::RequiresSandbox {
+                rule: "`make lint` matches project exec policy, which cannot auto-approve"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn reads_outside_readable_roots_require_sandbox() {
+        let home = TempDir::new().expect("home");
+        let cwd = TempDir::new().expect("cwd");
+        let (policy, errors) = ExecPolicy::load(home.path(), cwd.path());
+        assert!(errors.is_empty(), "{errors:?}");
+        let cwd = cwd.path().canonicalize().expect("canonical cwd");
+        let sandbox_policy = SandboxPolicy::ReadOnly {
+            readable_roots: vec![cwd.clone()],
+            deny_read_paths: Vec::new(),
+        };
+        let secret = home
+            .path()
+            .canonicalize()
+            .expect("canonical home")
+            .join("secret");
+
+        assert!(matches!(
+            policy.evaluate(&vec_str(&["cat", "notes.txt"]), &cwd, &sandbox_policy),
+            PolicyVerdict::Safe { .. }
+        ));
+        assert_eq!(
+            policy.evaluate(
+                &vec_str(&["cat", &secret.to_string_lossy()]),
+                &cwd,
+                &sandbox_policy
+            ),
+            PolicyVerdict::RequiresSandbox {
+                rule: format!(
+                    "`cat {}` matches exec policy but reads {} outside the readable roots",
+                    secret.display(),
+                    secret.display()
+                ),
+            }
+        );
+    }
+}
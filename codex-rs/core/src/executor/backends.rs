@@ -1,12 +1,18 @@
 use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 
 use crate::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::ApplyPatchExec;
+use crate::container::ContainerSession;
 use crate::exec::ExecParams;
+use crate::exec::ExecToolCallOutput;
 use crate::function_tool::FunctionCallError;
+use crate::protocol::ResourceLimitKind;
+use crate::protocol::SandboxPolicy;
 
 pub(crate) enum ExecutionMode {
     Shell,
@@ -27,6 +33,24 @@ pub(crate) trait ExecutionBackend: Send + Sync {
     fn stream_stdout(&self, _mode: &ExecutionMode) -> bool {
         true
     }
+
+    /// Makes sure the environment the command runs in is available. Called
+    /// after approval, right before the command is spawned.
+    async fn ensure_ready(&self) -> Result<(), FunctionCallError> {
+        Ok(())
+    }
+
+    /// Whether prepared commands may be wrapped in the platform sandbox
+    /// (Seatbelt or Landlock).
+    fn uses_platform_sandbox(&self) -> bool {
+        true
+    }
+
+    /// Resource limit that stopped a command whose limits the backend applies
+    /// itself rather than the local process.
+    async fn exceeded_limit(&self, _output: &ExecToolCallOutput) -> Option<ResourceLimitKind> {
+        None
+    }
 }
 
 static SHELL_BACKEND: ShellBackend = ShellBackend;
@@ -99,3 +123,55 @@ impl ExecutionBackend for ApplyPatchBackend {
         false
     }
 }
+
+/// Runs shell commands inside the session's container. The container takes
+/// the place of the platform sandbox, which would otherwise also confine the
+/// container runtime itself.
+pub(crate) struct ContainerBackend {
+    container: Arc<ContainerSession>,
+}
+
+impl ContainerBackend {
+    pub(crate) fn new(container: Arc<ContainerSession>) -> Self {
+        Self { container }
+    }
+
+    pub(crate) fn update_environment(&self, cwd: PathBuf, sandbox_policy: &SandboxPolicy) {
+        self.container.update_environment(cwd, sandbox_policy);
+    }
+}
+
+#[async_trait]
+impl ExecutionBackend for ContainerBackend {
+    fn prepare(
+        &self,
+        params: ExecParams,
+        mode: &ExecutionMode,
+    ) -> Result<ExecParams, FunctionCallError> {
+        match mode {
+            ExecutionMode::Shell => Ok(ExecParams {
+                command: self
+                    .container
+                    .wrap_command(params.command, &params.cwd, false),
+                ..params
+            }),
+            _ => Err(FunctionCallError::RespondToModel(
+                "container backend invoked with non-shell mode".to_string(),
+            )),
+        }
+    }
+
+    async fn ensure_ready(&self) -> Result<(), FunctionCallError> {
+        self.container.ensure_started().await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to start container: {err}"))
+        })
+    }
+
+    fn uses_platform_sandbox(&self) -> bool {
+        false
+    }
+
+    async fn exceeded_limit(&self, output: &ExecToolCallOutput) -> Option<ResourceLimitKind> {
+        self.container.exceeded_limit(output.exit_code).await
+    }
+}
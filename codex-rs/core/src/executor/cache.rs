@@ -1,15 +1,32 @@
 use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use crate::approval_rules;
+use crate::approval_rules::ApprovalRule;
+use codex_protocol::protocol::ApprovalRuleScope;
+
 #[derive(Clone, Debug, Default)]
 /// Thread-safe store of user approvals so repeated commands can reuse
-/// previously granted trust.
+/// previously granted trust. Besides exact commands approved for the session,
+/// it holds the persistent rules from `$CODEX_HOME/approval_rules.toml`.
 pub(crate) struct ApprovalCache {
     inner: Arc<Mutex<HashSet<Vec<String>>>>,
+    rules: Arc<Mutex<Vec<ApprovalRule>>>,
+    codex_home: Option<PathBuf>,
 }
 
 impl ApprovalCache {
+    pub(crate) fn with_rules(codex_home: PathBuf, rules: Vec<ApprovalRule>) -> Self {
+        Self {
+            inner: Arc::default(),
+            rules: Arc::new(Mutex::new(rules)),
+            codex_home: Some(codex_home),
+        }
+    }
+
     pub(crate) fn insert(&self, command: Vec<String>) {
         if command.is_empty() {
             return;
@@ -22,6 +39,51 @@ impl ApprovalCache {
     pub(crate) fn snapshot(&self) -> HashSet<Vec<String>> {
         self.inner.lock().map(|g| g.clone()).unwrap_or_default()
     }
+
+    /// Whether a persistent rule covers `command` when run from `cwd`.
+    pub(crate) fn matches_rule(&self, command: &[String], cwd: &Path) -> bool {
+        self.rules
+            .lock()
+            .map(|rules| approval_rules::command_matches_rules(&rules, command, cwd))
+            .unwrap_or(false)
+    }
+
+    /// Creates a persistent rule for `command` and writes it to disk.
+    /// The file is re-read first so rules added by other sessions are kept.
+    /// Returns the rule that now covers the command.
+    pub(crate) fn persist_rule(
+        &self,
+        command: &[String],
+        scope: ApprovalRuleScope,
+        cwd: &Path,
+    ) -> Result<ApprovalRule, String> {
+        let Some(codex_home) = &self.codex_home else {
+            return Err("approval rules are not available in this session".to_string());
+        };
+        let Some(pattern) = approval_rules::rule_pattern_for_command(command) else {
+            return Err("no approval rule can be derived from this command".to_string());
+        };
+        let rule = ApprovalRule::new(pattern, scope, cwd);
+        let mut guard = self
+            .rules
+            .lock()
+            .map_err(|_| "approval rules lock poisoned".to_string())?;
+        let mut rules = approval_rules::load_rules(codex_home).map_err(|e| {
+            format!(
+                "failed to read {}: {e}",
+                approval_rules::rules_path(codex_home).display()
+            )
+        })?;
+        approval_rules::add_rule(&mut rules, rule.clone());
+        approval_rules::save_rules(codex_home, &rules).map_err(|e| {
+            format!(
+                "failed to write {}: {e}",
+                approval_rules::rules_path(codex_home).display()
+            )
+        })?;
+        *guard = rules;
+        Ok(rule)
+    }
 }
 
 #[cfg(test)]
@@ -48,4 +110,31 @@ mod tests {
         let snap2 = cache.snapshot();
         assert_eq!(snap1, snap2);
     }
+
+    #[test]
+    fn persisted_rule_matches_later_commands() {
+        let home = tempfile::TempDir::new().expect("home");
+        let cache = ApprovalCache::with_rules(home.path().to_path_buf(), Vec::new());
+        let cwd = home.path().join("project");
+        let cmd: Vec<String> = ["cargo", "fmt", "--all"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(!cache.matches_rule(&cmd, &cwd));
+
+        let rule = cache
+            .persist_rule(&cmd, ApprovalRuleScope::Global, &cwd)
+            .expect("persist");
+        assert!(cache.matches_rule(&["cargo".to_string(), "fmt".to_string()], &cwd));
+        assert_eq!(
+            approval_rules::load_rules(home.path()).expect("load"),
+            vec![rule]
+        );
+
+        assert!(
+            ApprovalCache::default()
+                .persist_rule(&cmd, ApprovalRuleScope::Global, &cwd)
+                .is_err()
+        );
+    }
 }
@@ -3,10 +3,14 @@ use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
 
+use super::backends::ContainerBackend;
+use super::backends::ExecutionBackend;
 use super::backends::ExecutionMode;
 use super::backends::backend_for_mode;
 use super::cache::ApprovalCache;
+use crate::approval_rules::ApprovalRule;
 use crate::codex::Session;
+use crate::container::ContainerSession;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::error::get_error_message_ui;
@@ -16,12 +20,15 @@ use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
 use crate::exec::process_exec_tool_call;
+use crate::exec_policy::ExecPolicy;
 use crate::executor::errors::ExecError;
 use crate::executor::sandbox::select_sandbox;
 use crate::function_tool::FunctionCallError;
+use crate::protocol::ApprovalRuleScope;
 use crate::protocol::AskForApproval;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
 use crate::shell;
 use crate::tools::context::ExecCommandContext;
 use codex_otel::otel_event_manager::ToolDecisionSource;
@@ -30,6 +37,8 @@ use codex_otel::otel_event_manager::ToolDecisionSource;
 pub(crate) struct ExecutorConfig {
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) sandbox_cwd: PathBuf,
+    pub(crate) exec_policy: ExecPolicy,
+    pub(crate) resource_limits: ResourceLimits,
     codex_linux_sandbox_exe: Option<PathBuf>,
 }
 
@@ -42,9 +51,21 @@ impl ExecutorConfig {
         Self {
             sandbox_policy,
             sandbox_cwd,
+            exec_policy: ExecPolicy::default(),
+            resource_limits: ResourceLimits::default(),
             codex_linux_sandbox_exe,
         }
     }
+
+    pub(crate) fn with_exec_policy(mut self, exec_policy: ExecPolicy) -> Self {
+        self.exec_policy = exec_policy;
+        self
+    }
+
+    pub(crate) fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
 }
 
 /// Coordinates sandbox selection, backend-specific preparation, and command
@@ -52,6 +73,7 @@ impl ExecutorConfig {
 pub(crate) struct Executor {
     approval_cache: ApprovalCache,
     config: Arc<RwLock<ExecutorConfig>>,
+    container: Option<ContainerBackend>,
 }
 
 impl Executor {
@@ -59,12 +81,34 @@ impl Executor {
         Self {
             approval_cache: ApprovalCache::default(),
             config: Arc::new(RwLock::new(config)),
+            container: None,
         }
     }
 
+    /// Runs shell commands inside `container` instead of on the host.
+    pub(crate) fn with_container(mut self, container: Option<Arc<ContainerSession>>) -> Self {
+        self.container = container.map(ContainerBackend::new);
+        self
+    }
+
+    /// Seeds the executor with persistent approval rules loaded from
+    /// `codex_home`; rules created during the session are written back there.
+    pub(crate) fn with_approval_rules(
+        mut self,
+        codex_home: PathBuf,
+        rules: Vec<ApprovalRule>,
+    ) -> Self {
+        self.approval_cache = ApprovalCache::with_rules(codex_home, rules);
+        self
+    }
+
     /// Updates the sandbox policy and working directory used for future
-    /// executions without recreating the executor.
+    /// executions without recreating the executor. The container, if any, is
+    /// recreated with the new mounts before the next command.
     pub(crate) fn update_environment(&self, sandbox_policy: SandboxPolicy, sandbox_cwd: PathBuf) {
+        if let Some(container) = &self.container {
+            container.update_environment(sandbox_cwd.clone(), &sandbox_policy);
+        }
         if let Ok(mut cfg) = self.config.write() {
             cfg.sandbox_policy = sandbox_policy;
             cfg.sandbox_cwd = sandbox_cwd;
@@ -81,13 +125,21 @@ impl Executor {
         approval_policy: AskForApproval,
         context: &ExecCommandContext,
     ) -> Result<ExecToolCallOutput, ExecError> {
-        if matches!(request.mode, ExecutionMode::Shell) {
+        let container = match request.mode {
+            ExecutionMode::Shell => self.container.as_ref(),
+            ExecutionMode::ApplyPatch(_) => None,
+        };
+        // The user's shell only exists on the host.
+        if matches!(request.mode, ExecutionMode::Shell) && container.is_none() {
             request.params =
                 maybe_translate_shell_command(request.params, session, request.use_shell_profile);
         }
 
         // Step 1: Normalise parameters via the selected backend.
-        let backend = backend_for_mode(&request.mode);
+        let backend: &dyn ExecutionBackend = match container {
+            Some(container) => container,
+            None => backend_for_mode(&request.mode),
+        };
         let stdout_stream = if backend.stream_stdout(&request.mode) {
             request.stdout_stream.clone()
         } else {
@@ -98,17 +150,27 @@ impl Executor {
             .map_err(ExecError::from)?;
 
         // Step 2: Snapshot sandbox configuration so it stays stable for this run.
-        let config = self
+        let mut config = self
             .config
             .read()
             .map_err(|_| ExecError::rejection("executor config poisoned"))?
             .clone();
+        // The container runtime applies the process limits to the container;
+        // applied here they would only confine the local `<runtime> exec`.
+        if container.is_some() {
+            config.resource_limits = config.resource_limits.output_only();
+        }
 
         // Step 3: Decide sandbox placement, prompting for approval when needed.
-        let sandbox_decision = select_sandbox(
+        let approved_snapshot = self.approval_cache.snapshot();
+        let rule_approved = self
+            .approval_cache
+            .matches_rule(&request.approval_command, &request.params.cwd);
+        let mut sandbox_decision = select_sandbox(
             &request,
             approval_policy,
-            self.approval_cache.snapshot(),
+            approved_snapshot,
+            rule_approved,
             &config,
             session,
             &context.sub_id,
@@ -119,8 +181,17 @@ impl Executor {
         if sandbox_decision.record_session_approval {
             self.approval_cache.insert(request.approval_command.clone());
         }
+        if let Some(scope) = sandbox_decision.persist_rule {
+            self.persist_approval_rule(&request, scope, session, context)
+                .await;
+        }
 
         // Step 4: Launch the command within the chosen sandbox.
+        backend.ensure_ready().await.map_err(ExecError::from)?;
+        if !backend.uses_platform_sandbox() {
+            sandbox_decision.initial_sandbox = SandboxType::None;
+            sandbox_decision.escalate_on_failure = false;
+        }
         let first_attempt = self
             .spawn(
                 request.params.clone(),
@@ -132,10 +203,24 @@ impl Executor {
 
         // Step 5: Handle sandbox outcomes, optionally escalating to an unsandboxed retry.
         match first_attempt {
-            Ok(output) => Ok(output),
+            Ok(mut output) => match backend.exceeded_limit(&output).await {
+                Some(limit) => {
+                    output.resource_limit_exceeded = Some(limit);
+                    Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                        limit,
+                        output: Box::new(output),
+                    })
+                    .into())
+                }
+                None => Ok(output),
+            },
             Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => {
                 Err(CodexErr::Sandbox(SandboxErr::Timeout { output }).into())
             }
+            // Running without the sandbox would hit the same limit.
+            Err(CodexErr::Sandbox(err @ SandboxErr::ResourceLimit { .. })) => {
+                Err(CodexErr::Sandbox(err).into())
+            }
             Err(CodexErr::Sandbox(error)) => {
                 if sandbox_decision.escalate_on_failure {
                     self.retry_without_sandbox(
@@ -180,6 +265,7 @@ impl Executor {
                 request.approval_command.clone(),
                 request.params.cwd.clone(),
                 Some("command failed; retry without sandbox?".to_string()),
+                None,
             )
             .await;
 
@@ -190,10 +276,16 @@ impl Executor {
             ToolDecisionSource::User,
         );
         match decision {
-            ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-                if matches!(decision, ReviewDecision::ApprovedForSession) {
+            ReviewDecision::Approved
+            | ReviewDecision::ApprovedForSession
+            | ReviewDecision::ApprovedRule { .. } => {
+                if !matches!(decision, ReviewDecision::Approved) {
                     self.approval_cache.insert(request.approval_command.clone());
                 }
+                if let ReviewDecision::ApprovedRule { scope } = decision {
+                    self.persist_approval_rule(request, scope, session, context)
+                        .await;
+                }
                 session
                     .notify_background_event(&context.sub_id, "retrying command without sandbox")
                     .await;
@@ -215,6 +307,32 @@ impl Executor {
         }
     }
 
+    /// Writes a persistent approval rule for the request's command, reporting
+    /// failures as background events rather than failing the command.
+    async fn persist_approval_rule(
+        &self,
+        request: &ExecutionRequest,
+        scope: ApprovalRuleScope,
+        session: &Session,
+        context: &ExecCommandContext,
+    ) {
+        let message = match self.approval_cache.persist_rule(
+            &request.approval_command,
+            scope,
+            &request.params.cwd,
+        ) {
+            Ok(rule) => format!(
+                "saved approval rule {}: `{}`",
+                rule.id,
+                rule.describe_pattern()
+            ),
+            Err(err) => format!("approval rule not saved: {err}"),
+        };
+        session
+            .notify_background_event(&context.sub_id, message)
+            .await;
+    }
+
     async fn spawn(
         &self,
         params: ExecParams,
@@ -228,6 +346,7 @@ impl Executor {
             &config.sandbox_policy,
             &config.sandbox_cwd,
             &config.codex_linux_sandbox_exe,
+            &config.resource_limits,
             stdout_stream,
         )
         .await
@@ -293,12 +412,12 @@ pub(crate) fn normalize_exec_result(
             borrowed: Some(output),
             synthetic: None,
         },
-        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
-            NormalizedExecOutput {
-                borrowed: Some(output.as_ref()),
-                synthetic: None,
-            }
-        }
+        Err(ExecError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output } | SandboxErr::ResourceLimit { output, .. },
+        ))) => NormalizedExecOutput {
+            borrowed: Some(output.as_ref()),
+            synthetic: None,
+        },
         Err(err) => {
             let message = match err {
                 ExecError::Function(FunctionCallError::RespondToModel(msg)) => msg.clone(),
@@ -312,6 +431,7 @@ pub(crate) fn normalize_exec_result(
                 aggregated_output: StreamOutput::new(message),
                 duration: Duration::default(),
                 timed_out: false,
+                resource_limit_exceeded: None,
             };
             NormalizedExecOutput {
                 borrowed: None,
@@ -338,6 +458,7 @@ mod tests {
             aggregated_output: StreamOutput::new(text.to_string()),
             duration: Duration::from_millis(123),
             timed_out: false,
+            resource_limit_exceeded: None,
         }
     }
 
@@ -372,6 +493,7 @@ mod tests {
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(10),
             timed_out: false,
+            resource_limit_exceeded: None,
         };
         let err = SandboxErr::Denied {
             output: Box::new(output),
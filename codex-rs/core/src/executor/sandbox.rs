@@ -8,10 +8,13 @@ use crate::executor::errors::ExecError;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_patch_safety;
+use crate::safety::get_platform_sandbox;
 use codex_otel::otel_event_manager::OtelEventManager;
 use codex_otel::otel_event_manager::ToolDecisionSource;
+use codex_protocol::protocol::ApprovalRuleScope;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SandboxPolicy;
 use std::collections::HashSet;
 
 /// Sandbox placement options selected for an execution run, including whether
@@ -20,6 +23,8 @@ pub(crate) struct SandboxDecision {
     pub(crate) initial_sandbox: SandboxType,
     pub(crate) escalate_on_failure: bool,
     pub(crate) record_session_approval: bool,
+    /// Set when the user asked for a persistent approval rule.
+    pub(crate) persist_rule: Option<ApprovalRuleScope>,
 }
 
 impl SandboxDecision {
@@ -28,6 +33,7 @@ impl SandboxDecision {
             initial_sandbox: sandbox,
             escalate_on_failure,
             record_session_approval: false,
+            persist_rule: None,
         }
     }
 
@@ -36,6 +42,14 @@ impl SandboxDecision {
             initial_sandbox: SandboxType::None,
             escalate_on_failure: false,
             record_session_approval,
+            persist_rule: None,
+        }
+    }
+
+    fn user_rule(scope: ApprovalRuleScope) -> Self {
+        Self {
+            persist_rule: Some(scope),
+            ..Self::user_override(true)
         }
     }
 }
@@ -51,12 +65,14 @@ fn should_escalate_on_failure(approval: AskForApproval, sandbox: SandboxType) ->
 }
 
 /// Determines how a command should be sandboxed, prompting the user when
-/// policy requires explicit approval.
+/// policy requires explicit approval. `rule_approved` is set when a persistent
+/// approval rule covers the command.
 #[allow(clippy::too_many_arguments)]
 pub async fn select_sandbox(
     request: &ExecutionRequest,
     approval_policy: AskForApproval,
     approval_cache: HashSet<Vec<String>>,
+    rule_approved: bool,
     config: &ExecutorConfig,
     session: &Session,
     sub_id: &str,
@@ -69,6 +85,7 @@ pub async fn select_sandbox(
                 request,
                 approval_policy,
                 approval_cache,
+                rule_approved,
                 config,
                 session,
                 sub_id,
@@ -88,6 +105,7 @@ async fn select_shell_sandbox(
     request: &ExecutionRequest,
     approval_policy: AskForApproval,
     approved_snapshot: HashSet<Vec<String>>,
+    rule_approved: bool,
     config: &ExecutorConfig,
     session: &Session,
     sub_id: &str,
@@ -100,14 +118,42 @@ async fn select_shell_sandbox(
         request.approval_command.clone()
     };
 
+    let policy_verdict = config.exec_policy.evaluate(
+        &command_for_safety,
+        &request.params.cwd,
+        &config.sandbox_policy,
+    );
+    let with_escalated_permissions = request.params.with_escalated_permissions.unwrap_or(false);
     let safety = assess_command_safety(
         &command_for_safety,
         approval_policy,
         &config.sandbox_policy,
         &approved_snapshot,
-        request.params.with_escalated_permissions.unwrap_or(false),
+        with_escalated_permissions,
+        &policy_verdict,
     );
 
+    // A persistent rule only skips the prompt: the command still runs in the
+    // sandbox, so it is asked for again when it needs to escape it.
+    if matches!(safety, SafetyCheck::AskUser) && rule_approved && !with_escalated_permissions {
+        let sandbox_type = match &config.sandbox_policy {
+            SandboxPolicy::DangerFullAccess => Some(SandboxType::None),
+            _ => get_platform_sandbox(),
+        };
+        if let Some(sandbox_type) = sandbox_type {
+            otel_event_manager.tool_decision(
+                "local_shell",
+                call_id,
+                ReviewDecision::Approved,
+                ToolDecisionSource::Config,
+            );
+            return Ok(SandboxDecision::auto(
+                sandbox_type,
+                should_escalate_on_failure(approval_policy, sandbox_type),
+            ));
+        }
+    }
+
     match safety {
         SafetyCheck::AutoApprove {
             sandbox_type,
@@ -136,6 +182,7 @@ async fn select_shell_sandbox(
                     request.approval_command.clone(),
                     request.params.cwd.clone(),
                     request.params.justification.clone(),
+                    policy_verdict.matched_rule(),
                 )
                 .await;
 
@@ -148,6 +195,7 @@ async fn select_shell_sandbox(
             match decision {
                 ReviewDecision::Approved => Ok(SandboxDecision::user_override(false)),
                 ReviewDecision::ApprovedForSession => Ok(SandboxDecision::user_override(true)),
+                ReviewDecision::ApprovedRule { scope } => Ok(SandboxDecision::user_rule(scope)),
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     Err(ExecError::rejection("exec command rejected by user"))
                 }
@@ -207,7 +255,11 @@ mod tests {
             action,
             user_explicitly_approved_this_action: true,
         };
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 command: vec!["apply_patch".into()],
@@ -227,6 +279,7 @@ mod tests {
             &request,
             AskForApproval::OnRequest,
             Default::default(),
+            false,
             &cfg,
             &session,
             "sub",
@@ -270,6 +323,7 @@ mod tests {
             &request,
             AskForApproval::OnRequest,
             Default::default(),
+            false,
             &cfg,
             &session,
             "sub",
@@ -294,7 +348,11 @@ mod tests {
             action,
             user_explicitly_approved_this_action: false,
         };
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 command: vec!["apply_patch".into()],
@@ -314,6 +372,7 @@ mod tests {
             &request,
             AskForApproval::UnlessTrusted,
             Default::default(),
+            false,
             &cfg,
             &session,
             "sub",
@@ -353,6 +412,7 @@ mod tests {
             &request,
             AskForApproval::OnRequest,
             Default::default(),
+            false,
             &cfg,
             &session,
             "sub",
@@ -369,7 +429,11 @@ mod tests {
     #[tokio::test]
     async fn select_shell_escalates_on_failure_with_platform_sandbox() {
         let (session, ctx) = make_session_and_context();
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 // Unknown command => untrusted but not flagged dangerous
@@ -390,6 +454,7 @@ mod tests {
             &request,
             AskForApproval::OnFailure,
             Default::default(),
+            false,
             &cfg,
             &session,
             "sub",
@@ -402,4 +467,47 @@ mod tests {
         assert_ne!(decision.initial_sandbox, SandboxType::None);
         assert_eq!(decision.escalate_on_failure, true);
     }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[tokio::test]
+    async fn select_shell_keeps_sandbox_for_rule_approved_commands() {
+        let (session, ctx) = make_session_and_context();
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
+        let request = ExecutionRequest {
+            params: ExecParams {
+                command: vec!["rm".into(), "-rf".into(), "out".into()],
+                cwd: std::env::temp_dir(),
+                timeout_ms: None,
+                env: std::collections::HashMap::new(),
+                with_escalated_permissions: None,
+                justification: None,
+            },
+            approval_command: vec!["rm".into(), "-rf".into(), "out".into()],
+            mode: ExecutionMode::Shell,
+            stdout_stream: None,
+            use_shell_profile: false,
+        };
+        let otel_event_manager = ctx.client.get_otel_event_manager();
+        // Without the rule this command would prompt for approval.
+        let decision = select_sandbox(
+            &request,
+            AskForApproval::UnlessTrusted,
+            Default::default(),
+            true,
+            &cfg,
+            &session,
+            "sub",
+            "call",
+            &otel_event_manager,
+        )
+        .await
+        .expect("ok");
+        assert_ne!(decision.initial_sandbox, SandboxType::None);
+        assert_eq!(decision.escalate_on_failure, true);
+        assert_eq!(decision.record_session_approval, false);
+    }
 }
@@ -0,0 +1,576 @@
+//! User-configured hooks that run around tool calls.
+//!
+//! `pre_tool_use` hooks run before a tool call is dispatched and can allow it,
+//! deny it, or replace its payload. `post_tool_use` hooks run after the call
+//! with its output, e.g. to format files after `apply_patch` or to keep an
+//! audit log. Each hook receives a JSON description of the call on stdin.
+//!
+//! A pre hook allows the call by exiting successfully with empty output, or
+//! by printing `{"decision": "allow"}`. It denies the call by printing
+//! `{"decision": "deny", "reason": "..."}` or by exiting with a non-zero
+//! status. It rewrites the call by printing `{"decision": "modify",
+//! "payload": {...}}` with a payload of the same type. Hooks that time out,
+//! cannot be started, or print something else deny the call.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config_types::HooksConfig;
+use crate::config_types::ToolHookConfig;
+use crate::tools::context::ToolPayload;
+
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const PRE_TOOL_USE: &str = "pre_tool_use";
+const POST_TOOL_USE: &str = "post_tool_use";
+
+/// The hooks configured for a session.
+#[derive(Debug, Default)]
+pub(crate) struct ToolHooks {
+    pre_tool_use: Vec<ToolHookConfig>,
+    post_tool_use: Vec<ToolHookConfig>,
+}
+
+/// Identifies the tool call a hook runs for.
+pub(crate) struct HookContext<'a> {
+    pub session: &'a Session,
+    pub turn: &'a TurnContext,
+    pub sub_id: &'a str,
+    pub tool_name: &'a str,
+    pub call_id: &'a str,
+}
+
+/// JSON form of a [`ToolPayload`] as seen by hooks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HookPayload {
+    Function {
+        arguments: String,
+    },
+    Custom {
+        input: String,
+    },
+    LocalShell {
+        command: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        workdir: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+    },
+    UnifiedExec {
+        arguments: String,
+    },
+    Mcp {
+        server: String,
+        tool: String,
+        arguments: String,
+    },
+}
+
+impl From<&ToolPayload> for HookPayload {
+    fn from(payload: &ToolPayload) -> Self {
+        match payload {
+            ToolPayload::Function { arguments } => Self::Function {
+                arguments: arguments.clone(),
+            },
+            ToolPayload::Custom { input } => Self::Custom {
+                input: input.clone(),
+            },
+            ToolPayload::LocalShell { params } => Self::LocalShell {
+                command: params.command.clone(),
+                workdir: params.workdir.clone(),
+                timeout_ms: params.timeout_ms,
+            },
+            ToolPayload::UnifiedExec { arguments } => Self::UnifiedExec {
+                arguments: arguments.clone(),
+            },
+            ToolPayload::Mcp {
+                server,
+                tool,
+                raw_arguments,
+            } => Self::Mcp {
+                server: server.clone(),
+                tool: tool.clone(),
+                arguments: raw_arguments.clone(),
+            },
+        }
+    }
+}
+
+impl HookPayload {
+    /// Applies a payload returned by a hook to `original`. The payload must
+    /// describe the same kind of call (and for MCP, the same tool).
+    fn apply_to(self, original: &ToolPayload) -> Result<ToolPayload, String> {
+        match (self, original) {
+            (Self::Function { arguments }, ToolPayload::Function { .. }) => {
+                Ok(ToolPayload::Function { arguments })
+            }
+            (Self::Custom { input }, ToolPayload::Custom { .. }) => {
+                Ok(ToolPayload::Custom { input })
+            }
+            (
+                Self::LocalShell {
+                    command,
+                    workdir,
+                    timeout_ms,
+                },
+                ToolPayload::LocalShell { params },
+            ) => Ok(ToolPayload::LocalShell {
+                params: ShellToolCallParams {
+                    command,
+                    workdir,
+                    timeout_ms,
+                    with_escalated_permissions: params.with_escalated_permissions,
+                    justification: params.justification.clone(),
+                },
+            }),
+            (Self::UnifiedExec { arguments }, ToolPayload::UnifiedExec { .. }) => {
+                Ok(ToolPayload::UnifiedExec { arguments })
+            }
+            (
+                Self::Mcp {
+                    server,
+                    tool,
+                    arguments,
+                },
+                ToolPayload::Mcp {
+                    server: original_server,
+                    tool: original_tool,
+                    ..
+                },
+            ) if &server == original_server && &tool == original_tool => Ok(ToolPayload::Mcp {
+                server,
+                tool,
+                raw_arguments: arguments,
+            }),
+            _ => Err("hook returned a payload for a different kind of tool call".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HookInput<'a> {
+    hook_event: &'a str,
+    tool_name: &'a str,
+    call_id: &'a str,
+    cwd: &'a Path,
+    payload: HookPayload,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<HookToolOutput>,
+}
+
+#[derive(Serialize)]
+struct HookToolOutput {
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    success: Option<bool>,
+}
+
+impl From<&ResponseInputItem> for HookToolOutput {
+    fn from(response: &ResponseInputItem) -> Self {
+        match ResponseItem::from(response.clone()) {
+            ResponseItem::FunctionCallOutput {
+                output: FunctionCallOutputPayload { content, success },
+                ..
+            } => Self { content, success },
+            ResponseItem::CustomToolCallOutput { output, .. } => Self {
+                content: output,
+                success: None,
+            },
+            _ => Self {
+                content: String::new(),
+                success: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+enum PreToolUseResponse {
+    Allow,
+    Deny {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    Modify {
+        payload: HookPayload,
+    },
+}
+
+/// What a single hook run amounted to.
+#[derive(Debug, PartialEq)]
+enum HookOutcome {
+    Allow,
+    Deny(String),
+    Modify(HookPayload),
+    Failed(String),
+}
+
+impl HookOutcome {
+    fn label(&self) -> &'static str {
+        match self {
+            HookOutcome::Allow => "allow",
+            HookOutcome::Deny(_) => "deny",
+            HookOutcome::Modify(_) => "modify",
+            HookOutcome::Failed(_) => "error",
+        }
+    }
+}
+
+impl ToolHooks {
+    pub(crate) fn new(config: HooksConfig) -> Self {
+        Self {
+            pre_tool_use: config.pre_tool_use,
+            post_tool_use: config.post_tool_use,
+        }
+    }
+
+    pub(crate) fn has_post_tool_use(&self, tool_name: &str) -> bool {
+        self.post_tool_use
+            .iter()
+            .any(|hook| hook_applies(hook, tool_name))
+    }
+
+    /// Runs the `pre_tool_use` hooks in order, applying any rewrites to
+    /// `payload`. Returns the reason if a hook denied the call.
+    pub(crate) async fn run_pre_tool_use(
+        &self,
+        ctx: &HookContext<'_>,
+        payload: &mut ToolPayload,
+    ) -> Result<(), String> {
+        for hook in &self.pre_tool_use {
+            if !hook_applies(hook, ctx.tool_name) {
+                continue;
+            }
+            let input = HookInput {
+                hook_event: PRE_TOOL_USE,
+                tool_name: ctx.tool_name,
+                call_id: ctx.call_id,
+                cwd: &ctx.turn.cwd,
+                payload: HookPayload::from(&*payload),
+                output: None,
+            };
+            let start = Instant::now();
+            let outcome = match run_hook(hook, &ctx.turn.cwd, &input).await {
+                Ok(stdout) => parse_pre_tool_use_response(&stdout),
+                Err(err) => HookOutcome::Failed(err),
+            };
+            report(ctx, PRE_TOOL_USE, hook, &outcome, start.elapsed()).await;
+
+            match outcome {
+                HookOutcome::Allow => {}
+                HookOutcome::Modify(modified) => {
+                    *payload = modified.apply_to(payload).map_err(|err| {
+                        format!(
+                            "tool call denied: {PRE_TOOL_USE} hook `{}` returned an invalid payload: {err}",
+                            describe(hook)
+                        )
+                    })?;
+                }
+                HookOutcome::Deny(reason) | HookOutcome::Failed(reason) => {
+                    return Err(format!(
+                        "tool call denied by {PRE_TOOL_USE} hook `{}`: {reason}",
+                        describe(hook)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the `post_tool_use` hooks in order. Failures are reported but do
+    /// not change the tool call's result.
+    pub(crate) async fn run_post_tool_use(
+        &self,
+        ctx: &HookContext<'_>,
+        payload: &ToolPayload,
+        response: &ResponseInputItem,
+    ) {
+        for hook in &self.post_tool_use {
+            if !hook_applies(hook, ctx.tool_name) {
+                continue;
+            }
+            let input = HookInput {
+                hook_event: POST_TOOL_USE,
+                tool_name: ctx.tool_name,
+                call_id: ctx.call_id,
+                cwd: &ctx.turn.cwd,
+                payload: HookPayload::from(payload),
+                output: Some(HookToolOutput::from(response)),
+            };
+            let start = Instant::now();
+            let outcome = match run_hook(hook, &ctx.turn.cwd, &input).await {
+                Ok(_) => HookOutcome::Allow,
+                Err(err) => HookOutcome::Failed(err),
+            };
+            report(ctx, POST_TOOL_USE, hook, &outcome, start.elapsed()).await;
+        }
+    }
+}
+
+fn hook_applies(hook: &ToolHookConfig, tool_name: &str) -> bool {
+    hook.tools.is_empty() || hook.tools.iter().any(|tool| tool == tool_name)
+}
+
+fn describe(hook: &ToolHookConfig) -> String {
+    shlex::try_join(hook.command.iter().map(String::as_str))
+        .unwrap_or_else(|_| hook.command.join(" "))
+}
+
+/// Runs `hook` with `input` on stdin and returns its stdout. A non-zero exit
+/// status, a timeout or a failure to start are reported as errors.
+async fn run_hook(
+    hook: &ToolHookConfig,
+    cwd: &Path,
+    input: &HookInput<'_>,
+) -> Result<String, String> {
+    let Some((program, args)) = hook.command.split_first() else {
+        return Err("hook command is empty".to_string());
+    };
+    let input = serde_json::to_vec(input).map_err(|err| err.to_string())?;
+    let timeout = hook
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_HOOK_TIMEOUT);
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("failed to start hook: {err}"))?;
+
+    let run = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            // Hooks may exit without reading their input.
+            let _ = stdin.write_all(&input).await;
+        }
+        child.wait_with_output().await
+    };
+    let output = match tokio::time::timeout(timeout, run).await {
+        Ok(output) => output.map_err(|err| format!("failed to run hook: {err}"))?,
+        Err(_) => return Err(format!("hook timed out after {} ms", timeout.as_millis())),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        return Err(if stderr.is_empty() {
+            format!("hook exited with {}", output.status)
+        } else {
+            stderr.to_string()
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_pre_tool_use_response(stdout: &str) -> HookOutcome {
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        return HookOutcome::Allow;
+    }
+    match serde_json::from_str::<PreToolUseResponse>(stdout) {
+        Ok(PreToolUseResponse::Allow) => HookOutcome::Allow,
+        Ok(PreToolUseResponse::Deny { reason }) => {
+            HookOutcome::Deny(reason.unwrap_or_else(|| "no reason given".to_string()))
+        }
+        Ok(PreToolUseResponse::Modify { payload }) => HookOutcome::Modify(payload),
+        Err(err) => HookOutcome::Failed(format!("invalid hook response: {err}")),
+    }
+}
+
+/// Records the outcome in telemetry and tells the user about anything other
+/// than a plain allow.
+async fn report(
+    ctx: &HookContext<'_>,
+    hook_event: &str,
+    hook: &ToolHookConfig,
+    outcome: &HookOutcome,
+    duration: Duration,
+) {
+    let command = describe(hook);
+    ctx.turn.client.get_otel_event_manager().tool_hook(
+        hook_event,
+        ctx.tool_name,
+        ctx.call_id,
+        &command,
+        outcome.label(),
+        duration,
+    );
+
+    let tool_name = ctx.tool_name;
+    let message = match outcome {
+        HookOutcome::Allow => return,
+        HookOutcome::Deny(reason) => {
+            format!("{hook_event} hook `{command}` denied {tool_name}: {reason}")
+        }
+        HookOutcome::Modify(_) => {
+            format!("{hook_event} hook `{command}` modified the {tool_name} call")
+        }
+        HookOutcome::Failed(err) => {
+            format!("{hook_event} hook `{command}` failed for {tool_name}: {err}")
+        }
+    };
+    ctx.session
+        .notify_background_event(ctx.sub_id, message)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pre_tool_use_responses_are_parsed() {
+        assert_eq!(parse_pre_tool_use_response("\n"), HookOutcome::Allow);
+        assert_eq!(
+            parse_pre_tool_use_response(r#"{"decision":"allow"}"#),
+            HookOutcome::Allow
+        );
+        assert_eq!(
+            parse_pre_tool_use_response(r#"{"decision":"deny","reason":"no rm"}"#),
+            HookOutcome::Deny("no rm".to_string())
+        );
+        assert_eq!(
+            parse_pre_tool_use_response(
+                r#"{"decision":"modify","payload":{"type":"local_shell","command":["ls"]}}"#
+            ),
+            HookOutcome::Modify(HookPayload::LocalShell {
+                command: vec!["ls".to_string()],
+                workdir: None,
+                timeout_ms: None,
+            })
+        );
+        assert!(matches!(
+            parse_pre_tool_use_response("yes please"),
+            HookOutcome::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn modified_payload_must_match_the_original_kind() {
+        let original = ToolPayload::Function {
+            arguments: r#"{"command":["rm","-rf","target"]}"#.to_string(),
+        };
+
+        let modified = HookPayload::Function {
+            arguments: r#"{"command":["cargo","clean"]}"#.to_string(),
+        }
+        .apply_to(&original);
+        assert!(matches!(
+            modified,
+            Ok(ToolPayload::Function { arguments }) if arguments.contains("cargo")
+        ));
+
+        let wrong_kind = HookPayload::Custom {
+            input: "*** Begin Patch".to_string(),
+        }
+        .apply_to(&original);
+        assert!(wrong_kind.is_err());
+    }
+
+    #[test]
+    fn hook_input_describes_the_call() {
+        let input = HookInput {
+            hook_event: POST_TOOL_USE,
+            tool_name: "local_shell",
+            call_id: "call-1",
+            cwd: Path::new("/repo"),
+            payload: HookPayload::from(&ToolPayload::LocalShell {
+                params: ShellToolCallParams {
+                    command: vec!["echo".to_string(), "hi".to_string()],
+                    workdir: None,
+                    timeout_ms: Some(1_000),
+                    with_escalated_permissions: None,
+                    justification: None,
+                },
+            }),
+            output: Some(HookToolOutput::from(
+                &ResponseInputItem::FunctionCallOutput {
+                    call_id: "call-1".to_string(),
+                    output: FunctionCallOutputPayload {
+                        content: "hi\n".to_string(),
+                        success: Some(true),
+                    },
+                },
+            )),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&input).expect("serialize hook input"),
+            serde_json::json!({
+                "hook_event": "post_tool_use",
+                "tool_name": "local_shell",
+                "call_id": "call-1",
+                "cwd": "/repo",
+                "payload": {
+                    "type": "local_shell",
+                    "command": ["echo", "hi"],
+                    "timeout_ms": 1000,
+                },
+                "output": {"content": "hi\n", "success": true},
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_hook_reports_failures() {
+        let hook = |command: &[&str], timeout_ms| ToolHookConfig {
+            command: command.iter().map(ToString::to_string).collect(),
+            tools: Vec::new(),
+            timeout_ms,
+        };
+        let input = HookInput {
+            hook_event: PRE_TOOL_USE,
+            tool_name: "shell",
+            call_id: "call-1",
+            cwd: Path::new("/"),
+            payload: HookPayload::Function {
+                arguments: "{}".to_string(),
+            },
+            output: None,
+        };
+
+        assert_eq!(
+            run_hook(
+                &hook(&["sh", "-c", "cat >/dev/null; echo ok"], None),
+                Path::new("/"),
+                &input
+            )
+            .await,
+            Ok("ok\n".to_string())
+        );
+        assert_eq!(
+            run_hook(
+                &hook(&["sh", "-c", "echo 'not allowed' >&2; exit 2"], None),
+                Path::new("/"),
+                &input
+            )
+            .await,
+            Err("not allowed".to_string())
+        );
+        assert_eq!(
+            run_hook(&hook(&["sleep", "5"], Some(50)), Path::new("/"), &input).await,
+            Err("hook timed out after 50 ms".to_string())
+        );
+    }
+}
@@ -6,6 +6,7 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
 mod apply_patch;
+pub mod approval_rules;
 pub mod auth;
 pub mod bash;
 mod chat_completions;
@@ -21,6 +22,7 @@ pub mod config_edit;
 pub mod config_loader;
 pub mod config_profile;
 pub mod config_types;
+mod container;
 mod conversation_history;
 pub mod custom_prompts;
 mod environment_context;
@@ -28,9 +30,11 @@ pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+mod exec_policy;
 pub mod executor;
 mod flags;
 pub mod git_info;
+mod hooks;
 pub mod landlock;
 mod mcp_connection_manager;
 mod mcp_tool_call;
@@ -59,8 +63,10 @@ pub mod model_family;
 mod openai_model_info;
 mod openai_tools;
 pub mod project_doc;
+pub mod resource_limits;
 mod rollout;
 pub(crate) mod safety;
+mod script_tools;
 pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
@@ -72,10 +78,33 @@ pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::diff::Divergence;
+pub use rollout::diff::ListChange;
+pub use rollout::diff::SessionDiff;
+pub use rollout::diff::SessionSummary;
+pub use rollout::diff::TurnDiff;
+pub use rollout::diff::TurnSummary;
+pub use rollout::diff::diff_lists;
+pub use rollout::diff::diff_sessions;
+pub use rollout::export::ExportFormat;
+pub use rollout::export::ExportOptions;
+pub use rollout::export::export_session;
 pub use rollout::find_conversation_path_by_id_str;
+pub use rollout::gc::GcAction;
+pub use rollout::gc::GcActionKind;
+pub use rollout::gc::GcReport;
+pub use rollout::gc::collect_garbage;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::metadata::SessionMetadata;
+pub use rollout::metadata::load_session_metadata;
+pub use rollout::metadata::normalize_tag;
+pub use rollout::metadata::update_session_metadata;
+pub use rollout::search::IndexEntry;
+pub use rollout::search::IndexEntryKind;
+pub use rollout::search::SearchHit;
+pub use rollout::search::search_sessions;
 mod function_tool;
 mod state;
 mod tasks;
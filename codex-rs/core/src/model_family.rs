@@ -44,6 +44,9 @@ pub struct ModelFamily {
 
     /// Names of beta tools that should be exposed to this model family.
     pub experimental_supported_tools: Vec<String>,
+
+    /// True if the model can request several tool calls in one response.
+    pub supports_parallel_tool_calls: bool,
 }
 
 macro_rules! model_family {
@@ -61,6 +64,7 @@ macro_rules! model_family {
             apply_patch_tool_type: None,
             base_instructions: BASE_INSTRUCTIONS.to_string(),
             experimental_supported_tools: Vec::new(),
+            supports_parallel_tool_calls: false,
         };
         // apply overrides
         $(
@@ -96,11 +100,16 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
         model_family!(
             slug, "gpt-4.1",
             needs_special_apply_patch_instructions: true,
+            supports_parallel_tool_calls: true,
         )
     } else if slug.starts_with("gpt-oss") || slug.starts_with("openai/gpt-oss") {
         model_family!(slug, "gpt-oss", apply_patch_tool_type: Some(ApplyPatchToolType::Function))
     } else if slug.starts_with("gpt-4o") {
-        model_family!(slug, "gpt-4o", needs_special_apply_patch_instructions: true)
+        model_family!(
+            slug, "gpt-4o",
+            needs_special_apply_patch_instructions: true,
+            supports_parallel_tool_calls: true,
+        )
     } else if slug.starts_with("gpt-3.5") {
         model_family!(slug, "gpt-3.5", needs_special_apply_patch_instructions: true)
     } else if slug.starts_with("codex-") || slug.starts_with("gpt-5-codex") {
@@ -110,12 +119,14 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
             reasoning_summary_format: ReasoningSummaryFormat::Experimental,
             base_instructions: GPT_5_CODEX_INSTRUCTIONS.to_string(),
             experimental_supported_tools: vec!["read_file".to_string()],
+            supports_parallel_tool_calls: true,
         )
     } else if slug.starts_with("gpt-5") {
         model_family!(
             slug, "gpt-5",
             supports_reasoning_summaries: true,
             needs_special_apply_patch_instructions: true,
+            supports_parallel_tool_calls: true,
         )
     } else {
         None
@@ -133,5 +144,6 @@ pub fn derive_default_model_family(model: &str) -> ModelFamily {
         apply_patch_tool_type: None,
         base_instructions: BASE_INSTRUCTIONS.to_string(),
         experimental_supported_tools: Vec::new(),
+        supports_parallel_tool_calls: false,
     }
 }
@@ -0,0 +1,451 @@
+//! Per-command resource limits for commands spawned on behalf of the model.
+//!
+//! Limits are applied with `setrlimit(2)` right before the command is
+//! exec'd, so they are inherited by the sandbox helper and every process the
+//! command starts. The output size limit is enforced by Codex itself while it
+//! reads the command's output.
+
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::protocol::ResourceLimitKind;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Exit codes a shell reports when a child was killed by `signal`.
+const EXIT_CODE_SIGNAL_BASE: i32 = 128;
+
+/// Resource limits applied to every command Codex runs for the model, read
+/// from the `[resource_limits]` table in `config.toml`. Unset fields keep the
+/// limits inherited from Codex.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`).
+    pub cpu_time_secs: Option<u64>,
+
+    /// Virtual address space per process in MiB (`RLIMIT_AS`).
+    pub max_memory_mb: Option<u64>,
+
+    /// Number of processes for the user running Codex (`RLIMIT_NPROC`). Note
+    /// that processes started outside of Codex count towards this limit too.
+    /// Running into it only makes `fork(2)` fail, so it is never reported as
+    /// the reason a command stopped.
+    pub max_processes: Option<u64>,
+
+    /// Size of the largest file a command may write, in MiB (`RLIMIT_FSIZE`).
+    pub max_file_size_mb: Option<u64>,
+
+    /// Combined stdout and stderr in MiB; the command is killed once it
+    /// prints more than this.
+    pub max_output_mb: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Only the output limit, which Codex enforces itself. Used for commands
+    /// whose process limits are applied elsewhere, such as by the container
+    /// runtime, instead of to the local process.
+    pub(crate) fn output_only(&self) -> Self {
+        Self {
+            max_output_mb: self.max_output_mb,
+            ..Self::default()
+        }
+    }
+
+    /// `<runtime> run` arguments that apply the process limits to a container.
+    /// Memory and process limits apply to the container as a whole.
+    pub(crate) fn container_run_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(secs) = self.cpu_time_secs {
+            args.push("--ulimit".to_string());
+            args.push(format!("cpu={secs}:{}", secs.saturating_add(1)));
+        }
+        if let Some(mb) = self.max_memory_mb {
+            args.push("--memory".to_string());
+            args.push(format!("{mb}m"));
+        }
+        if let Some(processes) = self.max_processes {
+            args.push("--pids-limit".to_string());
+            args.push(processes.to_string());
+        }
+        if let Some(mb) = self.max_file_size_mb {
+            let bytes = mb.saturating_mul(BYTES_PER_MB);
+            args.push("--ulimit".to_string());
+            args.push(format!("fsize={bytes}:{bytes}"));
+        }
+        args
+    }
+
+    pub(crate) fn max_output_bytes(&self) -> Option<usize> {
+        self.max_output_mb
+            .map(|mb| usize::try_from(mb.saturating_mul(BYTES_PER_MB)).unwrap_or(usize::MAX))
+    }
+
+    /// Applies the limits to the calling process. Only async-signal-safe calls
+    /// are made so this can run in a `pre_exec` hook.
+    #[cfg(unix)]
+    pub(crate) fn apply_to_current_process(&self) -> std::io::Result<()> {
+        if let Some(secs) = self.cpu_time_secs {
+            // The soft limit delivers SIGXCPU, the hard limit SIGKILL a second
+            // later for commands that ignore it.
+            set_rlimit(libc::RLIMIT_CPU, secs, secs.saturating_add(1))?;
+        }
+        if let Some(mb) = self.max_memory_mb {
+            let bytes = mb.saturating_mul(BYTES_PER_MB);
+            set_rlimit(libc::RLIMIT_AS, bytes, bytes)?;
+        }
+        if let Some(processes) = self.max_processes {
+            set_rlimit(libc::RLIMIT_NPROC, processes, processes)?;
+        }
+        if let Some(mb) = self.max_file_size_mb {
+            let bytes = mb.saturating_mul(BYTES_PER_MB);
+            set_rlimit(libc::RLIMIT_FSIZE, bytes, bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the limits to an already running process, used for PTY
+    /// sessions whose spawn cannot be hooked.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn apply_to_process(&self, pid: u32) -> std::io::Result<()> {
+        let pid = libc::pid_t::try_from(pid).map_err(std::io::Error::other)?;
+        let limits = [
+            (
+                libc::RLIMIT_CPU,
+                self.cpu_time_secs
+                    .map(|secs| (secs, secs.saturating_add(1))),
+            ),
+            (
+                libc::RLIMIT_AS,
+                self.max_memory_mb
+                    .map(|mb| mb.saturating_mul(BYTES_PER_MB))
+                    .map(|bytes| (bytes, bytes)),
+            ),
+            (
+                libc::RLIMIT_NPROC,
+                self.max_processes.map(|processes| (processes, processes)),
+            ),
+            (
+                libc::RLIMIT_FSIZE,
+                self.max_file_size_mb
+                    .map(|mb| mb.saturating_mul(BYTES_PER_MB))
+                    .map(|bytes| (bytes, bytes)),
+            ),
+        ];
+        for (resource, limit) in limits {
+            let Some((soft, hard)) = limit else {
+                continue;
+            };
+            let mut current = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            // SAFETY: `current` is a valid, writable rlimit and no new value is set.
+            if unsafe { libc::prlimit(pid, resource, std::ptr::null(), &mut current) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let rlim = clamped_rlimit(soft, hard, &current);
+            // SAFETY: `rlim` is a valid rlimit and the old value is not requested.
+            if unsafe { libc::prlimit(pid, resource, &rlim, std::ptr::null_mut()) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// Lowers `resource` to `soft`/`hard`, never raising it above the inherited
+/// hard limit (which would fail for unprivileged users).
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut current = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `current` is a valid, writable rlimit.
+    if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let rlim = clamped_rlimit(soft, hard, &current);
+    // SAFETY: `rlim` is a valid rlimit.
+    if unsafe { libc::setrlimit(resource, &rlim) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn clamped_rlimit(soft: u64, hard: u64, current: &libc::rlimit) -> libc::rlimit {
+    let hard = libc::rlim_t::from(hard).min(current.rlim_max);
+    libc::rlimit {
+        rlim_cur: libc::rlim_t::from(soft).min(hard),
+        rlim_max: hard,
+    }
+}
+
+/// Works out whether a finished command was stopped by one of `limits`, from
+/// the signal that killed it (or the matching `128 + signal` exit code
+/// reported by a wrapping shell) and the CPU time it used, if measured (see
+/// [`cpu_time_at_exit`]).
+///
+/// Only signals that a limit is the sole sender of, or that come with proof,
+/// are attributed to it: `SIGXCPU` and `SIGXFSZ`, and `SIGKILL` once the
+/// command has used up its CPU time. A `SIGKILL` from `kill -9` or the
+/// system's OOM killer, or a segfault, is reported as an ordinary signal.
+/// Memory and process limits only make allocations and `fork(2)` fail, so
+/// commands that give up because of them are ordinary failures; a container's
+/// memory limit is detected by the runtime instead.
+pub(crate) fn exceeded_limit(
+    limits: &ResourceLimits,
+    exit_status: ExitStatus,
+    cpu_time: Option<Duration>,
+) -> Option<ResourceLimitKind> {
+    if exit_status.success() || limits.is_empty() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        let signal = exit_status
+            .signal()
+            .or_else(|| exit_status.code().and_then(signal_from_exit_code));
+        signal.and_then(|signal| exceeded_limit_for_signal(limits, signal, cpu_time))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = cpu_time;
+        None
+    }
+}
+
+/// Maps the `128 + signal` exit code a shell reports for a killed child back
+/// to the signal.
+pub(crate) fn signal_from_exit_code(code: i32) -> Option<i32> {
+    code.checked_sub(EXIT_CODE_SIGNAL_BASE)
+        .filter(|signal| *signal > 0)
+}
+
+/// Returns the limit that delivered `signal`, if that limit is configured.
+///
+/// The CPU time limit sends `SIGXCPU`, then `SIGKILL` a second later to
+/// commands that ignore it; the `SIGKILL` only counts when `cpu_time` shows
+/// the limit was reached.
+pub(crate) fn exceeded_limit_for_signal(
+    limits: &ResourceLimits,
+    signal: i32,
+    cpu_time: Option<Duration>,
+) -> Option<ResourceLimitKind> {
+    #[cfg(unix)]
+    {
+        if let Some(secs) = limits.cpu_time_secs {
+            let used_up = cpu_time.is_some_and(|cpu_time| cpu_time.as_secs() >= secs);
+            if signal == libc::SIGXCPU || (signal == libc::SIGKILL && used_up) {
+                return Some(ResourceLimitKind::CpuTime);
+            }
+        }
+        if limits.max_file_size_mb.is_some() && signal == libc::SIGXFSZ {
+            return Some(ResourceLimitKind::FileSize);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (limits, signal, cpu_time);
+    None
+}
+
+/// Blocks until the child process `pid` has exited, without reaping it, and
+/// returns the CPU time it and the children it waited for used. Must be
+/// called before the child is reaped; returns `None` when that already
+/// happened or the usage cannot be read.
+#[cfg(target_os = "linux")]
+pub(crate) fn cpu_time_at_exit(pid: u32) -> Option<Duration> {
+    let id = libc::id_t::try_from(pid).ok()?;
+    // SAFETY: an all-zero siginfo_t is a valid value to be filled in.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: `info` is a valid, writable siginfo_t. `WNOWAIT` leaves the
+        // child to be reaped by its owner.
+        let rc = unsafe { libc::waitid(libc::P_PID, id, &mut info, libc::WEXITED | libc::WNOWAIT) };
+        if rc == 0 {
+            break;
+        }
+        if std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+            return None;
+        }
+    }
+    // The zombie keeps its accounting until it is reaped.
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    cpu_time_from_stat(&stat)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn cpu_time_at_exit(_pid: u32) -> Option<Duration> {
+    None
+}
+
+/// Sums `utime`, `stime`, `cutime` and `cstime` of a `/proc/<pid>/stat` line.
+#[cfg(target_os = "linux")]
+fn cpu_time_from_stat(stat: &str) -> Option<Duration> {
+    // The command name may contain spaces and parentheses; the fields after
+    // it start with `state`, the third field of the line.
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    let ticks = fields
+        .get(11..15)?
+        .iter()
+        .map(|field| field.parse::<u64>().ok())
+        .sum::<Option<u64>>()?;
+    // SAFETY: sysconf has no preconditions.
+    let ticks_per_sec = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) })
+        .ok()
+        .filter(|ticks| *ticks > 0)?;
+    Some(Duration::from_millis(
+        ticks.saturating_mul(1000) / ticks_per_sec,
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn killed_by(signal: i32) -> ExitStatus {
+        ExitStatus::from_raw(signal)
+    }
+
+    fn exited_with(code: i32) -> ExitStatus {
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[test]
+    fn signals_map_to_configured_limits() {
+        let limits = ResourceLimits {
+            cpu_time_secs: Some(10),
+            max_file_size_mb: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            exceeded_limit(&limits, killed_by(libc::SIGXCPU), None),
+            Some(ResourceLimitKind::CpuTime)
+        );
+        assert_eq!(
+            exceeded_limit(&limits, exited_with(128 + libc::SIGXFSZ), None),
+            Some(ResourceLimitKind::FileSize)
+        );
+        assert_eq!(
+            exceeded_limit(&limits, killed_by(libc::SIGTERM), None),
+            None
+        );
+        assert_eq!(
+            exceeded_limit(&ResourceLimits::default(), killed_by(libc::SIGXCPU), None),
+            None
+        );
+    }
+
+    #[test]
+    fn sigkill_counts_as_cpu_time_only_once_the_limit_is_used_up() {
+        let limits = ResourceLimits {
+            cpu_time_secs: Some(10),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            exceeded_limit(
+                &limits,
+                killed_by(libc::SIGKILL),
+                Some(Duration::from_secs(11))
+            ),
+            Some(ResourceLimitKind::CpuTime)
+        );
+        assert_eq!(
+            exceeded_limit(
+                &limits,
+                exited_with(128 + libc::SIGKILL),
+                Some(Duration::from_secs(10))
+            ),
+            Some(ResourceLimitKind::CpuTime)
+        );
+        // `kill -9`, or an interrupted command whose CPU time was not measured.
+        assert_eq!(
+            exceeded_limit(
+                &limits,
+                killed_by(libc::SIGKILL),
+                Some(Duration::from_secs(2))
+            ),
+            None
+        );
+        assert_eq!(
+            exceeded_limit(&limits, killed_by(libc::SIGKILL), None),
+            None
+        );
+    }
+
+    #[test]
+    fn memory_and_process_limits_are_not_guessed_from_signals() {
+        let limits = ResourceLimits {
+            max_memory_mb: Some(64),
+            max_processes: Some(32),
+            ..Default::default()
+        };
+
+        // Real segfaults and the system's OOM killer are not the limits.
+        assert_eq!(
+            exceeded_limit(&limits, killed_by(libc::SIGSEGV), None),
+            None
+        );
+        assert_eq!(
+            exceeded_limit(&limits, killed_by(libc::SIGKILL), None),
+            None
+        );
+        assert_eq!(
+            exceeded_limit(&limits, exited_with(128 + libc::SIGKILL), None),
+            None
+        );
+        // Commands that report a failed allocation or fork and exit are
+        // ordinary failures.
+        assert_eq!(exceeded_limit(&limits, exited_with(1), None), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cpu_time_is_read_from_proc_stat() {
+        // SAFETY: sysconf has no preconditions.
+        let ticks_per_sec = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).unwrap();
+        let stat = format!(
+            "42 (my (odd) cmd) S 1 42 42 0 -1 4194560 100 0 0 0 {} {} {} {} 20 0 1 0",
+            ticks_per_sec * 3,
+            ticks_per_sec,
+            ticks_per_sec * 2,
+            0
+        );
+        assert_eq!(cpu_time_from_stat(&stat), Some(Duration::from_secs(6)));
+        assert_eq!(cpu_time_from_stat("42 (cmd) S 1"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cpu_time_at_exit_measures_a_busy_child() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "i=0; while [ $i -lt 200000 ]; do i=$((i+1)); done"])
+            .spawn()
+            .unwrap();
+        let cpu_time = cpu_time_at_exit(child.id());
+        assert!(child.wait().unwrap().success());
+        assert!(cpu_time.is_some_and(|cpu_time| cpu_time > Duration::ZERO));
+    }
+}
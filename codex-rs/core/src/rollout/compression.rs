@@ -0,0 +1,118 @@
+//! Gzip compression of rollout files.
+//!
+//! Old rollouts can be stored as `rollout-…jsonl.gz` (see [`super::gc`]).
+//! Readers go through [`read_rollout_text`], and resuming a compressed session
+//! restores the plain `.jsonl` file so the recorder can append to it again.
+
+use std::io::Read;
+use std::io::Write;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use tempfile::NamedTempFile;
+
+use super::file_lock::replace_file;
+
+pub(crate) const ROLLOUT_EXTENSION: &str = ".jsonl";
+pub(crate) const COMPRESSED_ROLLOUT_EXTENSION: &str = ".jsonl.gz";
+
+pub(crate) fn is_compressed(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(COMPRESSED_ROLLOUT_EXTENSION))
+}
+
+/// Strips the `.jsonl` or `.jsonl.gz` extension from a rollout filename.
+pub(crate) fn rollout_file_stem(name: &str) -> Option<&str> {
+    name.strip_suffix(COMPRESSED_ROLLOUT_EXTENSION)
+        .or_else(|| name.strip_suffix(ROLLOUT_EXTENSION))
+}
+
+/// Reads a rollout file, decompressing it if needed.
+pub(crate) async fn read_rollout_text(path: &Path) -> io::Result<String> {
+    if !is_compressed(path) {
+        return tokio::fs::read_to_string(path).await;
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut text = String::new();
+        GzDecoder::new(std::fs::File::open(&path)?).read_to_string(&mut text)?;
+        Ok(text)
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+/// Replaces the rollout file at `path` with a gzip-compressed copy and returns
+/// the path of the compressed file. The copy is written to a temporary file
+/// and synced before it is renamed into place, so an interrupted compression
+/// never leaves a truncated `.gz` next to (or instead of) the original.
+pub(crate) async fn compress_rollout(path: &Path) -> io::Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".gz");
+    let compressed_path = path.with_file_name(name);
+    let source = path.to_path_buf();
+    let target = compressed_path.clone();
+    tokio::task::spawn_blocking(move || -> io::Result<()> {
+        let dir = source
+            .parent()
+            .ok_or_else(|| io::Error::other(format!("{} has no parent", source.display())))?;
+        let mut input = std::fs::File::open(&source)?;
+        let mut encoder = GzEncoder::new(NamedTempFile::new_in(dir)?, Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        let mut tmp = encoder.finish()?;
+        tmp.flush()?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(&target).map_err(|e| e.error)?;
+        std::fs::remove_file(&source)
+    })
+    .await
+    .map_err(io::Error::other)??;
+    Ok(compressed_path)
+}
+
+/// Size the rollout file at `path` would have after [`compress_rollout`],
+/// without writing anything.
+pub(crate) async fn compressed_len(path: &Path) -> io::Result<u64> {
+    let source = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> io::Result<u64> {
+        let mut input = std::fs::File::open(&source)?;
+        let mut encoder = GzEncoder::new(ByteCounter::default(), Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        Ok(encoder.finish()?.0)
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+/// Writer that only counts the bytes written to it.
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Restores the plain `.jsonl` file for a compressed rollout and returns its
+/// path. Plain rollouts are returned unchanged.
+pub(crate) async fn decompress_rollout(path: &Path) -> io::Result<PathBuf> {
+    if !is_compressed(path) {
+        return Ok(path.to_path_buf());
+    }
+    let text = read_rollout_text(path).await?;
+    let plain_path = path.with_extension("");
+    replace_file(plain_path.clone(), text.into_bytes()).await?;
+    tokio::fs::remove_file(path).await?;
+    Ok(plain_path)
+}
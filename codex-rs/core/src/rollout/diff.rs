@@ -0,0 +1,558 @@
+//! Compare two recorded sessions turn by turn.
+//!
+//! Each rollout is split into turns at the user's messages. The turns of the
+//! two sessions are aligned on identical user messages, and every aligned pair
+//! lists what the sessions did differently: the model, the commands and other
+//! tool calls, the patches and the final agent message.
+
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::WebSearchAction;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TokenUsage;
+use serde::Serialize;
+use similar::Algorithm;
+use similar::DiffTag;
+
+use super::compression::read_rollout_text;
+use super::export::patch_to_diff;
+use super::tool_calls::ToolCall;
+use super::tool_calls::tool_call;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDiff {
+    pub left: SessionSummary,
+    pub right: SessionSummary,
+    pub turns: Vec<TurnDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// Models used, in order of first use.
+    pub models: Vec<String>,
+    pub turns: usize,
+    /// Time from the first to the last recorded item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Token usage of the whole session, as last reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<TokenUsage>,
+}
+
+/// What one session did in response to a user message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TurnSummary {
+    pub user_message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<ReasoningEffort>,
+    pub commands: Vec<String>,
+    /// Calls of tools other than the shell, `apply_patch` and `update_plan`,
+    /// as the tool name followed by its arguments.
+    pub tool_calls: Vec<String>,
+    /// Patches as unified-style diffs.
+    pub patches: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Tokens used by the turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Divergence {
+    UserMessage,
+    Model,
+    Commands,
+    ToolCalls,
+    Patches,
+    FinalMessage,
+}
+
+/// A turn of either session, paired with the matching turn of the other one
+/// when there is one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TurnDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<TurnSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<TurnSummary>,
+    /// Where the two turns differ; empty for turns only one session has.
+    pub divergences: Vec<Divergence>,
+}
+
+impl TurnDiff {
+    fn new(left: Option<TurnSummary>, right: Option<TurnSummary>) -> Self {
+        let divergences = match (&left, &right) {
+            (Some(left), Some(right)) => divergences(left, right),
+            _ => Vec::new(),
+        };
+        Self {
+            left,
+            right,
+            divergences,
+        }
+    }
+
+    /// Both sessions have the turn and did the same thing in it.
+    pub fn is_same(&self) -> bool {
+        self.left.is_some() && self.right.is_some() && self.divergences.is_empty()
+    }
+}
+
+/// An entry of two lists compared with [`diff_lists`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListChange<'a> {
+    Both(&'a str),
+    Left(&'a str),
+    Right(&'a str),
+}
+
+/// Reads the rollout files at `left` and `right` and compares them.
+pub async fn diff_sessions(left: &Path, right: &Path) -> io::Result<SessionDiff> {
+    let (left_summary, left_turns) = summarize_session(left, &read_rollout_lines(left).await?);
+    let (right_summary, right_turns) = summarize_session(right, &read_rollout_lines(right).await?);
+    Ok(SessionDiff {
+        left: left_summary,
+        right: right_summary,
+        turns: align_turns(left_turns, right_turns),
+    })
+}
+
+/// Compares two lists, keeping their common entries in order.
+pub fn diff_lists<'a>(left: &'a [String], right: &'a [String]) -> Vec<ListChange<'a>> {
+    let mut changes = Vec::new();
+    for op in similar::capture_diff_slices(Algorithm::Myers, left, right) {
+        let (tag, old, new) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => changes.extend(left[old].iter().map(|s| ListChange::Both(s))),
+            DiffTag::Delete => changes.extend(left[old].iter().map(|s| ListChange::Left(s))),
+            DiffTag::Insert => changes.extend(right[new].iter().map(|s| ListChange::Right(s))),
+            DiffTag::Replace => {
+                changes.extend(left[old].iter().map(|s| ListChange::Left(s)));
+                changes.extend(right[new].iter().map(|s| ListChange::Right(s)));
+            }
+        }
+    }
+    changes
+}
+
+async fn read_rollout_lines(path: &Path) -> io::Result<Vec<RolloutLine>> {
+    Ok(read_rollout_text(path)
+        .await?
+        .lines()
+        .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+        .collect())
+}
+
+/// Splits a rollout into turns. Items before the first user message, such as
+/// the session meta and instructions, belong to no turn.
+fn summarize_session(path: &Path, lines: &[RolloutLine]) -> (SessionSummary, Vec<TurnSummary>) {
+    let mut summary = SessionSummary {
+        path: path.to_path_buf(),
+        id: None,
+        started_at: None,
+        models: Vec::new(),
+        turns: 0,
+        duration_ms: None,
+        token_usage: None,
+    };
+    let mut turns: Vec<TurnSummary> = Vec::new();
+    // Timestamp of the first line of the current turn.
+    let mut turn_start: Option<DateTime<FixedOffset>> = None;
+    // Session-wide total tokens when the current turn started, and as last reported.
+    let mut tokens_at_turn_start = 0;
+    let mut total_tokens = 0;
+    let first = lines
+        .first()
+        .and_then(|line| parse_timestamp(&line.timestamp));
+    let mut last = first;
+
+    for line in lines {
+        let timestamp = parse_timestamp(&line.timestamp);
+        if timestamp.is_some() {
+            last = timestamp;
+        }
+        match &line.item {
+            RolloutItem::SessionMeta(meta_line) if summary.id.is_none() => {
+                summary.id = Some(meta_line.meta.id.to_string());
+                summary.started_at = Some(meta_line.meta.timestamp.clone());
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(ev))
+                if matches!(ev.kind, None | Some(InputMessageKind::Plain)) =>
+            {
+                turns.push(TurnSummary {
+                    user_message: ev.message.clone(),
+                    ..Default::default()
+                });
+                turn_start = timestamp;
+                tokens_at_turn_start = total_tokens;
+            }
+            RolloutItem::TurnContext(context) => {
+                if !summary.models.contains(&context.model) {
+                    summary.models.push(context.model.clone());
+                }
+                if let Some(turn) = turns.last_mut()
+                    && turn.model.is_none()
+                {
+                    turn.model = Some(context.model.clone());
+                    turn.effort = context.effort;
+                }
+            }
+            RolloutItem::EventMsg(EventMsg::TokenCount(ev)) => {
+                if let Some(info) = &ev.info {
+                    total_tokens = info.total_token_usage.total_tokens;
+                    summary.token_usage = Some(info.total_token_usage.clone());
+                    if let Some(turn) = turns.last_mut() {
+                        turn.total_tokens = Some(total_tokens.saturating_sub(tokens_at_turn_start));
+                    }
+                }
+            }
+            item => {
+                if let Some(turn) = turns.last_mut() {
+                    record_item(turn, item);
+                }
+            }
+        }
+        if let (Some(turn), Some(start), Some(now)) = (turns.last_mut(), turn_start, timestamp) {
+            turn.duration_ms = millis_between(start, now);
+        }
+    }
+
+    summary.turns = turns.len();
+    summary.duration_ms = first
+        .zip(last)
+        .and_then(|(first, last)| millis_between(first, last));
+    (summary, turns)
+}
+
+fn record_item(turn: &mut TurnSummary, item: &RolloutItem) {
+    match tool_call(item) {
+        Some(ToolCall::Command { command, .. }) => {
+            turn.commands.push(command);
+            return;
+        }
+        Some(ToolCall::Patch(patch)) => {
+            turn.patches.push(patch_to_diff(&patch));
+            return;
+        }
+        None => {}
+    }
+    match item {
+        RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+            turn.final_message = Some(ev.message.clone());
+        }
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+            name, arguments, ..
+        }) => match name.as_str() {
+            // Reached only when the arguments do not parse.
+            "shell" | "container.exec" => turn.tool_calls.push(format!("{name} {arguments}")),
+            // Patches whose arguments do not parse are dropped. Plans are
+            // reworded on every attempt, so they are not compared.
+            "apply_patch" | "update_plan" => {}
+            _ => turn
+                .tool_calls
+                .push(format!("{name} {}", compact_json(arguments))),
+        },
+        RolloutItem::ResponseItem(ResponseItem::CustomToolCall { name, input, .. }) => {
+            turn.tool_calls.push(format!("{name} {input}"));
+        }
+        RolloutItem::ResponseItem(ResponseItem::WebSearchCall {
+            action: WebSearchAction::Search { query },
+            ..
+        }) => turn.tool_calls.push(format!("web_search {query}")),
+        _ => {}
+    }
+}
+
+/// Pairs the turns of two sessions. Turns with the same user message are
+/// matched in order; between those, the remaining turns are paired by position
+/// and any left over belong to one session only.
+fn align_turns(left: Vec<TurnSummary>, right: Vec<TurnSummary>) -> Vec<TurnDiff> {
+    let left_keys: Vec<&str> = left.iter().map(|turn| turn.user_message.trim()).collect();
+    let right_keys: Vec<&str> = right.iter().map(|turn| turn.user_message.trim()).collect();
+    let ops = similar::capture_diff_slices(Algorithm::Myers, &left_keys, &right_keys);
+
+    let mut left: Vec<Option<TurnSummary>> = left.into_iter().map(Some).collect();
+    let mut right: Vec<Option<TurnSummary>> = right.into_iter().map(Some).collect();
+    let mut turns = Vec::new();
+    for op in ops {
+        let (_, old, new) = op.as_tag_tuple();
+        let paired = old.len().min(new.len());
+        for (i, j) in old.clone().zip(new.clone()) {
+            turns.push(TurnDiff::new(left[i].take(), right[j].take()));
+        }
+        for i in old.skip(paired) {
+            turns.push(TurnDiff::new(left[i].take(), None));
+        }
+        for j in new.skip(paired) {
+            turns.push(TurnDiff::new(None, right[j].take()));
+        }
+    }
+    turns
+}
+
+fn divergences(left: &TurnSummary, right: &TurnSummary) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    if left.user_message.trim() != right.user_message.trim() {
+        divergences.push(Divergence::UserMessage);
+    }
+    if (&left.model, left.effort) != (&right.model, right.effort) {
+        divergences.push(Divergence::Model);
+    }
+    if left.commands != right.commands {
+        divergences.push(Divergence::Commands);
+    }
+    if left.tool_calls != right.tool_calls {
+        divergences.push(Divergence::ToolCalls);
+    }
+    if left.patches != right.patches {
+        divergences.push(Divergence::Patches);
+    }
+    if left.final_message.as_deref().map(str::trim) != right.final_message.as_deref().map(str::trim)
+    {
+        divergences.push(Divergence::FinalMessage);
+    }
+    divergences
+}
+
+/// Re-serializes JSON arguments so formatting differences do not count as a
+/// divergence.
+fn compact_json(arguments: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(arguments)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|_| arguments.to_string())
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
+
+fn millis_between(start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) -> Option<u64> {
+    u64::try_from((end - start).num_milliseconds()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::ConversationId;
+    use codex_protocol::config_types::ReasoningSummary;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use codex_protocol::protocol::AskForApproval;
+    use codex_protocol::protocol::SandboxPolicy;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use codex_protocol::protocol::TokenCountEvent;
+    use codex_protocol::protocol::TokenUsageInfo;
+    use codex_protocol::protocol::TurnContextItem;
+    use codex_protocol::protocol::UserMessageEvent;
+    use pretty_assertions::assert_eq;
+
+    /// Builds rollout lines one second apart.
+    fn lines(items: Vec<RolloutItem>) -> Vec<RolloutLine> {
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| RolloutLine {
+                timestamp: format!("2025-01-02T03:04:{i:02}.000Z"),
+                item,
+            })
+            .collect()
+    }
+
+    fn meta() -> RolloutItem {
+        RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: ConversationId::from_string("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap(),
+                timestamp: "2025-01-02T03:04:00.000Z".to_string(),
+                ..Default::default()
+            },
+            git: None,
+        })
+    }
+
+    fn user(message: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+            message: message.to_string(),
+            kind: Some(InputMessageKind::Plain),
+            images: None,
+        }))
+    }
+
+    fn turn_context(model: &str) -> RolloutItem {
+        RolloutItem::TurnContext(TurnContextItem {
+            cwd: "/repo".into(),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: model.to_string(),
+            effort: Some(ReasoningEffort::High),
+            summary: ReasoningSummary::Auto,
+        })
+    }
+
+    fn shell(command: &str) -> RolloutItem {
+        let command: Vec<&str> = command.split(' ').collect();
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: serde_json::json!({ "command": command }).to_string(),
+            call_id: "call".to_string(),
+        })
+    }
+
+    fn agent(message: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::AgentMessage(AgentMessageEvent {
+            message: message.to_string(),
+        }))
+    }
+
+    fn tokens(total: u64) -> RolloutItem {
+        let usage = TokenUsage {
+            total_tokens: total,
+            ..Default::default()
+        };
+        RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: usage.clone(),
+                last_token_usage: usage,
+                model_context_window: None,
+            }),
+            rate_limits: None,
+        }))
+    }
+
+    #[test]
+    fn splits_turns_and_summarizes_usage() {
+        let (summary, turns) = summarize_session(
+            Path::new("a.jsonl"),
+            &lines(vec![
+                meta(),
+                user("Fix the test"),
+                turn_context("gpt-5"),
+                shell("cargo test"),
+                RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                    id: None,
+                    status: None,
+                    call_id: "patch".to_string(),
+                    name: "apply_patch".to_string(),
+                    input: "*** Begin Patch\n*** Update File: a.rs\n-1\n+2\n*** End Patch"
+                        .to_string(),
+                }),
+                agent("Fixed."),
+                tokens(100),
+                user("Now run clippy"),
+                turn_context("o3"),
+                shell("cargo clippy"),
+                agent("Clean."),
+                tokens(250),
+            ]),
+        );
+
+        assert_eq!(
+            summary.id.as_deref(),
+            Some("67e55044-10b1-426f-9247-bb680e5fe0c8")
+        );
+        assert_eq!(summary.models, vec!["gpt-5".to_string(), "o3".to_string()]);
+        assert_eq!(summary.turns, 2);
+        assert_eq!(summary.duration_ms, Some(11_000));
+        assert_eq!(
+            summary.token_usage.map(|usage| usage.total_tokens),
+            Some(250)
+        );
+
+        assert_eq!(
+            turns[0],
+            TurnSummary {
+                user_message: "Fix the test".to_string(),
+                model: Some("gpt-5".to_string()),
+                effort: Some(ReasoningEffort::High),
+                commands: vec!["cargo test".to_string()],
+                tool_calls: Vec::new(),
+                patches: vec!["--- a/a.rs\n+++ b/a.rs\n-1\n+2".to_string()],
+                final_message: Some("Fixed.".to_string()),
+                duration_ms: Some(5_000),
+                total_tokens: Some(100),
+            }
+        );
+        assert_eq!(turns[1].total_tokens, Some(150));
+        assert_eq!(turns[1].duration_ms, Some(4_000));
+    }
+
+    #[test]
+    fn aligns_turns_on_user_messages() {
+        let turn = |message: &str, command: &str, reply: &str| TurnSummary {
+            user_message: message.to_string(),
+            commands: vec![command.to_string()],
+            final_message: Some(reply.to_string()),
+            ..Default::default()
+        };
+        let left = vec![
+            turn("Fix the test", "cargo test", "Fixed."),
+            turn("Explain the fix", "git diff", "It was off by one."),
+            turn("Commit it", "git commit", "Committed."),
+        ];
+        let right = vec![
+            turn("Fix the test", "cargo test -p core", "Fixed."),
+            turn("Commit it", "git commit", "Committed."),
+            turn("Push it", "git push", "Pushed."),
+        ];
+
+        let aligned: Vec<_> = align_turns(left, right)
+            .into_iter()
+            .map(|diff| {
+                (
+                    diff.left.map(|turn| turn.user_message),
+                    diff.right.map(|turn| turn.user_message),
+                    diff.divergences,
+                )
+            })
+            .collect();
+        let message = |text: &str| Some(text.to_string());
+        assert_eq!(
+            aligned,
+            vec![
+                (
+                    message("Fix the test"),
+                    message("Fix the test"),
+                    vec![Divergence::Commands]
+                ),
+                (message("Explain the fix"), None, Vec::new()),
+                (message("Commit it"), message("Commit it"), Vec::new()),
+                (None, message("Push it"), Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_lists_keeps_common_entries_in_order() {
+        let left = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let right = vec!["a".to_string(), "x".to_string(), "c".to_string()];
+        assert_eq!(
+            diff_lists(&left, &right),
+            vec![
+                ListChange::Both("a"),
+                ListChange::Left("b"),
+                ListChange::Right("x"),
+                ListChange::Both("c"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Cross-process locking and atomic replacement for the shared files under
+//! `CODEX_HOME` (the search index and session metadata), which several codex
+//! processes may update at once.
+
+use std::fs::File;
+use std::io::Write;
+use std::io::{self};
+use std::path::PathBuf;
+
+/// An exclusive advisory lock on a lock file, released when dropped. The lock
+/// lives in its own file because the files it guards are replaced by rename.
+pub(crate) struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Blocks until the lock on `path` is held, creating the file if needed.
+    pub(crate) async fn acquire(path: PathBuf) -> io::Result<Self> {
+        tokio::task::spawn_blocking(move || {
+            let file = File::options()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+            file.lock()?;
+            Ok(Self { _file: file })
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Replaces `path` with `contents` by writing a temporary file next to it,
+/// syncing it and renaming it over `path`, so readers never see a partial file.
+pub(crate) async fn replace_file(path: PathBuf, contents: Vec<u8>) -> io::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        let dir = path
+            .parent()
+            .ok_or_else(|| io::Error::other(format!("{} has no parent", path.display())))?;
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(&contents)?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(&path).map_err(|e| e.error)?;
+        Ok(())
+    })
+    .await
+    .map_err(io::Error::other)?
+}
@@ -0,0 +1,225 @@
+//! Garbage collection of recorded sessions.
+//!
+//! Applies the retention settings in [`SessionsConfig`] to the rollout files
+//! under `~/.codex/sessions`. Age is measured from the last write to a file.
+//! Sessions that a recorder in any codex process still holds open are left
+//! alone (the recorder keeps a shared lock on its rollout file). The size
+//! limit also never deletes the newest session or one written within
+//! [`SIZE_LIMIT_GRACE`].
+//!
+//! Retention is only applied when `codex sessions gc` runs; nothing is deleted
+//! or compressed in the background.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::TryLockError;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::ConversationId;
+use serde::Serialize;
+
+use super::SESSIONS_SUBDIR;
+use super::compression::compress_rollout;
+use super::compression::compressed_len;
+use super::compression::is_compressed;
+use super::list::collect_rollout_files;
+use super::metadata::load_session_metadata;
+use super::search::prune_index;
+use crate::config_types::SessionsConfig;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Sessions written more recently than this are kept by the size limit.
+const SIZE_LIMIT_GRACE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GcActionKind {
+    /// Deleted because it is older than `max_age_days`.
+    Expired,
+    /// Deleted to get under `max_total_size_mb`.
+    OverSizeLimit,
+    /// Compressed because it is older than `compress_after_days`.
+    Compressed,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GcAction {
+    pub kind: GcActionKind,
+    pub path: PathBuf,
+    /// Size of the file before the action.
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub actions: Vec<GcAction>,
+    /// Pinned sessions that would otherwise have been deleted.
+    pub kept_pinned: usize,
+}
+
+impl GcReport {
+    /// Bytes freed by deleting sessions.
+    pub fn deleted_bytes(&self) -> u64 {
+        self.actions
+            .iter()
+            .filter(|action| action.kind != GcActionKind::Compressed)
+            .map(|action| action.bytes)
+            .sum()
+    }
+}
+
+struct RolloutFile {
+    path: PathBuf,
+    id: Option<ConversationId>,
+    modified: SystemTime,
+    bytes: u64,
+}
+
+/// Locks the rollout file at `path` exclusively so no recorder can open it
+/// while it is deleted or compressed. Returns `None` if a recorder holds it.
+async fn lock_unless_recording(path: &Path) -> io::Result<Option<File>> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let file = File::open(&path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(file)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+/// Applies `config` to the recorded sessions in `codex_home`. Sessions older
+/// than `max_age_days` are deleted first, then sessions older than
+/// `compress_after_days` are compressed, and finally the least recently
+/// written sessions are deleted until the rest fit in `max_total_size_mb`.
+/// Sessions that are still being recorded are skipped by all three steps.
+/// With `dry_run` set, nothing is changed and the report lists what would be
+/// done; the size limit then uses the compressed size each compressed session
+/// would have.
+pub async fn collect_garbage(
+    codex_home: &Path,
+    config: &SessionsConfig,
+    dry_run: bool,
+) -> io::Result<GcReport> {
+    let pinned: HashSet<ConversationId> = if config.keep_pinned {
+        load_session_metadata(codex_home)
+            .await?
+            .into_iter()
+            .filter(|(_, metadata)| metadata.pinned)
+            .map(|(id, _)| id)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut files = Vec::new();
+    for (_, uuid, path) in collect_rollout_files(&codex_home.join(SESSIONS_SUBDIR)).await? {
+        let metadata = tokio::fs::metadata(&path).await?;
+        files.push(RolloutFile {
+            path,
+            id: ConversationId::from_string(&uuid.to_string()).ok(),
+            modified: metadata.modified()?,
+            bytes: metadata.len(),
+        });
+    }
+    // Least recently written first.
+    files.sort_by_key(|file| file.modified);
+
+    let now = SystemTime::now();
+    let older_than = |days: Option<u64>, file: &RolloutFile| {
+        days.is_some_and(|days| {
+            now.duration_since(file.modified)
+                .is_ok_and(|age| age > Duration::from_secs(days * SECONDS_PER_DAY))
+        })
+    };
+    let is_pinned = |file: &RolloutFile| file.id.is_some_and(|id| pinned.contains(&id));
+
+    let mut report = GcReport::default();
+    let mut kept = Vec::with_capacity(files.len());
+    for file in files {
+        if !older_than(config.max_age_days, &file) {
+            kept.push(file);
+        } else if is_pinned(&file) {
+            report.kept_pinned += 1;
+            kept.push(file);
+        } else if let Some(_lock) = lock_unless_recording(&file.path).await? {
+            if !dry_run {
+                tokio::fs::remove_file(&file.path).await?;
+            }
+            report.actions.push(GcAction {
+                kind: GcActionKind::Expired,
+                path: file.path,
+                bytes: file.bytes,
+            });
+        } else {
+            kept.push(file);
+        }
+    }
+
+    for file in &mut kept {
+        if is_compressed(&file.path) || !older_than(config.compress_after_days, file) {
+            continue;
+        }
+        let Some(_lock) = lock_unless_recording(&file.path).await? else {
+            continue;
+        };
+        report.actions.push(GcAction {
+            kind: GcActionKind::Compressed,
+            path: file.path.clone(),
+            bytes: file.bytes,
+        });
+        if dry_run {
+            file.bytes = compressed_len(&file.path).await?;
+        } else {
+            file.path = compress_rollout(&file.path).await?;
+            file.bytes = tokio::fs::metadata(&file.path).await?.len();
+        }
+    }
+
+    if let Some(max_mb) = config.max_total_size_mb {
+        let limit = max_mb.saturating_mul(BYTES_PER_MB);
+        let mut total: u64 = kept.iter().map(|file| file.bytes).sum();
+        let newest = kept.len().saturating_sub(1);
+        for (index, file) in kept.iter().enumerate() {
+            if total <= limit {
+                break;
+            }
+            let recently_written = !now
+                .duration_since(file.modified)
+                .is_ok_and(|age| age >= SIZE_LIMIT_GRACE);
+            if index == newest || recently_written {
+                continue;
+            }
+            if is_pinned(file) {
+                report.kept_pinned += 1;
+                continue;
+            }
+            let Some(_lock) = lock_unless_recording(&file.path).await? else {
+                continue;
+            };
+            if !dry_run {
+                tokio::fs::remove_file(&file.path).await?;
+            }
+            total -= file.bytes;
+            report.actions.push(GcAction {
+                kind: GcActionKind::OverSizeLimit,
+                path: file.path.clone(),
+                bytes: file.bytes,
+            });
+        }
+    }
+
+    if !dry_run && !report.actions.is_empty() {
+        prune_index(codex_home).await?;
+    }
+    Ok(report)
+}
@@ -14,6 +14,9 @@ use time::macros::format_description;
 use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
+use super::compression::is_compressed;
+use super::compression::read_rollout_text;
+use super::compression::rollout_file_stem;
 use crate::protocol::EventMsg;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
@@ -71,9 +74,15 @@ pub struct Cursor {
 }
 
 impl Cursor {
-    fn new(ts: OffsetDateTime, id: Uuid) -> Self {
+    pub(super) fn new(ts: OffsetDateTime, id: Uuid) -> Self {
         Self { ts, id }
     }
+
+    /// Whether the file identified by `ts` and `id` comes after the cursor in
+    /// listing order (timestamp desc, then UUID desc).
+    pub(super) fn precedes(&self, ts: OffsetDateTime, id: Uuid) -> bool {
+        ts < self.ts || (ts == self.ts && id < self.id)
+    }
 }
 
 impl serde::Serialize for Cursor {
@@ -133,7 +142,7 @@ pub(crate) async fn get_conversations(
 /// Returns the entire file contents as a String.
 #[allow(dead_code)]
 pub(crate) async fn get_conversation(path: &Path) -> io::Result<String> {
-    tokio::fs::read_to_string(path).await
+    read_rollout_text(path).await
 }
 
 /// Load conversation file paths from disk using directory traversal.
@@ -171,7 +180,7 @@ async fn traverse_directories_for_paths(
                     break 'outer;
                 }
                 let mut day_files = collect_files(day_path, |name_str, path| {
-                    if !name_str.starts_with("rollout-") || !name_str.ends_with(".jsonl") {
+                    if !name_str.starts_with("rollout-") || rollout_file_stem(name_str).is_none() {
                         return None;
                     }
 
@@ -240,6 +249,109 @@ async fn traverse_directories_for_paths(
     })
 }
 
+/// Collects every rollout file under `root`, newest first, with the timestamp
+/// and UUID parsed from its filename.
+pub(super) async fn collect_rollout_files(
+    root: &Path,
+) -> io::Result<Vec<(OffsetDateTime, Uuid, PathBuf)>> {
+    let mut files = Vec::new();
+    if !root.exists() {
+        return Ok(files);
+    }
+    for (_year, year_path) in collect_dirs_desc(root, |s| s.parse::<u16>().ok()).await? {
+        for (_month, month_path) in collect_dirs_desc(&year_path, |s| s.parse::<u8>().ok()).await? {
+            for (_day, day_path) in collect_dirs_desc(&month_path, |s| s.parse::<u8>().ok()).await?
+            {
+                let day_files = collect_files(&day_path, |name_str, path| {
+                    if !name_str.starts_with("rollout-") || rollout_file_stem(name_str).is_none() {
+                        return None;
+                    }
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .map(|(ts, id)| (ts, id, path.to_path_buf()))
+                })
+                .await?;
+                files.extend(day_files);
+            }
+        }
+    }
+    files.sort_by_key(|(ts, id, _path)| (Reverse(*ts), Reverse(*id)));
+    Ok(files)
+}
+
+/// Pages through `files` (as returned by [`collect_rollout_files`], newest
+/// first) like a conversation listing.
+pub(super) async fn paginate_rollout_files(
+    files: Vec<(OffsetDateTime, Uuid, PathBuf)>,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+    allowed_sources: &[SessionSource],
+) -> io::Result<ConversationsPage> {
+    let num_scanned_files = files.len();
+    let mut remaining = files
+        .into_iter()
+        .filter(|(ts, id, _)| cursor.is_none_or(|cursor| cursor.precedes(*ts, *id)));
+
+    let mut items = Vec::with_capacity(page_size);
+    let mut last = None;
+    for (ts, id, path) in remaining.by_ref() {
+        if let Some(item) = read_conversation_item(path, allowed_sources).await {
+            items.push(item);
+            last = Some(Cursor::new(ts, id));
+        }
+        if items.len() == page_size {
+            break;
+        }
+    }
+    let next_cursor = if remaining.next().is_some() {
+        last
+    } else {
+        None
+    };
+    Ok(ConversationsPage {
+        items,
+        next_cursor,
+        num_scanned_files,
+        reached_scan_cap: false,
+    })
+}
+
+/// Reads the summary of the rollout file at `path` the same way a listing
+/// does. Returns `None` for files without session metadata or whose source is
+/// not in `allowed_sources` (unless it is empty).
+async fn read_conversation_item(
+    path: PathBuf,
+    allowed_sources: &[SessionSource],
+) -> Option<ConversationItem> {
+    let summary = read_head_and_tail(&path, HEAD_RECORD_LIMIT, TAIL_RECORD_LIMIT)
+        .await
+        .ok()?;
+    if !summary.saw_session_meta {
+        return None;
+    }
+    if !allowed_sources.is_empty()
+        && !summary
+            .source
+            .is_some_and(|source| allowed_sources.contains(&source))
+    {
+        return None;
+    }
+    let HeadTailSummary {
+        head,
+        tail,
+        created_at,
+        updated_at,
+        ..
+    } = summary;
+    let updated_at = updated_at.or_else(|| created_at.clone());
+    Some(ConversationItem {
+        path,
+        head,
+        tail,
+        created_at,
+        updated_at,
+    })
+}
+
 /// Pagination cursor token format: "<file_ts>|<uuid>" where `file_ts` matches the
 /// filename timestamp portion (YYYY-MM-DDThh-mm-ss) used in rollout filenames.
 /// The cursor orders files by timestamp desc, then UUID desc.
@@ -312,8 +424,8 @@ where
 }
 
 fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
-    // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl
-    let core = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
+    // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl (or .jsonl.gz)
+    let core = rollout_file_stem(name.strip_prefix("rollout-")?)?;
 
     // Scan from the right for a '-' such that the suffix parses as a UUID.
     let (sep_idx, uuid) = core
@@ -335,55 +447,33 @@ async fn read_head_and_tail(
 ) -> io::Result<HeadTailSummary> {
     use tokio::io::AsyncBufReadExt;
 
+    let mut summary = HeadTailSummary::default();
+
+    // Compressed rollouts are old and small enough to read in one go.
+    if is_compressed(path) {
+        let text = read_rollout_text(path).await?;
+        for line in text.lines() {
+            if summary.head.len() >= head_limit {
+                break;
+            }
+            summarize_head_line(&mut summary, line);
+        }
+        if tail_limit != 0 {
+            let (tail, updated_at) = collect_last_response_values(text.as_bytes(), tail_limit);
+            summary.tail = tail;
+            summary.updated_at = updated_at;
+        }
+        return Ok(summary);
+    }
+
     let file = tokio::fs::File::open(path).await?;
     let reader = tokio::io::BufReader::new(file);
     let mut lines = reader.lines();
-    let mut summary = HeadTailSummary::default();
 
     while summary.head.len() < head_limit {
         let line_opt = lines.next_line().await?;
         let Some(line) = line_opt else { break };
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        let parsed: Result<RolloutLine, _> = serde_json::from_str(trimmed);
-        let Ok(rollout_line) = parsed else { continue };
-
-        match rollout_line.item {
-            RolloutItem::SessionMeta(session_meta_line) => {
-                summary.source = Some(session_meta_line.meta.source);
-                summary.created_at = summary
-                    .created_at
-                    .clone()
-                    .or_else(|| Some(rollout_line.timestamp.clone()));
-                if let Ok(val) = serde_json::to_value(session_meta_line) {
-                    summary.head.push(val);
-                    summary.saw_session_meta = true;
-                }
-            }
-            RolloutItem::ResponseItem(item) => {
-                summary.created_at = summary
-                    .created_at
-                    .clone()
-                    .or_else(|| Some(rollout_line.timestamp.clone()));
-                if let Ok(val) = serde_json::to_value(item) {
-                    summary.head.push(val);
-                }
-            }
-            RolloutItem::TurnContext(_) => {
-                // Not included in `head`; skip.
-            }
-            RolloutItem::Compacted(_) => {
-                // Not included in `head`; skip.
-            }
-            RolloutItem::EventMsg(ev) => {
-                if matches!(ev, EventMsg::UserMessage(_)) {
-                    summary.saw_user_event = true;
-                }
-            }
-        }
+        summarize_head_line(&mut summary, &line);
     }
 
     if tail_limit != 0 {
@@ -394,6 +484,53 @@ async fn read_head_and_tail(
     Ok(summary)
 }
 
+fn summarize_head_line(summary: &mut HeadTailSummary, line: &str) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+
+    let parsed: Result<RolloutLine, _> = serde_json::from_str(trimmed);
+    let Ok(rollout_line) = parsed else { return };
+
+    match rollout_line.item {
+        RolloutItem::SessionMeta(session_meta_line) => {
+            summary.source = Some(session_meta_line.meta.source);
+            summary.created_at = summary
+                .created_at
+                .clone()
+                .or_else(|| Some(rollout_line.timestamp.clone()));
+            if let Ok(val) = serde_json::to_value(session_meta_line) {
+                summary.head.push(val);
+                summary.saw_session_meta = true;
+            }
+        }
+        RolloutItem::ResponseItem(item) => {
+            summary.created_at = summary
+                .created_at
+                .clone()
+                .or_else(|| Some(rollout_line.timestamp.clone()));
+            if let Ok(val) = serde_json::to_value(item) {
+                summary.head.push(val);
+            }
+        }
+        RolloutItem::TurnContext(_) => {
+            // Not included in `head`; skip.
+        }
+        RolloutItem::Compacted(_) => {
+            // Not included in `head`; skip.
+        }
+        RolloutItem::ModeState(_) => {
+            // Not included in `head`; skip.
+        }
+        RolloutItem::EventMsg(ev) => {
+            if matches!(ev, EventMsg::UserMessage(_)) {
+                summary.saw_user_event = true;
+            }
+        }
+    }
+}
+
 async fn read_tail_records(
     path: &Path,
     max_records: usize,
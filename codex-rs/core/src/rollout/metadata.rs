@@ -0,0 +1,223 @@
+//! User-provided metadata about recorded sessions.
+//!
+//! `~/.codex/session_metadata.json` maps conversation ids to the title, tags
+//! and pin state a user attached to a session. It lives next to the rollout
+//! files rather than inside them so it can change after a session has ended,
+//! including for sessions whose rollout has been compressed.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::{self};
+use std::path::Path;
+
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::SessionSource;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::SESSIONS_SUBDIR;
+use super::file_lock::FileLock;
+use super::file_lock::replace_file;
+use super::list::ConversationsPage;
+use super::list::Cursor;
+use super::list::collect_rollout_files;
+use super::list::paginate_rollout_files;
+
+/// Name of the session metadata file under `CODEX_HOME`.
+pub const SESSION_METADATA_FILE: &str = "session_metadata.json";
+
+/// Longest title generated from a first message, in characters.
+const AUTO_TITLE_MAX_CHARS: usize = 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionMetadata {
+    /// Name shown instead of the first message when listing sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Normalized tags (see [`normalize_tag`]), sorted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Pinned sessions are kept by garbage collection when
+    /// `sessions.keep_pinned` is set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl SessionMetadata {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        normalize_tag(tag).is_some_and(|tag| self.tags.contains(&tag))
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if let Some(tag) = normalize_tag(tag)
+            && let Err(idx) = self.tags.binary_search(&tag)
+        {
+            self.tags.insert(idx, tag);
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        if let Some(tag) = normalize_tag(tag) {
+            self.tags.retain(|existing| *existing != tag);
+        }
+    }
+
+    /// Sets the title; a blank title clears it.
+    pub fn set_title(&mut self, title: &str) {
+        let title = title.trim();
+        self.title = (!title.is_empty()).then(|| title.to_string());
+    }
+}
+
+/// Lowercases a tag and strips a leading `#`. Returns `None` for tags that are
+/// empty or contain whitespace.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag);
+    if tag.is_empty() || tag.chars().any(char::is_whitespace) {
+        return None;
+    }
+    Some(tag.to_lowercase())
+}
+
+/// Reads the metadata of all sessions. A missing file means no session has
+/// metadata yet.
+pub async fn load_session_metadata(
+    codex_home: &Path,
+) -> io::Result<HashMap<ConversationId, SessionMetadata>> {
+    match tokio::fs::read_to_string(codex_home.join(SESSION_METADATA_FILE)).await {
+        Ok(text) => serde_json::from_str(&text).map_err(io::Error::other),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Applies `update` to the metadata of one session, saves the result and
+/// returns it. The read-modify-write cycle holds a lock file next to the
+/// metadata file, so concurrent codex processes do not drop each other's
+/// changes.
+pub async fn update_session_metadata(
+    codex_home: &Path,
+    conversation_id: ConversationId,
+    update: impl FnOnce(&mut SessionMetadata),
+) -> io::Result<SessionMetadata> {
+    let path = codex_home.join(SESSION_METADATA_FILE);
+    let _lock = FileLock::acquire(path.with_extension("lock")).await?;
+    let mut all = load_session_metadata(codex_home).await?;
+    let mut metadata = all.remove(&conversation_id).unwrap_or_default();
+    update(&mut metadata);
+    if metadata != SessionMetadata::default() {
+        all.insert(conversation_id, metadata.clone());
+    }
+
+    replace_file(path, serde_json::to_vec_pretty(&all)?).await?;
+    Ok(metadata)
+}
+
+/// Titles a session after its first user message unless it already has a
+/// title.
+pub(crate) async fn set_auto_title(
+    codex_home: &Path,
+    conversation_id: ConversationId,
+    message: &str,
+) -> io::Result<()> {
+    let Some(title) = auto_title(message) else {
+        return Ok(());
+    };
+    update_session_metadata(codex_home, conversation_id, |metadata| {
+        if metadata.title.is_none() {
+            metadata.title = Some(title);
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+/// The first non-blank line of `message`, whitespace collapsed and shortened
+/// to [`AUTO_TITLE_MAX_CHARS`].
+fn auto_title(message: &str) -> Option<String> {
+    let line = message.lines().find(|line| !line.trim().is_empty())?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let title = words.join(" ");
+    if title.chars().count() <= AUTO_TITLE_MAX_CHARS {
+        return Some(title);
+    }
+    let mut shortened: String = title.chars().take(AUTO_TITLE_MAX_CHARS - 1).collect();
+    // Prefer cutting at a word boundary.
+    if let Some(idx) = shortened.rfind(' ')
+        && idx > AUTO_TITLE_MAX_CHARS / 2
+    {
+        shortened.truncate(idx);
+    }
+    shortened.push('…');
+    Some(shortened)
+}
+
+/// Lists the conversations tagged with `tag`, newest first, paged like
+/// [`super::list::get_conversations`].
+pub(crate) async fn tagged_conversations(
+    codex_home: &Path,
+    tag: &str,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+    allowed_sources: &[SessionSource],
+) -> io::Result<ConversationsPage> {
+    let tagged = tagged_ids(codex_home, tag).await?;
+    let files = collect_rollout_files(&codex_home.join(SESSIONS_SUBDIR))
+        .await?
+        .into_iter()
+        .filter(|(_, id, _)| {
+            ConversationId::from_string(&id.to_string()).is_ok_and(|id| tagged.contains(&id))
+        })
+        .collect();
+    paginate_rollout_files(files, page_size, cursor, allowed_sources).await
+}
+
+/// Ids of the conversations tagged with `tag`.
+pub(crate) async fn tagged_ids(
+    codex_home: &Path,
+    tag: &str,
+) -> io::Result<HashSet<ConversationId>> {
+    Ok(load_session_metadata(codex_home)
+        .await?
+        .into_iter()
+        .filter(|(_, metadata)| metadata.has_tag(tag))
+        .map(|(id, _)| id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn auto_title_uses_first_line_and_shortens_at_a_word() {
+        assert_eq!(
+            auto_title("\n  Fix   the login\ttest \nand more details"),
+            Some("Fix the login test".to_string())
+        );
+        assert_eq!(
+            auto_title(
+                "Refactor the configuration loader so that profiles can override tool filters per session"
+            ),
+            Some("Refactor the configuration loader so that profiles can…".to_string())
+        );
+        assert_eq!(auto_title("   \n  "), None);
+    }
+
+    #[test]
+    fn tags_are_normalized_sorted_and_unique() {
+        let mut metadata = SessionMetadata::default();
+        metadata.add_tag("#Bug");
+        metadata.add_tag("auth");
+        metadata.add_tag("bug");
+        metadata.add_tag("two words");
+        assert_eq!(metadata.tags, vec!["auth".to_string(), "bug".to_string()]);
+        assert!(metadata.has_tag("#BUG"));
+        metadata.remove_tag("Auth");
+        assert_eq!(metadata.tags, vec!["bug".to_string()]);
+    }
+}
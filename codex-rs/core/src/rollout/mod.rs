@@ -7,9 +7,17 @@ pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 pub const INTERACTIVE_SESSION_SOURCES: &[SessionSource] =
     &[SessionSource::Cli, SessionSource::VSCode];
 
+pub(crate) mod compression;
+pub mod diff;
+pub mod export;
+mod file_lock;
+pub mod gc;
 pub mod list;
+pub mod metadata;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod search;
+mod tool_calls;
 
 pub use codex_protocol::protocol::SessionMeta;
 pub use list::find_conversation_path_by_id_str;
@@ -19,14 +19,22 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::compression::decompress_rollout;
+use super::compression::read_rollout_text;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
+use super::metadata::set_auto_title;
+use super::metadata::tagged_conversations;
 use super::policy::is_persisted_response_item;
+use super::search;
+use super::search::search_conversations;
 use crate::config::Config;
 use crate::default_client::originator;
 use crate::git_info::collect_git_info;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::InitialHistory;
+use codex_protocol::protocol::InputMessageKind;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
@@ -101,6 +109,32 @@ impl RolloutRecorder {
         get_conversations(codex_home, page_size, cursor, allowed_sources).await
     }
 
+    /// Like [`Self::list_conversations`], but only returns conversations whose
+    /// recorded text matches `query` and, if set, that carry `tag`. See
+    /// [`super::search`].
+    pub async fn search_conversations(
+        codex_home: &Path,
+        query: &str,
+        tag: Option<&str>,
+        page_size: usize,
+        cursor: Option<&Cursor>,
+        allowed_sources: &[SessionSource],
+    ) -> std::io::Result<ConversationsPage> {
+        search_conversations(codex_home, query, tag, page_size, cursor, allowed_sources).await
+    }
+
+    /// Like [`Self::list_conversations`], but only returns conversations
+    /// tagged with `tag`. See [`super::metadata`].
+    pub async fn list_tagged_conversations(
+        codex_home: &Path,
+        tag: &str,
+        page_size: usize,
+        cursor: Option<&Cursor>,
+        allowed_sources: &[SessionSource],
+    ) -> std::io::Result<ConversationsPage> {
+        tagged_conversations(codex_home, tag, page_size, cursor, allowed_sources).await
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
@@ -140,18 +174,33 @@ impl RolloutRecorder {
                     }),
                 )
             }
-            RolloutRecorderParams::Resume { path } => (
-                tokio::fs::OpenOptions::new()
-                    .append(true)
-                    .open(&path)
-                    .await?,
-                path,
-                None,
-            ),
+            RolloutRecorderParams::Resume { path } => {
+                // Sessions compressed by garbage collection are restored so
+                // new items can be appended.
+                let path = decompress_rollout(&path).await?;
+                let file = std::fs::OpenOptions::new().append(true).open(&path)?;
+                hold_while_recording(&file, &path);
+                (tokio::fs::File::from_std(file), path, None)
+            }
         };
 
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
+        let index = SearchIndexState {
+            codex_home: config.codex_home.clone(),
+            path: rollout_path.clone(),
+            // A new file has nothing indexed yet; a resumed one is looked up
+            // on the first flush.
+            indexed_to: meta.is_some().then_some(0),
+        };
+        // Only new sessions get a title from their first message.
+        let auto_title = meta
+            .as_ref()
+            .filter(|_| config.sessions.auto_title)
+            .map(|meta| AutoTitle {
+                codex_home: config.codex_home.clone(),
+                conversation_id: meta.id,
+            });
 
         // A reasonably-sized bounded channel. If the buffer fills up the send
         // future will yield, which is fine – we only need to ensure we do not
@@ -161,7 +210,7 @@ impl RolloutRecorder {
         // Spawn a Tokio task that owns the file handle and performs async
         // writes. Using `tokio::fs::File` keeps everything on the async I/O
         // driver instead of blocking the runtime.
-        tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
+        tokio::task::spawn(rollout_writer(file, rx, meta, cwd, index, auto_title));
 
         Ok(Self { tx, rollout_path })
     }
@@ -198,7 +247,7 @@ impl RolloutRecorder {
 
     pub(crate) async fn get_rollout_history(path: &Path) -> std::io::Result<InitialHistory> {
         info!("Resuming rollout from {path:?}");
-        let text = tokio::fs::read_to_string(path).await?;
+        let text = read_rollout_text(path).await?;
         if text.trim().is_empty() {
             return Err(IoError::other("empty session file"));
         }
@@ -237,6 +286,9 @@ impl RolloutRecorder {
                     RolloutItem::TurnContext(item) => {
                         items.push(RolloutItem::TurnContext(item));
                     }
+                    RolloutItem::ModeState(item) => {
+                        items.push(RolloutItem::ModeState(item));
+                    }
                     RolloutItem::EventMsg(_ev) => {
                         items.push(RolloutItem::EventMsg(_ev));
                     }
@@ -330,6 +382,7 @@ fn create_log_file(
         .append(true)
         .create(true)
         .open(&path)?;
+    hold_while_recording(&file, &path);
 
     Ok(LogFileInfo {
         file,
@@ -339,11 +392,22 @@ fn create_log_file(
     })
 }
 
+/// Takes a shared lock on the rollout file that lasts as long as the file is
+/// open, so garbage collection leaves the session alone while it is recorded.
+/// Filesystems without lock support only lose that protection.
+fn hold_while_recording(file: &File, path: &Path) {
+    if let Err(e) = file.lock_shared() {
+        warn!("failed to lock rollout file {}: {e}", path.display());
+    }
+}
+
 async fn rollout_writer(
     file: tokio::fs::File,
     mut rx: mpsc::Receiver<RolloutCmd>,
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
+    mut index: SearchIndexState,
+    mut auto_title: Option<AutoTitle>,
 ) -> std::io::Result<()> {
     let mut writer = JsonlWriter { file };
 
@@ -367,6 +431,12 @@ async fn rollout_writer(
             RolloutCmd::AddItems(items) => {
                 for item in items {
                     if is_persisted_response_item(&item) {
+                        if let RolloutItem::EventMsg(EventMsg::UserMessage(event)) = &item
+                            && matches!(event.kind, None | Some(InputMessageKind::Plain))
+                            && let Some(auto_title) = auto_title.take()
+                        {
+                            auto_title.apply(&event.message).await;
+                        }
                         writer.write_rollout_item(item).await?;
                     }
                 }
@@ -377,9 +447,11 @@ async fn rollout_writer(
                     let _ = ack.send(());
                     return Err(e);
                 }
+                index.update().await;
                 let _ = ack.send(());
             }
             RolloutCmd::Shutdown { ack } => {
+                index.update().await;
                 let _ = ack.send(());
             }
         }
@@ -388,6 +460,49 @@ async fn rollout_writer(
     Ok(())
 }
 
+/// Tracks how much of the rollout file has been added to the search index.
+struct SearchIndexState {
+    codex_home: PathBuf,
+    path: PathBuf,
+    indexed_to: Option<u64>,
+}
+
+impl SearchIndexState {
+    /// Indexes everything written since the last update. Failures only cost
+    /// search freshness (the next search catches up), so they are logged.
+    async fn update(&mut self) {
+        let start = match self.indexed_to {
+            Some(offset) => offset,
+            None => match search::indexed_to(&self.codex_home, &self.path).await {
+                Ok(offset) => offset,
+                Err(e) => {
+                    warn!("failed to read session search index: {e}");
+                    return;
+                }
+            },
+        };
+        match search::update_index(&self.codex_home, &self.path, start).await {
+            Ok(end) => self.indexed_to = Some(end),
+            Err(e) => warn!("failed to update session search index: {e}"),
+        }
+    }
+}
+
+/// Titles a new session after its first user message
+/// (`sessions.auto_title`).
+struct AutoTitle {
+    codex_home: PathBuf,
+    conversation_id: ConversationId,
+}
+
+impl AutoTitle {
+    async fn apply(self, message: &str) {
+        if let Err(e) = set_auto_title(&self.codex_home, self.conversation_id, message).await {
+            warn!("failed to set session title: {e}");
+        }
+    }
+}
+
 struct JsonlWriter {
     file: tokio::fs::File,
 }
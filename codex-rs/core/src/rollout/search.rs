@@ -0,0 +1,409 @@
+//! Full-text search over recorded sessions.
+//!
+//! `~/.codex/session_index.jsonl` holds the searchable text of rollout files:
+//! user and agent messages, commands and the paths of files touched by
+//! patches. Each line covers a byte range of one rollout file. The recorder
+//! appends a line for the items it wrote whenever it flushes, and a search
+//! first indexes whatever part of a rollout file is not covered yet, so
+//! sessions recorded before the index existed are found too.
+
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SessionSource;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use super::SESSIONS_SUBDIR;
+use super::compression::is_compressed;
+use super::compression::read_rollout_text;
+use super::file_lock::FileLock;
+use super::file_lock::replace_file;
+use super::list::ConversationsPage;
+use super::list::Cursor;
+use super::list::collect_rollout_files;
+use super::list::paginate_rollout_files;
+use super::metadata::tagged_ids;
+use super::tool_calls::ToolCall;
+use super::tool_calls::tool_call;
+
+/// Name of the search index file under `CODEX_HOME`.
+pub const SEARCH_INDEX_FILE: &str = "session_index.jsonl";
+
+/// Searchable text taken from a rollout item.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub kind: IndexEntryKind,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexEntryKind {
+    UserMessage,
+    AgentMessage,
+    Command,
+    FilePath,
+}
+
+/// One line of the index: the entries found in `path` between byte offsets
+/// `start` and `end`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct IndexRecord {
+    path: PathBuf,
+    start: u64,
+    end: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<SessionSource>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    entries: Vec<IndexEntry>,
+}
+
+/// Everything indexed so far for one rollout file.
+#[derive(Debug, Default)]
+struct IndexedSession {
+    indexed_to: u64,
+    source: Option<SessionSource>,
+    entries: Vec<IndexEntry>,
+}
+
+impl IndexedSession {
+    fn merge(&mut self, record: IndexRecord) {
+        // Ranges indexed twice (e.g. by a search while the session was still
+        // recording) only need to be counted once.
+        if record.end <= self.indexed_to {
+            return;
+        }
+        self.indexed_to = record.end;
+        self.source = self.source.take().or(record.source);
+        self.entries.extend(record.entries);
+    }
+
+    fn matches(&self, terms: &[String]) -> Vec<IndexEntry> {
+        let lowered: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| entry.text.to_lowercase())
+            .collect();
+        if !terms
+            .iter()
+            .all(|term| lowered.iter().any(|text| text.contains(term.as_str())))
+        {
+            return Vec::new();
+        }
+        self.entries
+            .iter()
+            .zip(&lowered)
+            .filter(|(_, text)| terms.iter().any(|term| text.contains(term.as_str())))
+            .map(|(entry, _)| entry.clone())
+            .collect()
+    }
+}
+
+/// A session that matches a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    pub conversation_id: Option<ConversationId>,
+    /// Start of the session, taken from the rollout filename.
+    pub started_at: OffsetDateTime,
+    /// First user message of the session, if any.
+    pub preview: Option<String>,
+    /// Entries that contain at least one of the search terms, in session order.
+    pub matches: Vec<IndexEntry>,
+}
+
+/// Returns the sessions whose indexed text contains every whitespace-separated
+/// term of `query` (case-insensitive), newest first. Sessions whose source is
+/// not in `allowed_sources` are skipped unless `allowed_sources` is empty.
+pub async fn search_sessions(
+    codex_home: &Path,
+    query: &str,
+    allowed_sources: &[SessionSource],
+) -> io::Result<Vec<SearchHit>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut index = load_index(codex_home).await?;
+    let files = collect_rollout_files(&codex_home.join(SESSIONS_SUBDIR)).await?;
+    let mut hits = Vec::new();
+    for (started_at, id, path) in files {
+        let session = index.entry(path.clone()).or_default();
+        // Compressed rollouts no longer change, but their size on disk says
+        // nothing about how much of their text is indexed.
+        let needs_indexing = if is_compressed(&path) {
+            session.indexed_to == 0
+        } else {
+            tokio::fs::metadata(&path).await?.len() > session.indexed_to
+        };
+        if needs_indexing
+            && let Some(record) = index_rollout_range(codex_home, &path, session.indexed_to).await?
+        {
+            session.merge(record);
+        }
+        if !allowed_sources.is_empty()
+            && !session
+                .source
+                .is_some_and(|source| allowed_sources.contains(&source))
+        {
+            continue;
+        }
+        let matches = session.matches(&terms);
+        if matches.is_empty() {
+            continue;
+        }
+        let preview = session
+            .entries
+            .iter()
+            .find(|entry| entry.kind == IndexEntryKind::UserMessage)
+            .map(|entry| entry.text.clone());
+        hits.push(SearchHit {
+            path,
+            conversation_id: ConversationId::from_string(&id.to_string()).ok(),
+            started_at,
+            preview,
+            matches,
+        });
+    }
+    Ok(hits)
+}
+
+/// Pages through [`search_sessions`] results like a conversation listing.
+/// With `tag` set, only conversations carrying that tag are included.
+pub(crate) async fn search_conversations(
+    codex_home: &Path,
+    query: &str,
+    tag: Option<&str>,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+    allowed_sources: &[SessionSource],
+) -> io::Result<ConversationsPage> {
+    let tagged = match tag {
+        Some(tag) => Some(tagged_ids(codex_home, tag).await?),
+        None => None,
+    };
+    let files = search_sessions(codex_home, query, allowed_sources)
+        .await?
+        .into_iter()
+        .filter_map(|hit| {
+            let conversation_id = hit.conversation_id?;
+            if let Some(tagged) = &tagged
+                && !tagged.contains(&conversation_id)
+            {
+                return None;
+            }
+            let id = Uuid::parse_str(&conversation_id.to_string()).ok()?;
+            Some((hit.started_at, id, hit.path))
+        })
+        .collect();
+    paginate_rollout_files(files, page_size, cursor, allowed_sources).await
+}
+
+/// Indexes the complete lines of the rollout file at `path` from byte offset
+/// `start` on and appends them to the index. Returns the new end of the
+/// indexed range.
+pub(crate) async fn update_index(codex_home: &Path, path: &Path, start: u64) -> io::Result<u64> {
+    Ok(index_rollout_range(codex_home, path, start)
+        .await?
+        .map_or(start, |record| record.end))
+}
+
+/// Returns how far the rollout file at `path` is already indexed.
+pub(crate) async fn indexed_to(codex_home: &Path, path: &Path) -> io::Result<u64> {
+    Ok(load_index(codex_home)
+        .await?
+        .get(path)
+        .map_or(0, |session| session.indexed_to))
+}
+
+/// Drops index records for rollout files that no longer exist, e.g. after
+/// garbage collection deleted or compressed them, and compacts the records of
+/// each remaining file into one. The index is rewritten atomically under the
+/// index lock so concurrent recorders neither lose appends nor read a partial
+/// file.
+pub(crate) async fn prune_index(codex_home: &Path) -> io::Result<()> {
+    let index_path = codex_home.join(SEARCH_INDEX_FILE);
+    let _lock = FileLock::acquire(index_lock_path(codex_home)).await?;
+    let text = match tokio::fs::read_to_string(&index_path).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut compacted: Vec<IndexRecord> = Vec::new();
+    let mut positions: HashMap<PathBuf, Option<usize>> = HashMap::new();
+    for line in text.lines() {
+        let Ok(record) = serde_json::from_str::<IndexRecord>(line) else {
+            continue;
+        };
+        let position = match positions.get(&record.path) {
+            Some(position) => *position,
+            None => {
+                let position = tokio::fs::try_exists(&record.path)
+                    .await?
+                    .then_some(compacted.len());
+                positions.insert(record.path.clone(), position);
+                if position.is_some() {
+                    compacted.push(IndexRecord {
+                        path: record.path.clone(),
+                        start: record.start,
+                        end: record.start,
+                        source: None,
+                        entries: Vec::new(),
+                    });
+                }
+                position
+            }
+        };
+        let Some(position) = position else {
+            continue;
+        };
+        // Same rules as `IndexedSession::merge`: ranges already covered are
+        // dropped, so a file ends up with a single record.
+        let merged = &mut compacted[position];
+        if record.end <= merged.end {
+            continue;
+        }
+        merged.end = record.end;
+        merged.source = merged.source.take().or(record.source);
+        merged.entries.extend(record.entries);
+    }
+    let mut kept = String::with_capacity(text.len());
+    for record in &compacted {
+        kept.push_str(&serde_json::to_string(record)?);
+        kept.push('\n');
+    }
+    if kept != text {
+        replace_file(index_path, kept.into_bytes()).await?;
+    }
+    Ok(())
+}
+
+fn index_lock_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(SEARCH_INDEX_FILE).with_extension("lock")
+}
+
+async fn index_rollout_range(
+    codex_home: &Path,
+    path: &Path,
+    start: u64,
+) -> io::Result<Option<IndexRecord>> {
+    let buffer = if is_compressed(path) {
+        let mut text = read_rollout_text(path).await?.into_bytes();
+        text.split_off((start as usize).min(text.len()))
+    } else {
+        let mut file = tokio::fs::File::open(path).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).await?;
+        buffer
+    };
+    // Leave a partially written last line for the next update.
+    let Some(complete) = buffer.iter().rposition(|&b| b == b'\n').map(|idx| idx + 1) else {
+        return Ok(None);
+    };
+
+    let mut record = IndexRecord {
+        path: path.to_path_buf(),
+        start,
+        end: start + complete as u64,
+        source: None,
+        entries: Vec::new(),
+    };
+    for line in String::from_utf8_lossy(&buffer[..complete]).lines() {
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(line.trim()) else {
+            continue;
+        };
+        if let RolloutItem::SessionMeta(meta_line) = &rollout_line.item {
+            record.source = record.source.or(Some(meta_line.meta.source));
+        }
+        record.entries.extend(index_entries(&rollout_line.item));
+    }
+
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+    let _lock = FileLock::acquire(index_lock_path(codex_home)).await?;
+    let mut index = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(codex_home.join(SEARCH_INDEX_FILE))
+        .await?;
+    index.write_all(line.as_bytes()).await?;
+    index.flush().await?;
+    Ok(Some(record))
+}
+
+async fn load_index(codex_home: &Path) -> io::Result<HashMap<PathBuf, IndexedSession>> {
+    let text = match tokio::fs::read_to_string(codex_home.join(SEARCH_INDEX_FILE)).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+    let mut index: HashMap<PathBuf, IndexedSession> = HashMap::new();
+    for line in text.lines() {
+        let Ok(record) = serde_json::from_str::<IndexRecord>(line) else {
+            continue;
+        };
+        index.entry(record.path.clone()).or_default().merge(record);
+    }
+    Ok(index)
+}
+
+/// Extracts the searchable text of a rollout item.
+fn index_entries(item: &RolloutItem) -> Vec<IndexEntry> {
+    let entry = |kind, text: &str| IndexEntry {
+        kind,
+        text: text.to_string(),
+    };
+    match tool_call(item) {
+        Some(ToolCall::Command { command, .. }) => {
+            return vec![entry(IndexEntryKind::Command, &command)];
+        }
+        Some(ToolCall::Patch(patch)) => return patched_paths(&patch),
+        None => {}
+    }
+    match item {
+        RolloutItem::EventMsg(EventMsg::UserMessage(ev)) => {
+            vec![entry(IndexEntryKind::UserMessage, &ev.message)]
+        }
+        RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+            vec![entry(IndexEntryKind::AgentMessage, &ev.message)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn patched_paths(patch: &str) -> Vec<IndexEntry> {
+    const FILE_MARKERS: [&str; 4] = [
+        "*** Add File: ",
+        "*** Update File: ",
+        "*** Delete File: ",
+        "*** Move to: ",
+    ];
+    patch
+        .lines()
+        .filter_map(|line| {
+            FILE_MARKERS
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+        })
+        .map(|path| IndexEntry {
+            kind: IndexEntryKind::FilePath,
+            text: path.trim().to_string(),
+        })
+        .collect()
+}
@@ -12,18 +12,38 @@ use time::format_description::FormatItem;
 use time::macros::format_description;
 use uuid::Uuid;
 
+use crate::config_types::SessionsConfig;
 use crate::rollout::INTERACTIVE_SESSION_SOURCES;
+use crate::rollout::RolloutRecorder;
+use crate::rollout::compression::compress_rollout;
+use crate::rollout::compression::decompress_rollout;
+use crate::rollout::gc::GcActionKind;
+use crate::rollout::gc::GcReport;
+use crate::rollout::gc::collect_garbage;
 use crate::rollout::list::ConversationItem;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
 use crate::rollout::list::get_conversation;
 use crate::rollout::list::get_conversations;
+use crate::rollout::metadata::SESSION_METADATA_FILE;
+use crate::rollout::metadata::SessionMetadata;
+use crate::rollout::metadata::load_session_metadata;
+use crate::rollout::metadata::tagged_conversations;
+use crate::rollout::metadata::update_session_metadata;
+use crate::rollout::search::IndexEntry;
+use crate::rollout::search::IndexEntryKind;
+use crate::rollout::search::SEARCH_INDEX_FILE;
+use crate::rollout::search::prune_index;
+use crate::rollout::search::search_conversations;
+use crate::rollout::search::search_sessions;
+use crate::rollout::search::update_index;
 use anyhow::Result;
 use codex_protocol::ConversationId;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::CompactedItem;
 use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::InputMessageKind;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
@@ -925,3 +945,687 @@ async fn test_source_filter_excludes_non_matching_sessions() {
         path.ends_with("rollout-2025-08-01T10-00-00-00000000-0000-0000-0000-00000000004d.jsonl")
     }));
 }
+
+fn append_rollout_line(path: &Path, mut line: serde_json::Value) {
+    line["timestamp"] = serde_json::json!("2025-08-03T10:00:00.000Z");
+    let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+    writeln!(file, "{line}").unwrap();
+}
+
+fn session_file_path(home: &Path, ts_str: &str, uuid: Uuid) -> std::path::PathBuf {
+    let day = &ts_str[..10];
+    home.join("sessions")
+        .join(&day[..4])
+        .join(&day[5..7])
+        .join(&day[8..10])
+        .join(format!("rollout-{ts_str}-{uuid}.jsonl"))
+}
+
+#[tokio::test]
+async fn test_search_matches_messages_commands_and_paths() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let older = Uuid::from_u128(1);
+    let newer = Uuid::from_u128(2);
+    write_session_file(
+        home,
+        "2025-08-01T10-00-00",
+        older,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    write_session_file(
+        home,
+        "2025-08-02T10-00-00",
+        newer,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+
+    let older_path = session_file_path(home, "2025-08-01T10-00-00", older);
+    append_rollout_line(
+        &older_path,
+        serde_json::json!({
+            "type": "response_item",
+            "payload": {
+                "type": "function_call",
+                "name": "shell",
+                "arguments": serde_json::json!({ "command": ["cargo", "test", "-p", "codex-core"] }).to_string(),
+                "call_id": "call-1"
+            }
+        }),
+    );
+    append_rollout_line(
+        &older_path,
+        serde_json::json!({
+            "type": "response_item",
+            "payload": {
+                "type": "custom_tool_call",
+                "name": "apply_patch",
+                "input": "*** Begin Patch\n*** Update File: src/widget.rs\n@@\n-a\n+b\n*** End Patch",
+                "call_id": "call-2"
+            }
+        }),
+    );
+    let newer_path = session_file_path(home, "2025-08-02T10-00-00", newer);
+    append_rollout_line(
+        &newer_path,
+        serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "The Widget renders now." }
+        }),
+    );
+
+    let hits = search_sessions(home, "cargo TEST", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, older_path);
+    assert_eq!(hits[0].preview.as_deref(), Some("Hello from user"));
+    assert_eq!(
+        hits[0].matches,
+        vec![IndexEntry {
+            kind: IndexEntryKind::Command,
+            text: "cargo test -p codex-core".to_string(),
+        }]
+    );
+
+    let hits = search_sessions(home, "widget", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    let paths: Vec<_> = hits.iter().map(|hit| hit.path.clone()).collect();
+    assert_eq!(paths, vec![newer_path.clone(), older_path.clone()]);
+    assert_eq!(
+        hits[1].matches,
+        vec![IndexEntry {
+            kind: IndexEntryKind::FilePath,
+            text: "src/widget.rs".to_string(),
+        }]
+    );
+
+    // Every term has to match somewhere in the session.
+    let hits = search_sessions(home, "widget cargo renders", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert!(hits.is_empty());
+}
+
+#[tokio::test]
+async fn test_search_indexes_appended_lines_once() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let id = Uuid::from_u128(3);
+    write_session_file(home, "2025-08-01T10-00-00", id, 0, Some(SessionSource::Cli)).unwrap();
+    let path = session_file_path(home, "2025-08-01T10-00-00", id);
+
+    assert!(
+        search_sessions(home, "flaky", NO_SOURCE_FILTER)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let index_len = fs::metadata(home.join(SEARCH_INDEX_FILE)).unwrap().len();
+
+    // Nothing new to index: the index is left alone.
+    search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::metadata(home.join(SEARCH_INDEX_FILE)).unwrap().len(),
+        index_len
+    );
+
+    append_rollout_line(
+        &path,
+        serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "Why is this test flaky?", "kind": "plain" }
+        }),
+    );
+    let hits = search_sessions(home, "flaky", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].preview.as_deref(), Some("Hello from user"));
+
+    let hits = search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].matches.len(), 1);
+}
+
+#[tokio::test]
+async fn test_prune_index_compacts_records_and_drops_missing_sessions() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let kept = Uuid::from_u128(4);
+    let deleted = Uuid::from_u128(5);
+    write_session_file(
+        home,
+        "2025-08-01T10-00-00",
+        kept,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    write_session_file(
+        home,
+        "2025-08-02T10-00-00",
+        deleted,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    let kept_path = session_file_path(home, "2025-08-01T10-00-00", kept);
+    search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    append_rollout_line(
+        &kept_path,
+        serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "Why is this test flaky?", "kind": "plain" }
+        }),
+    );
+    search_sessions(home, "flaky", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    // A range indexed twice, as when a search races the recorder.
+    update_index(home, &kept_path, 0).await.unwrap();
+    fs::remove_file(session_file_path(home, "2025-08-02T10-00-00", deleted)).unwrap();
+
+    prune_index(home).await.unwrap();
+
+    let index = fs::read_to_string(home.join(SEARCH_INDEX_FILE)).unwrap();
+    assert_eq!(index.lines().count(), 1);
+    assert!(!index.contains(&deleted.to_string()));
+    let hits = search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].matches.len(), 1);
+    let hits = search_sessions(home, "flaky", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+
+    // Nothing left to compact: the index is not rewritten.
+    prune_index(home).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(home.join(SEARCH_INDEX_FILE)).unwrap(),
+        index
+    );
+}
+
+#[tokio::test]
+async fn test_search_conversations_filters_sources_and_paginates() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    for (n, ts) in [
+        "2025-08-01T10-00-00",
+        "2025-08-02T10-00-00",
+        "2025-08-03T10-00-00",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        write_session_file(
+            home,
+            ts,
+            Uuid::from_u128(n as u128 + 10),
+            0,
+            Some(SessionSource::Cli),
+        )
+        .unwrap();
+    }
+    write_session_file(
+        home,
+        "2025-08-04T10-00-00",
+        Uuid::from_u128(20),
+        0,
+        Some(SessionSource::Exec),
+    )
+    .unwrap();
+
+    let page1 = search_conversations(home, "hello", None, 2, None, INTERACTIVE_SESSION_SOURCES)
+        .await
+        .unwrap();
+    let ids: Vec<_> = page1
+        .items
+        .iter()
+        .map(|item| item.path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            format!("rollout-2025-08-03T10-00-00-{}.jsonl", Uuid::from_u128(12)),
+            format!("rollout-2025-08-02T10-00-00-{}.jsonl", Uuid::from_u128(11)),
+        ]
+    );
+    let cursor = page1.next_cursor.expect("more hits remain");
+
+    let page2 = search_conversations(
+        home,
+        "hello",
+        None,
+        2,
+        Some(&cursor),
+        INTERACTIVE_SESSION_SOURCES,
+    )
+    .await
+    .unwrap();
+    assert_eq!(page2.items.len(), 1);
+    assert!(page2.items[0].path.ends_with(format!(
+        "rollout-2025-08-01T10-00-00-{}.jsonl",
+        Uuid::from_u128(10)
+    )));
+    assert_eq!(page2.next_cursor, None);
+}
+
+fn set_modified_days_ago(path: &Path, days: u64) {
+    let modified =
+        std::time::SystemTime::now() - std::time::Duration::from_secs(days * 24 * 60 * 60);
+    File::options()
+        .append(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_compressed_rollouts_are_listed_searched_and_resumed() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let id = Uuid::from_u128(5);
+    write_session_file(home, "2025-08-01T10-00-00", id, 1, Some(SessionSource::Cli)).unwrap();
+    let plain_path = session_file_path(home, "2025-08-01T10-00-00", id);
+    let original = fs::read_to_string(&plain_path).unwrap();
+
+    let compressed_path = compress_rollout(&plain_path).await.unwrap();
+    assert!(!plain_path.exists());
+    // The temporary file the archive was written to is renamed into place.
+    assert_eq!(
+        fs::read_dir(compressed_path.parent().unwrap())
+            .unwrap()
+            .count(),
+        1
+    );
+    assert!(
+        compressed_path
+            .to_string_lossy()
+            .ends_with(&format!("{id}.jsonl.gz"))
+    );
+
+    let page = get_conversations(home, 10, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].path, compressed_path);
+    assert_eq!(page.items[0].head[0]["id"], serde_json::json!(id));
+    assert_eq!(get_conversation(&compressed_path).await.unwrap(), original);
+
+    let hits = search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].path, compressed_path);
+
+    let history = RolloutRecorder::get_rollout_history(&compressed_path)
+        .await
+        .unwrap();
+    let InitialHistory::Resumed(resumed) = history else {
+        panic!("expected resumed history");
+    };
+    assert_eq!(
+        resumed.conversation_id,
+        ConversationId::from_string(&id.to_string()).unwrap()
+    );
+
+    // Resuming restores the plain file so the recorder can append to it.
+    assert_eq!(
+        decompress_rollout(&compressed_path).await.unwrap(),
+        plain_path
+    );
+    assert!(!compressed_path.exists());
+    assert_eq!(fs::read_to_string(&plain_path).unwrap(), original);
+}
+
+#[tokio::test]
+async fn test_gc_expires_compresses_and_keeps_pinned_sessions() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let expired = Uuid::from_u128(6);
+    let pinned = Uuid::from_u128(7);
+    let stale = Uuid::from_u128(8);
+    let fresh = Uuid::from_u128(9);
+    for (ts, id, days) in [
+        ("2025-06-01T10-00-00", expired, 90),
+        ("2025-06-02T10-00-00", pinned, 90),
+        ("2025-07-01T10-00-00", stale, 10),
+        ("2025-08-01T10-00-00", fresh, 0),
+    ] {
+        write_session_file(home, ts, id, 0, Some(SessionSource::Cli)).unwrap();
+        set_modified_days_ago(&session_file_path(home, ts, id), days);
+    }
+    fs::write(
+        home.join(SESSION_METADATA_FILE),
+        serde_json::json!({ pinned.to_string(): { "pinned": true } }).to_string(),
+    )
+    .unwrap();
+    // Index the sessions so pruning can be checked.
+    search_sessions(home, "hello", NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+
+    let config = SessionsConfig {
+        max_age_days: Some(30),
+        compress_after_days: Some(7),
+        ..SessionsConfig::default()
+    };
+    let expired_path = session_file_path(home, "2025-06-01T10-00-00", expired);
+    let stale_path = session_file_path(home, "2025-07-01T10-00-00", stale);
+
+    let dry_run = collect_garbage(home, &config, true).await.unwrap();
+    let actions: Vec<_> = dry_run
+        .actions
+        .iter()
+        .map(|action| (action.kind, action.path.clone()))
+        .collect();
+    let pinned_path = session_file_path(home, "2025-06-02T10-00-00", pinned);
+    assert_eq!(
+        actions,
+        vec![
+            (GcActionKind::Expired, expired_path.clone()),
+            (GcActionKind::Compressed, pinned_path.clone()),
+            (GcActionKind::Compressed, stale_path.clone()),
+        ]
+    );
+    assert_eq!(dry_run.kept_pinned, 1);
+    assert!(expired_path.exists());
+    assert!(stale_path.exists());
+
+    let report = collect_garbage(home, &config, false).await.unwrap();
+    assert_eq!(report, dry_run);
+    assert!(!expired_path.exists());
+    assert!(!stale_path.exists());
+    assert!(
+        session_file_path(home, "2025-06-02T10-00-00", pinned)
+            .with_extension("jsonl.gz")
+            .exists()
+    );
+    let index = fs::read_to_string(home.join(SEARCH_INDEX_FILE)).unwrap();
+    assert!(!index.contains(&expired.to_string()));
+
+    let page = get_conversations(home, 10, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 3);
+}
+
+#[tokio::test]
+async fn test_gc_skips_sessions_that_are_still_recording() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let id = Uuid::from_u128(20);
+    write_session_file(home, "2025-06-01T10-00-00", id, 0, Some(SessionSource::Cli)).unwrap();
+    let path = session_file_path(home, "2025-06-01T10-00-00", id);
+    set_modified_days_ago(&path, 90);
+    let config = SessionsConfig {
+        max_age_days: Some(30),
+        compress_after_days: Some(7),
+        ..SessionsConfig::default()
+    };
+
+    // The lock a recorder holds on its rollout file.
+    let recording = File::open(&path).unwrap();
+    recording.lock_shared().unwrap();
+    let report = collect_garbage(home, &config, false).await.unwrap();
+    assert_eq!(report, GcReport::default());
+    assert!(path.exists());
+
+    drop(recording);
+    let report = collect_garbage(home, &config, false).await.unwrap();
+    assert_eq!(report.actions.len(), 1);
+    assert_eq!(report.actions[0].kind, GcActionKind::Expired);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_gc_deletes_oldest_sessions_over_size_limit() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let older = Uuid::from_u128(10);
+    let newer = Uuid::from_u128(11);
+    write_session_file(home, "2025-08-01T10-00-00", older, 0, None).unwrap();
+    write_session_file(home, "2025-08-02T10-00-00", newer, 0, None).unwrap();
+    let older_path = session_file_path(home, "2025-08-01T10-00-00", older);
+    let newer_path = session_file_path(home, "2025-08-02T10-00-00", newer);
+    set_modified_days_ago(&older_path, 2);
+    set_modified_days_ago(&newer_path, 1);
+    // Pad the older session past the 1 MB limit.
+    append_rollout_line(
+        &older_path,
+        serde_json::json!({ "padding": "x".repeat(1024 * 1024) }),
+    );
+    set_modified_days_ago(&older_path, 2);
+
+    let config = SessionsConfig {
+        max_total_size_mb: Some(1),
+        ..SessionsConfig::default()
+    };
+    let report = collect_garbage(home, &config, false).await.unwrap();
+    assert_eq!(report.actions.len(), 1);
+    assert_eq!(report.actions[0].kind, GcActionKind::OverSizeLimit);
+    assert_eq!(report.actions[0].path, older_path);
+    assert!(report.deleted_bytes() > 1024 * 1024);
+    assert!(!older_path.exists());
+    assert!(newer_path.exists());
+}
+
+#[tokio::test]
+async fn test_gc_size_limit_keeps_newest_and_recent_sessions() {
+    let config = SessionsConfig {
+        max_total_size_mb: Some(1),
+        ..SessionsConfig::default()
+    };
+    let minutes_ago =
+        |minutes: u64| std::time::SystemTime::now() - std::time::Duration::from_secs(minutes * 60);
+
+    // Each session is over the limit on its own. Only `old` is deleted:
+    // `recent` is within the grace window and `newest` is the latest session.
+    for (recent_modified, newest_modified) in [
+        (minutes_ago(2), minutes_ago(1)),
+        (minutes_ago(3 * 24 * 60), minutes_ago(2 * 24 * 60)),
+    ] {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        let mut paths = Vec::new();
+        for (ts, id, modified) in [
+            ("2025-08-01T10-00-00", 15, minutes_ago(4 * 24 * 60)),
+            ("2025-08-02T10-00-00", 16, recent_modified),
+            ("2025-08-03T10-00-00", 17, newest_modified),
+        ] {
+            let id = Uuid::from_u128(id);
+            write_session_file(home, ts, id, 0, None).unwrap();
+            let path = session_file_path(home, ts, id);
+            append_rollout_line(
+                &path,
+                serde_json::json!({ "padding": "x".repeat(1024 * 1024) }),
+            );
+            File::options()
+                .append(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+            paths.push(path);
+        }
+
+        let report = collect_garbage(home, &config, false).await.unwrap();
+        let deleted: Vec<_> = report
+            .actions
+            .iter()
+            .map(|action| (action.kind, action.path.clone()))
+            .collect();
+        let mut expected = vec![(GcActionKind::OverSizeLimit, paths[0].clone())];
+        if recent_modified < minutes_ago(60) {
+            expected.push((GcActionKind::OverSizeLimit, paths[1].clone()));
+        }
+        assert_eq!(deleted, expected);
+        assert!(paths[2].exists());
+    }
+}
+
+#[tokio::test]
+async fn test_gc_dry_run_counts_compressed_sizes() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let older = Uuid::from_u128(18);
+    let newer = Uuid::from_u128(19);
+    let mut paths = Vec::new();
+    for (ts, id, days) in [
+        ("2025-08-01T10-00-00", older, 5),
+        ("2025-08-02T10-00-00", newer, 4),
+    ] {
+        write_session_file(home, ts, id, 0, None).unwrap();
+        let path = session_file_path(home, ts, id);
+        append_rollout_line(
+            &path,
+            serde_json::json!({ "padding": "x".repeat(1024 * 1024) }),
+        );
+        set_modified_days_ago(&path, days);
+        paths.push(path);
+    }
+
+    // Both sessions fit in the limit once compressed, so nothing is deleted.
+    let config = SessionsConfig {
+        compress_after_days: Some(1),
+        max_total_size_mb: Some(1),
+        ..SessionsConfig::default()
+    };
+    let dry_run = collect_garbage(home, &config, true).await.unwrap();
+    assert_eq!(
+        dry_run
+            .actions
+            .iter()
+            .map(|action| action.kind)
+            .collect::<Vec<_>>(),
+        vec![GcActionKind::Compressed, GcActionKind::Compressed]
+    );
+    let report = collect_garbage(home, &config, false).await.unwrap();
+    assert_eq!(report, dry_run);
+}
+
+#[tokio::test]
+async fn test_tagged_conversations_filter_listing_and_search() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let untagged = Uuid::from_u128(12);
+    let tagged_old = Uuid::from_u128(13);
+    let tagged_new = Uuid::from_u128(14);
+    for (ts, id) in [
+        ("2025-08-01T10-00-00", tagged_old),
+        ("2025-08-02T10-00-00", untagged),
+        ("2025-08-03T10-00-00", tagged_new),
+    ] {
+        write_session_file(home, ts, id, 0, Some(SessionSource::Cli)).unwrap();
+    }
+    for id in [tagged_old, tagged_new] {
+        let conversation_id = ConversationId::from_string(&id.to_string()).unwrap();
+        update_session_metadata(home, conversation_id, |metadata| {
+            metadata.add_tag("#Auth");
+        })
+        .await
+        .unwrap();
+    }
+    append_rollout_line(
+        &session_file_path(home, "2025-08-01T10-00-00", tagged_old),
+        serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "Token refresh fixed." }
+        }),
+    );
+
+    let page1 = tagged_conversations(home, "auth", 1, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page1.items.len(), 1);
+    assert_eq!(
+        page1.items[0].path,
+        session_file_path(home, "2025-08-03T10-00-00", tagged_new)
+    );
+    let cursor = page1.next_cursor.expect("one more tagged session");
+    let page2 = tagged_conversations(home, "AUTH", 1, Some(&cursor), NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(
+        page2.items[0].path,
+        session_file_path(home, "2025-08-01T10-00-00", tagged_old)
+    );
+    assert!(page2.next_cursor.is_none());
+
+    let page = search_conversations(home, "hello", Some("auth"), 10, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 2);
+    let page = search_conversations(home, "refresh", Some("auth"), 10, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    let page = search_conversations(home, "hello", Some("billing"), 10, None, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+
+    // Clearing the last piece of metadata drops the session from the file.
+    let conversation_id = ConversationId::from_string(&tagged_new.to_string()).unwrap();
+    let metadata = update_session_metadata(home, conversation_id, |metadata| {
+        metadata.remove_tag("auth");
+    })
+    .await
+    .unwrap();
+    assert_eq!(metadata, SessionMetadata::default());
+    let all = load_session_metadata(home).await.unwrap();
+    assert_eq!(all.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_metadata_updates_are_all_kept() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path().to_path_buf();
+    let ids: Vec<ConversationId> = (0..16)
+        .map(|i| ConversationId::from_string(&Uuid::from_u128(100 + i).to_string()).unwrap())
+        .collect();
+
+    let mut updates = tokio::task::JoinSet::new();
+    for &conversation_id in &ids {
+        let home = home.clone();
+        updates.spawn(async move {
+            update_session_metadata(&home, conversation_id, |metadata| {
+                metadata.pinned = true;
+            })
+            .await
+        });
+    }
+    for update in updates.join_all().await {
+        update.unwrap();
+    }
+
+    let all = load_session_metadata(&home).await.unwrap();
+    assert_eq!(all.len(), ids.len());
+    assert!(all.values().all(|metadata| metadata.pinned));
+    // Only the metadata file and its lock file are left behind.
+    let mut names: Vec<String> = fs::read_dir(&home)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["session_metadata.json", "session_metadata.lock"]
+    );
+}
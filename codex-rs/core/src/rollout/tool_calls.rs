@@ -0,0 +1,64 @@
+//! Shell commands and patches recorded as tool calls in a rollout, shared by
+//! export, diff and the search index so they all read them the same way.
+
+use std::borrow::Cow;
+
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::RolloutItem;
+
+pub(super) enum ToolCall<'a> {
+    /// A shell command, joined for display. `call_id` pairs it with the
+    /// `FunctionCallOutput` that carries its result.
+    Command {
+        call_id: Option<&'a str>,
+        command: String,
+    },
+    /// The raw `apply_patch` input.
+    Patch(Cow<'a, str>),
+}
+
+/// The shell command or patch `item` records, if any. Calls whose arguments do
+/// not parse yield `None`.
+pub(super) fn tool_call(item: &RolloutItem) -> Option<ToolCall<'_>> {
+    let RolloutItem::ResponseItem(item) = item else {
+        return None;
+    };
+    match item {
+        ResponseItem::LocalShellCall {
+            call_id,
+            action: LocalShellAction::Exec(exec),
+            ..
+        } => Some(ToolCall::Command {
+            call_id: call_id.as_deref(),
+            command: join_command(&exec.command),
+        }),
+        ResponseItem::FunctionCall {
+            name,
+            arguments,
+            call_id,
+            ..
+        } => match name.as_str() {
+            "shell" | "container.exec" => serde_json::from_str::<ShellToolCallParams>(arguments)
+                .ok()
+                .map(|params| ToolCall::Command {
+                    call_id: Some(call_id),
+                    command: join_command(&params.command),
+                }),
+            "apply_patch" => serde_json::from_str::<serde_json::Value>(arguments)
+                .ok()
+                .and_then(|args| args.get("input")?.as_str().map(str::to_string))
+                .map(|patch| ToolCall::Patch(Cow::Owned(patch))),
+            _ => None,
+        },
+        ResponseItem::CustomToolCall { name, input, .. } if name == "apply_patch" => {
+            Some(ToolCall::Patch(Cow::Borrowed(input)))
+        }
+        _ => None,
+    }
+}
+
+pub(super) fn join_command(command: &[String]) -> String {
+    shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "))
+}
@@ -10,6 +10,7 @@ use crate::exec::SandboxType;
 
 use crate::command_safety::is_dangerous_command::command_might_be_dangerous;
 use crate::command_safety::is_safe_command::is_known_safe_command;
+use crate::exec_policy::PolicyVerdict;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 
@@ -87,15 +88,25 @@ pub fn assess_patch_safety(
 /// true:
 ///
 /// - the user has explicitly approved the command
-/// - the command is on the "known safe" list
+/// - the exec policy allows the command, or it is on the "known safe" list,
+///   and the sandbox policy does not restrict reads
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// Commands forbidden by the exec policy are always rejected.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
     with_escalated_permissions: bool,
+    policy_verdict: &PolicyVerdict,
 ) -> SafetyCheck {
+    if let PolicyVerdict::Forbidden { reason } = policy_verdict {
+        return SafetyCheck::Reject {
+            reason: reason.clone(),
+        };
+    }
+
     // Some commands look dangerous. Even if they are run inside a sandbox,
     // unless the user has explicitly approved them, we should ask,
     // or reject if the approval_policy tells us not to ask.
@@ -124,13 +135,37 @@ pub fn assess_command_safety(
     // `approved.contains(command)` is `true`, the user may have approved it for
     // the session _because_ they know it needs to run outside a sandbox.
 
-    if is_known_safe_command(command) || approved.contains(command) {
-        let user_explicitly_approved = approved.contains(command);
+    // A rule that requires the sandbox overrides the hard-coded list: such
+    // commands go through the sandbox/approval flow below.
+    let trusted = match policy_verdict {
+        PolicyVerdict::Safe { .. } => true,
+        PolicyVerdict::RequiresSandbox { .. } => false,
+        PolicyVerdict::Forbidden { .. } | PolicyVerdict::Unmatched => {
+            is_known_safe_command(command)
+        }
+    };
+    if approved.contains(command) {
         return SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
-            user_explicitly_approved,
+            user_explicitly_approved: true,
         };
     }
+    if trusted {
+        // Read-only commands still read files, so `readable_roots` and
+        // `deny_read_paths` must be enforced by the sandbox.
+        if sandbox_policy.has_full_disk_read_access() {
+            return SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None,
+                user_explicitly_approved: false,
+            };
+        }
+        if let Some(sandbox_type) = get_platform_sandbox() {
+            return SafetyCheck::AutoApprove {
+                sandbox_type,
+                user_explicitly_approved: false,
+            };
+        }
+    }
 
     assess_safety_for_untrusted_command(approval_policy, sandbox_policy, with_escalated_permissions)
 }
@@ -156,7 +191,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
             sandbox_type: SandboxType::None,
             user_explicitly_approved: false,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. }) | (OnRequest, WorkspaceWrite { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -171,9 +206,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
+        | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove {
@@ -211,6 +246,25 @@ pub fn get_platform_sandbox() -> Option<SandboxType> {
     }
 }
 
+/// Whether `sandbox_policy` lets `path` be read. Tools that read files inside
+/// the codex process (`read_file`, `view_image`) do not run under the platform
+/// sandbox, so they check `readable_roots` and `deny_read_paths` with this.
+/// Symlinks are resolved before comparing, as the sandbox does.
+pub(crate) fn is_read_allowed(sandbox_policy: &SandboxPolicy, cwd: &Path, path: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path = canonical(path);
+    if sandbox_policy
+        .deny_read_paths()
+        .iter()
+        .any(|denied| path.starts_with(canonical(denied)))
+    {
+        return false;
+    }
+    sandbox_policy
+        .get_readable_roots_with_cwd(cwd)
+        .is_none_or(|roots| roots.iter().any(|root| path.starts_with(canonical(root))))
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
@@ -218,7 +272,7 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
@@ -309,8 +363,11 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: vec![],
+            deny_read_paths: vec![],
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -330,8 +387,11 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: vec![],
+            deny_read_paths: vec![],
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -345,7 +405,7 @@ mod tests {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = true;
 
@@ -355,6 +415,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &PolicyVerdict::Unmatched,
         );
 
         assert_eq!(safety_check, SafetyCheck::AskUser);
@@ -364,7 +425,7 @@ mod tests {
     fn dangerous_command_allowed_if_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let mut approved: HashSet<Vec<String>> = HashSet::new();
         approved.insert(command.clone());
         let request_escalated_privileges = false;
@@ -375,6 +436,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &PolicyVerdict::Unmatched,
         );
 
         assert_eq!(
@@ -390,7 +452,7 @@ mod tests {
     fn dangerous_command_not_allowed_if_not_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::Never;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -400,6 +462,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &PolicyVerdict::Unmatched,
         );
 
         assert_eq!(
@@ -415,7 +478,7 @@ mod tests {
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -425,8 +488,97 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            &PolicyVerdict::Unmatched,
+        );
+
+        let expected = match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove {
+                sandbox_type,
+                user_explicitly_approved: false,
+            },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(safety_check, expected);
+    }
+
+    #[test]
+    fn exec_policy_forbidden_rejects_even_when_approved() {
+        let command = vec!["ls".to_string()];
+        let mut approved: HashSet<Vec<String>> = HashSet::new();
+        approved.insert(command.clone());
+        let verdict = PolicyVerdict::Forbidden {
+            reason: "`ls` is forbidden by exec policy: no".to_string(),
+        };
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            false,
+            &verdict,
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::Reject {
+                reason: "`ls` is forbidden by exec policy: no".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn exec_policy_verdict_overrides_known_safe_list() {
+        let approved: HashSet<Vec<String>> = HashSet::new();
+
+        let allowed = assess_command_safety(
+            &["cargo".to_string(), "check".to_string()],
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            false,
+            &PolicyVerdict::Safe {
+                rule: "`cargo check` is allowed by exec policy".to_string(),
+            },
         );
+        assert_eq!(
+            allowed,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None,
+                user_explicitly_approved: false,
+            }
+        );
+
+        let writes = assess_command_safety(
+            &["ls".to_string()],
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            false,
+            &PolicyVerdict::RequiresSandbox {
+                rule: "`ls` matches exec policy but may write out.txt".to_string(),
+            },
+        );
+        assert_eq!(writes, SafetyCheck::AskUser);
+    }
 
+    #[test]
+    fn trusted_commands_are_sandboxed_when_reads_are_restricted() {
+        let command = vec!["cat".to_string(), "/home/user/.ssh/id_rsa".to_string()];
+        let approved: HashSet<Vec<String>> = HashSet::new();
+        let sandbox_policy = SandboxPolicy::ReadOnly {
+            readable_roots: vec![],
+            deny_read_paths: vec![PathBuf::from("/home/user/.ssh")],
+        };
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &sandbox_policy,
+            &approved,
+            false,
+            &PolicyVerdict::Unmatched,
+        );
         let expected = match get_platform_sandbox() {
             Some(sandbox_type) => SafetyCheck::AutoApprove {
                 sandbox_type,
@@ -435,5 +587,62 @@ mod tests {
             None => SafetyCheck::AskUser,
         };
         assert_eq!(safety_check, expected);
+
+        // Without read restrictions, known-safe commands run unsandboxed.
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::new_read_only_policy(),
+            &approved,
+            false,
+            &PolicyVerdict::Unmatched,
+        );
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None,
+                user_explicitly_approved: false,
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_outside_readable_roots_or_under_denied_paths_are_refused() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().join("project");
+        let secrets = cwd.join(".secrets");
+        let elsewhere = tmp.path().join("elsewhere");
+        for dir in [&secrets, &elsewhere] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let sandbox_policy = SandboxPolicy::ReadOnly {
+            readable_roots: vec![cwd.join("src")],
+            deny_read_paths: vec![secrets.clone()],
+        };
+
+        assert!(is_read_allowed(
+            &sandbox_policy,
+            &cwd,
+            &cwd.join("README.md")
+        ));
+        assert!(!is_read_allowed(
+            &sandbox_policy,
+            &cwd,
+            &secrets.join("token")
+        ));
+        assert!(!is_read_allowed(
+            &sandbox_policy,
+            &cwd,
+            &elsewhere.join("notes.txt")
+        ));
+        // A symlink into a denied folder does not get around it.
+        std::os::unix::fs::symlink(&secrets, cwd.join("link")).unwrap();
+        assert!(!is_read_allowed(&sandbox_policy, &cwd, &cwd.join("link")));
+        assert!(is_read_allowed(
+            &SandboxPolicy::DangerFullAccess,
+            &cwd,
+            &elsewhere.join("notes.txt")
+        ));
     }
 }
@@ -0,0 +1,187 @@
+//! Function tools backed by local commands.
+//!
+//! Tools come from `[tools.scripts.<name>]` tables in `config.toml`, followed
+//! by `.codex/tools/<name>.toml` at the project root (the git repository root,
+//! or the cwd outside a repository). A project tool replaces a configured tool
+//! with the same name. Relative program paths in project tools (`./` or `../`)
+//! are resolved against the project root so the tool works from any
+//! subdirectory.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config_types::ScriptToolConfig;
+use crate::git_info::get_git_repo_root;
+
+const TOOLS_DIR: &str = "tools";
+const TOOL_EXTENSION: &str = "toml";
+
+/// Longest tool name accepted by the model APIs.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Returns the configured tools merged with the project's tools. Invalid
+/// tools and unreadable files are skipped and described in the returned
+/// messages so the caller can surface them.
+pub(crate) fn load_script_tools(
+    configured: &BTreeMap<String, ScriptToolConfig>,
+    cwd: &Path,
+) -> (BTreeMap<String, ScriptToolConfig>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut tools = BTreeMap::new();
+    for (name, tool) in configured {
+        match validate(name, tool) {
+            Ok(()) => {
+                tools.insert(name.clone(), tool.clone());
+            }
+            Err(e) => errors.push(format!("invalid tool `{name}` in config.toml: {e}")),
+        }
+    }
+
+    let project_root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    for path in tool_files_in(&project_root.join(".codex").join(TOOLS_DIR)) {
+        let Some(name) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let tool = match read_tool(&path) {
+            Ok(tool) => resolve_program(tool, &project_root),
+            Err(e) => {
+                errors.push(format!("failed to load tool {}: {e}", path.display()));
+                continue;
+            }
+        };
+        match validate(&name, &tool) {
+            Ok(()) => {
+                tools.insert(name, tool);
+            }
+            Err(e) => errors.push(format!("invalid tool {}: {e}", path.display())),
+        }
+    }
+
+    (tools, errors)
+}
+
+fn read_tool(path: &Path) -> Result<ScriptToolConfig, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    toml::from_str(&contents).map_err(|e| e.to_string())
+}
+
+fn validate(name: &str, tool: &ScriptToolConfig) -> Result<(), String> {
+    if name.is_empty()
+        || name.len() > MAX_TOOL_NAME_LEN
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "tool names may only contain letters, digits, `_` and `-` and must be at most {MAX_TOOL_NAME_LEN} characters"
+        ));
+    }
+    if tool.command.is_empty() {
+        return Err("`command` must not be empty".to_string());
+    }
+    if !tool.parameters.is_object() {
+        return Err("`parameters` must be a JSON schema object".to_string());
+    }
+    Ok(())
+}
+
+fn resolve_program(mut tool: ScriptToolConfig, project_root: &Path) -> ScriptToolConfig {
+    if let Some(program) = tool.command.first_mut()
+        && (program.starts_with("./") || program.starts_with("../"))
+    {
+        *program = project_root.join(&*program).to_string_lossy().to_string();
+    }
+    tool
+}
+
+fn tool_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == TOOL_EXTENSION))
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn tool(description: &str, command: &[&str]) -> ScriptToolConfig {
+        ScriptToolConfig {
+            description: description.to_string(),
+            command: command.iter().map(ToString::to_string).collect(),
+            parameters: json!({ "type": "object", "properties": {} }),
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn project_tools_override_configured_tools() {
+        let cwd = TempDir::new().expect("tempdir");
+        let tools_dir = cwd.path().join(".codex/tools");
+        std::fs::create_dir_all(&tools_dir).expect("create tools dir");
+        std::fs::write(
+            tools_dir.join("schema.toml"),
+            r#"
+description = "Look up a schema in the project's registry"
+command = ["./scripts/schema.sh"]
+timeout_ms = 5000
+
+[parameters]
+type = "object"
+required = ["subject"]
+
+[parameters.properties.subject]
+type = "string"
+"#,
+        )
+        .expect("write tool");
+        std::fs::write(tools_dir.join("broken.toml"), "command = 1").expect("write tool");
+        std::fs::write(tools_dir.join("README.md"), "not a tool").expect("write readme");
+
+        let configured = BTreeMap::from([
+            ("schema".to_string(), tool("user schema tool", &["schema"])),
+            ("weather".to_string(), tool("weather", &["weather-cli"])),
+            ("bad name".to_string(), tool("spaces", &["true"])),
+        ]);
+        let (tools, errors) = load_script_tools(&configured, cwd.path());
+
+        assert_eq!(
+            tools.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["schema", "weather"]
+        );
+        assert_eq!(
+            tools["schema"],
+            ScriptToolConfig {
+                description: "Look up a schema in the project's registry".to_string(),
+                command: vec![
+                    cwd.path()
+                        .join("./scripts/schema.sh")
+                        .to_string_lossy()
+                        .to_string()
+                ],
+                parameters: json!({
+                    "type": "object",
+                    "required": ["subject"],
+                    "properties": { "subject": { "type": "string" } },
+                }),
+                timeout_ms: Some(5000),
+            }
+        );
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].contains("invalid tool `bad name`"), "{errors:?}");
+        assert!(errors[1].contains("broken.toml"), "{errors:?}");
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use tokio::process::Child;
 
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -21,6 +22,7 @@ pub async fn spawn_command_under_seatbelt(
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    resource_limits: &ResourceLimits,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
@@ -33,6 +35,7 @@ pub async fn spawn_command_under_seatbelt(
         arg0,
         command_cwd,
         sandbox_policy,
+        resource_limits,
         stdio_policy,
         env,
     )
@@ -97,11 +100,8 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
-    } else {
-        ""
-    };
+    let (file_read_policy, read_cli_args) =
+        create_seatbelt_read_policy(sandbox_policy, sandbox_policy_cwd);
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
@@ -116,11 +116,56 @@ fn create_seatbelt_command_args(
 
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(read_cli_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
 }
 
+/// Builds the `file-read*` rules for `sandbox_policy`. Later rules take
+/// precedence in Seatbelt, so denied paths are listed last.
+fn create_seatbelt_read_policy(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+) -> (String, Vec<String>) {
+    let mut cli_args: Vec<String> = Vec::new();
+    let mut add_param = |prefix: &str, index: usize, path: &Path| {
+        // Canonicalize to avoid mismatches like /var vs /private/var on macOS.
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let param = format!("{prefix}_{index}");
+        cli_args.push(format!("-D{param}={}", canonical.to_string_lossy()));
+        format!("(subpath (param \"{param}\"))")
+    };
+
+    let mut policy = match sandbox_policy.get_readable_roots_with_cwd(sandbox_policy_cwd) {
+        None => "; allow read-only file operations\n(allow file-read*)".to_string(),
+        Some(roots) => {
+            let subpaths: Vec<String> = roots
+                .iter()
+                .enumerate()
+                .map(|(index, root)| add_param("READABLE_ROOT", index, root))
+                .collect();
+            // Path lookups need metadata of every parent folder.
+            format!(
+                "(allow file-read-metadata)\n(allow file-read* (literal \"/\")\n{}\n)",
+                subpaths.join(" ")
+            )
+        }
+    };
+
+    let denied: Vec<String> = sandbox_policy
+        .deny_read_paths()
+        .iter()
+        .enumerate()
+        .map(|(index, path)| add_param("DENY_READ", index, path))
+        .collect();
+    if !denied.is_empty() {
+        policy.push_str(&format!("\n(deny file-read*\n{}\n)", denied.join(" ")));
+    }
+
+    (policy, cli_args)
+}
+
 #[cfg(test)]
 mod tests {
     use super::MACOS_SEATBELT_BASE_POLICY;
@@ -156,8 +201,11 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            readable_roots: vec![],
+            deny_read_paths: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -208,6 +256,44 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_with_read_restrictions() {
+        if cfg!(target_os = "windows") {
+            // /tmp does not exist on Windows, so skip this test.
+            return;
+        }
+
+        let tmp = TempDir::new().expect("tempdir");
+        let data = tmp.path().join("data");
+        let secret = data.join("secret");
+        fs::create_dir_all(&secret).expect("create secret dir");
+        let cwd = tmp.path().join("cwd");
+        let policy = SandboxPolicy::ReadOnly {
+            readable_roots: vec![data.clone()],
+            deny_read_paths: vec![secret.clone()],
+        };
+
+        let args = create_seatbelt_command_args(vec!["/bin/true".to_string()], &policy, &cwd);
+
+        let full_policy = &args[1];
+        assert!(!full_policy.contains("(allow file-read*)"));
+        assert!(full_policy.contains(
+            "(allow file-read* (literal \"/\")\n(subpath (param \"READABLE_ROOT_0\")) (subpath (param \"READABLE_ROOT_1\"))"
+        ));
+        assert!(
+            full_policy.contains(")\n(deny file-read*\n(subpath (param \"DENY_READ_0\"))\n)"),
+            "deny rules must follow the allow rules: {full_policy}"
+        );
+        let data_canon = data.canonicalize().expect("canonicalize data");
+        let secret_canon = secret.canonicalize().expect("canonicalize secret");
+        assert!(args.contains(&format!(
+            "-DREADABLE_ROOT_0={}",
+            data_canon.to_string_lossy()
+        )));
+        assert!(args.contains(&format!("-DREADABLE_ROOT_1={}", cwd.to_string_lossy())));
+        assert!(args.contains(&format!("-DDENY_READ_0={}", secret_canon.to_string_lossy())));
+    }
+
     #[test]
     fn create_seatbelt_args_for_cwd_as_git_repo() {
         if cfg!(target_os = "windows") {
@@ -231,8 +317,11 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: vec![],
+            deny_read_paths: vec![],
         };
 
         let args = create_seatbelt_command_args(
@@ -1,6 +1,10 @@
+use std::collections::BTreeMap;
+
 use crate::RolloutRecorder;
+use crate::config_types::ScriptToolConfig;
 use crate::exec_command::ExecSessionManager;
 use crate::executor::Executor;
+use crate::hooks::ToolHooks;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
@@ -15,4 +19,6 @@ pub(crate) struct SessionServices {
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
     pub(crate) executor: Executor,
+    pub(crate) hooks: ToolHooks,
+    pub(crate) script_tools: BTreeMap<String, ScriptToolConfig>,
 }
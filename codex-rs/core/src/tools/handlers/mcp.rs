@@ -8,7 +8,22 @@ use crate::tools::context::ToolPayload;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
-pub struct McpHandler;
+pub struct McpHandler {
+    /// Whether the server annotates the tool as read-only, the only MCP tools
+    /// trusted to run alongside other parallel calls.
+    read_only: bool,
+}
+
+impl McpHandler {
+    pub(crate) fn new(tool: &mcp_types::Tool) -> Self {
+        let read_only = tool
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.read_only_hint)
+            == Some(true);
+        Self { read_only }
+    }
+}
 
 #[async_trait]
 impl ToolHandler for McpHandler {
@@ -16,6 +31,10 @@ impl ToolHandler for McpHandler {
         ToolKind::Mcp
     }
 
+    fn supports_parallel_calls(&self) -> bool {
+        self.read_only
+    }
+
     async fn handle(
         &self,
         invocation: ToolInvocation<'_>,
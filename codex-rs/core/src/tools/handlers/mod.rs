@@ -3,6 +3,7 @@ mod exec_stream;
 mod mcp;
 mod plan;
 mod read_file;
+mod script;
 mod shell;
 mod unified_exec;
 mod view_image;
@@ -14,6 +15,7 @@ pub use exec_stream::ExecStreamHandler;
 pub use mcp::McpHandler;
 pub use plan::PlanHandler;
 pub use read_file::ReadFileHandler;
+pub use script::ScriptToolHandler;
 pub use shell::ShellHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
@@ -9,6 +9,7 @@ use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 
 use crate::function_tool::FunctionCallError;
+use crate::safety::is_read_allowed;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -42,11 +43,15 @@ impl ToolHandler for ReadFileHandler {
         ToolKind::Function
     }
 
+    fn supports_parallel_calls(&self) -> bool {
+        true
+    }
+
     async fn handle(
         &self,
         invocation: ToolInvocation<'_>,
     ) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation { turn, payload, .. } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -88,6 +93,12 @@ impl ToolHandler for ReadFileHandler {
             ));
         }
 
+        if !is_read_allowed(&turn.sandbox_policy, &turn.cwd, &path) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "reading `{file_path}` is not allowed by the sandbox policy"
+            )));
+        }
+
         let collected = read_file_slice(&path, offset, limit).await?;
         Ok(ToolOutput::Function {
             content: collected.join("\n"),
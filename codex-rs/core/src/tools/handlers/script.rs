@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+
+use crate::config_types::ScriptToolConfig;
+use crate::exec::ExecParams;
+use crate::exec_env::create_env;
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::format_exec_output_apply_patch;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::run_exec_with_params;
+use crate::tools::truncate_for_model;
+
+/// Runs a user-defined tool's command with the model's arguments and returns
+/// its stdout. The command goes through the same sandbox, approval rules and
+/// exec events as the shell tool.
+pub struct ScriptToolHandler {
+    tool: ScriptToolConfig,
+}
+
+impl ScriptToolHandler {
+    pub(crate) fn new(tool: ScriptToolConfig) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for ScriptToolHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(
+        &self,
+        invocation: ToolInvocation<'_>,
+    ) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tracker,
+            sub_id,
+            call_id,
+            tool_name,
+            payload,
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported payload for tool {tool_name}"
+                )));
+            }
+        };
+        // Normalize the arguments so the command always gets a JSON object.
+        let arguments = if arguments.trim().is_empty() {
+            serde_json::Value::Object(serde_json::Map::new())
+        } else {
+            serde_json::from_str(&arguments).map_err(|e| {
+                FunctionCallError::RespondToModel(format!(
+                    "failed to parse function arguments: {e:?}"
+                ))
+            })?
+        };
+
+        let mut command = self.tool.command.clone();
+        command.push(arguments.to_string());
+        let params = ExecParams {
+            command,
+            cwd: turn.cwd.clone(),
+            timeout_ms: self.tool.timeout_ms,
+            env: create_env(&turn.shell_environment_policy),
+            with_escalated_permissions: None,
+            justification: None,
+        };
+
+        let output = run_exec_with_params(
+            tool_name.as_str(),
+            params,
+            None,
+            session,
+            turn,
+            tracker,
+            sub_id.to_string(),
+            call_id,
+        )
+        .await?;
+        if output.exit_code != 0 {
+            return Err(FunctionCallError::RespondToModel(
+                format_exec_output_apply_patch(&output),
+            ));
+        }
+
+        Ok(ToolOutput::Function {
+            content: truncate_for_model(&output.stdout.text),
+            success: Some(true),
+        })
+    }
+}
@@ -38,7 +38,10 @@ impl ToolHandler for UnifiedExecHandler {
         invocation: ToolInvocation<'_>,
     ) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
-            session, payload, ..
+            session,
+            turn,
+            payload,
+            ..
         } = invocation;
 
         let args = match payload {
@@ -82,7 +85,7 @@ impl ToolHandler for UnifiedExecHandler {
         };
 
         let value = session
-            .run_unified_exec_request(request)
+            .run_unified_exec_request(turn, request)
             .await
             .map_err(|err| {
                 FunctionCallError::RespondToModel(format!("unified exec failed: {err:?}"))
@@ -7,6 +7,7 @@ use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::InputItem;
 use crate::protocol::ViewImageToolCallEvent;
+use crate::safety::is_read_allowed;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -53,6 +54,12 @@ impl ToolHandler for ViewImageHandler {
         })?;
 
         let abs_path = turn.resolve_path(Some(args.path));
+        if !is_read_allowed(&turn.sandbox_policy, &turn.cwd, &abs_path) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "reading `{}` is not allowed by the sandbox policy",
+                abs_path.display()
+            )));
+        }
 
         let metadata = fs::metadata(&abs_path).await.map_err(|error| {
             FunctionCallError::RespondToModel(format!(
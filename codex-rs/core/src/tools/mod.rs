@@ -54,8 +54,6 @@ pub(crate) async fn handle_container_exec_with_params(
     sub_id: String,
     call_id: String,
 ) -> Result<String, FunctionCallError> {
-    let otel_event_manager = turn_context.client.get_otel_event_manager();
-
     if params.with_escalated_permissions.unwrap_or(false)
         && !matches!(turn_context.approval_policy, AskForApproval::OnRequest)
     {
@@ -90,6 +88,41 @@ pub(crate) async fn handle_container_exec_with_params(
         MaybeApplyPatchVerified::NotApplyPatch => None,
     };
 
+    let output = run_exec_with_params(
+        tool_name,
+        params,
+        apply_patch_exec,
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id,
+        call_id,
+    )
+    .await?;
+    let content = format_exec_output_apply_patch(&output);
+    if output.exit_code == 0 {
+        Ok(content)
+    } else {
+        Err(FunctionCallError::RespondToModel(content))
+    }
+}
+
+/// Runs `params` through the executor with approvals and exec events and
+/// returns the raw output. Timeouts and exceeded resource limits are reported
+/// to the model as errors.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_exec_with_params(
+    tool_name: &str,
+    params: ExecParams,
+    apply_patch_exec: Option<ApplyPatchExec>,
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    call_id: String,
+) -> Result<ExecToolCallOutput, FunctionCallError> {
+    let otel_event_manager = turn_context.client.get_otel_event_manager();
+
     let command_for_display = if let Some(exec) = apply_patch_exec.as_ref() {
         vec!["apply_patch".to_string(), exec.action.patch.clone()]
     } else {
@@ -146,19 +179,13 @@ pub(crate) async fn handle_container_exec_with_params(
         .await;
 
     match output_result {
-        Ok(output) => {
-            let ExecToolCallOutput { exit_code, .. } = &output;
-            let content = format_exec_output_apply_patch(&output);
-            if *exit_code == 0 {
-                Ok(content)
-            } else {
-                Err(FunctionCallError::RespondToModel(content))
-            }
-        }
+        Ok(output) => Ok(output),
         Err(ExecError::Function(err)) => Err(err),
-        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => Err(
-            FunctionCallError::RespondToModel(format_exec_output_apply_patch(&output)),
-        ),
+        Err(ExecError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output } | SandboxErr::ResourceLimit { output, .. },
+        ))) => Err(FunctionCallError::RespondToModel(
+            format_exec_output_apply_patch(&output),
+        )),
         Err(ExecError::Codex(err)) => Err(FunctionCallError::RespondToModel(format!(
             "execution error: {err:?}"
         ))),
@@ -218,8 +245,16 @@ pub fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
             exec_output.duration.as_millis()
         ) + s;
         s = &prefixed_str;
+    } else if let Some(limit) = exec_output.resource_limit_exceeded {
+        prefixed_str = format!("command exceeded the {limit} limit\n") + s;
+        s = &prefixed_str;
     }
 
+    truncate_for_model(s)
+}
+
+/// Truncates `s` to the model's output budget, keeping its head and tail.
+pub(crate) fn truncate_for_model(s: &str) -> String {
     let total_lines = s.lines().count();
     if s.len() <= MODEL_FORMAT_MAX_BYTES && total_lines <= MODEL_FORMAT_MAX_LINES {
         return s.to_string();
@@ -32,6 +32,13 @@ pub trait ToolHandler: Send + Sync {
         )
     }
 
+    /// Whether calls to this tool may run concurrently with other parallel
+    /// calls from the same model response. Only handlers that never change
+    /// the workspace or ask for approval should opt in.
+    fn supports_parallel_calls(&self) -> bool {
+        false
+    }
+
     async fn handle(&self, invocation: ToolInvocation<'_>)
     -> Result<ToolOutput, FunctionCallError>;
 }
@@ -154,6 +161,10 @@ impl ToolRegistryBuilder {
         self.specs.push(spec);
     }
 
+    pub fn has_handler(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
     pub fn register_handler(&mut self, name: impl Into<String>, handler: Arc<dyn ToolHandler>) {
         let name = name.into();
         if self
@@ -183,6 +194,13 @@ impl ToolRegistryBuilder {
     //     }
     // }
 
+    /// Drops the specs and handlers of every tool for which `keep` returns
+    /// false.
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.specs.retain(|spec| keep(spec.name()));
+        self.handlers.retain(|name, _| keep(name));
+    }
+
     pub fn build(self) -> (Vec<ToolSpec>, ToolRegistry) {
         let registry = ToolRegistry::new(self.handlers);
         (self.specs, registry)
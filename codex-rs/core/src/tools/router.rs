@@ -1,14 +1,18 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config_types::ScriptToolConfig;
 use crate::function_tool::FunctionCallError;
+use crate::hooks::HookContext;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolPayload;
 use crate::tools::registry::ToolRegistry;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::build_specs;
+use crate::tools::spec::register_script_tools;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use codex_protocol::models::LocalShellAction;
 use codex_protocol::models::ResponseInputItem;
@@ -31,8 +35,11 @@ impl ToolRouter {
     pub fn from_config(
         config: &ToolsConfig,
         mcp_tools: Option<HashMap<String, mcp_types::Tool>>,
+        script_tools: &BTreeMap<String, ScriptToolConfig>,
     ) -> Self {
-        let builder = build_specs(config, mcp_tools);
+        let mut builder = build_specs(config, mcp_tools);
+        register_script_tools(&mut builder, script_tools);
+        builder.retain(|name| config.exposes_tool(name));
         let (specs, registry) = builder.build();
         Self { registry, specs }
     }
@@ -41,6 +48,16 @@ impl ToolRouter {
         &self.specs
     }
 
+    /// Whether `call` may run concurrently with the other parallel calls of
+    /// the same model response.
+    pub fn supports_parallel_calls(&self, call: &ToolCall) -> bool {
+        self.registry
+            .handler(&call.tool_name)
+            .is_some_and(|handler| {
+                handler.supports_parallel_calls() && handler.matches_kind(&call.payload)
+            })
+    }
+
     pub fn build_tool_call(
         session: &Session,
         item: ResponseItem,
@@ -127,11 +144,36 @@ impl ToolRouter {
         let ToolCall {
             tool_name,
             call_id,
-            payload,
+            mut payload,
         } = call;
         let payload_outputs_custom = matches!(payload, ToolPayload::Custom { .. });
         let failure_call_id = call_id.clone();
 
+        let hooks = &session.services.hooks;
+        let hook_context = HookContext {
+            session,
+            turn,
+            sub_id,
+            tool_name: &tool_name,
+            call_id: &call_id,
+        };
+        let run_post_hooks = hooks.has_post_tool_use(&tool_name);
+        if let Err(reason) = hooks.run_pre_tool_use(&hook_context, &mut payload).await {
+            let response = Self::failure_response(
+                failure_call_id,
+                payload_outputs_custom,
+                FunctionCallError::RespondToModel(reason),
+            );
+            if run_post_hooks {
+                hooks
+                    .run_post_tool_use(&hook_context, &payload, &response)
+                    .await;
+            }
+            return Ok(response);
+        }
+        let post_hook_payload =
+            run_post_hooks.then(|| (tool_name.clone(), call_id.clone(), payload.clone()));
+
         let invocation = ToolInvocation {
             session,
             turn,
@@ -142,15 +184,27 @@ impl ToolRouter {
             payload,
         };
 
-        match self.registry.dispatch(invocation).await {
-            Ok(response) => Ok(response),
-            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
-            Err(err) => Ok(Self::failure_response(
-                failure_call_id,
-                payload_outputs_custom,
-                err,
-            )),
+        let response = match self.registry.dispatch(invocation).await {
+            Ok(response) => response,
+            Err(FunctionCallError::Fatal(message)) => {
+                return Err(FunctionCallError::Fatal(message));
+            }
+            Err(err) => Self::failure_response(failure_call_id, payload_outputs_custom, err),
+        };
+
+        if let Some((tool_name, call_id, payload)) = post_hook_payload {
+            let hook_context = HookContext {
+                session,
+                turn,
+                sub_id,
+                tool_name: &tool_name,
+                call_id: &call_id,
+            };
+            hooks
+                .run_post_tool_use(&hook_context, &payload, &response)
+                .await;
         }
+        Ok(response)
     }
 
     fn failure_response(
@@ -1,17 +1,21 @@
 use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
+use crate::config_types::ScriptToolConfig;
+use crate::mcp_connection_manager::MCP_TOOL_NAME_DELIMITER;
 use crate::model_family::ModelFamily;
 use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::apply_patch::ApplyPatchToolType;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
 use crate::tools::registry::ToolRegistryBuilder;
+use codex_protocol::config_types::ToolFilter;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 use serde_json::json;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use wildmatch::WildMatch;
 
 #[derive(Debug, Clone)]
 pub enum ConfigShellToolType {
@@ -29,6 +33,7 @@ pub(crate) struct ToolsConfig {
     pub include_view_image_tool: bool,
     pub experimental_unified_exec_tool: bool,
     pub experimental_supported_tools: Vec<String>,
+    pub tool_filter: ToolFilter,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -80,8 +85,27 @@ impl ToolsConfig {
             include_view_image_tool: *include_view_image_tool,
             experimental_unified_exec_tool: *experimental_unified_exec_tool,
             experimental_supported_tools: model_family.experimental_supported_tools.clone(),
+            tool_filter: ToolFilter::default(),
         }
     }
+
+    pub fn with_tool_filter(mut self, tool_filter: ToolFilter) -> Self {
+        self.tool_filter = tool_filter;
+        self
+    }
+
+    /// Whether the tool filter exposes `tool_name` to the model.
+    pub(crate) fn exposes_tool(&self, tool_name: &str) -> bool {
+        let matches = |pattern: &String| {
+            // `<server>/<tool>` patterns address MCP tools by their fully
+            // qualified name.
+            let pattern = pattern.replacen('/', MCP_TOOL_NAME_DELIMITER, 1);
+            WildMatch::new(&pattern).matches(tool_name)
+        };
+        let filter = &self.tool_filter;
+        (filter.include.is_empty() || filter.include.iter().any(matches))
+            && !filter.exclude.iter().any(matches)
+    }
 }
 
 /// Generic JSON‑Schema subset needed for our tool definitions
@@ -381,6 +405,52 @@ pub(crate) fn mcp_tool_to_openai_tool(
     })
 }
 
+pub(crate) fn script_tool_to_openai_tool(
+    name: String,
+    tool: &ScriptToolConfig,
+) -> Result<ResponsesApiTool, serde_json::Error> {
+    let mut parameters = tool.parameters.clone();
+    if let Some(map) = parameters.as_object_mut() {
+        map.entry("properties")
+            .or_insert_with(|| JsonValue::Object(serde_json::Map::new()));
+    }
+    sanitize_json_schema(&mut parameters);
+    let parameters = serde_json::from_value::<JsonSchema>(parameters)?;
+
+    Ok(ResponsesApiTool {
+        name,
+        description: tool.description.clone(),
+        strict: false,
+        parameters,
+    })
+}
+
+/// Adds the user-defined script tools to `builder`. Tools whose name is
+/// already taken by a built-in or MCP tool are skipped.
+pub(crate) fn register_script_tools(
+    builder: &mut ToolRegistryBuilder,
+    script_tools: &BTreeMap<String, ScriptToolConfig>,
+) {
+    use crate::tools::handlers::ScriptToolHandler;
+    use std::sync::Arc;
+
+    for (name, tool) in script_tools {
+        if builder.has_handler(name) {
+            tracing::warn!("skipping tool `{name}`: the name is taken by another tool");
+            continue;
+        }
+        match script_tool_to_openai_tool(name.clone(), tool) {
+            Ok(converted_tool) => {
+                builder.push_spec(ToolSpec::Function(converted_tool));
+                builder.register_handler(name, Arc::new(ScriptToolHandler::new(tool.clone())));
+            }
+            Err(e) => {
+                tracing::error!("Failed to convert {name:?} script tool to OpenAI tool: {e:?}");
+            }
+        }
+    }
+}
+
 /// Sanitize a JSON Schema (as serde_json::Value) so it can fit our limited
 /// JsonSchema enum. This function:
 /// - Ensures every schema object has a "type". If missing, infers it from
@@ -519,7 +589,6 @@ pub(crate) fn build_specs(
     let plan_handler = Arc::new(PlanHandler);
     let apply_patch_handler = Arc::new(ApplyPatchHandler);
     let view_image_handler = Arc::new(ViewImageHandler);
-    let mcp_handler = Arc::new(McpHandler);
 
     if config.experimental_unified_exec_tool {
         builder.push_spec(create_unified_exec_tool());
@@ -591,10 +660,11 @@ pub(crate) fn build_specs(
         entries.sort_by(|a, b| a.0.cmp(&b.0));
 
         for (name, tool) in entries.into_iter() {
-            match mcp_tool_to_openai_tool(name.clone(), tool.clone()) {
+            let mcp_handler = Arc::new(McpHandler::new(&tool));
+            match mcp_tool_to_openai_tool(name.clone(), tool) {
                 Ok(converted_tool) => {
                     builder.push_spec(ToolSpec::Function(converted_tool));
-                    builder.register_handler(name, mcp_handler.clone());
+                    builder.register_handler(name, mcp_handler);
                 }
                 Err(e) => {
                     tracing::error!("Failed to convert {name:?} MCP tool to OpenAI tool: {e:?}");
@@ -803,6 +873,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_only_read_only_mcp_tools_support_parallel_calls() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            experimental_unified_exec_tool: false,
+        });
+        let mcp_tool = |name: &str, read_only_hint: Option<bool>| mcp_types::Tool {
+            name: name.to_string(),
+            input_schema: ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            output_schema: None,
+            title: None,
+            annotations: Some(mcp_types::ToolAnnotations {
+                destructive_hint: None,
+                idempotent_hint: None,
+                open_world_hint: None,
+                read_only_hint,
+                title: None,
+            }),
+            description: None,
+        };
+        let (_, registry) = build_specs(
+            &config,
+            Some(HashMap::from([
+                ("docs/search".to_string(), mcp_tool("search", Some(true))),
+                ("docs/update".to_string(), mcp_tool("update", Some(false))),
+                ("docs/unknown".to_string(), mcp_tool("unknown", None)),
+            ])),
+        )
+        .build();
+
+        let supports_parallel_calls = |name: &str| {
+            registry
+                .handler(name)
+                .expect("registered MCP handler")
+                .supports_parallel_calls()
+        };
+        assert!(supports_parallel_calls("docs/search"));
+        assert!(!supports_parallel_calls("docs/update"));
+        assert!(!supports_parallel_calls("docs/unknown"));
+    }
+
     #[test]
     fn test_build_specs_mcp_tools_sorted_by_name() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -1283,4 +1404,102 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_script_tools_are_registered_after_builtin_tools() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            experimental_unified_exec_tool: false,
+        });
+        let script_tool = |description: &str| ScriptToolConfig {
+            description: description.to_string(),
+            command: vec!["schema-registry".to_string()],
+            parameters: json!({
+                "type": "object",
+                "properties": { "subject": { "type": "string" } },
+                "required": ["subject"],
+            }),
+            timeout_ms: None,
+        };
+        let script_tools = BTreeMap::from([
+            ("query_schema".to_string(), script_tool("Look up a schema")),
+            ("shell".to_string(), script_tool("Shadows the shell tool")),
+        ]);
+
+        let mut builder = build_specs(&config, None);
+        register_script_tools(&mut builder, &script_tools);
+        let (tools, registry) = builder.build();
+
+        assert_eq_tool_names(&tools, &["shell", "query_schema"]);
+        assert!(registry.handler("query_schema").is_some());
+        assert_eq!(
+            tools[1],
+            ToolSpec::Function(ResponsesApiTool {
+                name: "query_schema".to_string(),
+                description: "Look up a schema".to_string(),
+                strict: false,
+                parameters: JsonSchema::Object {
+                    properties: BTreeMap::from([(
+                        "subject".to_string(),
+                        JsonSchema::String { description: None },
+                    )]),
+                    required: Some(vec!["subject".to_string()]),
+                    additional_properties: None,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_tool_filter_hides_builtin_and_mcp_tools() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: true,
+            include_apply_patch_tool: true,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            experimental_unified_exec_tool: false,
+        })
+        .with_tool_filter(ToolFilter {
+            include: vec![
+                "shell".to_string(),
+                "apply_*".to_string(),
+                "github/*".to_string(),
+            ],
+            exclude: vec!["apply_patch".to_string(), "github/delete_*".to_string()],
+        });
+        let mcp_tool = |name: &str| mcp_types::Tool {
+            name: name.to_string(),
+            input_schema: ToolInputSchema {
+                properties: Some(serde_json::json!({})),
+                required: None,
+                r#type: "object".to_string(),
+            },
+            output_schema: None,
+            title: None,
+            annotations: None,
+            description: None,
+        };
+        let mcp_tools = HashMap::from([
+            ("github__create_issue".to_string(), mcp_tool("create_issue")),
+            ("github__delete_repo".to_string(), mcp_tool("delete_repo")),
+            ("jira__create_issue".to_string(), mcp_tool("create_issue")),
+        ]);
+
+        let mut builder = build_specs(&config, Some(mcp_tools));
+        builder.retain(|name| config.exposes_tool(name));
+        let (tools, registry) = builder.build();
+
+        assert_eq_tool_names(&tools, &["shell", "github__create_issue"]);
+        assert!(registry.handler("update_plan").is_none());
+        assert!(registry.handler("github__delete_repo").is_none());
+    }
 }
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::io::ErrorKind;
 use std::io::Read;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use std::sync::atomic::AtomicBool;
@@ -17,7 +18,14 @@ use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tokio::time::Instant;
 
+use crate::container::ContainerSession;
 use crate::exec_command::ExecCommandSession;
+use crate::protocol::ResourceLimitKind;
+use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
+use crate::resource_limits::cpu_time_at_exit;
+use crate::resource_limits::exceeded_limit_for_signal;
+use crate::resource_limits::signal_from_exit_code;
 use crate::truncate::truncate_middle;
 
 mod errors;
@@ -28,6 +36,15 @@ const DEFAULT_TIMEOUT_MS: u64 = 1_000;
 const MAX_TIMEOUT_MS: u64 = 60_000;
 const UNIFIED_EXEC_OUTPUT_MAX_BYTES: usize = 128 * 1024; // 128 KiB
 
+/// Descriptions `portable_pty` reports (via `strsignal(3)`) for the signals
+/// sent when a resource limit is exceeded.
+#[cfg(unix)]
+const LIMIT_SIGNAL_DESCRIPTIONS: &[(&str, i32)] = &[
+    ("CPU time limit exceeded", libc::SIGXCPU),
+    ("File size limit exceeded", libc::SIGXFSZ),
+    ("Killed", libc::SIGKILL),
+];
+
 #[derive(Debug)]
 pub(crate) struct UnifiedExecRequest<'a> {
     pub session_id: Option<i32>,
@@ -45,6 +62,8 @@ pub(crate) struct UnifiedExecResult {
 pub(crate) struct UnifiedExecSessionManager {
     next_session_id: AtomicI32,
     sessions: Mutex<HashMap<i32, ManagedUnifiedExecSession>>,
+    resource_limits: ResourceLimits,
+    container: Option<Arc<ContainerSession>>,
 }
 
 #[derive(Debug)]
@@ -149,6 +168,27 @@ impl Drop for ManagedUnifiedExecSession {
 }
 
 impl UnifiedExecSessionManager {
+    pub(crate) fn with_resource_limits(resource_limits: ResourceLimits) -> Self {
+        Self {
+            resource_limits,
+            ..Default::default()
+        }
+    }
+
+    /// Starts new sessions inside `container` instead of on the host.
+    pub(crate) fn with_container(mut self, container: Option<Arc<ContainerSession>>) -> Self {
+        self.container = container;
+        self
+    }
+
+    /// Records the working directory and sandbox policy of the current turn,
+    /// so new sessions start in a container with matching mounts.
+    pub(crate) fn update_environment(&self, cwd: PathBuf, sandbox_policy: &SandboxPolicy) {
+        if let Some(container) = &self.container {
+            container.update_environment(cwd, sandbox_policy);
+        }
+    }
+
     pub async fn handle_request(
         &self,
         request: UnifiedExecRequest<'_>,
@@ -194,9 +234,24 @@ impl UnifiedExecSessionManager {
             }
             drop(sessions);
         } else {
-            let command = request.input_chunks.to_vec();
+            let mut command = request.input_chunks.to_vec();
+            let mut resource_limits = self.resource_limits;
+            let mut session_container = None;
+            if let Some(container) = &self.container
+                && !command.is_empty()
+            {
+                // The runtime applies the process limits to the container.
+                resource_limits = resource_limits.output_only();
+                container
+                    .ensure_started()
+                    .await
+                    .map_err(|err| UnifiedExecError::create_session(anyhow::Error::new(err)))?;
+                command = container.wrap_command(command, &container.workspace(), true);
+                session_container = Some(Arc::clone(container));
+            }
             let new_id = self.next_session_id.fetch_add(1, Ordering::SeqCst);
-            let (session, initial_output_rx) = create_unified_exec_session(&command).await?;
+            let (session, initial_output_rx) =
+                create_unified_exec_session(&command, &resource_limits, session_container).await?;
             let managed_session = ManagedUnifiedExecSession::new(session, initial_output_rx);
             let (buffer, notify) = managed_session.output_handles();
             writer_tx = managed_session.writer_sender();
@@ -298,8 +353,12 @@ impl UnifiedExecSessionManager {
     }
 }
 
+/// `container` is the container the command runs in, whose runtime applies
+/// the process limits and reports when they are exceeded.
 async fn create_unified_exec_session(
     command: &[String],
+    resource_limits: &ResourceLimits,
+    container: Option<Arc<ContainerSession>>,
 ) -> Result<
     (
         ExecCommandSession,
@@ -334,6 +393,17 @@ async fn create_unified_exec_session(
         .map_err(UnifiedExecError::create_session)?;
     let killer = child.clone_killer();
 
+    // The PTY spawn cannot be hooked, so limits are applied right after the
+    // process starts. Only Linux can change another process's limits.
+    #[cfg(target_os = "linux")]
+    if !resource_limits.is_empty()
+        && let Some(pid) = child.process_id()
+        && let Err(err) = resource_limits.apply_to_process(pid)
+    {
+        let _ = child.kill();
+        return Err(UnifiedExecError::create_session(anyhow::Error::new(err)));
+    }
+
     let (writer_tx, mut writer_rx) = mpsc::channel::<Vec<u8>>(128);
     let (output_tx, _) = tokio::sync::broadcast::channel::<Vec<u8>>(256);
 
@@ -342,12 +412,25 @@ async fn create_unified_exec_session(
         .try_clone_reader()
         .map_err(UnifiedExecError::create_session)?;
     let output_tx_clone = output_tx.clone();
+    let max_output_bytes = resource_limits.max_output_bytes();
+    let mut output_killer = killer.clone_killer();
     let reader_handle = tokio::task::spawn_blocking(move || {
         let mut buf = [0u8; 8192];
+        let mut total_bytes: usize = 0;
+        let mut output_limit_exceeded = false;
         loop {
             match reader.read(&mut buf) {
                 Ok(0) => break,
+                Ok(_) if output_limit_exceeded => continue,
                 Ok(n) => {
+                    total_bytes = total_bytes.saturating_add(n);
+                    if max_output_bytes.is_some_and(|max| total_bytes > max) {
+                        output_limit_exceeded = true;
+                        let _ = output_killer.kill();
+                        let _ = output_tx_clone
+                            .send(limit_exceeded_note(ResourceLimitKind::OutputSize));
+                        continue;
+                    }
                     let _ = output_tx_clone.send(buf[..n].to_vec());
                 }
                 Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
@@ -384,8 +467,27 @@ async fn create_unified_exec_session(
 
     let exit_status = Arc::new(AtomicBool::new(false));
     let wait_exit_status = Arc::clone(&exit_status);
+    let wait_output_tx = output_tx.clone();
+    let resource_limits = *resource_limits;
+    let pid = child
+        .process_id()
+        .filter(|_| resource_limits.cpu_time_secs.is_some());
+    let runtime = tokio::runtime::Handle::current();
     let wait_handle = tokio::task::spawn_blocking(move || {
-        let _ = child.wait();
+        let cpu_time = pid.and_then(cpu_time_at_exit);
+        let Ok(status) = child.wait() else {
+            wait_exit_status.store(true, Ordering::SeqCst);
+            return;
+        };
+        let limit = match &container {
+            Some(container) => i32::try_from(status.exit_code())
+                .ok()
+                .and_then(|code| runtime.block_on(container.exceeded_limit(code))),
+            None => exceeded_limit_on_exit(&resource_limits, &status, cpu_time),
+        };
+        if let Some(limit) = limit {
+            let _ = wait_output_tx.send(limit_exceeded_note(limit));
+        }
         wait_exit_status.store(true, Ordering::SeqCst);
     });
 
@@ -401,6 +503,31 @@ async fn create_unified_exec_session(
     Ok((session, initial_output_rx))
 }
 
+fn limit_exceeded_note(limit: ResourceLimitKind) -> Vec<u8> {
+    format!("\ncommand exceeded the {limit} limit\n").into_bytes()
+}
+
+/// Works out whether a PTY session ended because of a resource limit, either
+/// from the signal that killed it or from a wrapping shell's `128 + signal`
+/// exit code.
+fn exceeded_limit_on_exit(
+    resource_limits: &ResourceLimits,
+    status: &portable_pty::ExitStatus,
+    cpu_time: Option<Duration>,
+) -> Option<ResourceLimitKind> {
+    #[cfg(unix)]
+    if let Some(description) = status.signal() {
+        return LIMIT_SIGNAL_DESCRIPTIONS
+            .iter()
+            .find(|(limit_description, _)| description == *limit_description)
+            .and_then(|(_, signal)| exceeded_limit_for_signal(resource_limits, *signal, cpu_time));
+    }
+    i32::try_from(status.exit_code())
+        .ok()
+        .and_then(signal_from_exit_code)
+        .and_then(|signal| exceeded_limit_for_signal(resource_limits, signal, cpu_time))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,4 +779,37 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn cpu_time_limit_is_reported_in_output() -> Result<(), UnifiedExecError> {
+        skip_if_sandbox!(Ok(()));
+
+        let manager = UnifiedExecSessionManager::with_resource_limits(ResourceLimits {
+            cpu_time_secs: Some(1),
+            ..Default::default()
+        });
+        let result = manager
+            .handle_request(UnifiedExecRequest {
+                session_id: None,
+                input_chunks: &[
+                    "/bin/bash".to_string(),
+                    "-c".to_string(),
+                    "while :; do :; done".to_string(),
+                ],
+                timeout_ms: Some(5_000),
+            })
+            .await?;
+
+        assert!(result.session_id.is_none());
+        assert!(
+            result
+                .output
+                .contains("command exceeded the CPU time limit"),
+            "output: {}",
+            result.output
+        );
+
+        Ok(())
+    }
 }
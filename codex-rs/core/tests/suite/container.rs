@@ -0,0 +1,183 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use codex_core::config_types::ContainerConfig;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_local_shell_call;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::matchers::any;
+
+/// Writes a stand-in for `podman`/`docker` that records every invocation in
+/// `log` and runs `exec`'d commands directly on the host.
+fn write_fake_runtime(dir: &Path, log: &Path) -> std::io::Result<String> {
+    let script = format!(
+        r#"#!/bin/sh
+echo "$*" >> "{log}"
+case "$1" in
+  run) echo 0123456789ab ;;
+  exec)
+    shift
+    while [ $# -gt 0 ]; do
+      case "$1" in
+        --interactive|--tty) shift ;;
+        --workdir) cd "$2" || exit 125; shift 2 ;;
+        *) break ;;
+      esac
+    done
+    shift
+    exec "$@"
+    ;;
+esac
+"#,
+        log = log.display()
+    );
+    let path = dir.join("fake-runtime");
+    std::fs::write(&path, script)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn shell_outputs(requests: &[wiremock::Request]) -> Vec<String> {
+    requests
+        .iter()
+        .filter_map(|request| request.body_json::<Value>().ok())
+        .filter_map(|body| {
+            body.get("input")
+                .and_then(Value::as_array)
+                .and_then(|items| {
+                    items.iter().rev().find(|item| {
+                        item.get("type").and_then(Value::as_str) == Some("function_call_output")
+                    })
+                })
+                .and_then(|item| item.get("output"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shell_commands_run_in_one_container() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let runtime_dir = TempDir::new()?;
+    let log = runtime_dir.path().join("runtime.log");
+    let runtime = write_fake_runtime(runtime_dir.path(), &log)?;
+
+    let mut builder = test_codex().with_config(move |config| {
+        config.container = Some(ContainerConfig {
+            runtime,
+            image: "toolchain:1.0".to_string(),
+            mounts: Vec::new(),
+            env: HashMap::new(),
+            run_args: Vec::new(),
+        });
+    });
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = builder.build(&server).await?;
+
+    for (index, text) in ["first", "second"].iter().enumerate() {
+        let response_id = format!("resp-{index}");
+        responses::mount_sse_once_match(
+            &server,
+            any(),
+            sse(vec![
+                ev_local_shell_call(
+                    &format!("call-{index}"),
+                    "completed",
+                    vec!["/bin/echo", text],
+                ),
+                ev_completed(&response_id),
+            ]),
+        )
+        .await;
+    }
+    responses::mount_sse_once_match(
+        &server,
+        any(),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-done"),
+        ]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "run the commands".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    let outputs = shell_outputs(&requests);
+    assert!(
+        outputs.iter().any(|output| output.contains("first"))
+            && outputs.iter().any(|output| output.contains("second")),
+        "expected both command outputs, got {outputs:?}"
+    );
+
+    let log = std::fs::read_to_string(&log)?;
+    let invocations: Vec<&str> = log.lines().collect();
+    let workspace = cwd.path().to_string_lossy();
+    let runs: Vec<&&str> = invocations
+        .iter()
+        .filter(|line| line.starts_with("run "))
+        .collect();
+    assert_eq!(
+        runs.len(),
+        1,
+        "container should start once: {invocations:?}"
+    );
+    assert!(
+        runs[0].contains(&format!("--volume {workspace}:{workspace}"))
+            && runs[0].ends_with("toolchain:1.0 sleep infinity"),
+        "unexpected run invocation: {}",
+        runs[0]
+    );
+    let execs: Vec<&&str> = invocations
+        .iter()
+        .filter(|line| line.starts_with("exec "))
+        .collect();
+    assert_eq!(execs.len(), 2, "unexpected invocations: {invocations:?}");
+    assert!(
+        execs[0].starts_with(&format!("exec --interactive --workdir {workspace} codex-"))
+            && execs[0].ends_with("/bin/echo first"),
+        "unexpected exec invocation: {}",
+        execs[0]
+    );
+
+    Ok(())
+}
@@ -16,6 +16,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::resource_limits::ResourceLimits;
 
 fn collect_stdout_events(rx: Receiver<Event>) -> Vec<u8> {
     let mut out = Vec::new();
@@ -67,6 +68,7 @@ async fn test_exec_stdout_stream_events_echo() {
         &policy,
         cwd.as_path(),
         &None,
+        &ResourceLimits::default(),
         Some(stdout_stream),
     )
     .await;
@@ -119,6 +121,7 @@ async fn test_exec_stderr_stream_events_echo() {
         &policy,
         cwd.as_path(),
         &None,
+        &ResourceLimits::default(),
         Some(stdout_stream),
     )
     .await;
@@ -174,6 +177,7 @@ async fn test_aggregated_output_interleaves_in_order() {
         &policy,
         cwd.as_path(),
         &None,
+        &ResourceLimits::default(),
         None,
     )
     .await
@@ -212,6 +216,7 @@ async fn test_exec_timeout_returns_partial_output() {
         &policy,
         cwd.as_path(),
         &None,
+        &ResourceLimits::default(),
         None,
     )
     .await;
@@ -0,0 +1,225 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use codex_core::config_types::HooksConfig;
+use codex_core::config_types::ToolHookConfig;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_local_shell_call;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::MockServer;
+use wiremock::matchers::any;
+
+fn write_hook(dir: &Path, name: &str, body: &str) -> anyhow::Result<ToolHookConfig> {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{body}"))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(ToolHookConfig {
+        command: vec![path.to_string_lossy().to_string()],
+        tools: Vec::new(),
+        timeout_ms: None,
+    })
+}
+
+fn shell_outputs(requests: &[wiremock::Request]) -> Vec<String> {
+    requests
+        .iter()
+        .filter_map(|request| request.body_json::<Value>().ok())
+        .filter_map(|body| {
+            body.get("input")
+                .and_then(Value::as_array)
+                .and_then(|items| {
+                    items.iter().rev().find(|item| {
+                        item.get("type").and_then(Value::as_str) == Some("function_call_output")
+                    })
+                })
+                .and_then(|item| item.get("output"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Runs one turn in which the model calls `local_shell` with `command` and
+/// returns the events and the output sent back to the model.
+async fn run_shell_call(
+    server: &MockServer,
+    hooks: HooksConfig,
+    command: Vec<&str>,
+) -> anyhow::Result<(Vec<EventMsg>, String)> {
+    let mut builder = test_codex().with_config(move |config| {
+        config.hooks = hooks;
+    });
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = builder.build(server).await?;
+
+    responses::mount_sse_once_match(
+        server,
+        any(),
+        sse(vec![
+            ev_local_shell_call("call-1", "completed", command),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once_match(
+        server,
+        any(),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "run the command".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    let mut events = Vec::new();
+    loop {
+        let event = codex.next_event().await?;
+        if matches!(event.msg, EventMsg::TaskComplete(_)) {
+            break;
+        }
+        events.push(event.msg);
+    }
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    let output = shell_outputs(&requests)
+        .pop()
+        .expect("shell output sent to the model");
+    Ok((events, output))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_use_hook_denies_call_and_post_hook_sees_result() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let hooks_dir = TempDir::new()?;
+    let marker = hooks_dir.path().join("should-not-exist");
+    let post_log = hooks_dir.path().join("post.log");
+    let pre = write_hook(
+        hooks_dir.path(),
+        "deny-touch.sh",
+        r#"if grep -q '"touch"'; then
+  echo '{"decision":"deny","reason":"touch is not allowed"}'
+fi
+"#,
+    )?;
+    let post = write_hook(
+        hooks_dir.path(),
+        "log.sh",
+        &format!("cat >> \"{}\"\n", post_log.display()),
+    )?;
+
+    let marker_path = marker.to_string_lossy().to_string();
+    let (events, output) = run_shell_call(
+        &server,
+        HooksConfig {
+            pre_tool_use: vec![pre],
+            post_tool_use: vec![post],
+        },
+        vec!["touch", &marker_path],
+    )
+    .await?;
+
+    assert!(!marker.exists(), "denied command must not run");
+    assert!(
+        output.contains("tool call denied by pre_tool_use hook")
+            && output.contains("touch is not allowed"),
+        "unexpected output: {output}"
+    );
+    assert!(
+        events.iter().any(|event| matches!(
+            event,
+            EventMsg::BackgroundEvent(ev) if ev.message.contains("denied local_shell")
+        )),
+        "expected a background event for the denial: {events:?}"
+    );
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event, EventMsg::ExecCommandBegin(_))),
+        "denied command must not start"
+    );
+
+    let logged: Value = serde_json::from_str(std::fs::read_to_string(&post_log)?.trim())?;
+    assert_eq!(logged["hook_event"], "post_tool_use");
+    assert_eq!(logged["call_id"], "call-1");
+    assert_eq!(logged["payload"]["type"], "local_shell");
+    assert_eq!(logged["payload"]["command"][0], "touch");
+    assert_eq!(logged["output"]["success"], false);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_use_hook_rewrites_call() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let hooks_dir = TempDir::new()?;
+    let pre = write_hook(
+        hooks_dir.path(),
+        "rewrite.sh",
+        r#"cat >/dev/null
+echo '{"decision":"modify","payload":{"type":"local_shell","command":["/bin/echo","rewritten"]}}'
+"#,
+    )?;
+
+    let (events, output) = run_shell_call(
+        &server,
+        HooksConfig {
+            pre_tool_use: vec![pre],
+            post_tool_use: Vec::new(),
+        },
+        vec!["/bin/echo", "original"],
+    )
+    .await?;
+
+    assert!(
+        output.contains("rewritten") && !output.contains("original"),
+        "unexpected output: {output}"
+    );
+    assert!(
+        events.iter().any(|event| matches!(
+            event,
+            EventMsg::BackgroundEvent(ev) if ev.message.contains("modified the local_shell call")
+        )),
+        "expected a background event for the rewrite: {events:?}"
+    );
+
+    Ok(())
+}
@@ -6,19 +6,24 @@ mod cli_stream;
 mod client;
 mod compact;
 mod compact_resume_fork;
+mod container;
 mod exec;
 mod exec_stream_events;
 mod fork_conversation;
+mod hooks;
 mod json_result;
 mod live_cli;
+mod mode_state;
 mod model_overrides;
 mod model_tools;
 mod otel;
+mod parallel_tool_calls;
 mod prompt_caching;
 mod read_file;
 mod review;
 mod rmcp_client;
 mod rollout_list_find;
+mod script_tools;
 mod seatbelt;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use codex_core::CodexAuth;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::NewConversation;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::ConversationPathResponseEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::ModeStateItem;
+use codex_core::protocol::Op;
+use core_test_support::load_default_config_for_test;
+use core_test_support::skip_if_no_network;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn sse_completed(id: &str) -> String {
+    core_test_support::load_sse_fixture_with_id("tests/fixtures/completed_template.json", id)
+}
+
+fn mode_state(enabled: &[&str], focus: &str) -> ModeStateItem {
+    let vars = BTreeMap::from([("focus".to_string(), Some(focus.to_string()))]);
+    ModeStateItem {
+        enabled: enabled.iter().map(ToString::to_string).collect(),
+        variables: BTreeMap::from([("/review".to_string(), vars)]),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn resume_and_fork_restore_latest_mode_state() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_completed("resp"), "text/event-stream"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let NewConversation {
+        conversation: codex,
+        session_configured,
+        ..
+    } = conversation_manager
+        .new_conversation(config.clone())
+        .await
+        .expect("create conversation");
+    assert_eq!(session_configured.mode_state, None);
+
+    // The latest item wins: resume sees the state recorded after the second
+    // turn, while a fork cut before the second user message keeps the one
+    // recorded before the first turn.
+    let before_first = mode_state(&["/review"], "tests");
+    let after_first = mode_state(&["/review", "/docs"], "docs");
+    codex
+        .submit(Op::RecordModeState {
+            mode_state: mode_state(&[], "ignored"),
+        })
+        .await
+        .unwrap();
+    codex
+        .submit(Op::RecordModeState {
+            mode_state: before_first.clone(),
+        })
+        .await
+        .unwrap();
+    for (text, state) in [("first", None), ("second", Some(after_first.clone()))] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: text.to_string(),
+                }],
+            })
+            .await
+            .unwrap();
+        let _ = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+        if let Some(mode_state) = state {
+            codex
+                .submit(Op::RecordModeState { mode_state })
+                .await
+                .unwrap();
+        }
+    }
+
+    codex.submit(Op::GetPath).await.unwrap();
+    let rollout_path =
+        match wait_for_event(&codex, |ev| matches!(ev, EventMsg::ConversationPath(_))).await {
+            EventMsg::ConversationPath(ConversationPathResponseEvent { path, .. }) => path,
+            _ => panic!("expected ConversationPath event"),
+        };
+
+    let auth_manager =
+        codex_core::AuthManager::from_auth_for_testing(CodexAuth::from_api_key("dummy"));
+    let resumed = conversation_manager
+        .resume_conversation_from_rollout(config.clone(), rollout_path.clone(), auth_manager)
+        .await
+        .expect("resume conversation");
+    assert_eq!(resumed.session_configured.mode_state, Some(after_first));
+
+    let forked = conversation_manager
+        .fork_conversation(1, config, rollout_path)
+        .await
+        .expect("fork conversation");
+    assert_eq!(forked.session_configured.mode_state, Some(before_first));
+}
@@ -39,6 +39,7 @@ async fn override_turn_context_does_not_persist_when_config_exists() {
             effort: Some(Some(ReasoningEffort::High)),
             summary: None,
             user_instructions: None,
+            tool_filter: None,
         })
         .await
         .expect("submit override");
@@ -80,6 +81,7 @@ async fn override_turn_context_does_not_create_config_file() {
             effort: Some(Some(ReasoningEffort::Medium)),
             summary: None,
             user_instructions: None,
+            tool_filter: None,
         })
         .await
         .expect("submit override");
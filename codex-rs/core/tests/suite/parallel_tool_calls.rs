@@ -0,0 +1,324 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use codex_core::config_types::HooksConfig;
+use codex_core::config_types::ToolHookConfig;
+use codex_core::model_family::find_family_for_model;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_local_shell_call;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use tempfile::TempDir;
+use wiremock::MockServer;
+use wiremock::matchers::any;
+
+/// A `pre_tool_use` hook that logs when each call starts and ends.
+fn logging_hook(dir: &Path, log: &Path) -> anyhow::Result<ToolHookConfig> {
+    hook_script(
+        dir,
+        "log-call.sh",
+        &format!(
+            r#"#!/bin/sh
+id=$(sed -n 's/.*"call_id":"\([^"]*\)".*/\1/p')
+echo "start $id" >> "{log}"
+echo "end $id" >> "{log}"
+"#,
+            log = log.display()
+        ),
+    )
+}
+
+/// A `pre_tool_use` hook acting as a barrier between `read-slow` and
+/// `read-fast`: `read-fast` waits until `read-slow` has started, and
+/// `read-slow` waits until `read-fast` has ended. Both can only finish when
+/// they run concurrently; otherwise the waiting call gives up and logs a
+/// timeout.
+fn barrier_hook(dir: &Path, log: &Path) -> anyhow::Result<ToolHookConfig> {
+    hook_script(
+        dir,
+        "barrier.sh",
+        &format!(
+            r#"#!/bin/sh
+id=$(sed -n 's/.*"call_id":"\([^"]*\)".*/\1/p')
+echo "start $id" >> "{log}"
+case "$id" in
+  read-slow) wait_for="end read-fast" ;;
+  *) wait_for="start read-slow" ;;
+esac
+i=0
+until grep -qx "$wait_for" "{log}"; do
+  i=$((i + 1))
+  if [ "$i" -gt 100 ]; then
+    echo "timeout $id" >> "{log}"
+    break
+  fi
+  sleep 0.05
+done
+echo "end $id" >> "{log}"
+"#,
+            log = log.display()
+        ),
+    )
+}
+
+fn hook_script(dir: &Path, name: &str, script: &str) -> anyhow::Result<ToolHookConfig> {
+    let path = dir.join(name);
+    std::fs::write(&path, script)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(ToolHookConfig {
+        command: vec![path.to_string_lossy().to_string()],
+        tools: Vec::new(),
+        timeout_ms: None,
+    })
+}
+
+/// Runs one turn whose first model response is `tool_events`, and returns the
+/// events of the turn and the call ids of the tool outputs sent back to the
+/// model, in order.
+async fn run_turn_with_tool_calls(
+    server: &MockServer,
+    hooks: HooksConfig,
+    tool_events: Vec<Value>,
+) -> anyhow::Result<(Vec<EventMsg>, Vec<String>)> {
+    let mut builder = test_codex().with_config(move |config| {
+        config.hooks = hooks;
+    });
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = builder.build(server).await?;
+
+    let mut first_response = tool_events;
+    first_response.push(ev_completed("resp-1"));
+    responses::mount_sse_once_match(server, any(), sse(first_response)).await;
+    responses::mount_sse_once_match(
+        server,
+        any(),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "look around".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    let mut events = Vec::new();
+    loop {
+        let event = codex.next_event().await?;
+        if matches!(event.msg, EventMsg::TaskComplete(_)) {
+            break;
+        }
+        events.push(event.msg);
+    }
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    let follow_up = requests
+        .last()
+        .expect("follow-up request")
+        .body_json::<Value>()?;
+    let output_call_ids = follow_up["input"]
+        .as_array()
+        .expect("input items")
+        .iter()
+        .filter(|item| item["type"] == "function_call_output")
+        .filter_map(|item| item["call_id"].as_str().map(str::to_string))
+        .collect();
+    Ok((events, output_call_ids))
+}
+
+fn read_log(log: &Path) -> anyhow::Result<Vec<String>> {
+    Ok(std::fs::read_to_string(log)?
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_file_calls_run_concurrently_and_keep_output_order() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let hooks_dir = TempDir::new()?;
+    let log = hooks_dir.path().join("calls.log");
+    let sample = hooks_dir.path().join("sample.txt");
+    std::fs::write(&sample, "first\nsecond\n")?;
+    let arguments = serde_json::json!({ "file_path": sample }).to_string();
+
+    let (_, output_call_ids) = run_turn_with_tool_calls(
+        &server,
+        HooksConfig {
+            pre_tool_use: vec![barrier_hook(hooks_dir.path(), &log)?],
+            post_tool_use: Vec::new(),
+        },
+        vec![
+            ev_function_call("read-slow", "read_file", &arguments),
+            ev_function_call("read-fast", "read_file", &arguments),
+        ],
+    )
+    .await?;
+
+    let log = read_log(&log)?;
+    assert_eq!(log.len(), 4, "unexpected log: {log:?}");
+    assert!(
+        log[..2].iter().all(|line| line.starts_with("start ")),
+        "both calls should start before either ends: {log:?}"
+    );
+    assert_eq!(
+        log[2..],
+        ["end read-fast".to_string(), "end read-slow".to_string()]
+    );
+    assert_eq!(
+        output_call_ids,
+        vec!["read-slow".to_string(), "read-fast".to_string()],
+        "outputs should follow the order of the calls"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shell_calls_run_one_at_a_time_between_parallel_calls() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let hooks_dir = TempDir::new()?;
+    let log = hooks_dir.path().join("calls.log");
+    let sample = hooks_dir.path().join("sample.txt");
+    std::fs::write(&sample, "first\nsecond\n")?;
+    let arguments = serde_json::json!({ "file_path": sample }).to_string();
+
+    let (events, output_call_ids) = run_turn_with_tool_calls(
+        &server,
+        HooksConfig {
+            pre_tool_use: vec![logging_hook(hooks_dir.path(), &log)?],
+            post_tool_use: Vec::new(),
+        },
+        vec![
+            ev_function_call("read-slow", "read_file", &arguments),
+            ev_local_shell_call("shell-1", "completed", vec!["/bin/echo", "one"]),
+            ev_local_shell_call("shell-2", "completed", vec!["/bin/echo", "two"]),
+            ev_function_call("read-fast", "read_file", &arguments),
+        ],
+    )
+    .await?;
+
+    assert_eq!(
+        read_log(&log)?,
+        [
+            "start read-slow",
+            "end read-slow",
+            "start shell-1",
+            "end shell-1",
+            "start shell-2",
+            "end shell-2",
+            "start read-fast",
+            "end read-fast",
+        ]
+        .map(str::to_string)
+    );
+    let exec_events: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            EventMsg::ExecCommandBegin(ev) => Some(format!("begin {}", ev.call_id)),
+            EventMsg::ExecCommandEnd(ev) => Some(format!("end {}", ev.call_id)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        exec_events,
+        [
+            "begin shell-1",
+            "end shell-1",
+            "begin shell-2",
+            "end shell-2"
+        ]
+        .map(str::to_string)
+    );
+    assert_eq!(
+        output_call_ids,
+        ["read-slow", "shell-1", "shell-2", "read-fast"].map(str::to_string)
+    );
+
+    Ok(())
+}
+
+/// Returns the `parallel_tool_calls` flag sent for `model` when the setting is
+/// `enabled`.
+async fn requested_parallel_tool_calls(model: &str, enabled: bool) -> anyhow::Result<Value> {
+    let server = start_mock_server().await;
+    responses::mount_sse_once_match(
+        &server,
+        any(),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    let model = model.to_string();
+    let mut builder = test_codex().with_config(move |config| {
+        config.model_family = find_family_for_model(&model)
+            .unwrap_or_else(|| panic!("unknown model family for {model}"));
+        config.model = model;
+        config.parallel_tool_calls = enabled;
+    });
+    let TestCodex { codex, .. } = builder.build(&server).await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    let body = requests.last().expect("request").body_json::<Value>()?;
+    Ok(body["parallel_tool_calls"].clone())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parallel_tool_calls_requested_when_enabled_and_supported() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    assert_eq!(requested_parallel_tool_calls("gpt-5", true).await?, true);
+    assert_eq!(requested_parallel_tool_calls("gpt-5", false).await?, false);
+    assert_eq!(requested_parallel_tool_calls("o3", true).await?, false);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+#![cfg(unix)]
+
+use std::collections::BTreeMap;
+use std::os::unix::fs::PermissionsExt;
+
+use codex_core::config_types::ScriptToolConfig;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::matchers::any;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn script_tool_is_offered_and_returns_stdout() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let scripts = TempDir::new()?;
+    let script = scripts.path().join("query-schema");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\necho \"schema for $1\"\necho 'lookup done' >&2\n",
+    )?;
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+    let command = script.to_string_lossy().to_string();
+
+    let mut builder = test_codex().with_config(move |config| {
+        config.script_tools = BTreeMap::from([(
+            "query_schema".to_string(),
+            ScriptToolConfig {
+                description: "Look up a schema in the schema registry".to_string(),
+                command: vec![command],
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "subject": { "type": "string" } },
+                    "required": ["subject"],
+                }),
+                timeout_ms: None,
+            },
+        )]);
+    });
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = builder.build(&server).await?;
+
+    responses::mount_sse_once_match(
+        &server,
+        any(),
+        sse(vec![
+            ev_function_call("call-1", "query_schema", r#"{"subject":"orders"}"#),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    responses::mount_sse_once_match(
+        &server,
+        any(),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "look up the orders schema".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+    wait_for_event(&codex, |event| matches!(event, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    assert_eq!(requests.len(), 2);
+
+    let first: Value = requests[0].body_json()?;
+    let tool = first["tools"]
+        .as_array()
+        .and_then(|tools| tools.iter().find(|tool| tool["name"] == "query_schema"))
+        .expect("query_schema tool offered to the model");
+    assert_eq!(
+        tool["description"],
+        "Look up a schema in the schema registry"
+    );
+    assert_eq!(tool["parameters"]["required"], json!(["subject"]));
+
+    let second: Value = requests[1].body_json()?;
+    let output = second["input"]
+        .as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-1")
+        })
+        .expect("tool output sent to the model");
+    assert_eq!(
+        output["output"], "schema for {\"subject\":\"orders\"}\n",
+        "only stdout is returned"
+    );
+
+    Ok(())
+}
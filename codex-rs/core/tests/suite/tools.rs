@@ -458,3 +458,63 @@ async fn shell_timeout_includes_timeout_prefix_and_metadata() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn known_safe_command_cannot_read_denied_path() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex();
+    let test = builder.build(&server).await?;
+
+    let secrets = tempfile::TempDir::new()?;
+    let secret = secrets.path().join("id_rsa");
+    std::fs::write(&secret, "PRIVATE KEY MATERIAL")?;
+
+    // `cat` is on the known-safe list, so it is approved without asking; the
+    // sandbox must still keep it out of the denied folder.
+    let call_id = "cat-denied";
+    let args = json!({
+        "command": ["cat", secret.to_string_lossy()],
+        "timeout_ms": 5_000,
+    });
+    let responses = vec![
+        sse(vec![
+            json!({"type": "response.created", "response": {"id": "resp-1"}}),
+            ev_function_call(call_id, "shell", &serde_json::to_string(&args)?),
+            ev_completed("resp-1"),
+        ]),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    ];
+    mount_sse_sequence(&server, responses).await;
+
+    submit_turn(
+        &test,
+        "print the key",
+        AskForApproval::Never,
+        SandboxPolicy::ReadOnly {
+            readable_roots: vec![],
+            deny_read_paths: vec![secrets.path().to_path_buf()],
+        },
+    )
+    .await?;
+
+    let requests = server.received_requests().await.expect("recorded requests");
+    let bodies = request_bodies(&requests)?;
+    let function_outputs = collect_output_items(&bodies, "function_call_output");
+    let output = function_outputs
+        .iter()
+        .find(|item| item.get("call_id").and_then(Value::as_str) == Some(call_id))
+        .and_then(|item| item.get("output"))
+        .and_then(Value::as_str)
+        .expect("cat output present");
+    assert!(
+        !output.contains("PRIVATE KEY MATERIAL"),
+        "denied file was readable: {output:?}"
+    );
+
+    Ok(())
+}
@@ -52,6 +52,14 @@ pub struct Cli {
     #[arg(long = "skip-git-repo-check", default_value_t = false)]
     pub skip_git_repo_check: bool,
 
+    /// Enable a persistent mode by id (e.g. `/review`). Repeat to enable several.
+    #[arg(long = "mode", value_name = "ID")]
+    pub modes: Vec<String>,
+
+    /// Set a variable of an enabled mode, as `/id:name=value`. Repeatable.
+    #[arg(long = "mode-var", value_name = "ID:NAME=VALUE")]
+    pub mode_vars: Vec<String>,
+
     /// Path to a JSON Schema file describing the model's final response shape.
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
@@ -221,6 +221,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 aggregated_output,
                 duration,
                 exit_code,
+                limit_exceeded,
                 ..
             }) => {
                 let duration = format!(" in {}", format_duration(duration));
@@ -236,7 +237,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         ts_msg!(self, "{}", title.style(self.green));
                     }
                     _ => {
-                        let title = format!(" exited {exit_code}{duration}:");
+                        let title = match limit_exceeded {
+                            Some(limit) => format!(
+                                " exited {exit_code}{duration} (exceeded the {limit} limit):"
+                            ),
+                            None => format!(" exited {exit_code}{duration}:"),
+                        };
                         ts_msg!(self, "{}", title.style(self.red));
                     }
                 }
@@ -435,6 +441,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     history_log_id: _,
                     history_entry_count: _,
                     initial_messages: _,
+                    mode_state: _,
                     rollout_path: _,
                 } = session_configured_event;
 
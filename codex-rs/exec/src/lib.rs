@@ -9,6 +9,7 @@ mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+mod modes;
 
 pub use cli::Cli;
 use codex_core::AuthManager;
@@ -29,6 +30,8 @@ use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+pub use modes::ResolvedModes;
+pub use modes::resolve_modes;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use serde_json::Value;
 use std::io::IsTerminal;
@@ -69,6 +72,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         prompt,
         output_schema: output_schema_path,
         include_plan_tool,
+        modes: mode_ids,
+        mode_vars,
         config_overrides,
     } = cli;
 
@@ -191,7 +196,58 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+    let mut config =
+        Config::load_with_cli_overrides(cli_kv_overrides.clone(), overrides.clone()).await?;
+
+    // Resolve `--mode` before anything starts so invalid modes fail the run.
+    let resolved_modes = if mode_ids.is_empty() && mode_vars.is_empty() {
+        None
+    } else {
+        match modes::resolve_modes(&config, &mode_ids, &mode_vars).await {
+            Ok(resolved) => Some(resolved),
+            Err(errors) => {
+                modes::report_mode_errors(&errors, json_mode);
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Some(resolved) = &resolved_modes {
+        // Mode overrides beat config.toml but not explicit CLI flags.
+        let mode_overrides = &resolved.overrides;
+        let mut overrides = overrides;
+        let mut reload = false;
+        if overrides.model.is_none()
+            && let Some(model) = &mode_overrides.model
+        {
+            overrides.model = Some(model.clone());
+            reload = true;
+        }
+        if overrides.sandbox_mode.is_none()
+            && let Some(sandbox_mode) = mode_overrides
+                .sandbox
+                .as_deref()
+                .and_then(codex_modes::parse_sandbox_mode)
+        {
+            overrides.sandbox_mode = Some(sandbox_mode);
+            reload = true;
+        }
+        if reload {
+            config = Config::load_with_cli_overrides(cli_kv_overrides.clone(), overrides).await?;
+        }
+        if !cli_kv_overrides
+            .iter()
+            .any(|(key, _)| key == "model_reasoning_effort")
+            && let Some(effort) = mode_overrides
+                .effort
+                .as_deref()
+                .and_then(codex_modes::parse_effort)
+        {
+            config.model_reasoning_effort = Some(effort);
+        }
+        if let Some(tools) = &mode_overrides.tools {
+            config.tool_filter = tools.clone();
+        }
+    }
 
     let otel = codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"));
 
@@ -269,6 +325,27 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             .new_conversation(config.clone())
             .await?
     };
+    if let Some(resolved) = resolved_modes {
+        info!("Enabled modes: {}", resolved.enabled.join(", "));
+        conversation
+            .submit(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: None,
+                sandbox_policy: None,
+                model: None,
+                effort: None,
+                summary: None,
+                user_instructions: Some(resolved.user_instructions),
+                tool_filter: None,
+            })
+            .await?;
+        conversation
+            .submit(Op::RecordModeState {
+                mode_state: resolved.mode_state,
+            })
+            .await?;
+    }
+
     // Print the effective configuration and prompt so users can see what Codex
     // is using.
     event_processor.print_config_summary(&config, &prompt, &session_configured);
@@ -0,0 +1,168 @@
+//! `--mode` / `--mode-var` support: resolve persistent modes up front so a
+//! headless run fails fast (non-zero exit) instead of starting a session with
+//! a half-applied mode set.
+
+use codex_core::config::Config;
+use codex_core::project_doc::get_user_instructions;
+use codex_core::protocol::ModeStateItem;
+use codex_modes::EnabledMode;
+use codex_modes::IndexMap;
+use codex_modes::ModeDefinition;
+use codex_modes::ModeKind;
+use codex_modes::ModeOverrides;
+
+use crate::exec_events::ThreadErrorEvent;
+use crate::exec_events::ThreadEvent;
+
+/// Modes requested on the command line, rendered and ready to apply.
+pub struct ResolvedModes {
+    /// Enabled mode ids in render order.
+    pub enabled: Vec<String>,
+    /// Full `<user_instructions>` text including `<mode_instructions>`.
+    pub user_instructions: String,
+    /// Session settings requested by the enabled modes' `overrides`.
+    pub overrides: ModeOverrides,
+    /// Recorded in the rollout so a later resume restores the same modes.
+    pub mode_state: ModeStateItem,
+}
+
+/// Resolve `--mode` ids and `--mode-var` assignments against the modes
+/// visible from `config.cwd`. Returns every problem found, each prefixed with
+/// its error code where one exists (e.g. `E3102 EnumMismatch: ...`).
+pub async fn resolve_modes(
+    config: &Config,
+    mode_ids: &[String],
+    mode_vars: &[String],
+) -> Result<ResolvedModes, Vec<String>> {
+    let scan = codex_modes::scan_modes(&config.cwd, Some(&config.codex_home));
+    let defs = &scan.defs;
+
+    let mut errors: Vec<String> = Vec::new();
+    let mut enable_order: Vec<&ModeDefinition> = Vec::new();
+    for id in mode_ids {
+        match defs.iter().find(|d| &d.id == id) {
+            Some(def) if def.kind == ModeKind::Persistent => {
+                if !enable_order.iter().any(|d| d.id == def.id) {
+                    enable_order.push(def);
+                }
+            }
+            Some(_) => errors.push(format!("{id} is not a persistent mode")),
+            None => errors.push(codex_modes::format_modes_error(&scan.missing(id))),
+        }
+    }
+
+    let mut values: IndexMap<String, IndexMap<String, String>> = IndexMap::new();
+    for raw in mode_vars {
+        let Some((mode_id, name, value)) = parse_mode_var(raw) else {
+            errors.push(format!(
+                "invalid --mode-var `{raw}` (expected /id:name=value)"
+            ));
+            continue;
+        };
+        let Some(def) = enable_order.iter().find(|d| d.id == mode_id) else {
+            errors.push(format!(
+                "--mode-var `{raw}` targets {mode_id}, which is not passed with --mode"
+            ));
+            continue;
+        };
+        let Some(var) = def.variables.iter().find(|v| v.name == name) else {
+            errors.push(format!("unknown variable {mode_id}/{name}"));
+            continue;
+        };
+        if let Some(err) = codex_modes::validate_var_value(&def.id, var, value)
+            && let Some(msg) = codex_modes::format_validation_error(&err)
+        {
+            errors.push(msg);
+            continue;
+        }
+        values.entry(def.id.clone()).or_default().insert(
+            name.to_string(),
+            var.normalize_value(value).unwrap_or_default(),
+        );
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut enabled: Vec<EnabledMode> = enable_order
+        .iter()
+        .map(|def| {
+            let explicit = values.get(&def.id);
+            EnabledMode {
+                id: &def.id,
+                display_name: def.display_name.as_deref(),
+                scope: &def.scope,
+                variables: def
+                    .variables
+                    .iter()
+                    .map(|v| {
+                        let val = explicit.and_then(|m| m.get(&v.name).cloned());
+                        (v.name.as_str(), val)
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    let errors =
+        codex_modes::format_validation_errors(&codex_modes::validate_enabled(defs, &enabled));
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    codex_modes::sort_by_priority(defs, &mut enabled);
+
+    let base = get_user_instructions(config).await.unwrap_or_default();
+    let user_instructions = codex_modes::render_user_instructions(&base, &enabled, defs)
+        .map_err(|e| vec![codex_modes::format_modes_error(&e)])?;
+    let enabled: Vec<String> = enabled.iter().map(|em| em.id.to_string()).collect();
+    let enable_order: Vec<String> = enable_order.iter().map(|d| d.id.clone()).collect();
+    let overrides = codex_modes::resolve_overrides(defs, &enable_order).overrides;
+    let mode_state = ModeStateItem {
+        enabled: enable_order,
+        variables: values
+            .into_iter()
+            .map(|(id, vars)| {
+                // secret values are never written to the rollout
+                let vars = vars
+                    .into_iter()
+                    .filter(|(k, _)| codex_modes::is_recordable_var(defs, &id, k))
+                    .map(|(k, v)| (k, Some(v)))
+                    .collect();
+                (id, vars)
+            })
+            .collect(),
+    };
+    Ok(ResolvedModes {
+        enabled,
+        user_instructions,
+        overrides,
+        mode_state,
+    })
+}
+
+/// Split `/id:name=value` at the first `=` and the last `:` before it, since
+/// mode ids may themselves contain `:` (e.g. `/team:qa:focus=tests`).
+fn parse_mode_var(raw: &str) -> Option<(&str, &str, &str)> {
+    let (target, value) = raw.split_once('=')?;
+    let (mode_id, name) = target.rsplit_once(':')?;
+    if !mode_id.starts_with('/') || mode_id.len() < 2 || name.is_empty() {
+        return None;
+    }
+    Some((mode_id, name, value))
+}
+
+/// Report mode errors on stderr and, in `--json` mode, as `error` events on
+/// stdout so JSONL consumers see them too.
+#[allow(clippy::print_stdout, clippy::print_stderr)]
+pub(crate) fn report_mode_errors(errors: &[String], json_mode: bool) {
+    for message in errors {
+        eprintln!("{message}");
+        if json_mode {
+            let event = ThreadEvent::Error(ThreadErrorEvent {
+                message: message.clone(),
+            });
+            if let Ok(line) = serde_json::to_string(&event) {
+                println!("{line}");
+            }
+        }
+    }
+}
@@ -68,6 +68,7 @@ fn session_configured_produces_thread_started_event() {
             history_log_id: 0,
             history_entry_count: 0,
             initial_messages: None,
+            mode_state: None,
             rollout_path,
         }),
     );
@@ -535,6 +536,7 @@ fn exec_command_end_success_produces_completed_command_item() {
             exit_code: 0,
             duration: Duration::from_millis(5),
             formatted_output: String::new(),
+            limit_exceeded: None,
         }),
     );
     let out_ok = ep.collect_thread_events(&end_ok);
@@ -594,6 +596,7 @@ fn exec_command_end_failure_produces_failed_command_item() {
             exit_code: 1,
             duration: Duration::from_millis(2),
             formatted_output: String::new(),
+            limit_exceeded: None,
         }),
     );
     let out_fail = ep.collect_thread_events(&end_fail);
@@ -628,6 +631,7 @@ fn exec_command_end_without_begin_is_ignored() {
             exit_code: 0,
             duration: Duration::from_millis(1),
             formatted_output: String::new(),
+            limit_exceeded: None,
         }),
     );
     let out = ep.collect_thread_events(&end_only);
@@ -0,0 +1,155 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+use wiremock::matchers::any;
+
+const REVIEW_MODE: &str = "---
+kind: persistent
+variables:
+  - name: focus
+    enum: [tests, docs]
+    default: tests
+---
+Review with focus on {{focus}}.
+";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_mode_renders_into_user_instructions() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let modes_dir = test.home_path().join("modes");
+    std::fs::create_dir_all(&modes_dir)?;
+    std::fs::write(modes_dir.join("review.md"), REVIEW_MODE)?;
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_assistant_message("m1", "done"),
+        responses::ev_completed("resp1"),
+    ]);
+    responses::mount_sse_once_match(&server, any(), body).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--mode")
+        .arg("/review")
+        .arg("--mode-var")
+        .arg("/review:focus=docs")
+        .arg("check the repo")
+        .assert()
+        .success();
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("failed to capture requests");
+    assert_eq!(requests.len(), 1, "expected exactly one request");
+    let payload: Value = serde_json::from_slice(&requests[0].body)?;
+    let input = payload
+        .get("input")
+        .expect("request missing input")
+        .to_string();
+    assert!(input.contains("<mode_instructions>"), "{input}");
+    assert!(input.contains("Review with focus on docs."), "{input}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_mode_tools_override_filters_tools() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let modes_dir = test.home_path().join("modes");
+    std::fs::create_dir_all(&modes_dir)?;
+    std::fs::write(
+        modes_dir.join("no-patch.md"),
+        "---\noverrides:\n  tools:\n    exclude: [apply_patch]\n---\nNo patches.\n",
+    )?;
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_assistant_message("m1", "done"),
+        responses::ev_completed("resp1"),
+    ]);
+    responses::mount_sse_once_match(&server, any(), body).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--mode")
+        .arg("/no-patch")
+        .arg("check the repo")
+        .assert()
+        .success();
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("failed to capture requests");
+    let payload: Value = serde_json::from_slice(&requests[0].body)?;
+    let names: Vec<&str> = payload["tools"]
+        .as_array()
+        .expect("request missing tools")
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(names.contains(&"shell"), "{names:?}");
+    assert!(!names.contains(&"apply_patch"), "{names:?}");
+
+    Ok(())
+}
+
+#[test]
+fn exec_mode_invalid_variable_exits_non_zero() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let modes_dir = test.home_path().join("modes");
+    std::fs::create_dir_all(&modes_dir)?;
+    std::fs::write(modes_dir.join("review.md"), REVIEW_MODE)?;
+
+    test.cmd()
+        .arg("--skip-git-repo-check")
+        .arg("--mode")
+        .arg("/review")
+        .arg("--mode-var")
+        .arg("/review:focus=perf")
+        .arg("check the repo")
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains(
+            "E3102 EnumMismatch: focus=perf (allowed: tests|docs)",
+        ));
+
+    let output = test
+        .cmd()
+        .arg("--skip-git-repo-check")
+        .arg("--json")
+        .arg("--mode")
+        .arg("/missing")
+        .arg("check the repo")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let event: Value = serde_json::from_slice(&output)?;
+    assert_eq!(event["type"], "error");
+    assert!(
+        event["message"]
+            .as_str()
+            .unwrap_or_default()
+            .starts_with("E1201"),
+        "{event}"
+    );
+
+    // a mode whose file does not load reports that error, not "unknown mode"
+    std::fs::write(modes_dir.join("broken.md"), "---\nkind: [\n---\nB")?;
+    test.cmd()
+        .arg("--skip-git-repo-check")
+        .arg("--mode")
+        .arg("/broken")
+        .arg("check the repo")
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("E2001 Frontmatter: "));
+
+    Ok(())
+}
@@ -43,3 +43,11 @@ pub fn get_default_policy() -> starlark::Result<Policy> {
     let parser = PolicyParser::new("#default", DEFAULT_POLICY);
     parser.parse()
 }
+
+/// Parses the default policy followed by `extra` policy files. Programs
+/// defined in `extra` are tried after the defaults, and forbidden programs or
+/// substrings declared anywhere apply to every command.
+pub fn get_default_policy_with(extra: &[PolicyParser]) -> starlark::Result<Policy> {
+    let default = PolicyParser::new("#default", DEFAULT_POLICY);
+    PolicyParser::parse_all(std::iter::once(&default).chain(extra))
+}
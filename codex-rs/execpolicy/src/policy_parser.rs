@@ -35,6 +35,24 @@ impl PolicyParser {
     }
 
     pub fn parse(&self) -> starlark::Result<Policy> {
+        Self::parse_all([self])
+    }
+
+    /// Evaluates several policy files into a single `Policy`. Program specs
+    /// from earlier parsers are tried before those from later ones, and
+    /// forbidden programs/substrings from every parser apply.
+    pub fn parse_all<'a>(
+        parsers: impl IntoIterator<Item = &'a PolicyParser>,
+    ) -> starlark::Result<Policy> {
+        let policy_builder = PolicyBuilder::new();
+        for parser in parsers {
+            parser.eval_into(&policy_builder)?;
+        }
+        let policy = policy_builder.build();
+        policy.map_err(|e| starlark::Error::new_kind(starlark::ErrorKind::Other(e.into())))
+    }
+
+    fn eval_into(&self, policy_builder: &PolicyBuilder) -> starlark::Result<()> {
         let mut dialect = Dialect::Extended.clone();
         dialect.enable_f_strings = true;
         let ast = AstModule::parse(&self.policy_source, self.unparsed_policy.clone(), &dialect)?;
@@ -60,14 +78,10 @@ impl PolicyParser {
             heap.alloc(ArgMatcher::UnverifiedVarargs),
         );
 
-        let policy_builder = PolicyBuilder::new();
-        {
-            let mut eval = Evaluator::new(&module);
-            eval.extra = Some(&policy_builder);
-            eval.eval_module(ast, &globals)?;
-        }
-        let policy = policy_builder.build();
-        policy.map_err(|e| starlark::Error::new_kind(starlark::ErrorKind::Other(e.into())))
+        let mut eval = Evaluator::new(&module);
+        eval.extra = Some(policy_builder);
+        eval.eval_module(ast, &globals)?;
+        Ok(())
     }
 }
 
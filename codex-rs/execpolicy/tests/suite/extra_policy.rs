@@ -0,0 +1,52 @@
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::get_default_policy_with;
+
+extern crate codex_execpolicy;
+
+#[test]
+fn extra_policies_extend_and_restrict_the_default() {
+    let extra = PolicyParser::new(
+        "extra.policy",
+        r#"
+define_program(
+    program="cargo",
+    args=["check"],
+)
+
+forbid_program_regex(
+    regex="^head$",
+    reason="head is not allowed here",
+)
+"#,
+    );
+    let policy = get_default_policy_with(&[extra]).expect("failed to parse policies");
+
+    let ls = ExecCall::new("ls", &["-l"]);
+    assert!(matches!(policy.check(&ls), Ok(MatchedExec::Match { .. })));
+
+    let cargo = ExecCall::new("cargo", &["check"]);
+    assert!(matches!(
+        policy.check(&cargo),
+        Ok(MatchedExec::Match { .. })
+    ));
+
+    let head = ExecCall::new("head", &["-n", "1", "file.txt"]);
+    match policy.check(&head) {
+        Ok(MatchedExec::Forbidden { reason, .. }) => {
+            assert_eq!(reason, "head is not allowed here");
+        }
+        other => panic!("expected head to be forbidden, got {other:?}"),
+    }
+}
+
+#[test]
+fn errors_in_extra_policies_are_reported() {
+    let broken = PolicyParser::new("broken.policy", "define_program(program=)");
+    let err = match get_default_policy_with(&[broken]) {
+        Ok(_) => panic!("expected parse error"),
+        Err(err) => err.to_string(),
+    };
+    assert!(err.contains("broken.policy"), "{err}");
+}
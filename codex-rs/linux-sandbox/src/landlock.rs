@@ -31,46 +31,131 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
 ) -> Result<()> {
+    // With a network allowlist the command already runs in an isolated
+    // network namespace and must be able to reach the loopback proxy, so only
+    // the non-network part of the seccomp filter applies.
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        let block_network = sandbox_policy.network_allowlist().is_empty();
+        install_seccomp_filter_on_current_thread(block_network)?;
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
+        let deny_read_paths = sandbox_policy.deny_read_paths();
         let writable_roots = sandbox_policy
             .get_writable_roots_with_cwd(cwd)
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let readable_roots = sandbox_policy
+            .get_readable_roots_with_cwd(cwd)
+            .unwrap_or_else(|| vec![PathBuf::from("/")]);
+        // Write access includes read access, so denied paths are carved out
+        // of the writable roots too.
+        let write_grants = compute_grants(writable_roots, deny_read_paths);
+        let read_grants = compute_grants(readable_roots, deny_read_paths);
+        install_filesystem_landlock_rules_on_current_thread(write_grants, read_grants)?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
-
     Ok(())
 }
 
+/// Read or write access granted by the Landlock ruleset.
+#[derive(Debug, Default, PartialEq)]
+struct Grants {
+    /// Paths accessible in full, including everything beneath them.
+    full: Vec<PathBuf>,
+
+    /// Folders on the way to a denied path: their entries can be listed, and
+    /// each entry is granted separately, minus the denied one. In writable
+    /// roots these folders also keep the rights to create, write and remove
+    /// entries (see [`install_filesystem_landlock_rules_on_current_thread`]).
+    list_only: Vec<PathBuf>,
+}
+
+/// Landlock can only grant access, so a denied path under a readable or
+/// writable root is carved out by granting each sibling along the way instead
+/// of the root. Entries created later directly inside a `list_only` folder of
+/// a readable root stay unreadable, which errs on the side of caution.
+fn compute_grants(roots: Vec<PathBuf>, deny_read_paths: &[PathBuf]) -> Grants {
+    let denied: Vec<PathBuf> = deny_read_paths
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+        .collect();
+    let mut grants = Grants::default();
+    for root in roots {
+        // Roots that do not exist on this machine are skipped.
+        if let Ok(root) = root.canonicalize() {
+            split_root(root, &denied, &mut grants);
+        }
+    }
+    grants
+}
+
+fn split_root(path: PathBuf, denied: &[PathBuf], grants: &mut Grants) {
+    if denied.iter().any(|denied| path.starts_with(denied)) {
+        return;
+    }
+    if !denied.iter().any(|denied| denied.starts_with(&path)) {
+        grants.full.push(path);
+        return;
+    }
+    // Symlinks can lead back to a folder that was already split.
+    if grants.list_only.contains(&path) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(&path) else {
+        return;
+    };
+    grants.list_only.push(path);
+    for entry in entries.flatten() {
+        // Resolve symlinks so a link to a denied path is not granted.
+        if let Ok(resolved) = entry.path().canonicalize() {
+            split_root(resolved, denied, grants);
+        }
+    }
+}
+
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to `read_grants` while restricting write access to `/dev/null` and
+/// `write_grants`.
+///
+/// The `list_only` folders of `write_grants` get every write right but
+/// `Refer`, so files can still be created, written and removed next to a
+/// denied path. Those rights also reach into the denied path, which cannot be
+/// read or listed but may be written to; without `Refer` nothing can be moved
+/// or linked out of it to a readable place.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    write_grants: Grants,
+    read_grants: Grants,
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
+    let access_write_list_only = (AccessFs::from_write(abi) & !AccessFs::Refer) | AccessFs::ReadDir;
 
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&read_grants.full, access_ro))?
+        .add_rules(landlock::path_beneath_rules(
+            &read_grants.list_only,
+            AccessFs::ReadDir,
+        ))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    if !write_grants.full.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&write_grants.full, access_rw))?;
+    }
+    if !write_grants.list_only.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(
+            &write_grants.list_only,
+            access_write_list_only,
+        ))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,9 +167,11 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
-/// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// Installs a seccomp filter that blocks `ptrace` and, when `block_network` is
+/// set, outbound network access except for AF_UNIX domain sockets.
+fn install_seccomp_filter_on_current_thread(
+    block_network: bool,
+) -> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,36 +180,39 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
     deny_syscall(libc::SYS_ptrace);
 
-    // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
-    let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
-        0, // first argument (domain)
-        SeccompCmpArgLen::Dword,
-        SeccompCmpOp::Ne,
-        libc::AF_UNIX as u64,
-    )?])?;
-
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
-    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+    if block_network {
+        deny_syscall(libc::SYS_connect);
+        deny_syscall(libc::SYS_accept);
+        deny_syscall(libc::SYS_accept4);
+        deny_syscall(libc::SYS_bind);
+        deny_syscall(libc::SYS_listen);
+        deny_syscall(libc::SYS_getpeername);
+        deny_syscall(libc::SYS_getsockname);
+        deny_syscall(libc::SYS_shutdown);
+        deny_syscall(libc::SYS_sendto);
+        deny_syscall(libc::SYS_sendmsg);
+        deny_syscall(libc::SYS_sendmmsg);
+        // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+        // with their socketpair + child processes for sub-proc management
+        // deny_syscall(libc::SYS_recvfrom);
+        deny_syscall(libc::SYS_recvmsg);
+        deny_syscall(libc::SYS_recvmmsg);
+        deny_syscall(libc::SYS_getsockopt);
+        deny_syscall(libc::SYS_setsockopt);
+
+        // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
+        let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
+            0, // first argument (domain)
+            SeccompCmpArgLen::Dword,
+            SeccompCmpOp::Ne,
+            libc::AF_UNIX as u64,
+        )?])?;
+
+        rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+        rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+    }
 
     let filter = SeccompFilter::new(
         rules,
@@ -3,9 +3,16 @@ use std::ffi::CString;
 use std::path::PathBuf;
 
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::network_proxy::NetworkAllowlist;
+use crate::network_proxy::run_with_network_allowlist;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// File the network proxy appends each refused `host:port` to, one per
+    /// line.
+    #[arg(long)]
+    pub network_denied_report: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,11 +26,27 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        network_denied_report,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
+    let network_allowlist = sandbox_policy.network_allowlist();
+    if !network_allowlist.is_empty() {
+        let allowlist = match NetworkAllowlist::parse(network_allowlist) {
+            Ok(allowlist) => allowlist,
+            Err(e) => panic!("error running landlock: {e}"),
+        };
+        run_with_network_allowlist(
+            allowlist,
+            network_denied_report.as_deref(),
+            &sandbox_policy,
+            &sandbox_policy_cwd,
+            command,
+        );
+    }
+
     if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
         panic!("error running landlock: {e:?}");
     }
@@ -0,0 +1,537 @@
+//! Network allowlist enforcement for `SandboxPolicy::WorkspaceWrite`.
+//!
+//! When the policy blocks network access but lists allowed hosts, the command
+//! runs in a fresh user + network namespace whose only interface is loopback.
+//! Inside it, a small bridge process listens on `127.0.0.1` and forwards every
+//! connection over a Unix socket to a filtering HTTP proxy that stays in the
+//! original namespace. The command finds the bridge through the usual
+//! `HTTP_PROXY`/`HTTPS_PROXY` variables; the proxy accepts `CONNECT` tunnels
+//! and plain `http://` requests for allowlisted hosts only.
+//!
+//! Refused connections are answered with `403 Forbidden` and, when codex-core
+//! passes `--network-denied-report`, appended to that file so it can surface
+//! them as `SandboxErr::NetworkDenied` without looking at the command's output.
+//!
+//! The seccomp network filter is not installed in this mode so the command can
+//! reach the bridge. Only traffic from programs that honour the proxy
+//! variables, over HTTP or HTTPS, reaches the allowlisted hosts; everything
+//! else fails because the namespace has no other route out.
+
+use std::ffi::CString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+use codex_core::protocol::SandboxPolicy;
+
+use crate::landlock::apply_sandbox_policy_to_current_thread;
+
+/// Upper bound on the size of a proxied request head.
+const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Parsed form of `network_allowlist`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NetworkAllowlist {
+    entries: Vec<AllowEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AllowEntry {
+    host: HostPattern,
+    /// `None` allows every port.
+    port: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    Any,
+    Exact(String),
+    /// `*.example.com`: any subdomain of `example.com`, but not the apex.
+    Subdomain(String),
+}
+
+impl NetworkAllowlist {
+    /// Parses entries of the form `host`, `host:port`, `[v6addr]:port`,
+    /// `*.example.com` or `*:port`.
+    pub(crate) fn parse(entries: &[String]) -> Result<Self, String> {
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                parse_entry(entry)
+                    .ok_or_else(|| format!("invalid network_allowlist entry `{entry}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { entries })
+    }
+
+    pub(crate) fn allows(&self, host: &str, port: u16) -> bool {
+        let host = normalize_host(host);
+        self.entries.iter().any(|entry| {
+            entry.port.is_none_or(|allowed| allowed == port)
+                && match &entry.host {
+                    HostPattern::Any => true,
+                    HostPattern::Exact(allowed) => *allowed == host,
+                    HostPattern::Subdomain(suffix) => host
+                        .strip_suffix(suffix.as_str())
+                        .is_some_and(|rest| rest.len() > 1 && rest.ends_with('.')),
+                }
+        })
+    }
+}
+
+fn parse_entry(entry: &str) -> Option<AllowEntry> {
+    let entry = entry.trim();
+    let (host, port) = split_host_port(entry)?;
+    let host = normalize_host(host);
+    if host.is_empty() {
+        return None;
+    }
+    let host = if host == "*" {
+        HostPattern::Any
+    } else if let Some(suffix) = host.strip_prefix("*.") {
+        if suffix.is_empty() || suffix.contains('*') {
+            return None;
+        }
+        HostPattern::Subdomain(suffix.to_string())
+    } else if host.contains('*') {
+        return None;
+    } else {
+        HostPattern::Exact(host)
+    };
+    Some(AllowEntry { host, port })
+}
+
+/// Splits `host[:port]`, accepting bracketed IPv6 literals. A bare IPv6
+/// literal without brackets is treated as a host without a port.
+fn split_host_port(value: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = value.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        return match after {
+            "" => Some((host, None)),
+            _ => Some((host, Some(after.strip_prefix(':')?.parse().ok()?))),
+        };
+    }
+    match value.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => Some((host, Some(port.parse().ok()?))),
+        _ => Some((value, None)),
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Runs `command` with only the allowlisted hosts reachable. Never returns:
+/// the calling process becomes the proxy and exits with the command's status.
+pub(crate) fn run_with_network_allowlist(
+    allowlist: NetworkAllowlist,
+    network_denied_report: Option<&Path>,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    command: Vec<String>,
+) -> ! {
+    // A fresh directory only the current user can enter, so no other user can
+    // connect to the proxy or plant a socket at a predictable path.
+    let socket_dir = match tempfile::Builder::new()
+        .prefix("codex-sandbox-proxy-")
+        .permissions(std::fs::Permissions::from_mode(0o700))
+        .tempdir()
+    {
+        Ok(dir) => dir,
+        Err(e) => panic!("failed to create network proxy socket directory: {e}"),
+    };
+    let socket_path = socket_dir.path().join("proxy.sock");
+    let listener = match UnixListener::bind(&socket_path) {
+        Ok(listener) => listener,
+        Err(e) => panic!(
+            "failed to bind network proxy socket {}: {e}",
+            socket_path.display()
+        ),
+    };
+
+    // Only the proxy writes to the report; the child closes it right away.
+    let denied_report = network_denied_report.map(|path| {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => file,
+            Err(e) => panic!(
+                "failed to open network denial report {}: {e}",
+                path.display()
+            ),
+        }
+    });
+
+    // Fork before starting any threads so the child is single-threaded when
+    // it unshares namespaces and execs.
+    let pid = unsafe { libc::fork() };
+    if pid < 0 {
+        panic!("fork failed: {}", io::Error::last_os_error());
+    }
+    if pid == 0 {
+        drop(listener);
+        drop(denied_report);
+        // The directory belongs to the parent, which removes it.
+        std::mem::forget(socket_dir);
+        run_sandboxed_child(&socket_path, sandbox_policy, sandbox_policy_cwd, command);
+    }
+
+    let allowlist = Arc::new(allowlist);
+    let denied_report = Arc::new(denied_report);
+    thread::spawn(move || serve_proxy(listener, allowlist, denied_report));
+
+    let status = wait_for_child(pid);
+    // `exit` skips destructors, so remove the directory first.
+    drop(socket_dir);
+    std::process::exit(status);
+}
+
+fn wait_for_child(pid: libc::pid_t) -> i32 {
+    let mut status: libc::c_int = 0;
+    loop {
+        let rc = unsafe { libc::waitpid(pid, &mut status, 0) };
+        if rc == pid {
+            break;
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            panic!("waitpid failed: {err}");
+        }
+    }
+    if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        1
+    }
+}
+
+/// Child side: isolate the network, start the loopback bridge, point the
+/// proxy variables at it, apply the regular sandbox and exec the command.
+fn run_sandboxed_child(
+    socket_path: &Path,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    command: Vec<String>,
+) -> ! {
+    // Do not outlive the proxy process (e.g. when it is killed on timeout).
+    unsafe {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+    }
+
+    if let Err(e) = enter_isolated_network_namespace() {
+        panic!("failed to create network namespace for the sandbox: {e}");
+    }
+
+    let (bridge, port) = match bind_bridge() {
+        Ok(bridge) => bridge,
+        Err(e) => panic!("failed to start sandbox network bridge: {e}"),
+    };
+
+    let pid = unsafe { libc::fork() };
+    if pid < 0 {
+        panic!("fork failed: {}", io::Error::last_os_error());
+    }
+    if pid == 0 {
+        // The bridge exits together with the command, which keeps this pid.
+        unsafe {
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+        }
+        serve_bridge(bridge, socket_path);
+        std::process::exit(0);
+    }
+    drop(bridge);
+
+    let proxy_url = format!("http://127.0.0.1:{port}");
+    // SAFETY: this process is single-threaded; it was forked from a
+    // single-threaded parent and has not spawned any threads.
+    unsafe {
+        for var in PROXY_ENV_VARS {
+            std::env::set_var(var, &proxy_url);
+        }
+        std::env::remove_var("NO_PROXY");
+        std::env::remove_var("no_proxy");
+    }
+
+    if let Err(e) = apply_sandbox_policy_to_current_thread(sandbox_policy, sandbox_policy_cwd) {
+        panic!("error running landlock: {e:?}");
+    }
+    exec_command(command)
+}
+
+fn bind_bridge() -> io::Result<(TcpListener, u16)> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    Ok((listener, port))
+}
+
+/// Moves the current process into new user and network namespaces, mapping
+/// the current uid/gid, and brings up the loopback interface.
+fn enter_isolated_network_namespace() -> io::Result<()> {
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    bring_up_loopback()
+}
+
+fn bring_up_loopback() -> io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let result = (|| {
+        let mut ifr: libc::ifreq = unsafe { std::mem::zeroed() };
+        for (dst, src) in ifr.ifr_name.iter_mut().zip(b"lo\0") {
+            *dst = *src as libc::c_char;
+        }
+        if unsafe { libc::ioctl(fd, libc::SIOCGIFFLAGS, &mut ifr) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        unsafe {
+            ifr.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short;
+        }
+        if unsafe { libc::ioctl(fd, libc::SIOCSIFFLAGS, &ifr) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })();
+    unsafe {
+        libc::close(fd);
+    }
+    result
+}
+
+fn exec_command(command: Vec<String>) -> ! {
+    if command.is_empty() {
+        panic!("No command specified to execute.");
+    }
+
+    #[expect(clippy::expect_used)]
+    let c_args: Vec<CString> = command
+        .iter()
+        .map(|arg| CString::new(arg.as_str()).expect("Failed to convert arg to CString"))
+        .collect();
+    let mut c_args_ptrs: Vec<*const libc::c_char> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    c_args_ptrs.push(std::ptr::null());
+
+    unsafe {
+        libc::execvp(c_args[0].as_ptr(), c_args_ptrs.as_ptr());
+    }
+
+    let err = io::Error::last_os_error();
+    panic!("Failed to execvp {}: {err}", command[0].as_str());
+}
+
+/// Forwards every loopback connection inside the namespace to the proxy.
+fn serve_bridge(listener: TcpListener, socket_path: &Path) {
+    for stream in listener.incoming() {
+        let Ok(client) = stream else {
+            continue;
+        };
+        let socket_path = socket_path.to_path_buf();
+        thread::spawn(move || {
+            if let Ok(proxy) = UnixStream::connect(&socket_path) {
+                splice(proxy, client);
+            }
+        });
+    }
+}
+
+fn serve_proxy(
+    listener: UnixListener,
+    allowlist: Arc<NetworkAllowlist>,
+    denied_report: Arc<Option<File>>,
+) {
+    for stream in listener.incoming() {
+        let Ok(client) = stream else {
+            continue;
+        };
+        let allowlist = Arc::clone(&allowlist);
+        let denied_report = Arc::clone(&denied_report);
+        thread::spawn(move || {
+            let _ = handle_proxy_client(client, &allowlist, denied_report.as_ref().as_ref());
+        });
+    }
+}
+
+/// A request received by the filtering proxy.
+#[derive(Debug, PartialEq, Eq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// `CONNECT host:port` tunnel rather than an absolute-form HTTP request.
+    tunnel: bool,
+}
+
+fn handle_proxy_client(
+    mut client: UnixStream,
+    allowlist: &NetworkAllowlist,
+    mut denied_report: Option<&File>,
+) -> io::Result<()> {
+    let (head, rest) = read_request_head(&mut client)?;
+    let Some(request) = parse_request_line(&head) else {
+        return respond(
+            &mut client,
+            "400 Bad Request",
+            "unsupported proxy request\n",
+        );
+    };
+
+    let target = format_target(&request.host, request.port);
+    if !allowlist.allows(&request.host, request.port) {
+        if let Some(report) = denied_report.as_mut() {
+            // One write per line so concurrent refusals do not interleave.
+            let _ = report.write_all(format!("{target}\n").as_bytes());
+        }
+        return respond(
+            &mut client,
+            "403 Forbidden",
+            &format!("{target} is not in the sandbox network allowlist\n"),
+        );
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)) {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            return respond(
+                &mut client,
+                "502 Bad Gateway",
+                &format!("failed to connect to {target}: {e}\n"),
+            );
+        }
+    };
+
+    if request.tunnel {
+        client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")?;
+    } else {
+        upstream.write_all(&rewrite_request_head(&head))?;
+    }
+    upstream.write_all(&rest)?;
+    splice(client, upstream);
+    Ok(())
+}
+
+fn format_target(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+/// Reads up to and including the blank line ending the request head. Returns
+/// the head and any bytes read past it.
+fn read_request_head(stream: &mut impl Read) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            return Ok((buf, rest));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+fn parse_request_line(head: &[u8]) -> Option<ProxyRequest> {
+    let head = std::str::from_utf8(head).ok()?;
+    let line = head.lines().next()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(target)?;
+        return Some(ProxyRequest {
+            host: normalize_host(host),
+            port: port?,
+            tunnel: true,
+        });
+    }
+
+    let authority = target.strip_prefix("http://")?;
+    let authority = authority.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = split_host_port(authority)?;
+    Some(ProxyRequest {
+        host: normalize_host(host),
+        port: port.unwrap_or(80),
+        tunnel: false,
+    })
+}
+
+/// Drops hop-by-hop connection headers and asks the origin to close the
+/// connection after one response, so a reused client connection cannot
+/// carry requests meant for another host.
+fn rewrite_request_head(head: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(head);
+    let mut out = String::with_capacity(text.len() + 32);
+    for line in text.split("\r\n").filter(|line| !line.is_empty()) {
+        let name = line.split(':').next().unwrap_or_default().trim();
+        if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("proxy-connection")
+        {
+            continue;
+        }
+        out.push_str(line);
+        out.push_str("\r\n");
+    }
+    out.push_str("Connection: close\r\n\r\n");
+    out.into_bytes()
+}
+
+fn respond(client: &mut UnixStream, status: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    client.write_all(response.as_bytes())
+}
+
+/// Copies bytes in both directions until each side has finished sending.
+fn splice(unix: UnixStream, tcp: TcpStream) {
+    let (Ok(mut unix_read), Ok(mut tcp_write)) = (unix.try_clone(), tcp.try_clone()) else {
+        return;
+    };
+    let upload = thread::spawn(move || {
+        let _ = io::copy(&mut unix_read, &mut tcp_write);
+        let _ = tcp_write.shutdown(Shutdown::Write);
+    });
+    let (mut tcp_read, mut unix_write) = (tcp, unix);
+    let _ = io::copy(&mut tcp_read, &mut unix_write);
+    let _ = unix_write.shutdown(Shutdown::Write);
+    let _ = upload.join();
+}
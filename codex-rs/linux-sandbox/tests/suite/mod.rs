@@ -1,2 +1,5 @@
 // Aggregates all former standalone integration tests as modules.
 mod landlock;
+mod network_allowlist;
+mod read_restrictions;
+mod resource_limits;
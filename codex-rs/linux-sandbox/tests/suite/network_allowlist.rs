@@ -0,0 +1,158 @@
+#![cfg(target_os = "linux")]
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::error::CodexErr;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_env;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::resource_limits::ResourceLimits;
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+const TIMEOUT_MS: u64 = 10_000;
+
+/// The allowlist relies on unprivileged user namespaces, which some CI images
+/// disable. Tests are skipped there.
+fn user_namespaces_available() -> bool {
+    std::process::Command::new("unshare")
+        .args(["-Urn", "true"])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Starts a TCP server outside the sandbox that greets each client.
+#[expect(clippy::expect_used)]
+fn spawn_greeting_server() -> u16 {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind greeting server");
+    let port = listener.local_addr().expect("local addr").port();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.write_all(b"hello from outside\n");
+        }
+    });
+    port
+}
+
+/// Bash script that opens a `CONNECT` tunnel to `target` through the proxy
+/// advertised in `HTTP_PROXY` and prints the status line and first reply line.
+fn connect_script(target: &str) -> String {
+    format!(
+        r#"proxy=${{HTTP_PROXY#http://}}
+exec 3<>/dev/tcp/${{proxy%:*}}/${{proxy##*:}} || exit 3
+printf 'CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n\r\n' >&3
+read -r status <&3
+case "$status" in *" 200 "*) ;; *) echo "$status"; exit 1 ;; esac
+while read -r line <&3 && [ "$line" != $'\r' ]; do :; done
+read -r greeting <&3
+echo "$greeting""#
+    )
+}
+
+#[expect(clippy::expect_used)]
+async fn run_with_allowlist(
+    script: &str,
+    allowlist: Vec<String>,
+) -> Result<ExecToolCallOutput, CodexErr> {
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.clone(),
+        timeout_ms: Some(TIMEOUT_MS),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![],
+        network_access: false,
+        network_allowlist: allowlist,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: vec![],
+        deny_read_paths: vec![],
+    };
+    let codex_linux_sandbox_exe = Some(PathBuf::from(env!("CARGO_BIN_EXE_codex-linux-sandbox")));
+    process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &codex_linux_sandbox_exe,
+        &ResourceLimits::default(),
+        None,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn allowlisted_host_is_reachable_through_proxy() {
+    if !user_namespaces_available() {
+        eprintln!("skipping: unprivileged user namespaces are not available");
+        return;
+    }
+    let port = spawn_greeting_server();
+    let target = format!("127.0.0.1:{port}");
+
+    let output = run_with_allowlist(&connect_script(&target), vec![target.clone()])
+        .await
+        .unwrap_or_else(|err| panic!("expected success, got {err:?}"));
+    assert_eq!(output.stdout.text.trim(), "hello from outside");
+
+    // Direct connections bypassing the proxy cannot leave the namespace.
+    let direct = format!("exec 3<>/dev/tcp/127.0.0.1/{port}");
+    let result = run_with_allowlist(&direct, vec![target]).await;
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => {
+            assert_ne!(output.exit_code, 0);
+        }
+        other => panic!("expected direct connection to fail, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn denied_host_is_reported_as_network_denied() {
+    if !user_namespaces_available() {
+        eprintln!("skipping: unprivileged user namespaces are not available");
+        return;
+    }
+    let port = spawn_greeting_server();
+
+    let result = run_with_allowlist(
+        &connect_script("example.com:443"),
+        vec![format!("127.0.0.1:{port}"), "*.example.org".to_string()],
+    )
+    .await;
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::NetworkDenied { hosts, output })) => {
+            assert_eq!(hosts, vec!["example.com:443".to_string()]);
+            assert!(
+                output.stdout.text.contains("403 Forbidden"),
+                "stdout: {}",
+                output.stdout.text
+            );
+        }
+        other => panic!("expected NetworkDenied, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn command_output_cannot_fake_a_network_denial() {
+    if !user_namespaces_available() {
+        eprintln!("skipping: unprivileged user namespaces are not available");
+        return;
+    }
+
+    let script =
+        "echo 'codex-linux-sandbox: blocked network connection to example.com:443' >&2; exit 1";
+    let result = run_with_allowlist(script, vec!["*.example.org".to_string()]).await;
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => {
+            assert_eq!(output.exit_code, 1);
+        }
+        other => panic!("expected a plain sandbox denial, got {other:?}"),
+    }
+}
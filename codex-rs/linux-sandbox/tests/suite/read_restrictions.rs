@@ -0,0 +1,181 @@
+#![cfg(target_os = "linux")]
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::error::CodexErr;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_env;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::resource_limits::ResourceLimits;
+use std::path::Path;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const TIMEOUT_MS: u64 = 5_000;
+
+/// Runs `cat path` under `sandbox_policy` and returns its output, whether or
+/// not the sandbox denied the read.
+async fn cat_under_policy(path: &Path, sandbox_policy: &SandboxPolicy) -> ExecToolCallOutput {
+    run_under_policy(
+        vec!["cat".to_string(), path.to_string_lossy().to_string()],
+        sandbox_policy,
+    )
+    .await
+}
+
+/// Runs `script` with `sh -c` under `sandbox_policy`.
+async fn sh_under_policy(script: &str, sandbox_policy: &SandboxPolicy) -> ExecToolCallOutput {
+    run_under_policy(
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+        sandbox_policy,
+    )
+    .await
+}
+
+#[expect(clippy::expect_used)]
+async fn run_under_policy(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+) -> ExecToolCallOutput {
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let params = ExecParams {
+        command,
+        cwd: cwd.clone(),
+        timeout_ms: Some(TIMEOUT_MS),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let codex_linux_sandbox_exe = Some(PathBuf::from(env!("CARGO_BIN_EXE_codex-linux-sandbox")));
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        sandbox_policy,
+        cwd.as_path(),
+        &codex_linux_sandbox_exe,
+        &ResourceLimits::default(),
+        None,
+    )
+    .await;
+    match result {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[expect(clippy::expect_used)]
+fn write_file(dir: &Path, name: &str, contents: &str) -> PathBuf {
+    std::fs::create_dir_all(dir).expect("create dir");
+    let path = dir.join(name);
+    std::fs::write(&path, contents).expect("write file");
+    path
+}
+
+#[tokio::test]
+async fn deny_read_paths_hide_files_under_readable_roots() {
+    let tmp = TempDir::new().expect("tempdir");
+    let public = write_file(&tmp.path().join("public"), "notes.txt", "public notes");
+    let secret = write_file(&tmp.path().join(".ssh"), "id_ed25519", "private key");
+    let policy = SandboxPolicy::ReadOnly {
+        readable_roots: vec![],
+        deny_read_paths: vec![tmp.path().join(".ssh")],
+    };
+
+    let output = cat_under_policy(&public, &policy).await;
+    assert_eq!(output.exit_code, 0, "stderr: {}", output.stderr.text);
+    assert_eq!(output.stdout.text, "public notes");
+
+    let output = cat_under_policy(&secret, &policy).await;
+    assert_ne!(output.exit_code, 0);
+    assert!(!output.stdout.text.contains("private key"));
+
+    // A symlink into the denied folder does not bypass the restriction.
+    let link = tmp.path().join("public").join("key-link");
+    std::os::unix::fs::symlink(&secret, &link).expect("create symlink");
+    let output = cat_under_policy(&link, &policy).await;
+    assert_ne!(output.exit_code, 0);
+}
+
+#[tokio::test]
+async fn readable_roots_limit_reads_to_listed_folders() {
+    let tmp = TempDir::new().expect("tempdir");
+    let allowed = write_file(&tmp.path().join("allowed"), "data.txt", "allowed data");
+    let other = write_file(&tmp.path().join("other"), "data.txt", "other data");
+    let policy = SandboxPolicy::ReadOnly {
+        readable_roots: vec![tmp.path().join("allowed")],
+        deny_read_paths: vec![],
+    };
+
+    let output = cat_under_policy(&allowed, &policy).await;
+    assert_eq!(output.exit_code, 0, "stderr: {}", output.stderr.text);
+    assert_eq!(output.stdout.text, "allowed data");
+
+    let output = cat_under_policy(&other, &policy).await;
+    assert_ne!(output.exit_code, 0);
+    assert!(!output.stdout.text.contains("other data"));
+}
+
+#[tokio::test]
+async fn deny_read_paths_apply_inside_writable_roots() {
+    let tmp = TempDir::new().expect("tempdir");
+    let notes = write_file(&tmp.path().join("src"), "notes.txt", "project notes");
+    let secret = write_file(&tmp.path().join("secrets"), "token", "secret token");
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![tmp.path().to_path_buf()],
+        network_access: false,
+        network_allowlist: vec![],
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: vec![],
+        deny_read_paths: vec![tmp.path().join("secrets")],
+    };
+
+    let output = cat_under_policy(&notes, &policy).await;
+    assert_eq!(output.exit_code, 0, "stderr: {}", output.stderr.text);
+    assert_eq!(output.stdout.text, "project notes");
+
+    let output = cat_under_policy(&secret, &policy).await;
+    assert_ne!(output.exit_code, 0);
+    assert!(!output.stdout.text.contains("secret token"));
+}
+
+#[tokio::test]
+async fn writable_roots_with_denied_paths_can_still_create_files() {
+    let tmp = TempDir::new().expect("tempdir");
+    let secret = write_file(&tmp.path().join("secrets"), "token", "secret token");
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![tmp.path().to_path_buf()],
+        network_access: false,
+        network_allowlist: vec![],
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+        readable_roots: vec![],
+        deny_read_paths: vec![tmp.path().join("secrets")],
+    };
+
+    // New files and folders right next to the denied folder.
+    let root = tmp.path().display();
+    let output = sh_under_policy(
+        &format!("echo draft > {root}/new.txt && mkdir {root}/build && rm {root}/new.txt && echo out > {root}/build/out.txt"),
+        &policy,
+    )
+    .await;
+    assert_eq!(output.exit_code, 0, "stderr: {}", output.stderr.text);
+    assert!(!tmp.path().join("new.txt").exists());
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join("build").join("out.txt")).expect("read out.txt"),
+        "out\n"
+    );
+
+    // The denied file cannot be moved somewhere readable.
+    let output = sh_under_policy(
+        &format!("mv {} {root}/build/stolen", secret.display()),
+        &policy,
+    )
+    .await;
+    assert_ne!(output.exit_code, 0);
+    assert!(secret.exists());
+}
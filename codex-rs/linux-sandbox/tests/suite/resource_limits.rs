@@ -0,0 +1,88 @@
+#![cfg(target_os = "linux")]
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::error::CodexErr;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_env;
+use codex_core::protocol::ResourceLimitKind;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::resource_limits::ResourceLimits;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const TIMEOUT_MS: u64 = 10_000;
+
+/// Runs `script` under the workspace-write sandbox with `resource_limits` and
+/// returns the limit that stopped it along with its output.
+#[expect(clippy::expect_used)]
+async fn run_with_limits(
+    script: &str,
+    resource_limits: ResourceLimits,
+) -> (ResourceLimitKind, ExecToolCallOutput) {
+    let cwd = TempDir::new().expect("tempdir");
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.path().to_path_buf(),
+        timeout_ms: Some(TIMEOUT_MS),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+    let codex_linux_sandbox_exe = Some(PathBuf::from(env!("CARGO_BIN_EXE_codex-linux-sandbox")));
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.path(),
+        &codex_linux_sandbox_exe,
+        &resource_limits,
+        None,
+    )
+    .await;
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, output })) => (limit, *output),
+        other => panic!("expected ResourceLimit, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn file_size_limit_stops_large_writes() {
+    let (limit, output) = run_with_limits(
+        "head -c 2097152 /dev/zero > big.bin",
+        ResourceLimits {
+            max_file_size_mb: Some(1),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    assert_eq!(limit, ResourceLimitKind::FileSize);
+    assert_eq!(
+        output.resource_limit_exceeded,
+        Some(ResourceLimitKind::FileSize)
+    );
+    assert_ne!(output.exit_code, 0);
+}
+
+#[tokio::test]
+async fn output_limit_kills_noisy_commands() {
+    let (limit, output) = run_with_limits(
+        "yes codex",
+        ResourceLimits {
+            max_output_mb: Some(1),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    assert_eq!(limit, ResourceLimitKind::OutputSize);
+    assert!(
+        output.aggregated_output.text.len() <= 1024 * 1024,
+        "kept {} bytes of output",
+        output.aggregated_output.text.len()
+    );
+}
@@ -1,16 +1,36 @@
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::ToolFilter;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::SandboxPolicy;
 pub use indexmap::IndexMap;
 pub use indexmap::IndexSet;
 use regex::Regex;
 use serde::Deserialize;
 pub use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use thiserror::Error;
 
+mod lint;
+mod template;
+
+pub use lint::LintIssue;
+pub use lint::LintSeverity;
+pub use lint::ModeSource;
+pub use lint::lint_modes;
+pub use lint::mode_sources;
+pub use template::Template;
+pub use template::TemplateContext;
+pub use template::TemplateError;
+pub use template::split_list;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModeKind {
@@ -26,6 +46,12 @@ pub enum VarType {
     Boolean,
     Number,
     Path,
+    /// Comma or newline separated values, stored as `a, b`; `enum` applies per item.
+    List,
+    /// Free text that may span several lines.
+    Multiline,
+    /// Text that is masked in summaries and never recorded in the rollout.
+    Secret,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,6 +87,105 @@ pub struct ModeVariableDefinition {
     pub inline_edit: Option<bool>,
     #[serde(default)]
     pub mode_scoped: Option<bool>,
+    /// Dynamic default resolved when modes are scanned: `env:NAME` or
+    /// `git:branch`. Falls back to `default` when it yields nothing.
+    #[serde(default)]
+    pub default_from: Option<String>,
+}
+
+/// Shown instead of a secret variable's value.
+pub const SECRET_MASK: &str = "••••";
+
+/// Where a variable's dynamic default comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefaultSource {
+    Env(String),
+    GitBranch,
+}
+
+impl DefaultSource {
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.trim().split_once(':')? {
+            ("env", name) if !name.trim().is_empty() => Some(Self::Env(name.trim().to_string())),
+            ("git", "branch") => Some(Self::GitBranch),
+            _ => None,
+        }
+    }
+
+    /// Current value, or None when the variable is unset/empty or `cwd` is not
+    /// on a branch.
+    fn resolve(&self, cwd: &Path) -> Option<String> {
+        match self {
+            Self::Env(name) => std::env::var(name).ok().filter(|v| !v.trim().is_empty()),
+            Self::GitBranch => git_branch(cwd),
+        }
+    }
+}
+
+impl ModeVariableDefinition {
+    pub fn is_secret(&self) -> bool {
+        self.var_type == Some(VarType::Secret)
+    }
+
+    /// Declared default as a string; YAML sequences become a `a, b` list.
+    pub fn default_value(&self) -> Option<String> {
+        self.default.as_ref().and_then(coerce_value)
+    }
+
+    /// Single-line form for summaries and the `- variables:` line: secrets are
+    /// masked and line breaks are shown as `\n`.
+    pub fn display_value(&self, value: &str) -> String {
+        if self.is_secret() {
+            SECRET_MASK.to_string()
+        } else {
+            value.replace('\n', "\\n")
+        }
+    }
+
+    /// Canonical stored form of an edited value; None clears the value.
+    pub fn normalize_value(&self, value: &str) -> Option<String> {
+        // enum values are stored verbatim so they keep matching their option
+        let normalized = if self.r#enum.is_some() && self.var_type != Some(VarType::List) {
+            value.to_string()
+        } else {
+            match self.var_type {
+                Some(VarType::Boolean) => value.trim().to_lowercase(),
+                Some(VarType::List) => split_list(value).join(", "),
+                Some(VarType::Multiline) => value.trim_matches('\n').trim_end().to_string(),
+                _ => value.trim().to_string(),
+            }
+        };
+        (!normalized.is_empty()).then_some(normalized)
+    }
+
+    fn validate_default_from(&self) -> Result<(), ModesError> {
+        match &self.default_from {
+            Some(spec) if DefaultSource::parse(spec).is_none() => Err(ModesError::BadDefaultFrom(
+                format!("{}: {spec} (expected env:NAME or git:branch)", self.name),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Name of the branch checked out in the repository containing `cwd`, read
+/// from `HEAD` (worktrees included). None when detached or outside a repo.
+fn git_branch(cwd: &Path) -> Option<String> {
+    let dot_git = cwd
+        .ancestors()
+        .map(|d| d.join(".git"))
+        .find(|p| p.exists())?;
+    let git_dir = if dot_git.is_file() {
+        let text = fs::read_to_string(&dot_git).ok()?;
+        let target = text.trim().strip_prefix("gitdir:")?.trim();
+        dot_git.parent()?.join(target)
+    } else {
+        dot_git
+    };
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -75,9 +200,207 @@ pub struct ModeDefinition {
     pub scope: ModeScope,
     pub path: PathBuf,
     pub body: String,
+    /// Parent mode id from `extends:`; a mode may extend the definition it shadows by naming its own id.
+    pub extends: Option<String>,
+    /// Mode ids from `includes:` whose bodies and variables are pulled in after the parent.
+    pub includes: Vec<String>,
+    /// Bodies flattened from the `extends`/`includes` chain, rendered before `body`.
+    pub inherited: Vec<InheritedBody>,
+    /// Mode ids that cannot be enabled together with this one (symmetric).
+    pub conflicts_with: Vec<String>,
+    /// Mode ids that must be enabled whenever this one is.
+    pub requires: Vec<String>,
+    /// Render order among enabled modes: higher first, ties keep enable order.
+    pub priority: i32,
+    /// Session settings applied while the mode is enabled.
+    pub overrides: ModeOverrides,
+}
+
+/// Allowed `overrides.effort` values.
+pub const EFFORT_VALUES: &[&str] = &["minimal", "low", "medium", "high"];
+/// Allowed `overrides.sandbox` values, most restrictive first.
+pub const SANDBOX_VALUES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+/// Allowed `overrides.approval_policy` values, most restrictive first.
+pub const APPROVAL_VALUES: &[&str] = &["untrusted", "on-request", "on-failure", "never"];
+
+/// Parse an `overrides.effort` value.
+pub fn parse_effort(s: &str) -> Option<ReasoningEffort> {
+    match s {
+        "minimal" => Some(ReasoningEffort::Minimal),
+        "low" => Some(ReasoningEffort::Low),
+        "medium" => Some(ReasoningEffort::Medium),
+        "high" => Some(ReasoningEffort::High),
+        _ => None,
+    }
+}
+
+/// Parse an `overrides.sandbox` value.
+pub fn parse_sandbox_mode(s: &str) -> Option<SandboxMode> {
+    match s {
+        "read-only" => Some(SandboxMode::ReadOnly),
+        "workspace-write" => Some(SandboxMode::WorkspaceWrite),
+        "danger-full-access" => Some(SandboxMode::DangerFullAccess),
+        _ => None,
+    }
+}
+
+/// Parse an `overrides.sandbox` value into the policy to apply on top of the
+/// session's `current` policy. The read restrictions of `current` are kept,
+/// and switching to `workspace-write` from a workspace-write session also
+/// keeps its writable roots and network settings.
+pub fn parse_sandbox(s: &str, current: &SandboxPolicy) -> Option<SandboxPolicy> {
+    Some(match parse_sandbox_mode(s)? {
+        SandboxMode::ReadOnly => {
+            SandboxPolicy::new_read_only_policy().with_read_restrictions_of(current)
+        }
+        SandboxMode::WorkspaceWrite => match current {
+            SandboxPolicy::WorkspaceWrite { .. } => current.clone(),
+            _ => SandboxPolicy::new_workspace_write_policy().with_read_restrictions_of(current),
+        },
+        SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
+    })
+}
+
+/// Parse an `overrides.approval_policy` value.
+pub fn parse_approval(s: &str) -> Option<AskForApproval> {
+    match s {
+        "untrusted" => Some(AskForApproval::UnlessTrusted),
+        "on-request" => Some(AskForApproval::OnRequest),
+        "on-failure" => Some(AskForApproval::OnFailure),
+        "never" => Some(AskForApproval::Never),
+        _ => None,
+    }
+}
+
+/// Session settings declared under `overrides:`; values use the config spelling
+/// (e.g. `effort: high`, `sandbox: read-only`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModeOverrides {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub effort: Option<String>,
+    #[serde(default)]
+    pub sandbox: Option<String>,
+    #[serde(default)]
+    pub approval_policy: Option<String>,
+    /// Tools exposed while the mode is enabled, with the `include`/`exclude`
+    /// patterns of the `[tool_filter]` config table. Replaces the session filter.
+    #[serde(default)]
+    pub tools: Option<ToolFilter>,
+}
+
+impl ModeOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.model.is_none()
+            && self.effort.is_none()
+            && self.sandbox.is_none()
+            && self.approval_policy.is_none()
+            && self.tools.is_none()
+    }
+
+    /// Fields as `(name, value)` pairs in a fixed order.
+    pub fn fields(&self) -> [(&'static str, Option<&str>); 4] {
+        [
+            ("model", self.model.as_deref()),
+            ("effort", self.effort.as_deref()),
+            ("sandbox", self.sandbox.as_deref()),
+            ("approval_policy", self.approval_policy.as_deref()),
+        ]
+    }
+
+    /// Compact "effort=high, sandbox=read-only" form; empty when nothing is set.
+    pub fn describe(&self) -> String {
+        self.fields()
+            .iter()
+            .filter_map(|(name, value)| value.map(|v| format!("{name}={v}")))
+            .chain(
+                self.tools
+                    .as_ref()
+                    .map(|t| format!("tools={}", describe_tool_filter(t))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn validate(&self, mode_id: &str) -> Result<(), ModesError> {
+        let checks = [
+            ("effort", self.effort.as_deref(), EFFORT_VALUES),
+            ("sandbox", self.sandbox.as_deref(), SANDBOX_VALUES),
+            (
+                "approval_policy",
+                self.approval_policy.as_deref(),
+                APPROVAL_VALUES,
+            ),
+        ];
+        for (name, value, allowed) in checks {
+            if let Some(v) = value
+                && !allowed.contains(&v)
+            {
+                return Err(ModesError::BadOverride(format!(
+                    "{mode_id}: {name}={v} (allowed: {})",
+                    allowed.join("|")
+                )));
+            }
+        }
+        if self.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err(ModesError::BadOverride(format!(
+                "{mode_id}: model is empty"
+            )));
+        }
+        if let Some(tools) = &self.tools
+            && tools
+                .include
+                .iter()
+                .chain(&tools.exclude)
+                .any(|p| p.trim().is_empty())
+        {
+            return Err(ModesError::BadOverride(format!(
+                "{mode_id}: tools has an empty pattern"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Field-level fallback to `parent` for anything not set here.
+    fn or(self, parent: &ModeOverrides) -> ModeOverrides {
+        ModeOverrides {
+            model: self.model.or_else(|| parent.model.clone()),
+            effort: self.effort.or_else(|| parent.effort.clone()),
+            sandbox: self.sandbox.or_else(|| parent.sandbox.clone()),
+            approval_policy: self
+                .approval_policy
+                .or_else(|| parent.approval_policy.clone()),
+            tools: self.tools.or_else(|| parent.tools.clone()),
+        }
+    }
+}
+
+/// e.g. "only(shell, read_*) without(apply_patch)"; "all" for an empty filter.
+fn describe_tool_filter(filter: &ToolFilter) -> String {
+    let mut parts = Vec::new();
+    if !filter.include.is_empty() {
+        parts.push(format!("only({})", filter.include.join(", ")));
+    }
+    if !filter.exclude.is_empty() {
+        parts.push(format!("without({})", filter.exclude.join(", ")));
+    }
+    if parts.is_empty() {
+        return "all".to_string();
+    }
+    parts.join(" ")
+}
+
+/// A body contributed by another mode through `extends` or `includes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritedBody {
+    pub id: String,
+    pub path: PathBuf,
+    pub body: String,
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum ModesError {
     #[error("illegal id: {0}")]
     IllegalId(String), // E1001
@@ -91,9 +414,61 @@ pub enum ModesError {
     Regex(String), // E2201
     #[error("unknown mode: {0}")]
     UnknownMode(String), // E1201
+    #[error("composition cycle: {0}")]
+    CompositionCycle(String), // E2301
+    #[error("bad override: {0}")]
+    BadOverride(String), // E2401
+    #[error("bad default_from: {0}")]
+    BadDefaultFrom(String), // E2104
+    #[error("template syntax: {0}")]
+    TemplateSyntax(String), // E3201
+    #[error("template filter: {0}")]
+    TemplateFilter(String), // E3202
+}
+
+impl ModesError {
+    /// Stable error code, e.g. `E2201`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::IllegalId(_) => "E1001",
+            Self::Io(_) => "E1004",
+            Self::Frontmatter(_) => "E2001",
+            Self::VarDup(_) => "E2101",
+            Self::Regex(_) => "E2201",
+            Self::UnknownMode(_) => "E1201",
+            Self::CompositionCycle(_) => "E2301",
+            Self::BadOverride(_) => "E2401",
+            Self::BadDefaultFrom(_) => "E2104",
+            Self::TemplateSyntax(_) => "E3201",
+            Self::TemplateFilter(_) => "E3202",
+        }
+    }
+
+    /// Prefix a per-file load error with the file it came from.
+    fn in_file(self, path: &Path) -> Self {
+        let at = |msg: String| format!("{}: {msg}", path.display());
+        match self {
+            Self::Io(msg) => Self::Io(at(msg)),
+            Self::Frontmatter(msg) => Self::Frontmatter(at(msg)),
+            Self::VarDup(msg) => Self::VarDup(at(msg)),
+            Self::Regex(msg) => Self::Regex(at(msg)),
+            Self::BadDefaultFrom(msg) => Self::BadDefaultFrom(at(msg)),
+            other => other,
+        }
+    }
+
+    /// Attach the owning mode id to a template error.
+    fn template(mode_id: &str, err: TemplateError) -> Self {
+        match err {
+            TemplateError::Syntax { .. } => Self::TemplateSyntax(format!("{mode_id}: {err}")),
+            TemplateError::UnknownFilter { .. } => {
+                Self::TemplateFilter(format!("{mode_id}: {err}"))
+            }
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct FrontmatterRaw {
     #[serde(default)]
     kind: Option<ModeKind>,
@@ -107,6 +482,18 @@ struct FrontmatterRaw {
     default_enabled: Option<bool>,
     #[serde(default)]
     variables: Option<Vec<ModeVariableDefinition>>,
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    includes: Option<Vec<String>>,
+    #[serde(default)]
+    conflicts_with: Option<Vec<String>>,
+    #[serde(default)]
+    requires: Option<Vec<String>>,
+    #[serde(default)]
+    priority: Option<i32>,
+    #[serde(default)]
+    overrides: Option<ModeOverrides>,
 }
 
 /// Return `(frontmatter, body)` if frontmatter block exists; otherwise treat whole file as body.
@@ -186,36 +573,108 @@ fn project_chain_from_repo_root_to(cwd: &Path) -> Vec<PathBuf> {
     }
 }
 
-/// Scan .codex/modes along repo-root→cwd and append $CODEX_HOME/modes if present.
-pub fn scan_modes(
-    cwd: &Path,
-    codex_home: Option<&Path>,
-) -> Result<Vec<ModeDefinition>, ModesError> {
-    let mut search_dirs = project_chain_from_repo_root_to(cwd);
+/// Directories `scan_modes` reads, lowest precedence first.
+fn mode_search_dirs(cwd: &Path, codex_home: Option<&Path>) -> Vec<(PathBuf, ModeScope)> {
+    let mut search_dirs: Vec<(PathBuf, ModeScope)> = Vec::new();
     if let Some(home) = codex_home {
-        let home_modes = home.join("modes");
-        if home_modes.is_dir() {
-            search_dirs.push(home_modes);
+        search_dirs.push((home.join("modes"), ModeScope::Global));
+    }
+    for dir in project_chain_from_repo_root_to(cwd) {
+        let label = dir
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        search_dirs.push((dir.join(".codex/modes"), ModeScope::Project(label)));
+    }
+    search_dirs
+}
+
+/// How often mode watchers (TUI hot reload, app-server `modesChanged`) poll
+/// `modes_fingerprint`.
+pub const MODES_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Cheap change detector for the files `scan_modes` would read: hashes the
+/// path, size and mtime of every `.md` file under the search directories.
+/// Poll and compare successive values to notice edits without re-parsing.
+pub fn modes_fingerprint(cwd: &Path, codex_home: Option<&Path>) -> u64 {
+    use std::hash::Hash;
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for (modes_dir, _) in mode_search_dirs(cwd, codex_home) {
+        if !modes_dir.is_dir() {
+            continue;
         }
+        let mut files: Vec<(PathBuf, u64, Option<std::time::SystemTime>)> =
+            walkdir::WalkDir::new(&modes_dir)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("md"))
+                .map(|e| {
+                    let meta = e.metadata().ok();
+                    (
+                        e.path().to_path_buf(),
+                        meta.as_ref()
+                            .map(std::fs::Metadata::len)
+                            .unwrap_or_default(),
+                        meta.and_then(|m| m.modified().ok()),
+                    )
+                })
+                .collect();
+        files.sort();
+        files.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A mode file that `scan_modes` could not load, e.g. bad frontmatter or an
+/// `extends` naming an unknown mode. Modes built on a failed one fail too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeDiagnostic {
+    pub id: String,
+    pub path: PathBuf,
+    pub error: ModesError,
+}
+
+impl ModeDiagnostic {
+    /// Message with its error code, e.g. `E2401 BadOverride: /a: sandbox=open (...)`.
+    pub fn message(&self) -> String {
+        format_modes_error(&self.error)
     }
+}
+
+/// Modes that loaded plus one diagnostic per file that did not.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModesScan {
+    pub defs: Vec<ModeDefinition>,
+    pub diagnostics: Vec<ModeDiagnostic>,
+}
+
+impl ModesScan {
+    /// The load error of `id` if its file failed, else `UnknownMode`.
+    pub fn missing(&self, id: &str) -> ModesError {
+        self.diagnostics
+            .iter()
+            .rev()
+            .find(|d| d.id == id)
+            .map(|d| d.error.clone())
+            .unwrap_or_else(|| ModesError::UnknownMode(id.to_string()))
+    }
+}
 
-    let mut seen: IndexMap<String, ModeDefinition> = IndexMap::new();
-    for dir in search_dirs {
-        let modes_dir = dir.join(".codex/modes");
+/// Scan $CODEX_HOME/modes (if present) followed by .codex/modes along repo-root→cwd,
+/// so definitions nearer to cwd shadow global ones. `extends`/`includes` are
+/// resolved across all scanned scopes after the walk. A file that fails to
+/// load is reported in `diagnostics` and does not affect the others.
+pub fn scan_modes(cwd: &Path, codex_home: Option<&Path>) -> ModesScan {
+    let mut scanned: Vec<ScannedMode> = Vec::new();
+    let mut diagnostics: Vec<ModeDiagnostic> = Vec::new();
+    for (modes_dir, scope) in mode_search_dirs(cwd, codex_home) {
         if !modes_dir.is_dir() {
             continue;
         }
-        let scope = if codex_home.map(|h| h.join("modes")).as_ref() == Some(&modes_dir) {
-            ModeScope::Global
-        } else {
-            let label = dir
-                .file_name()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default();
-            ModeScope::Project(label)
-        };
         let walk = walkdir::WalkDir::new(&modes_dir).into_iter();
-        for entry in walk.filter_map(|e| e.ok()) {
+        for entry in walk.filter_map(Result::ok) {
             if !entry.file_type().is_file() {
                 continue;
             }
@@ -229,50 +688,248 @@ pub fn scan_modes(
             let Some(id) = id_from_rel_path(rel) else {
                 continue;
             }; // illegal id skipped
-            let text =
-                fs::read_to_string(entry.path()).map_err(|e| ModesError::Io(e.to_string()))?;
-            let (fm, body) = parse_frontmatter(&text)?;
-            let (kind, display_name, description, argument_hint, default_enabled, variables) =
-                if let Some(fm) = fm {
-                    (
-                        fm.kind.unwrap_or(ModeKind::Persistent),
-                        fm.display_name,
-                        fm.description,
-                        fm.argument_hint,
-                        fm.default_enabled.unwrap_or(false),
-                        fm.variables.unwrap_or_default(),
-                    )
-                } else {
-                    (ModeKind::Persistent, None, None, None, false, Vec::new())
-                };
-
-            // validate duplicate vars & regex
-            let mut names = std::collections::HashSet::new();
-            for v in &variables {
-                if !names.insert(v.name.clone()) {
-                    return Err(ModesError::VarDup(v.name.clone()));
+            match load_mode_file(entry.path(), &id, &scope) {
+                Ok(mode) => scanned.push(mode),
+                Err(error) => diagnostics.push(ModeDiagnostic {
+                    id,
+                    path: entry.path().to_path_buf(),
+                    error,
+                }),
+            }
+        }
+    }
+    let (mut defs, errors) = resolve_composition(&scanned);
+    diagnostics.extend(errors.into_iter().map(|(mode, error)| ModeDiagnostic {
+        id: mode.def.id.clone(),
+        path: mode.def.path.clone(),
+        error,
+    }));
+    resolve_dynamic_defaults(&mut defs, cwd);
+    ModesScan { defs, diagnostics }
+}
+
+/// Read and validate one mode file; composition is resolved later.
+fn load_mode_file(path: &Path, id: &str, scope: &ModeScope) -> Result<ScannedMode, ModesError> {
+    let text = fs::read_to_string(path).map_err(|e| ModesError::Io(e.to_string()).in_file(path))?;
+    let (fm, body) = parse_frontmatter(&text).map_err(|e| e.in_file(path))?;
+    let fm = fm.unwrap_or_default();
+
+    // validate duplicate vars & regex
+    let mut names = std::collections::HashSet::new();
+    for v in fm.variables.iter().flatten() {
+        if !names.insert(v.name.clone()) {
+            return Err(ModesError::VarDup(v.name.clone()).in_file(path));
+        }
+        if let Some(pat) = &v.pattern {
+            Regex::new(pat).map_err(|e| ModesError::Regex(e.to_string()).in_file(path))?;
+        }
+        v.validate_default_from().map_err(|e| e.in_file(path))?;
+    }
+    // A body that does not parse would fail on every render.
+    Template::parse(&body).map_err(|e| ModesError::template(id, e))?;
+    if let Some(overrides) = &fm.overrides {
+        overrides.validate(id)?;
+    }
+    Ok(ScannedMode::new(id.to_string(), scope, path, fm, body))
+}
+
+/// Replace `default` with the value of `default_from` where it yields one, so
+/// rendering and validation see a plain default.
+fn resolve_dynamic_defaults(defs: &mut [ModeDefinition], cwd: &Path) {
+    for v in defs.iter_mut().flat_map(|d| d.variables.iter_mut()) {
+        let resolved = v
+            .default_from
+            .as_deref()
+            .and_then(DefaultSource::parse)
+            .and_then(|source| source.resolve(cwd));
+        if let Some(value) = resolved {
+            v.default = Some(serde_yaml::Value::String(value));
+        }
+    }
+}
+
+/// A mode file as read from disk, before `extends`/`includes` are applied.
+struct ScannedMode {
+    def: ModeDefinition,
+    explicit_kind: Option<ModeKind>,
+    explicit_priority: Option<i32>,
+}
+
+impl ScannedMode {
+    fn new(id: String, scope: &ModeScope, path: &Path, fm: FrontmatterRaw, body: String) -> Self {
+        let def = ModeDefinition {
+            id,
+            display_name: fm.display_name,
+            description: fm.description,
+            argument_hint: fm.argument_hint,
+            kind: fm.kind.unwrap_or(ModeKind::Persistent),
+            default_enabled: fm.default_enabled.unwrap_or(false),
+            variables: fm.variables.unwrap_or_default(),
+            scope: scope.clone(),
+            path: path.to_path_buf(),
+            body,
+            extends: fm.extends,
+            includes: fm.includes.unwrap_or_default(),
+            inherited: Vec::new(),
+            conflicts_with: fm.conflicts_with.unwrap_or_default(),
+            requires: fm.requires.unwrap_or_default(),
+            priority: fm.priority.unwrap_or_default(),
+            overrides: fm.overrides.unwrap_or_default(),
+        };
+        Self {
+            def,
+            explicit_kind: fm.kind,
+            explicit_priority: fm.priority,
+        }
+    }
+}
+
+/// Flatten `extends`/`includes` for the visible definition of every id.
+/// Later scans still override earlier ones (nearest scope wins) while keeping
+/// the position of the first occurrence. A mode whose composition fails (cycle
+/// or unknown base) is returned as an error together with the modes built on
+/// it; the others are unaffected.
+fn resolve_composition(
+    scanned: &[ScannedMode],
+) -> (Vec<ModeDefinition>, Vec<(&ScannedMode, ModesError)>) {
+    let mut stacks: IndexMap<&str, Vec<usize>> = IndexMap::new();
+    for (idx, mode) in scanned.iter().enumerate() {
+        stacks.entry(mode.def.id.as_str()).or_default().push(idx);
+    }
+    let mut memo: HashMap<usize, Result<ModeDefinition, ModesError>> = HashMap::new();
+    let mut out = Vec::with_capacity(stacks.len());
+    let mut errors = Vec::new();
+    for stack in stacks.values() {
+        if let Some(&top) = stack.last() {
+            match compose_mode(top, scanned, &stacks, &mut memo, &mut Vec::new()) {
+                Ok(def) => out.push(def),
+                Err(e) => errors.push((&scanned[top], e)),
+            }
+        }
+    }
+    (out, errors)
+}
+
+/// Find the definition `target` refers to when named from `from`. Naming the
+/// mode's own id selects the lower-priority definition it shadows.
+fn lookup_base(
+    target: &str,
+    from: usize,
+    scanned: &[ScannedMode],
+    stacks: &IndexMap<&str, Vec<usize>>,
+) -> Option<usize> {
+    let stack = stacks.get(target)?;
+    if target == scanned[from].def.id {
+        let pos = stack.iter().position(|&i| i == from)?;
+        pos.checked_sub(1).map(|p| stack[p])
+    } else {
+        stack.last().copied()
+    }
+}
+
+fn compose_mode(
+    idx: usize,
+    scanned: &[ScannedMode],
+    stacks: &IndexMap<&str, Vec<usize>>,
+    memo: &mut HashMap<usize, Result<ModeDefinition, ModesError>>,
+    visiting: &mut Vec<usize>,
+) -> Result<ModeDefinition, ModesError> {
+    if let Some(done) = memo.get(&idx) {
+        return done.clone();
+    }
+    if let Some(pos) = visiting.iter().position(|&i| i == idx) {
+        let mut chain: Vec<&str> = visiting[pos..]
+            .iter()
+            .map(|&i| scanned[i].def.id.as_str())
+            .collect();
+        chain.push(scanned[idx].def.id.as_str());
+        return Err(ModesError::CompositionCycle(chain.join(" -> ")));
+    }
+    visiting.push(idx);
+    let composed = compose_bases(idx, scanned, stacks, memo, visiting);
+    visiting.pop();
+    memo.insert(idx, composed.clone());
+    composed
+}
+
+/// Body of `compose_mode` once `idx` is on the `visiting` stack.
+fn compose_bases(
+    idx: usize,
+    scanned: &[ScannedMode],
+    stacks: &IndexMap<&str, Vec<usize>>,
+    memo: &mut HashMap<usize, Result<ModeDefinition, ModesError>>,
+    visiting: &mut Vec<usize>,
+) -> Result<ModeDefinition, ModesError> {
+    let own = &scanned[idx];
+    let mut def = own.def.clone();
+    let mut kind = own.explicit_kind;
+    let mut priority = own.explicit_priority;
+    let mut variables: Vec<ModeVariableDefinition> = Vec::new();
+    let mut inherited: Vec<InheritedBody> = Vec::new();
+    let bases = own
+        .def
+        .extends
+        .iter()
+        .map(|t| (t, true))
+        .chain(own.def.includes.iter().map(|t| (t, false)));
+    for (target, is_parent) in bases {
+        let Some(base_idx) = lookup_base(target, idx, scanned, stacks) else {
+            return Err(ModesError::UnknownMode(format!(
+                "{target} (referenced by {})",
+                own.def.id
+            )));
+        };
+        let base = compose_mode(base_idx, scanned, stacks, memo, visiting)?;
+        if is_parent {
+            // metadata falls back to the parent; display_name/default_enabled stay per-mode
+            kind = kind.or(Some(base.kind));
+            priority = priority.or(Some(base.priority));
+            def.description = def.description.or(base.description);
+            def.argument_hint = def.argument_hint.or(base.argument_hint);
+            def.overrides = def.overrides.or(&base.overrides);
+            // constraints accumulate along the parent chain
+            for id in base.conflicts_with {
+                if !def.conflicts_with.contains(&id) {
+                    def.conflicts_with.push(id);
                 }
-                if let Some(pat) = &v.pattern {
-                    Regex::new(pat).map_err(|e| ModesError::Regex(e.to_string()))?;
+            }
+            for id in base.requires {
+                if !def.requires.contains(&id) {
+                    def.requires.push(id);
                 }
             }
+        }
+        let base_body = InheritedBody {
+            id: base.id,
+            path: base.path,
+            body: base.body,
+        };
+        for body in base.inherited.into_iter().chain(std::iter::once(base_body)) {
+            // diamonds (a base reachable twice) contribute their body once
+            if !inherited.iter().any(|b| b.path == body.path) {
+                inherited.push(body);
+            }
+        }
+        merge_variables(&mut variables, base.variables);
+    }
+    merge_variables(&mut variables, own.def.variables.clone());
 
-            let def = ModeDefinition {
-                id: id.clone(),
-                display_name,
-                description,
-                argument_hint,
-                kind,
-                default_enabled,
-                variables,
-                scope: scope.clone(),
-                path: entry.path().to_path_buf(),
-                body,
-            };
-            seen.insert(id, def); // later write overrides earlier
+    def.kind = kind.unwrap_or(ModeKind::Persistent);
+    def.priority = priority.unwrap_or_default();
+    def.variables = variables;
+    def.inherited = inherited;
+    Ok(def)
+}
+
+/// Later declarations replace earlier ones with the same name in place; new names are appended.
+fn merge_variables(into: &mut Vec<ModeVariableDefinition>, from: Vec<ModeVariableDefinition>) {
+    for v in from {
+        if let Some(existing) = into.iter_mut().find(|e| e.name == v.name) {
+            *existing = v;
+        } else {
+            into.push(v);
         }
     }
-    Ok(seen.into_values().collect())
 }
 
 pub struct EnabledMode<'a> {
@@ -288,6 +945,13 @@ fn coerce_value(v: &serde_yaml::Value) -> Option<String> {
         serde_yaml::Value::Bool(b) => Some(b.to_string()),
         serde_yaml::Value::Number(n) => Some(n.to_string()),
         serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Sequence(items) => Some(
+            items
+                .iter()
+                .filter_map(coerce_value)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
         _ => None,
     }
 }
@@ -295,7 +959,7 @@ fn coerce_value(v: &serde_yaml::Value) -> Option<String> {
 fn render_one(
     def: &ModeDefinition,
     vars: &IndexMap<&str, Option<String>>,
-) -> (String, String, String) {
+) -> Result<(String, String, String), ModesError> {
     // scope label
     let scope_label = match &def.scope {
         ModeScope::Global => "global".to_string(),
@@ -303,31 +967,43 @@ fn render_one(
     };
     // variables
     let mut kvs: Vec<String> = Vec::new();
+    let mut ctx = TemplateContext::new();
     for v in &def.variables {
         let name = v.name.as_str();
         let val = vars
             .get(name)
-            .and_then(|o| o.clone())
-            .or_else(|| v.default.as_ref().and_then(coerce_value));
-        if let Some(val) = val {
-            kvs.push(format!("{name}={val}"));
+            .and_then(Clone::clone)
+            .or_else(|| v.default_value());
+        if let Some(val) = &val {
+            kvs.push(format!("{name}={}", v.display_value(val)));
+        }
+        if let Some(options) = &v.r#enum {
+            ctx.insert_options(name, options.clone());
         }
+        ctx.insert(name, val);
     }
     let vars_line = kvs.join(", ");
 
-    // template substitute
-    let mut rendered = def.body.clone();
-    for v in &def.variables {
-        let name = v.name.as_str();
-        let val = vars
-            .get(name)
-            .and_then(|o| o.clone())
-            .or_else(|| v.default.as_ref().and_then(coerce_value))
-            .unwrap_or_default();
-        let placeholder = format!("{{{{{name}}}}}");
-        rendered = rendered.replace(&placeholder, &val);
+    let template = Template::parse(&def.body).map_err(|e| ModesError::template(&def.id, e))?;
+    let own = template.render(&ctx);
+    if def.inherited.is_empty() {
+        return Ok((scope_label, vars_line, own));
+    }
+    // flatten the extends/includes chain: bases first, then the mode's own body
+    let mut parts: Vec<String> = Vec::with_capacity(def.inherited.len() + 1);
+    for base in &def.inherited {
+        let template =
+            Template::parse(&base.body).map_err(|e| ModesError::template(&base.id, e))?;
+        parts.push(template.render(&ctx));
     }
-    (scope_label, vars_line, rendered)
+    parts.push(own);
+    let rendered = parts
+        .iter()
+        .map(|p| p.trim_start_matches('\n').trim_end())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok((scope_label, vars_line, rendered))
 }
 
 pub fn render_user_instructions(
@@ -346,7 +1022,9 @@ pub fn render_user_instructions(
     writeln!(out, "<user_instructions>\n").ok();
     writeln!(out, "{}\n", base_user_instructions.trim()).ok();
     writeln!(out, "<mode_instructions>").ok();
-    for em in enabled {
+    let mut ordered: Vec<&EnabledMode<'_>> = enabled.iter().collect();
+    ordered.sort_by_key(|em| std::cmp::Reverse(priority_of(defs, em.id)));
+    for em in ordered {
         let def = defs
             .iter()
             .find(|d| d.id == em.id)
@@ -355,7 +1033,7 @@ pub fn render_user_instructions(
             .display_name
             .or(def.display_name.as_deref())
             .unwrap_or_else(|| def.id.trim_start_matches('/'));
-        let (scope, vars_line, rendered) = render_one(def, &em.variables);
+        let (scope, vars_line, rendered) = render_one(def, &em.variables)?;
         writeln!(out, "### Mode: {display}").ok();
         writeln!(out, "- scope: {scope}").ok();
         if !vars_line.is_empty() {
@@ -374,6 +1052,233 @@ pub fn render_user_instructions(
     Ok(out)
 }
 
+fn priority_of(defs: &[ModeDefinition], id: &str) -> i32 {
+    find_def(defs, id).map(|d| d.priority).unwrap_or_default()
+}
+
+/// Stable sort into render order: higher `priority` first, ties keep enable order.
+pub fn sort_by_priority(defs: &[ModeDefinition], enabled: &mut [EnabledMode<'_>]) {
+    enabled.sort_by_key(|em| std::cmp::Reverse(priority_of(defs, em.id)));
+}
+
+/// An override field set differently by several enabled modes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverrideConflict {
+    pub field: &'static str,
+    pub value: String,
+    /// Mode whose value was applied.
+    pub winner: String,
+    /// Enabled modes whose differing value was ignored.
+    pub overruled: Vec<String>,
+}
+
+/// Effective overrides of the enabled set plus any disagreements that were settled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedOverrides {
+    pub overrides: ModeOverrides,
+    pub conflicts: Vec<OverrideConflict>,
+}
+
+impl ResolvedOverrides {
+    /// One-line summary of settled conflicts, e.g. "sandbox=read-only from /a (overrides /b)".
+    pub fn describe_conflicts(&self) -> Option<String> {
+        if self.conflicts.is_empty() {
+            return None;
+        }
+        Some(
+            self.conflicts
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{}={} from {} (overrides {})",
+                        c.field,
+                        c.value,
+                        c.winner,
+                        c.overruled.join(", ")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+}
+
+/// Combine `overrides` of the modes in `enable_order`, field by field. The
+/// highest `priority` wins; on a tie `sandbox`/`approval_policy` take the most
+/// restrictive value and `model`/`effort`/`tools` take the most recently
+/// enabled mode.
+pub fn resolve_overrides(defs: &[ModeDefinition], enable_order: &[String]) -> ResolvedOverrides {
+    let enabled: Vec<&ModeDefinition> = enable_order
+        .iter()
+        .filter_map(|id| find_def(defs, id))
+        .collect();
+    let mut conflicts = Vec::new();
+    let overrides = ModeOverrides {
+        model: pick_override(
+            &enabled,
+            &mut conflicts,
+            "model",
+            |o| o.model.as_ref(),
+            None,
+            String::clone,
+        ),
+        effort: pick_override(
+            &enabled,
+            &mut conflicts,
+            "effort",
+            |o| o.effort.as_ref(),
+            None,
+            String::clone,
+        ),
+        sandbox: pick_override(
+            &enabled,
+            &mut conflicts,
+            "sandbox",
+            |o| o.sandbox.as_ref(),
+            Some(|v| restrictive_rank(SANDBOX_VALUES, v)),
+            String::clone,
+        ),
+        approval_policy: pick_override(
+            &enabled,
+            &mut conflicts,
+            "approval_policy",
+            |o| o.approval_policy.as_ref(),
+            Some(|v| restrictive_rank(APPROVAL_VALUES, v)),
+            String::clone,
+        ),
+        tools: pick_override(
+            &enabled,
+            &mut conflicts,
+            "tools",
+            |o| o.tools.as_ref(),
+            None,
+            describe_tool_filter,
+        ),
+    };
+    ResolvedOverrides {
+        overrides,
+        conflicts,
+    }
+}
+
+/// Higher for more restrictive values of an `allowed` list ordered most
+/// restrictive first.
+fn restrictive_rank(allowed: &[&str], value: &str) -> i64 {
+    let rank = allowed
+        .iter()
+        .position(|o| *o == value)
+        .unwrap_or(allowed.len());
+    -(rank as i64)
+}
+
+/// Pick one field for `resolve_overrides`, recording a conflict when enabled
+/// modes disagree. Ties on `priority` go to the highest `restrictive` rank,
+/// or to the latest enabled mode when there is none.
+fn pick_override<T: Clone + PartialEq>(
+    enabled: &[&ModeDefinition],
+    conflicts: &mut Vec<OverrideConflict>,
+    field: &'static str,
+    get: fn(&ModeOverrides) -> Option<&T>,
+    restrictive: Option<fn(&T) -> i64>,
+    describe: fn(&T) -> String,
+) -> Option<T> {
+    let candidates: Vec<(usize, &ModeDefinition, &T)> = enabled
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, def)| get(&def.overrides).map(|v| (pos, *def, v)))
+        .collect();
+    let tiebreak = |pos: usize, value: &T| match restrictive {
+        Some(rank) => rank(value),
+        None => pos as i64,
+    };
+    let (_, winner, value) = candidates
+        .iter()
+        .max_by_key(|(pos, def, value)| (def.priority, tiebreak(*pos, value)))?;
+    let overruled: Vec<String> = candidates
+        .iter()
+        .filter(|(_, _, v)| v != value)
+        .map(|(_, def, _)| def.id.clone())
+        .collect();
+    if !overruled.is_empty() {
+        conflicts.push(OverrideConflict {
+            field,
+            value: describe(value),
+            winner: winner.id.clone(),
+            overruled,
+        });
+    }
+    Some((*value).clone())
+}
+
+/// A project-scope `sandbox`/`approval_policy` override that is less
+/// restrictive than the session's own setting.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LooseningOverride {
+    pub mode_id: String,
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl LooseningOverride {
+    /// e.g. "sandbox=danger-full-access from /yolo".
+    pub fn describe(&self) -> String {
+        format!("{}={} from {}", self.field, self.value, self.mode_id)
+    }
+}
+
+/// Remove the `sandbox`/`approval_policy` overrides of project-scope modes
+/// that would loosen the session's `sandbox`/`approval`, except those listed
+/// in `confirmed`, and return what was removed. Project modes ship with the
+/// repository, so they may only tighten these settings unless the user agrees.
+pub fn strip_loosening_overrides(
+    defs: &mut [ModeDefinition],
+    sandbox: &SandboxPolicy,
+    approval: AskForApproval,
+    confirmed: &[LooseningOverride],
+) -> Vec<LooseningOverride> {
+    let rank = |allowed: &[&str], value: &str| allowed.iter().position(|v| *v == value);
+    let session_sandbox = rank(SANDBOX_VALUES, &sandbox.to_string());
+    let session_approval = rank(APPROVAL_VALUES, &approval.to_string());
+    let mut stripped = Vec::new();
+    for def in defs
+        .iter_mut()
+        .filter(|d| matches!(d.scope, ModeScope::Project(_)))
+    {
+        let fields = [
+            (
+                "sandbox",
+                &mut def.overrides.sandbox,
+                SANDBOX_VALUES,
+                session_sandbox,
+            ),
+            (
+                "approval_policy",
+                &mut def.overrides.approval_policy,
+                APPROVAL_VALUES,
+                session_approval,
+            ),
+        ];
+        for (field, value, allowed, session) in fields {
+            let Some(v) = value.as_deref() else {
+                continue;
+            };
+            if rank(allowed, v) <= session {
+                continue;
+            }
+            let loosening = LooseningOverride {
+                mode_id: def.id.clone(),
+                field,
+                value: v.to_string(),
+            };
+            if !confirmed.contains(&loosening) {
+                *value = None;
+                stripped.push(loosening);
+            }
+        }
+    }
+    stripped
+}
+
 /// Byte-for-byte equality; callers may add a relaxed mode if needed later.
 /// Normalize a string for relaxed equivalence comparisons:
 /// - Convert CRLF to LF
@@ -412,7 +1317,6 @@ pub fn is_equivalent(a: &str, b: &str) -> bool {
 }
 
 /// Map a ModesError to a user-facing error message with a stable error code.
-/// Conservative default maps to E3201 TemplateError to preserve prior behavior.
 pub fn format_modes_error(err: &ModesError) -> String {
     match err {
         ModesError::IllegalId(msg) => format!("E1001 IllegalId: {msg}"),
@@ -421,6 +1325,11 @@ pub fn format_modes_error(err: &ModesError) -> String {
         ModesError::VarDup(var) => format!("E2101 VarDup: {var}"),
         ModesError::Regex(msg) => format!("E2201 Regex: {msg}"),
         ModesError::UnknownMode(id) => format!("E1201 UnknownMode: {id}"),
+        ModesError::CompositionCycle(chain) => format!("E2301 CompositionCycle: {chain}"),
+        ModesError::BadOverride(msg) => format!("E2401 BadOverride: {msg}"),
+        ModesError::BadDefaultFrom(msg) => format!("E2104 BadDefaultFrom: {msg}"),
+        ModesError::TemplateSyntax(msg) => format!("E3201 TemplateSyntax: {msg}"),
+        ModesError::TemplateFilter(msg) => format!("E3202 TemplateFilter: {msg}"),
     }
 }
 
@@ -435,7 +1344,7 @@ pub fn enabled_labels(enabled: &[EnabledMode<'_>]) -> String {
 
 /// Build the standard "Applied N mode(s)" message.
 pub fn applied_message(count: usize) -> String {
-    format!("Applied {} mode(s)", count)
+    format!("Applied {count} mode(s)")
 }
 
 /// Format persistent mode summary from labels; returns None when labels empty.
@@ -448,6 +1357,53 @@ pub fn format_mode_summary(labels: &str) -> Option<String> {
     }
 }
 
+/// Mode ids added, changed or removed between two scans.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModesDiff {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ModesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Compact description, e.g. "added /a; changed /b, /c; removed /d".
+    pub fn describe(&self) -> String {
+        [
+            ("added", &self.added),
+            ("changed", &self.changed),
+            ("removed", &self.removed),
+        ]
+        .iter()
+        .filter(|(_, ids)| !ids.is_empty())
+        .map(|(label, ids)| format!("{label} {}", ids.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
+}
+
+/// Compare two scans by id; a mode counts as changed when any part of its
+/// definition differs (frontmatter, body, path or a composed base).
+pub fn diff_modes(old: &[ModeDefinition], new: &[ModeDefinition]) -> ModesDiff {
+    let mut diff = ModesDiff::default();
+    for def in new {
+        match find_def(old, &def.id) {
+            None => diff.added.push(def.id.clone()),
+            Some(prev) if prev != def => diff.changed.push(def.id.clone()),
+            Some(_) => {}
+        }
+    }
+    for def in old {
+        if find_def(new, &def.id).is_none() {
+            diff.removed.push(def.id.clone());
+        }
+    }
+    diff
+}
+
 /// Lightweight generation-based debouncer helper. Each `next()` call returns a new
 /// monotonically increasing generation id. A task scheduled with this id should check
 /// `is_latest(id)` before committing side-effects; if it returns false, the task is
@@ -500,6 +1456,10 @@ pub enum ValidationError {
         var: String,
         got: String,
     },
+    /// E3109: two enabled modes conflict (either side lists the other in `conflicts_with`)
+    ModeConflict { mode_id: String, other: String },
+    /// E3110: an enabled mode requires a mode that is not enabled
+    RequiresMissing { mode_id: String, requires: String },
 }
 
 /// Validate a single variable's explicit value against its definition.
@@ -509,12 +1469,19 @@ pub fn validate_var_value(
     value: &str,
 ) -> Option<ValidationError> {
     if let Some(options) = &var_def.r#enum {
-        if !value.is_empty() && !options.iter().any(|o| o == value) {
+        let items = if var_def.var_type == Some(VarType::List) {
+            split_list(value)
+        } else if value.is_empty() {
+            Vec::new()
+        } else {
+            vec![value.to_string()]
+        };
+        if let Some(bad) = items.into_iter().find(|i| !options.contains(i)) {
             return Some(ValidationError::EnumMismatch {
                 mode_id: mode_id.to_string(),
                 var: var_def.name.clone(),
                 allowed: options.clone(),
-                got: value.to_string(),
+                got: bad,
             });
         }
     }
@@ -572,23 +1539,203 @@ pub fn validate_enabled<'a>(
             continue;
         };
         for v in &def.variables {
-            let explicit = em.variables.get(v.name.as_str()).and_then(|o| o.clone());
+            let explicit = em.variables.get(v.name.as_str()).and_then(Clone::clone);
             if v.required && explicit.is_none() && v.default.is_none() {
                 errs.push(ValidationError::RequiredMissing {
                     mode_id: def.id.clone(),
                     var: v.name.clone(),
                 });
             }
-            if let Some(val) = explicit.as_deref() {
-                if let Some(e) = validate_var_value(&def.id, v, val) {
-                    errs.push(e);
-                }
+            if let Some(val) = explicit.as_deref()
+                && let Some(e) = validate_var_value(&def.id, v, val)
+            {
+                errs.push(e);
+            }
+        }
+        for req in &def.requires {
+            if !enabled.iter().any(|e| e.id == req) {
+                errs.push(ValidationError::RequiresMissing {
+                    mode_id: def.id.clone(),
+                    requires: req.clone(),
+                });
+            }
+        }
+    }
+    // conflicts are symmetric; report each enabled pair once, in enable order
+    for (i, a) in enabled.iter().enumerate() {
+        for b in &enabled[i + 1..] {
+            if modes_conflict(defs, a.id, b.id) {
+                errs.push(ValidationError::ModeConflict {
+                    mode_id: a.id.to_string(),
+                    other: b.id.to_string(),
+                });
             }
         }
     }
     errs
 }
 
+fn find_def<'a>(defs: &'a [ModeDefinition], id: &str) -> Option<&'a ModeDefinition> {
+    defs.iter().find(|d| d.id == id)
+}
+
+/// Whether a variable value may be recorded in the rollout: false for secret
+/// variables and for variables `defs` does not declare.
+pub fn is_recordable_var(defs: &[ModeDefinition], mode_id: &str, name: &str) -> bool {
+    find_def(defs, mode_id)
+        .and_then(|d| d.variables.iter().find(|v| v.name == name))
+        .is_some_and(|v| !v.is_secret())
+}
+
+/// True if either mode lists the other in `conflicts_with`.
+pub fn modes_conflict(defs: &[ModeDefinition], a: &str, b: &str) -> bool {
+    let lists = |x: &str, y: &str| {
+        find_def(defs, x).is_some_and(|d| d.conflicts_with.iter().any(|c| c == y))
+    };
+    a != b && (lists(a, b) || lists(b, a))
+}
+
+/// Modes switched on or off as a side effect of enabling/disabling another mode.
+/// Each entry is `(mode_id, because_of)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintEffects {
+    pub auto_enabled: Vec<(String, String)>,
+    pub auto_disabled: Vec<(String, String)>,
+}
+
+impl ConstraintEffects {
+    pub fn is_empty(&self) -> bool {
+        self.auto_enabled.is_empty() && self.auto_disabled.is_empty()
+    }
+
+    /// One-line description for history cells, e.g. "enabled /b (required by /a); disabled /c (conflicts with /a)".
+    pub fn describe(&self) -> Option<String> {
+        let mut parts: Vec<String> = Vec::new();
+        for (id, by) in &self.auto_enabled {
+            parts.push(format!("enabled {id} (required by {by})"));
+        }
+        for (id, by) in &self.auto_disabled {
+            parts.push(format!("disabled {id} ({by})"));
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("; "))
+        }
+    }
+}
+
+/// Enable `id` in `enable_order`, also enabling its (transitive) `requires` and
+/// disabling enabled modes that conflict with anything newly enabled.
+pub fn enable_with_constraints(
+    defs: &[ModeDefinition],
+    enable_order: &mut Vec<String>,
+    id: &str,
+) -> ConstraintEffects {
+    let mut effects = ConstraintEffects::default();
+    if enable_order.iter().any(|e| e == id) {
+        return effects;
+    }
+    // requirements first so the enable order reflects dependencies
+    let mut closure: Vec<(String, Option<String>)> = Vec::new();
+    collect_requirements(defs, id, None, &mut closure);
+    for (m, required_by) in &closure {
+        if enable_order.contains(m) {
+            continue;
+        }
+        enable_order.push(m.clone());
+        if let Some(by) = required_by {
+            effects.auto_enabled.push((m.clone(), by.clone()));
+        }
+    }
+    for (m, _) in &closure {
+        let conflicting: Vec<String> = enable_order
+            .iter()
+            .filter(|e| !closure.iter().any(|(c, _)| c == *e) && modes_conflict(defs, m, e))
+            .cloned()
+            .collect();
+        for other in conflicting {
+            disable_cascade(
+                defs,
+                enable_order,
+                &other,
+                format!("conflicts with {m}"),
+                &mut effects,
+            );
+        }
+    }
+    effects
+}
+
+/// Disable `id` and any enabled modes that (transitively) require it.
+pub fn disable_with_constraints(
+    defs: &[ModeDefinition],
+    enable_order: &mut Vec<String>,
+    id: &str,
+) -> ConstraintEffects {
+    let mut effects = ConstraintEffects::default();
+    if !enable_order.iter().any(|e| e == id) {
+        return effects;
+    }
+    enable_order.retain(|e| e != id);
+    disable_dependents(defs, enable_order, id, &mut effects);
+    effects
+}
+
+fn collect_requirements(
+    defs: &[ModeDefinition],
+    id: &str,
+    required_by: Option<&str>,
+    out: &mut Vec<(String, Option<String>)>,
+) {
+    if out.iter().any(|(m, _)| m == id) {
+        return;
+    }
+    let Some(def) = find_def(defs, id) else {
+        return;
+    };
+    // mark before recursing so `requires` cycles terminate
+    out.push((id.to_string(), required_by.map(str::to_string)));
+    let pos = out.len() - 1;
+    for req in &def.requires {
+        collect_requirements(defs, req, Some(id), out);
+    }
+    // move the mode after its requirements
+    let entry = out.remove(pos);
+    out.push(entry);
+}
+
+fn disable_cascade(
+    defs: &[ModeDefinition],
+    enable_order: &mut Vec<String>,
+    id: &str,
+    reason: String,
+    effects: &mut ConstraintEffects,
+) {
+    if !enable_order.iter().any(|e| e == id) {
+        return;
+    }
+    enable_order.retain(|e| e != id);
+    effects.auto_disabled.push((id.to_string(), reason));
+    disable_dependents(defs, enable_order, id, effects);
+}
+
+fn disable_dependents(
+    defs: &[ModeDefinition],
+    enable_order: &mut Vec<String>,
+    id: &str,
+    effects: &mut ConstraintEffects,
+) {
+    let dependents: Vec<String> = enable_order
+        .iter()
+        .filter(|e| find_def(defs, e).is_some_and(|d| d.requires.iter().any(|r| r == id)))
+        .cloned()
+        .collect();
+    for dep in dependents {
+        disable_cascade(defs, enable_order, &dep, format!("requires {id}"), effects);
+    }
+}
+
 /// Format a single validation error to the user-facing short code string.
 /// Returns None for errors that are typically aggregated elsewhere (e.g., RequiredMissing).
 pub fn format_validation_error(err: &ValidationError) -> Option<String> {
@@ -602,18 +1749,42 @@ pub fn format_validation_error(err: &ValidationError) -> Option<String> {
             allowed.join("|")
         )),
         ValidationError::BooleanInvalid { var, got, .. } => {
-            Some(format!("E3106 BooleanInvalid: {}={}", var, got))
+            Some(format!("E3106 BooleanInvalid: {var}={got}"))
         }
         ValidationError::NumberInvalid { var, got, .. } => {
-            Some(format!("E3107 NumberInvalid: {}={}", var, got))
+            Some(format!("E3107 NumberInvalid: {var}={got}"))
         }
         ValidationError::PathInvalid { var, got, .. } => {
-            Some(format!("E3108 PathInvalid: {}={}", var, got))
+            Some(format!("E3108 PathInvalid: {var}={got}"))
         }
+        ValidationError::ModeConflict { mode_id, other } => Some(format!(
+            "E3109 ModeConflict: {mode_id} conflicts with {other}"
+        )),
+        ValidationError::RequiresMissing { mode_id, requires } => Some(format!(
+            "E3110 RequiresMissing: {mode_id} requires {requires}"
+        )),
         ValidationError::RequiredMissing { .. } => None,
     }
 }
 
+/// Format a batch of validation errors, aggregating all missing required
+/// variables into a single E3101 line ahead of the per-value errors.
+pub fn format_validation_errors(errs: &[ValidationError]) -> Vec<String> {
+    let missing: Vec<String> = errs
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::RequiredMissing { mode_id, var } => Some(format!("{mode_id}/{var}")),
+            _ => None,
+        })
+        .collect();
+    let mut out: Vec<String> = Vec::new();
+    if !missing.is_empty() {
+        out.push(format!("E3101 RequiredMissing: {}", missing.join(", ")));
+    }
+    out.extend(errs.iter().filter_map(format_validation_error));
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -647,10 +1818,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: ModeScope::Project("app".into()),
             path: PathBuf::new(),
             body: "Hello {{who}}".into(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         };
         let scope = def.scope.clone();
         let enabled = EnabledMode {
@@ -699,6 +1878,26 @@ mod tests {
             format_modes_error(&ModesError::UnknownMode("/m".into())),
             "E1201 UnknownMode: /m"
         );
+        assert_eq!(
+            format_modes_error(&ModesError::template(
+                "/m",
+                TemplateError::Syntax {
+                    line: 3,
+                    message: "unclosed `{{#if}}`".into()
+                }
+            )),
+            "E3201 TemplateSyntax: /m: line 3: unclosed `{{#if}}`"
+        );
+        assert_eq!(
+            format_modes_error(&ModesError::template(
+                "/m",
+                TemplateError::UnknownFilter {
+                    line: 1,
+                    name: "shout".into()
+                }
+            )),
+            "E3202 TemplateFilter: /m: line 1: unknown filter `shout`"
+        );
     }
 
     #[test]
@@ -734,6 +1933,7 @@ mod tests {
             pattern: None,
             inline_edit: None,
             mode_scoped: None,
+            default_from: None,
         };
         assert_eq!(
             validate_var_value("/m", &v_enum, "z"),
@@ -756,6 +1956,7 @@ mod tests {
             pattern: None,
             inline_edit: None,
             mode_scoped: None,
+            default_from: None,
         };
         assert_eq!(
             validate_var_value("/m", &v_bool, "maybe"),
@@ -777,6 +1978,7 @@ mod tests {
             pattern: None,
             inline_edit: None,
             mode_scoped: None,
+            default_from: None,
         };
         assert_eq!(
             validate_var_value("/m", &v_num, "abc"),
@@ -799,6 +2001,7 @@ mod tests {
             pattern: None,
             inline_edit: None,
             mode_scoped: None,
+            default_from: None,
         };
         assert_eq!(
             validate_var_value("/m", &v_path, "\u{0007}"),
@@ -830,10 +2033,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: ModeScope::Global,
             path: PathBuf::new(),
             body: String::new(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         };
         let scope = def.scope.clone();
         let em = EnabledMode {
@@ -902,6 +2113,732 @@ mod tests {
         assert_eq!(format_validation_error(&e5), None);
     }
 
+    fn write_mode(dir: &Path, rel: &str, text: &str) {
+        let path = dir.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn scan_resolves_extends_and_includes_across_scopes() {
+        let project = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        write_mode(
+            home.path(),
+            "modes/review.md",
+            "---\ndescription: Review\nvariables:\n  - name: strict\n    default: false\n  - name: ticket\n---\nReview {{ticket}}.\n",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/checklist.md",
+            "---\nvariables:\n  - name: items\n    default: tests\n---\nCheck {{items}}.\n",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/review/strict.md",
+            "---\nextends: /review\nincludes: [/checklist]\nvariables:\n  - name: strict\n    default: true\n---\n{{#if strict}}Be strict.{{/if}}\n",
+        );
+        let defs = scan_modes(project.path(), Some(home.path())).defs;
+        let strict = defs.iter().find(|d| d.id == "/review:strict").unwrap();
+        assert_eq!(strict.description.as_deref(), Some("Review"));
+        assert_eq!(
+            strict
+                .variables
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["strict", "ticket", "items"]
+        );
+        assert_eq!(
+            strict.variables[0].default,
+            Some(serde_yaml::Value::Bool(true))
+        );
+        assert_eq!(
+            strict
+                .inherited
+                .iter()
+                .map(|b| b.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/review", "/checklist"]
+        );
+
+        let scope = strict.scope.clone();
+        let enabled = EnabledMode {
+            id: "/review:strict",
+            display_name: None,
+            scope: &scope,
+            variables: IndexMap::from([("ticket", Some("T-1".to_string()))]),
+        };
+        let out = render_user_instructions("base", &[enabled], &defs).unwrap();
+        assert!(out.contains("Review T-1.\n\nCheck tests.\n\nBe strict."));
+    }
+
+    #[test]
+    fn scan_extends_own_id_uses_shadowed_definition() {
+        let project = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        write_mode(home.path(), "modes/qa.md", "Global QA.");
+        write_mode(project.path(), ".codex/modes/qa.md", "Project QA.");
+        let defs = scan_modes(project.path(), Some(home.path())).defs;
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].scope, ModeScope::Project(dir_label(project.path())));
+        assert!(defs[0].inherited.is_empty());
+
+        write_mode(
+            project.path(),
+            ".codex/modes/qa.md",
+            "---\nextends: /qa\n---\nProject QA.",
+        );
+        let defs = scan_modes(project.path(), Some(home.path())).defs;
+        assert_eq!(defs[0].inherited.len(), 1);
+        assert_eq!(defs[0].inherited[0].body, "Global QA.");
+        assert_eq!(defs[0].body, "Project QA.");
+    }
+
+    #[test]
+    fn scan_skips_modes_whose_body_does_not_parse() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".codex/modes/broken.md",
+            "Use {{#if x}} here.",
+        );
+        write_mode(project.path(), ".codex/modes/fine.md", "Literal \\{{x}}.");
+        let scan = scan_modes(project.path(), None);
+        let ids: Vec<&str> = scan.defs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["/fine"]);
+        assert_eq!(scan.diagnostics.len(), 1);
+        assert_eq!(scan.diagnostics[0].id, "/broken");
+        assert!(scan.diagnostics[0].path.ends_with("broken.md"));
+        assert_eq!(scan.diagnostics[0].error.code(), "E3201");
+    }
+
+    fn dir_label(dir: &Path) -> String {
+        dir.file_name().unwrap().to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn composition_errors_fail_only_the_affected_modes() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".codex/modes/a.md",
+            "---\nextends: /b\n---\nA",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/b.md",
+            "---\nincludes: [/a]\n---\nB",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/c.md",
+            "---\nextends: /nope\n---\nC",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/d.md",
+            "---\nincludes: [/c]\n---\nD",
+        );
+        write_mode(project.path(), ".codex/modes/fine.md", "F");
+        write_mode(
+            project.path(),
+            ".codex/modes/child.md",
+            "---\nextends: /fine\n---\nchild",
+        );
+
+        let scan = scan_modes(project.path(), None);
+        let mut ids: Vec<&str> = scan.defs.iter().map(|d| d.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["/child", "/fine"]);
+
+        let mut failed: Vec<(&str, String)> = scan
+            .diagnostics
+            .iter()
+            .map(|d| (d.id.as_str(), d.message()))
+            .collect();
+        failed.sort();
+        assert_eq!(failed.len(), 4, "{failed:?}");
+        assert!(
+            failed[0].1 == "E2301 CompositionCycle: /a -> /b -> /a"
+                || failed[0].1 == "E2301 CompositionCycle: /b -> /a -> /b",
+            "{failed:?}"
+        );
+        assert_eq!(
+            failed[1].1.split(':').next(),
+            Some("E2301 CompositionCycle")
+        );
+        assert_eq!(
+            failed[2..],
+            [
+                (
+                    "/c",
+                    "E1201 UnknownMode: /nope (referenced by /c)".to_string()
+                ),
+                (
+                    "/d",
+                    "E1201 UnknownMode: /nope (referenced by /c)".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            scan.missing("/d"),
+            ModesError::UnknownMode("/nope (referenced by /c)".to_string())
+        );
+        assert_eq!(
+            scan.missing("/gone"),
+            ModesError::UnknownMode("/gone".to_string())
+        );
+    }
+
+    fn constrained_def(
+        id: &str,
+        conflicts: &[&str],
+        requires: &[&str],
+        priority: i32,
+    ) -> ModeDefinition {
+        ModeDefinition {
+            id: id.into(),
+            display_name: None,
+            description: None,
+            argument_hint: None,
+            kind: ModeKind::Persistent,
+            default_enabled: false,
+            variables: Vec::new(),
+            scope: ModeScope::Global,
+            path: PathBuf::from(id),
+            body: format!("body {id}"),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: conflicts.iter().map(ToString::to_string).collect(),
+            requires: requires.iter().map(ToString::to_string).collect(),
+            priority,
+            overrides: ModeOverrides::default(),
+        }
+    }
+
+    #[test]
+    fn enable_with_constraints_enables_requirements_and_disables_conflicts() {
+        let defs = vec![
+            constrained_def("/terse", &["/explain"], &[], 0),
+            constrained_def("/explain", &[], &["/base"], 0),
+            constrained_def("/base", &[], &[], 0),
+            constrained_def("/tutor", &[], &["/explain"], 0),
+        ];
+        let mut order = vec!["/terse".to_string()];
+        let effects = enable_with_constraints(&defs, &mut order, "/tutor");
+        assert_eq!(order, vec!["/base", "/explain", "/tutor"]);
+        assert_eq!(
+            effects,
+            ConstraintEffects {
+                auto_enabled: vec![
+                    ("/base".into(), "/explain".into()),
+                    ("/explain".into(), "/tutor".into())
+                ],
+                auto_disabled: vec![("/terse".into(), "conflicts with /explain".into())],
+            }
+        );
+
+        // enabling /terse again pushes out /explain and everything that requires it
+        let effects = enable_with_constraints(&defs, &mut order, "/terse");
+        assert_eq!(order, vec!["/base", "/terse"]);
+        assert_eq!(
+            effects.describe().as_deref(),
+            Some("disabled /explain (conflicts with /terse); disabled /tutor (requires /explain)")
+        );
+
+        let effects = disable_with_constraints(&defs, &mut order, "/base");
+        assert_eq!(order, vec!["/terse"]);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn validate_enabled_reports_conflicts_and_missing_requirements() {
+        let defs = vec![
+            constrained_def("/a", &["/b"], &["/c"], 0),
+            constrained_def("/b", &[], &[], 0),
+        ];
+        let scope = ModeScope::Global;
+        let em = |id: &'static str| EnabledMode {
+            id,
+            display_name: None,
+            scope: &scope,
+            variables: IndexMap::new(),
+        };
+        let errs = validate_enabled(&defs, &[em("/b"), em("/a")]);
+        assert_eq!(
+            errs.iter()
+                .filter_map(format_validation_error)
+                .collect::<Vec<_>>(),
+            vec![
+                "E3110 RequiresMissing: /a requires /c".to_string(),
+                "E3109 ModeConflict: /b conflicts with /a".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_orders_by_priority_then_enable_order() {
+        let defs = vec![
+            constrained_def("/low", &[], &[], -1),
+            constrained_def("/a", &[], &[], 0),
+            constrained_def("/high", &[], &[], 5),
+            constrained_def("/b", &[], &[], 0),
+        ];
+        let scope = ModeScope::Global;
+        let enabled: Vec<EnabledMode> = ["/low", "/b", "/high", "/a"]
+            .into_iter()
+            .map(|id| EnabledMode {
+                id,
+                display_name: None,
+                scope: &scope,
+                variables: IndexMap::new(),
+            })
+            .collect();
+        let out = render_user_instructions("base", &enabled, &defs).unwrap();
+        let headers: Vec<&str> = out
+            .lines()
+            .filter_map(|l| l.strip_prefix("### Mode: "))
+            .collect();
+        assert_eq!(headers, vec!["high", "b", "a", "low"]);
+    }
+
+    #[test]
+    fn modes_fingerprint_tracks_mode_files() {
+        let project = tempfile::tempdir().unwrap();
+        let empty = modes_fingerprint(project.path(), None);
+        write_mode(project.path(), ".codex/modes/a.md", "A");
+        let one = modes_fingerprint(project.path(), None);
+        assert_ne!(empty, one);
+        assert_eq!(one, modes_fingerprint(project.path(), None));
+        write_mode(project.path(), ".codex/modes/notes.txt", "ignored");
+        assert_eq!(one, modes_fingerprint(project.path(), None));
+        write_mode(project.path(), ".codex/modes/a.md", "A, but longer");
+        assert_ne!(one, modes_fingerprint(project.path(), None));
+    }
+
+    #[test]
+    fn diff_modes_reports_added_changed_removed_and_file_errors() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(project.path(), ".codex/modes/a.md", "A");
+        write_mode(project.path(), ".codex/modes/b.md", "B");
+        let before = scan_modes(project.path(), None).defs;
+        write_mode(project.path(), ".codex/modes/b.md", "B2");
+        write_mode(project.path(), ".codex/modes/c.md", "C");
+        std::fs::remove_file(project.path().join(".codex/modes/a.md")).unwrap();
+        let after = scan_modes(project.path(), None).defs;
+        let diff = diff_modes(&before, &after);
+        assert_eq!(diff.describe(), "added /c; changed /b; removed /a");
+        assert!(diff_modes(&after, &after).is_empty());
+
+        write_mode(
+            project.path(),
+            ".codex/modes/c.md",
+            "---
+kind: [
+---
+C",
+        );
+        let scan = scan_modes(project.path(), None);
+        let ids: Vec<&str> = scan.defs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["/b"]);
+        let err = scan.diagnostics[0].message();
+        assert!(err.starts_with("E2001 Frontmatter: "), "{err}");
+        assert!(err.contains("c.md: "), "{err}");
+    }
+
+    #[test]
+    fn lint_reports_every_file_and_mode_sources_track_shadowing() {
+        let home = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        write_mode(home.path(), "modes/a.md", "global A");
+        write_mode(
+            project.path(),
+            ".codex/modes/a.md",
+            "---\nvariables:\n  - name: who\n  - name: unused\n---\nHi {{who}} {{typo}}",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/b.md",
+            "---\nvariables:\n  - name: x\n    pattern: \"(\"\n  - name: x\n---\n{{x}}",
+        );
+        write_mode(project.path(), ".codex/modes/c.md", "---\nkind: [\n---\nC");
+        write_mode(
+            project.path(),
+            ".codex/modes/d.md",
+            "---\nextends: /missing\n---\nD",
+        );
+        write_mode(project.path(), ".codex/modes/bad name.md", "E");
+
+        let issues = lint_modes(project.path(), Some(home.path()));
+        let summary: Vec<(LintSeverity, &str, Option<&str>)> = issues
+            .iter()
+            .map(|i| (i.severity, i.code, i.id.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (LintSeverity::Error, "E2201", Some("/b")),
+                (LintSeverity::Error, "E2101", Some("/b")),
+                (LintSeverity::Warning, "E1001", None),
+                (LintSeverity::Error, "E2001", Some("/c")),
+                (LintSeverity::Warning, "E3203", Some("/a")),
+                (LintSeverity::Warning, "E3204", Some("/a")),
+                (LintSeverity::Error, "E1201", Some("/d")),
+            ]
+        );
+        assert_eq!(issues[4].message, "unused variable: unused");
+        assert_eq!(issues[5].message, "placeholder with no variable: typo");
+
+        let sources = mode_sources(project.path(), Some(home.path()));
+        let a: Vec<&ModeScope> = sources
+            .iter()
+            .filter(|s| s.id == "/a")
+            .map(|s| &s.scope)
+            .collect();
+        assert_eq!(a.len(), 2);
+        assert_eq!(a[0], &ModeScope::Global);
+    }
+
+    #[test]
+    fn scan_validates_and_inherits_overrides() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".codex/modes/review.md",
+            "---\noverrides:\n  effort: high\n  sandbox: read-only\n  tools:\n    exclude: [apply_patch]\n---\nR",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/deep.md",
+            "---\nextends: /review\noverrides:\n  effort: medium\n  model: o3\n---\nD",
+        );
+        let defs = scan_modes(project.path(), None).defs;
+        let deep = defs.iter().find(|d| d.id == "/deep").unwrap();
+        assert_eq!(
+            deep.overrides,
+            ModeOverrides {
+                model: Some("o3".into()),
+                effort: Some("medium".into()),
+                sandbox: Some("read-only".into()),
+                approval_policy: None,
+                tools: Some(ToolFilter {
+                    include: Vec::new(),
+                    exclude: vec!["apply_patch".into()],
+                }),
+            }
+        );
+        assert_eq!(
+            deep.overrides.describe(),
+            "model=o3, effort=medium, sandbox=read-only, tools=without(apply_patch)"
+        );
+
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".codex/modes/bad.md",
+            "---\noverrides:\n  sandbox: open\n---\nB",
+        );
+        let err = scan_modes(project.path(), None).diagnostics.remove(0).error;
+        assert_eq!(
+            format_modes_error(&err),
+            "E2401 BadOverride: /bad: sandbox=open (allowed: read-only|workspace-write|danger-full-access)"
+        );
+    }
+
+    #[test]
+    fn list_multiline_and_secret_values_validate_normalize_and_render() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".codex/modes/ship.md",
+            "---\nvariables:\n  - name: tickets\n    type: list\n    enum: [A-1, A-2, B-1]\n    default: [A-1]\n  - name: notes\n    type: multiline\n  - name: token\n    type: secret\n---\n{{#each tickets}}- {{this}}\n{{/each}}{{notes}}\nauth {{token}}\n",
+        );
+        let defs = scan_modes(project.path(), None).defs;
+        let def = &defs[0];
+        let (tickets, notes, token) = (&def.variables[0], &def.variables[1], &def.variables[2]);
+
+        assert_eq!(tickets.default_value(), Some("A-1".to_string()));
+        assert_eq!(
+            tickets.normalize_value(" A-1,,B-1\n"),
+            Some("A-1, B-1".to_string())
+        );
+        assert_eq!(validate_var_value("/ship", tickets, "A-1, A-2"), None);
+        assert_eq!(
+            validate_var_value("/ship", tickets, "A-1, C-9"),
+            Some(ValidationError::EnumMismatch {
+                mode_id: "/ship".into(),
+                var: "tickets".into(),
+                allowed: vec!["A-1".into(), "A-2".into(), "B-1".into()],
+                got: "C-9".into(),
+            })
+        );
+        assert_eq!(
+            notes.normalize_value("\nline one\nline two  \n"),
+            Some("line one\nline two".to_string())
+        );
+        assert_eq!(notes.display_value("a\nb"), "a\\nb");
+        assert_eq!(token.normalize_value("  "), None);
+        assert_eq!(token.display_value("hunter2"), SECRET_MASK);
+        assert!(is_recordable_var(&defs, "/ship", "notes"));
+        assert!(!is_recordable_var(&defs, "/ship", "token"));
+        assert!(!is_recordable_var(&defs, "/ship", "missing"));
+
+        let scope = def.scope.clone();
+        let enabled = vec![EnabledMode {
+            id: "/ship",
+            display_name: None,
+            scope: &scope,
+            variables: IndexMap::from([
+                ("tickets", Some("A-1, A-2".to_string())),
+                ("notes", Some("first\nsecond".to_string())),
+                ("token", Some("hunter2".to_string())),
+            ]),
+        }];
+        let out = render_user_instructions("base", &enabled, &defs).unwrap();
+        assert!(
+            out.contains("- variables: tickets=A-1, A-2, notes=first\\nsecond, token=••••\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("- A-1\n- A-2\nfirst\nsecond\nauth hunter2"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn scan_resolves_env_and_git_branch_defaults() {
+        let project = tempfile::tempdir().unwrap();
+        write_mode(
+            project.path(),
+            ".git/HEAD",
+            "ref: refs/heads/feature/modes\n",
+        );
+        write_mode(
+            project.path(),
+            ".codex/modes/ctx.md",
+            "---\nvariables:\n  - name: branch\n    default_from: git:branch\n  - name: path\n    default_from: env:PATH\n  - name: unset\n    default_from: env:CODEX_MODES_TEST_UNSET\n    default: fallback\n---\n{{branch}}",
+        );
+        let defs = scan_modes(project.path(), None).defs;
+        let defaults: Vec<Option<String>> = defs[0]
+            .variables
+            .iter()
+            .map(ModeVariableDefinition::default_value)
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                Some("feature/modes".to_string()),
+                std::env::var("PATH").ok(),
+                Some("fallback".to_string()),
+            ]
+        );
+
+        // detached HEAD yields no default
+        write_mode(project.path(), ".git/HEAD", "0123456789abcdef\n");
+        let defs = scan_modes(project.path(), None).defs;
+        assert_eq!(defs[0].variables[0].default_value(), None);
+
+        write_mode(
+            project.path(),
+            ".codex/modes/ctx.md",
+            "---\nvariables:\n  - name: branch\n    default_from: git:commit\n---\nB",
+        );
+        let err = scan_modes(project.path(), None).diagnostics.remove(0).error;
+        assert_eq!(err.code(), "E2104");
+        assert!(
+            format_modes_error(&err)
+                .ends_with("branch: git:commit (expected env:NAME or git:branch)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn resolve_overrides_prefers_priority_then_restrictive_then_latest() {
+        let with = |id: &str, priority: i32, overrides: ModeOverrides| ModeDefinition {
+            overrides,
+            ..constrained_def(id, &[], &[], priority)
+        };
+        let defs = vec![
+            with(
+                "/fast",
+                0,
+                ModeOverrides {
+                    effort: Some("low".into()),
+                    sandbox: Some("workspace-write".into()),
+                    ..Default::default()
+                },
+            ),
+            with(
+                "/deep",
+                0,
+                ModeOverrides {
+                    effort: Some("high".into()),
+                    sandbox: Some("read-only".into()),
+                    ..Default::default()
+                },
+            ),
+            with(
+                "/yolo",
+                1,
+                ModeOverrides {
+                    approval_policy: Some("never".into()),
+                    ..Default::default()
+                },
+            ),
+        ];
+        let order = |ids: &[&str]| ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        // equal priority: latest enabled wins effort, most restrictive wins sandbox
+        let resolved = resolve_overrides(&defs, &order(&["/deep", "/fast"]));
+        assert_eq!(resolved.overrides.effort.as_deref(), Some("low"));
+        assert_eq!(resolved.overrides.sandbox.as_deref(), Some("read-only"));
+        assert_eq!(
+            resolved.describe_conflicts().as_deref(),
+            Some(
+                "effort=low from /fast (overrides /deep); sandbox=read-only from /deep (overrides /fast)"
+            )
+        );
+
+        let resolved = resolve_overrides(&defs, &order(&["/yolo", "/fast"]));
+        assert_eq!(resolved.overrides.approval_policy.as_deref(), Some("never"));
+        assert!(resolved.conflicts.is_empty());
+
+        assert_eq!(resolve_overrides(&defs, &[]), ResolvedOverrides::default());
+    }
+
+    #[test]
+    fn resolve_overrides_takes_tools_from_the_latest_mode() {
+        let with = |id: &str, include: &[&str], exclude: &[&str]| ModeDefinition {
+            overrides: ModeOverrides {
+                tools: Some(ToolFilter {
+                    include: include.iter().map(ToString::to_string).collect(),
+                    exclude: exclude.iter().map(ToString::to_string).collect(),
+                }),
+                ..Default::default()
+            },
+            ..constrained_def(id, &[], &[], 0)
+        };
+        let defs = vec![
+            with("/explore", &["shell", "read_*"], &[]),
+            with("/no-patch", &[], &["apply_patch"]),
+        ];
+        let resolved = resolve_overrides(&defs, &["/explore".into(), "/no-patch".into()]);
+        assert_eq!(
+            resolved.overrides.tools,
+            Some(ToolFilter {
+                include: Vec::new(),
+                exclude: vec!["apply_patch".into()],
+            })
+        );
+        assert_eq!(
+            resolved.describe_conflicts().as_deref(),
+            Some("tools=without(apply_patch) from /no-patch (overrides /explore)")
+        );
+    }
+
+    #[test]
+    fn project_modes_may_only_tighten_sandbox_and_approval() {
+        let with = |id: &str, scope: ModeScope, sandbox: &str, approval: &str| ModeDefinition {
+            scope,
+            overrides: ModeOverrides {
+                sandbox: Some(sandbox.into()),
+                approval_policy: Some(approval.into()),
+                ..Default::default()
+            },
+            ..constrained_def(id, &[], &[], 0)
+        };
+        let project = || ModeScope::Project("/repo".into());
+        let mut defs = vec![
+            with("/yolo", project(), "danger-full-access", "never"),
+            with("/strict", project(), "read-only", "untrusted"),
+            with("/mine", ModeScope::Global, "danger-full-access", "never"),
+        ];
+        let session = SandboxPolicy::new_workspace_write_policy();
+        let yolo_sandbox = LooseningOverride {
+            mode_id: "/yolo".into(),
+            field: "sandbox",
+            value: "danger-full-access".into(),
+        };
+
+        let stripped = strip_loosening_overrides(
+            &mut defs,
+            &session,
+            AskForApproval::OnRequest,
+            std::slice::from_ref(&yolo_sandbox),
+        );
+        assert_eq!(
+            stripped
+                .iter()
+                .map(LooseningOverride::describe)
+                .collect::<Vec<_>>(),
+            vec!["approval_policy=never from /yolo"]
+        );
+        // confirmed loosening, tightening and global modes are left alone
+        assert_eq!(
+            defs[0].overrides.sandbox.as_deref(),
+            Some("danger-full-access")
+        );
+        assert_eq!(defs[0].overrides.approval_policy, None);
+        assert_eq!(defs[1].overrides.sandbox.as_deref(), Some("read-only"));
+        assert_eq!(
+            defs[1].overrides.approval_policy.as_deref(),
+            Some("untrusted")
+        );
+        assert_eq!(defs[2].overrides.approval_policy.as_deref(), Some("never"));
+    }
+
+    #[test]
+    fn every_allowed_override_value_parses() {
+        for v in EFFORT_VALUES {
+            assert!(parse_effort(v).is_some(), "{v}");
+        }
+        for v in SANDBOX_VALUES {
+            assert!(parse_sandbox_mode(v).is_some(), "{v}");
+            assert!(
+                parse_sandbox(v, &SandboxPolicy::DangerFullAccess).is_some(),
+                "{v}"
+            );
+        }
+        for v in APPROVAL_VALUES {
+            assert!(parse_approval(v).is_some(), "{v}");
+        }
+        assert_eq!(parse_sandbox_mode("full"), None);
+    }
+
+    #[test]
+    fn parse_sandbox_keeps_read_restrictions_of_the_session() {
+        let session = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/work/out")],
+            network_access: true,
+            network_allowlist: vec![],
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            readable_roots: vec![PathBuf::from("/work")],
+            deny_read_paths: vec![PathBuf::from("/work/.env")],
+        };
+        assert_eq!(
+            parse_sandbox("read-only", &session),
+            Some(SandboxPolicy::ReadOnly {
+                readable_roots: vec![PathBuf::from("/work")],
+                deny_read_paths: vec![PathBuf::from("/work/.env")],
+            })
+        );
+        assert_eq!(parse_sandbox("workspace-write", &session), Some(session));
+
+        let read_only = SandboxPolicy::ReadOnly {
+            readable_roots: vec![],
+            deny_read_paths: vec![PathBuf::from("/home/me/.ssh")],
+        };
+        assert_eq!(
+            parse_sandbox("workspace-write", &read_only)
+                .as_ref()
+                .map(SandboxPolicy::deny_read_paths),
+            Some(&[PathBuf::from("/home/me/.ssh")][..])
+        );
+    }
+
     #[test]
     fn debounce_gen_monotonic_and_latest() {
         let g = DebounceGen::new();
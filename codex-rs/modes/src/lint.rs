@@ -0,0 +1,236 @@
+//! Whole-tree diagnostics for mode files. Unlike `scan_modes`, which only
+//! reports files that fail to load, warnings (unused variables, placeholders
+//! that name no variable, ...) are returned too so CI can report them in one
+//! pass.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use regex::Regex;
+
+use crate::ModeScope;
+use crate::ModesError;
+use crate::ScannedMode;
+use crate::Template;
+use crate::compose_mode;
+use crate::id_from_rel_path;
+use crate::mode_search_dirs;
+use crate::parse_frontmatter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+impl LintSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+        }
+    }
+}
+
+/// One finding for a mode file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// Stable error code, e.g. `E2201`.
+    pub code: &'static str,
+    pub path: PathBuf,
+    /// Mode id derived from the file name, when it is a legal id.
+    pub id: Option<String>,
+    pub message: String,
+}
+
+/// A mode file found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeSource {
+    pub id: String,
+    pub scope: ModeScope,
+    pub path: PathBuf,
+}
+
+/// Every mode file `scan_modes` would read, lowest precedence first. A later
+/// entry with the same id shadows the earlier ones.
+pub fn mode_sources(cwd: &Path, codex_home: Option<&Path>) -> Vec<ModeSource> {
+    let mut out = Vec::new();
+    for (modes_dir, scope) in mode_search_dirs(cwd, codex_home) {
+        for path in mode_files(&modes_dir) {
+            let Some(id) = path
+                .strip_prefix(&modes_dir)
+                .ok()
+                .and_then(id_from_rel_path)
+            else {
+                continue;
+            };
+            out.push(ModeSource {
+                id,
+                scope: scope.clone(),
+                path,
+            });
+        }
+    }
+    out
+}
+
+/// Check every mode file visible from `cwd`: frontmatter, duplicate variables,
+/// bad regexes, overrides, template syntax, composition, unused variables and
+/// placeholders that name no variable. Errors are what `scan_modes` would
+/// reject; warnings still load.
+pub fn lint_modes(cwd: &Path, codex_home: Option<&Path>) -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = Vec::new();
+    let mut scanned: Vec<ScannedMode> = Vec::new();
+    for (modes_dir, scope) in mode_search_dirs(cwd, codex_home) {
+        for path in mode_files(&modes_dir) {
+            let id = path
+                .strip_prefix(&modes_dir)
+                .ok()
+                .and_then(id_from_rel_path);
+            let Some(id) = id else {
+                issues.push(LintIssue {
+                    severity: LintSeverity::Warning,
+                    code: "E1001",
+                    path,
+                    id: None,
+                    message: "file name is not a legal mode id; the file is ignored".to_string(),
+                });
+                continue;
+            };
+            if let Some(mode) = lint_file(&path, id, &scope, &mut issues) {
+                scanned.push(mode);
+            }
+        }
+    }
+
+    let mut stacks: IndexMap<&str, Vec<usize>> = IndexMap::new();
+    for (idx, mode) in scanned.iter().enumerate() {
+        stacks.entry(mode.def.id.as_str()).or_default().push(idx);
+    }
+    let mut memo = HashMap::new();
+    for (idx, mode) in scanned.iter().enumerate() {
+        let own = &mode.def;
+        let issue = |severity, code, message| LintIssue {
+            severity,
+            code,
+            path: own.path.clone(),
+            id: Some(own.id.clone()),
+            message,
+        };
+        let composed = match compose_mode(idx, &scanned, &stacks, &mut memo, &mut Vec::new()) {
+            Ok(def) => def,
+            Err(err) => {
+                issues.push(issue(LintSeverity::Error, err.code(), err.to_string()));
+                continue;
+            }
+        };
+        let Ok(own_template) = Template::parse(&own.body) else {
+            // already reported by `lint_file`
+            continue;
+        };
+        let own_names = own_template.referenced_names();
+        let mut used: IndexSet<String> = own_names.clone();
+        for base in &composed.inherited {
+            if let Ok(template) = Template::parse(&base.body) {
+                used.extend(template.referenced_names());
+            }
+        }
+        for v in &own.variables {
+            if !used.contains(&v.name) {
+                issues.push(issue(
+                    LintSeverity::Warning,
+                    "E3203",
+                    format!("unused variable: {}", v.name),
+                ));
+            }
+        }
+        for name in own_names {
+            if !composed.variables.iter().any(|v| v.name == name) {
+                issues.push(issue(
+                    LintSeverity::Warning,
+                    "E3204",
+                    format!("placeholder with no variable: {name}"),
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Per-file checks. Returns the parsed mode unless the file could not be read
+/// or its frontmatter did not parse.
+fn lint_file(
+    path: &Path,
+    id: String,
+    scope: &ModeScope,
+    issues: &mut Vec<LintIssue>,
+) -> Option<ScannedMode> {
+    let mut report = |err: ModesError| {
+        issues.push(LintIssue {
+            severity: LintSeverity::Error,
+            code: err.code(),
+            path: path.to_path_buf(),
+            id: Some(id.clone()),
+            message: err.to_string(),
+        });
+    };
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            report(ModesError::Io(e.to_string()));
+            return None;
+        }
+    };
+    let (fm, body) = match parse_frontmatter(&text) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            report(err);
+            return None;
+        }
+    };
+    let fm = fm.unwrap_or_default();
+    let mut names = HashSet::new();
+    for v in fm.variables.iter().flatten() {
+        if !names.insert(v.name.as_str()) {
+            report(ModesError::VarDup(v.name.clone()));
+        }
+        if let Some(pat) = &v.pattern
+            && let Err(e) = Regex::new(pat)
+        {
+            report(ModesError::Regex(format!("{}: {e}", v.name)));
+        }
+        if let Err(err) = v.validate_default_from() {
+            report(err);
+        }
+    }
+    if let Err(e) = Template::parse(&body) {
+        report(ModesError::template(&id, e));
+    }
+    if let Some(overrides) = &fm.overrides
+        && let Err(err) = overrides.validate(&id)
+    {
+        report(err);
+    }
+    Some(ScannedMode::new(id, scope, path, fm, body))
+}
+
+/// `.md` files under `modes_dir`, sorted for stable output.
+fn mode_files(modes_dir: &Path) -> Vec<PathBuf> {
+    if !modes_dir.is_dir() {
+        return Vec::new();
+    }
+    walkdir::WalkDir::new(modes_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("md"))
+        .map(walkdir::DirEntry::into_path)
+        .collect()
+}
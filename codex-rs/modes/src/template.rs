@@ -0,0 +1,804 @@
+//! Small template layer for mode bodies.
+//!
+//! Supported syntax:
+//! - `{{name}}` substitutes a variable; placeholders naming an undeclared
+//!   variable are emitted verbatim so existing bodies keep rendering as before.
+//! - `{{name | default("x") | upper}}` applies filters left to right. Filters:
+//!   `default(v)`, `upper`, `lower`, `trim`, `escape`, `join(sep)`.
+//! - `{{#if cond}}…{{else}}…{{/if}}` where `cond` is `name`, `!name`,
+//!   `name == "v"` or `name != "v"`.
+//! - `{{#each name}}…{{/each}}` iterates comma/newline separated values;
+//!   `{{#each name.options}}` iterates the allowed values of an enum variable.
+//!   Inside a loop `{{this}}`, `{{@index}}`, `{{@first}}` and `{{@last}}` are
+//!   available.
+//! - `{{! comment }}` is dropped and `\{{` emits a literal `{{`.
+//!
+//! Block tags that sit alone on a line remove that whole line from the output
+//! so conditionals do not leave stray blank lines behind.
+
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TemplateError {
+    /// Malformed or unbalanced tags.
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    /// A filter name that is not part of the supported set.
+    #[error("line {line}: unknown filter `{name}`")]
+    UnknownFilter { line: usize, name: String },
+}
+
+/// Values visible to a template while rendering.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    values: IndexMap<String, Option<String>>,
+    options: IndexMap<String, Vec<String>>,
+}
+
+impl TemplateContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a variable with its resolved value (`None` = unset).
+    pub fn insert(&mut self, name: impl Into<String>, value: Option<String>) {
+        self.values.insert(name.into(), value);
+    }
+
+    /// Record the allowed values of an enum variable for `{{#each name.options}}`.
+    pub fn insert_options(&mut self, name: impl Into<String>, options: Vec<String>) {
+        self.options.insert(name.into(), options);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Default(String),
+    Upper,
+    Lower,
+    Trim,
+    Escape,
+    Join(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cond {
+    Truthy(String),
+    Falsy(String),
+    Eq(String, String),
+    Ne(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Var {
+        path: String,
+        filters: Vec<Filter>,
+        raw: String,
+    },
+    If {
+        cond: Cond,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TagKind {
+    Expr {
+        path: String,
+        filters: Vec<Filter>,
+        raw: String,
+    },
+    IfOpen(Cond),
+    Else,
+    IfClose,
+    EachOpen(String),
+    EachClose,
+    Comment,
+}
+
+impl TagKind {
+    fn is_block(&self) -> bool {
+        !matches!(self, TagKind::Expr { .. })
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Tag { kind: TagKind, line: usize },
+}
+
+/// Appended to errors about text that may have been meant literally.
+const LITERAL_HINT: &str = "write `\\{{` for a literal `{{`";
+
+/// A parsed mode body ready to be rendered against a [`TemplateContext`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(src: &str) -> Result<Self, TemplateError> {
+        let mut tokens = tokenize(src)?;
+        strip_standalone_lines(&mut tokens);
+        let mut iter = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut iter)?;
+        if let Some((kind, line)) = end {
+            return Err(TemplateError::Syntax {
+                line,
+                message: format!("unexpected {}", describe_tag(&kind)),
+            });
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, ctx: &TemplateContext) -> String {
+        let mut out = String::new();
+        let mut frames: Vec<LoopFrame> = Vec::new();
+        render_nodes(&self.nodes, ctx, &mut frames, &mut out);
+        out
+    }
+
+    /// Variable names referenced by placeholders, conditions and `#each`
+    /// targets, in first-use order. Loop names (`this`, `@index`, ...) are
+    /// excluded and `name.options` counts as a use of `name`.
+    pub fn referenced_names(&self) -> IndexSet<String> {
+        let mut names = IndexSet::new();
+        collect_names(&self.nodes, &mut names);
+        names
+    }
+}
+
+fn collect_names(nodes: &[Node], names: &mut IndexSet<String>) {
+    fn add(names: &mut IndexSet<String>, path: &str) {
+        let name = path.strip_suffix(".options").unwrap_or(path);
+        if name != "this" && !name.starts_with('@') {
+            names.insert(name.to_string());
+        }
+    }
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var { path, .. } => add(names, path),
+            Node::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                match cond {
+                    Cond::Truthy(p) | Cond::Falsy(p) | Cond::Eq(p, _) | Cond::Ne(p, _) => {
+                        add(names, p)
+                    }
+                }
+                collect_names(then, names);
+                collect_names(otherwise, names);
+            }
+            Node::Each { path, body } => {
+                add(names, path);
+                collect_names(body, names);
+            }
+        }
+    }
+}
+
+fn line_of(src: &str, byte_idx: usize) -> usize {
+    src[..byte_idx].matches('\n').count() + 1
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut text = String::new();
+    let mut rest = src;
+    let mut offset = 0usize;
+    while let Some(idx) = rest.find("{{") {
+        if rest[..idx].ends_with('\\') {
+            text.push_str(&rest[..idx - 1]);
+            text.push_str("{{");
+            rest = &rest[idx + 2..];
+            offset += idx + 2;
+            continue;
+        }
+        text.push_str(&rest[..idx]);
+        let line = line_of(src, offset + idx);
+        let after_open = &rest[idx + 2..];
+        let Some(close) = after_open.find("}}") else {
+            return Err(TemplateError::Syntax {
+                line,
+                message: format!("unclosed `{{{{` ({LITERAL_HINT})"),
+            });
+        };
+        let inner = &after_open[..close];
+        let raw = format!("{{{{{inner}}}}}");
+        let kind = parse_tag(inner.trim(), raw, line)?;
+        if !text.is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+        tokens.push(Token::Tag { kind, line });
+        let consumed = idx + 2 + close + 2;
+        rest = &rest[consumed..];
+        offset += consumed;
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(inner: &str, raw: String, line: usize) -> Result<TagKind, TemplateError> {
+    let syntax = |message: String| TemplateError::Syntax { line, message };
+    if inner.starts_with('!') {
+        return Ok(TagKind::Comment);
+    }
+    if let Some(rest) = inner.strip_prefix('#') {
+        let (keyword, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let arg = arg.trim();
+        return match keyword {
+            "if" => Ok(TagKind::IfOpen(parse_cond(arg).map_err(syntax)?)),
+            "each" => {
+                if !is_path(arg) {
+                    return Err(syntax(format!("invalid `#each` target `{arg}`")));
+                }
+                Ok(TagKind::EachOpen(arg.to_string()))
+            }
+            other => Err(syntax(format!("unknown block `#{other}`"))),
+        };
+    }
+    if let Some(rest) = inner.strip_prefix('/') {
+        return match rest.trim() {
+            "if" => Ok(TagKind::IfClose),
+            "each" => Ok(TagKind::EachClose),
+            other => Err(syntax(format!("unknown closing tag `/{other}`"))),
+        };
+    }
+    if inner == "else" {
+        return Ok(TagKind::Else);
+    }
+
+    let parts = split_unquoted(inner, '|');
+    let path = parts.first().map(|s| s.trim()).unwrap_or_default();
+    if !is_path(path) {
+        return Err(syntax(format!(
+            "invalid expression `{inner}` ({LITERAL_HINT})"
+        )));
+    }
+    let mut filters = Vec::new();
+    for part in parts.iter().skip(1) {
+        filters.push(parse_filter(part.trim(), line)?);
+    }
+    Ok(TagKind::Expr {
+        path: path.to_string(),
+        filters,
+        raw,
+    })
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn is_path(s: &str) -> bool {
+    if matches!(s, "@index" | "@first" | "@last") {
+        return true;
+    }
+    match s.split_once('.') {
+        Some((name, "options")) => is_ident(name),
+        Some(_) => false,
+        None => is_ident(s),
+    }
+}
+
+/// Split on `sep` while ignoring separators inside single or double quotes.
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0usize;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == sep => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_literal(s: &str) -> Option<String> {
+    let s = s.trim();
+    let quote = s.chars().next()?;
+    if (quote == '"' || quote == '\'') && s.len() >= 2 && s.ends_with(quote) {
+        Some(s[1..s.len() - 1].to_string())
+    } else {
+        None
+    }
+}
+
+fn parse_cond(arg: &str) -> Result<Cond, String> {
+    let invalid = || format!("invalid condition `{arg}`");
+    let literal =
+        |s: &str| parse_literal(s).ok_or_else(|| format!("expected quoted literal in `{arg}`"));
+    // `a == "b"` splits into ["a ", "", " \"b\""]; `a != "b"` into ["a !", " \"b\""].
+    let parts = split_unquoted(arg, '=');
+    match parts.as_slice() {
+        [lhs, "", rhs] => {
+            let lhs = lhs.trim();
+            if !is_path(lhs) {
+                return Err(invalid());
+            }
+            Ok(Cond::Eq(lhs.to_string(), literal(rhs)?))
+        }
+        [lhs, rhs] => {
+            let Some(lhs) = lhs.trim().strip_suffix('!').map(str::trim) else {
+                return Err(invalid());
+            };
+            if !is_path(lhs) {
+                return Err(invalid());
+            }
+            Ok(Cond::Ne(lhs.to_string(), literal(rhs)?))
+        }
+        [single] => match single.strip_prefix('!').map(str::trim) {
+            Some(name) if is_path(name) => Ok(Cond::Falsy(name.to_string())),
+            None if is_path(single) => Ok(Cond::Truthy(single.to_string())),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_filter(s: &str, line: usize) -> Result<Filter, TemplateError> {
+    let (name, arg) = match s.split_once('(') {
+        Some((name, rest)) => {
+            let Some(inner) = rest.trim_end().strip_suffix(')') else {
+                return Err(TemplateError::Syntax {
+                    line,
+                    message: format!("unclosed `(` in filter `{s}`"),
+                });
+            };
+            let Some(lit) = parse_literal(inner) else {
+                return Err(TemplateError::Syntax {
+                    line,
+                    message: format!("expected quoted literal in filter `{s}`"),
+                });
+            };
+            (name.trim(), Some(lit))
+        }
+        None => (s, None),
+    };
+    let missing_arg = || TemplateError::Syntax {
+        line,
+        message: format!("filter `{name}` requires an argument"),
+    };
+    match name {
+        "default" => Ok(Filter::Default(arg.ok_or_else(missing_arg)?)),
+        "join" => Ok(Filter::Join(arg.ok_or_else(missing_arg)?)),
+        "upper" => Ok(Filter::Upper),
+        "lower" => Ok(Filter::Lower),
+        "trim" => Ok(Filter::Trim),
+        "escape" => Ok(Filter::Escape),
+        other => Err(TemplateError::UnknownFilter {
+            line,
+            name: other.to_string(),
+        }),
+    }
+}
+
+/// Drop the surrounding whitespace and newline of block tags that occupy a line on their own.
+fn strip_standalone_lines(tokens: &mut [Token]) {
+    for i in 0..tokens.len() {
+        let Token::Tag { kind, .. } = &tokens[i] else {
+            continue;
+        };
+        if !kind.is_block() {
+            continue;
+        }
+        let prev_ok = match i.checked_sub(1).map(|p| &tokens[p]) {
+            None => true,
+            Some(Token::Text(t)) => {
+                let tail = t.rsplit('\n').next().unwrap_or_default();
+                tail.chars().all(|c| c == ' ' || c == '\t') && (t.contains('\n') || i == 1)
+            }
+            Some(Token::Tag { .. }) => false,
+        };
+        let next_ok = match tokens.get(i + 1) {
+            None => true,
+            Some(Token::Text(t)) => match t.find('\n') {
+                Some(nl) => t[..nl]
+                    .trim_end_matches('\r')
+                    .chars()
+                    .all(|c| c == ' ' || c == '\t'),
+                None => i + 2 == tokens.len() && t.chars().all(|c| c == ' ' || c == '\t'),
+            },
+            Some(Token::Tag { .. }) => false,
+        };
+        if !(prev_ok && next_ok) {
+            continue;
+        }
+        if i > 0
+            && let Token::Text(t) = &mut tokens[i - 1]
+        {
+            let keep = t.rfind('\n').map(|nl| nl + 1).unwrap_or(0);
+            t.truncate(keep);
+        }
+        if let Some(Token::Text(t)) = tokens.get_mut(i + 1) {
+            let drop = t.find('\n').map(|nl| nl + 1).unwrap_or(t.len());
+            t.drain(..drop);
+        }
+    }
+}
+
+type TagEnd = Option<(TagKind, usize)>;
+
+/// Parse nodes until a closing/else tag (returned) or end of input.
+fn parse_nodes(
+    iter: &mut impl Iterator<Item = Token>,
+) -> Result<(Vec<Node>, TagEnd), TemplateError> {
+    let mut nodes = Vec::new();
+    while let Some(tok) = iter.next() {
+        match tok {
+            Token::Text(t) => {
+                if !t.is_empty() {
+                    nodes.push(Node::Text(t));
+                }
+            }
+            Token::Tag { kind, line } => match kind {
+                TagKind::Comment => {}
+                TagKind::Expr { path, filters, raw } => {
+                    nodes.push(Node::Var { path, filters, raw })
+                }
+                TagKind::IfOpen(cond) => {
+                    let (then, end) = parse_nodes(iter)?;
+                    let otherwise = match end {
+                        Some((TagKind::IfClose, _)) => Vec::new(),
+                        Some((TagKind::Else, else_line)) => {
+                            let (otherwise, end) = parse_nodes(iter)?;
+                            match end {
+                                Some((TagKind::IfClose, _)) => otherwise,
+                                Some((kind, l)) => {
+                                    return Err(TemplateError::Syntax {
+                                        line: l,
+                                        message: format!(
+                                            "expected `{{{{/if}}}}`, found {}",
+                                            describe_tag(&kind)
+                                        ),
+                                    });
+                                }
+                                None => {
+                                    return Err(TemplateError::Syntax {
+                                        line: else_line,
+                                        message: "unclosed `{{#if}}`".to_string(),
+                                    });
+                                }
+                            }
+                        }
+                        Some((kind, l)) => {
+                            return Err(TemplateError::Syntax {
+                                line: l,
+                                message: format!(
+                                    "expected `{{{{/if}}}}`, found {}",
+                                    describe_tag(&kind)
+                                ),
+                            });
+                        }
+                        None => {
+                            return Err(TemplateError::Syntax {
+                                line,
+                                message: "unclosed `{{#if}}`".to_string(),
+                            });
+                        }
+                    };
+                    nodes.push(Node::If {
+                        cond,
+                        then,
+                        otherwise,
+                    });
+                }
+                TagKind::EachOpen(path) => {
+                    let (body, end) = parse_nodes(iter)?;
+                    match end {
+                        Some((TagKind::EachClose, _)) => nodes.push(Node::Each { path, body }),
+                        Some((kind, l)) => {
+                            return Err(TemplateError::Syntax {
+                                line: l,
+                                message: format!(
+                                    "expected `{{{{/each}}}}`, found {}",
+                                    describe_tag(&kind)
+                                ),
+                            });
+                        }
+                        None => {
+                            return Err(TemplateError::Syntax {
+                                line,
+                                message: "unclosed `{{#each}}`".to_string(),
+                            });
+                        }
+                    }
+                }
+                end @ (TagKind::Else | TagKind::IfClose | TagKind::EachClose) => {
+                    return Ok((nodes, Some((end, line))));
+                }
+            },
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn describe_tag(kind: &TagKind) -> &'static str {
+    match kind {
+        TagKind::Else => "`{{else}}`",
+        TagKind::IfClose => "`{{/if}}`",
+        TagKind::EachClose => "`{{/each}}`",
+        TagKind::IfOpen(_) => "`{{#if}}`",
+        TagKind::EachOpen(_) => "`{{#each}}`",
+        TagKind::Expr { .. } => "expression",
+        TagKind::Comment => "comment",
+    }
+}
+
+struct LoopFrame {
+    item: String,
+    index: usize,
+    len: usize,
+}
+
+/// Split a list-like value on commas and newlines, dropping empty items.
+pub fn split_list(value: &str) -> Vec<String> {
+    value
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_truthy(value: Option<&str>) -> bool {
+    match value.map(str::trim) {
+        None | Some("") => false,
+        Some(v) => !(v.eq_ignore_ascii_case("false") || v == "0"),
+    }
+}
+
+/// Resolve a path to its scalar value. The outer `Option` reports whether the
+/// path is known at all; the inner one whether it currently has a value.
+fn lookup(path: &str, ctx: &TemplateContext, frames: &[LoopFrame]) -> Option<Option<String>> {
+    let frame = frames.last();
+    match path {
+        "this" if frame.is_some() => return frame.map(|f| Some(f.item.clone())),
+        "@index" => return frame.map(|f| Some(f.index.to_string())),
+        "@first" => return frame.map(|f| Some((f.index == 0).to_string())),
+        "@last" => return frame.map(|f| Some((f.index + 1 == f.len).to_string())),
+        _ => {}
+    }
+    if let Some((name, "options")) = path.split_once('.') {
+        return ctx.options.get(name).map(|o| Some(o.join(", ")));
+    }
+    ctx.values.get(path).cloned()
+}
+
+fn lookup_list(path: &str, ctx: &TemplateContext, frames: &[LoopFrame]) -> Vec<String> {
+    if let Some((name, "options")) = path.split_once('.') {
+        return ctx.options.get(name).cloned().unwrap_or_default();
+    }
+    lookup(path, ctx, frames)
+        .flatten()
+        .map(|v| split_list(&v))
+        .unwrap_or_default()
+}
+
+fn apply_filters(value: Option<String>, filters: &[Filter]) -> String {
+    let mut cur = value;
+    for f in filters {
+        cur = match f {
+            Filter::Default(d) => match cur {
+                Some(v) if !v.is_empty() => Some(v),
+                _ => Some(d.clone()),
+            },
+            Filter::Upper => cur.map(|v| v.to_uppercase()),
+            Filter::Lower => cur.map(|v| v.to_lowercase()),
+            Filter::Trim => cur.map(|v| v.trim().to_string()),
+            Filter::Escape => cur.map(|v| escape_markup(&v)),
+            Filter::Join(sep) => cur.map(|v| split_list(&v).join(sep)),
+        };
+    }
+    cur.unwrap_or_default()
+}
+
+/// Escape characters that could otherwise open or close tags in `<user_instructions>`.
+fn escape_markup(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn eval_cond(cond: &Cond, ctx: &TemplateContext, frames: &[LoopFrame]) -> bool {
+    let get = |path: &str| lookup(path, ctx, frames).flatten();
+    match cond {
+        Cond::Truthy(p) => is_truthy(get(p).as_deref()),
+        Cond::Falsy(p) => !is_truthy(get(p).as_deref()),
+        Cond::Eq(p, lit) => get(p).unwrap_or_default() == *lit,
+        Cond::Ne(p, lit) => get(p).unwrap_or_default() != *lit,
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    ctx: &TemplateContext,
+    frames: &mut Vec<LoopFrame>,
+    out: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => out.push_str(t),
+            Node::Var { path, filters, raw } => match lookup(path, ctx, frames) {
+                Some(value) => out.push_str(&apply_filters(value, filters)),
+                None if filters.is_empty() => out.push_str(raw),
+                None => out.push_str(&apply_filters(None, filters)),
+            },
+            Node::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let branch = if eval_cond(cond, ctx, frames) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, ctx, frames, out);
+            }
+            Node::Each { path, body } => {
+                let items = lookup_list(path, ctx, frames);
+                let len = items.len();
+                for (index, item) in items.into_iter().enumerate() {
+                    frames.push(LoopFrame { item, index, len });
+                    render_nodes(body, ctx, frames, out);
+                    frames.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn ctx(pairs: &[(&str, Option<&str>)]) -> TemplateContext {
+        let mut c = TemplateContext::new();
+        for (k, v) in pairs {
+            c.insert(*k, v.map(str::to_string));
+        }
+        c
+    }
+
+    fn render(src: &str, c: &TemplateContext) -> String {
+        Template::parse(src).unwrap().render(c)
+    }
+
+    #[test]
+    fn substitutes_and_keeps_unknown_placeholders() {
+        let c = ctx(&[("who", Some("world")), ("empty", None)]);
+        assert_eq!(
+            render("Hello {{who}}, {{ who }}! [{{empty}}] {{other}}", &c),
+            "Hello world, world! [] {{other}}"
+        );
+    }
+
+    #[test]
+    fn if_else_and_comparisons() {
+        let c = ctx(&[("strict", Some("true")), ("level", Some("high"))]);
+        assert_eq!(
+            render("{{#if strict}}S{{else}}L{{/if}}", &c),
+            "S".to_string()
+        );
+        assert_eq!(render("{{#if !strict}}S{{else}}L{{/if}}", &c), "L");
+        assert_eq!(render("{{#if level == \"high\"}}H{{/if}}", &c), "H");
+        assert_eq!(render("{{#if level != 'high'}}H{{/if}}", &c), "");
+        let off = ctx(&[("strict", Some("false"))]);
+        assert_eq!(render("{{#if strict}}S{{else}}L{{/if}}", &off), "L");
+    }
+
+    #[test]
+    fn standalone_block_lines_are_removed() {
+        let c = ctx(&[("strict", Some("true"))]);
+        let src = "Intro\n{{#if strict}}\nBe strict.\n{{else}}\nBe lenient.\n{{/if}}\nOutro\n";
+        assert_eq!(render(src, &c), "Intro\nBe strict.\nOutro\n");
+    }
+
+    #[test]
+    fn each_over_list_and_enum_options() {
+        let mut c = ctx(&[("tickets", Some("A-1, B-2,C-3"))]);
+        c.insert_options("level", vec!["low".into(), "high".into()]);
+        let src = "{{#each tickets}}{{@index}}:{{this}}{{#if !@last}}; {{/if}}{{/each}}";
+        assert_eq!(render(src, &c), "0:A-1; 1:B-2; 2:C-3");
+        assert_eq!(
+            render("{{#each level.options}}[{{this | upper}}]{{/each}}", &c),
+            "[LOW][HIGH]"
+        );
+    }
+
+    #[test]
+    fn filters_default_escape_join() {
+        let c = ctx(&[("x", None), ("tags", Some("a,b")), ("html", Some("<b>&"))]);
+        assert_eq!(render("{{x | default(\"none\")}}", &c), "none");
+        assert_eq!(render("{{tags | join(\" / \")}}", &c), "a / b");
+        assert_eq!(render("{{html | escape}}", &c), "&lt;b&gt;&amp;");
+        assert_eq!(render("\\{{x}} {{! hidden }}", &c), "{{x}} ");
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_line() {
+        assert_eq!(
+            Template::parse("a\n{{#if x}}\nb"),
+            Err(TemplateError::Syntax {
+                line: 2,
+                message: "unclosed `{{#if}}`".into()
+            })
+        );
+        assert_eq!(
+            Template::parse("{{/each}}"),
+            Err(TemplateError::Syntax {
+                line: 1,
+                message: "unexpected `{{/each}}`".into()
+            })
+        );
+        assert_eq!(
+            Template::parse("x {{ y"),
+            Err(TemplateError::Syntax {
+                line: 1,
+                message: "unclosed `{{` (write `\\{{` for a literal `{{`)".into()
+            })
+        );
+        assert_eq!(
+            Template::parse("write {{ two words }} here"),
+            Err(TemplateError::Syntax {
+                line: 1,
+                message: "invalid expression `two words` (write `\\{{` for a literal `{{`)".into()
+            })
+        );
+        assert_eq!(
+            Template::parse("{{y | shout}}"),
+            Err(TemplateError::UnknownFilter {
+                line: 1,
+                name: "shout".into()
+            })
+        );
+    }
+
+    #[test]
+    fn referenced_names_cover_conditions_and_loops() {
+        let t = Template::parse(
+            "{{#if !a}}{{b | upper}}{{/if}}{{#each c.options}}{{this}}{{@index}}{{/each}}{{! d }}\\{{e}}",
+        )
+        .unwrap();
+        let names: Vec<String> = t.referenced_names().into_iter().collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+}
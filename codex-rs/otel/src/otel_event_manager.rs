@@ -361,6 +361,35 @@ impl OtelEventManager {
         );
     }
 
+    pub fn tool_hook(
+        &self,
+        hook_event: &str,
+        tool_name: &str,
+        call_id: &str,
+        command: &str,
+        outcome: &str,
+        duration: Duration,
+    ) {
+        tracing::event!(
+            tracing::Level::INFO,
+            event.name = "codex.tool_hook",
+            event.timestamp = %timestamp(),
+            conversation.id = %self.metadata.conversation_id,
+            app.version = %self.metadata.app_version,
+            auth_mode = self.metadata.auth_mode,
+            user.account_id = self.metadata.account_id,
+            terminal.type = %self.metadata.terminal_type,
+            model = %self.metadata.model,
+            slug = %self.metadata.slug,
+            hook_event = %hook_event,
+            tool_name = %tool_name,
+            call_id = %call_id,
+            command = %command,
+            outcome = %outcome,
+            duration_ms = %duration.as_millis(),
+        );
+    }
+
     pub async fn log_tool_result<F, Fut, E>(
         &self,
         tool_name: &str,
@@ -59,3 +59,24 @@ pub enum SandboxMode {
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
 }
+
+/// Limits the tools exposed to the model. Patterns are tool names with `*`
+/// and `?` wildcards (e.g. `apply_patch`, `read_*`); `<server>/<tool>`
+/// patterns match the tools of an MCP server (e.g. `github/delete_*`).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default, Serialize, TS)]
+pub struct ToolFilter {
+    /// When non-empty, only tools matching one of these patterns are exposed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
+    /// Tools matching any of these patterns are hidden, even if included.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl ToolFilter {
+    /// True when the filter exposes every tool.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
@@ -3,6 +3,7 @@
 //! Uses a SQ (Submission Queue) / EQ (Event Queue) pattern to asynchronously communicate
 //! between user and agent.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
@@ -13,6 +14,7 @@ use std::time::Duration;
 use crate::ConversationId;
 use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
+use crate::config_types::ToolFilter;
 use crate::custom_prompts::CustomPrompt;
 use crate::message_history::HistoryEntry;
 use crate::models::ContentItem;
@@ -131,6 +133,10 @@ pub enum Op {
         /// in the conversation history without triggering a model turn.
         #[serde(skip_serializing_if = "Option::is_none")]
         user_instructions: Option<String>,
+
+        /// Replace the filter limiting which tools are exposed to the model.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tool_filter: Option<ToolFilter>,
     },
 
     /// Approve a command execution
@@ -180,6 +186,11 @@ pub enum Op {
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
+    /// Record the client's enabled custom modes and variable values in the
+    /// rollout so resume and fork can restore them. Does not change the turn
+    /// context; clients apply the rendered instructions via `OverrideTurnContext`.
+    RecordModeState { mode_state: ModeStateItem },
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -221,9 +232,21 @@ pub enum SandboxPolicy {
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
 
-    /// Read-only access to the entire file-system.
+    /// Read-only access to the entire file-system, unless `readable_roots` or
+    /// `deny_read_paths` narrow it down.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// When non-empty, only these folders (plus the working directory
+        /// and [`PLATFORM_READABLE_ROOTS`]) are readable from within the
+        /// sandbox instead of the entire file-system.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        readable_roots: Vec<PathBuf>,
+
+        /// Paths that must not be read from within the sandbox, even when
+        /// they sit under a readable root (e.g. `~/.ssh`).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read_paths: Vec<PathBuf>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -239,6 +262,13 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// Hosts the sandbox may still reach when `network_access` is `false`,
+        /// as `host` or `host:port` (`*.example.com` matches subdomains).
+        /// Only enforced by the Linux sandbox, which routes traffic through a
+        /// filtering HTTP proxy; other platforms keep the network blocked.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        network_allowlist: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -249,9 +279,43 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// Same as for `ReadOnly`; writable roots are always readable.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        readable_roots: Vec<PathBuf>,
+
+        /// Same as for `ReadOnly`, including inside writable roots: files
+        /// can still be created next to a denied path there, but the denied
+        /// path itself cannot be read.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read_paths: Vec<PathBuf>,
     },
 }
 
+/// System folders that stay readable when a policy sets `readable_roots`, so
+/// that programs, shared libraries and basic configuration can still be
+/// loaded. Folders that do not exist on the current platform are ignored.
+pub const PLATFORM_READABLE_ROOTS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/etc",
+    "/opt",
+    "/dev",
+    "/proc",
+    "/sys",
+    "/run",
+    "/nix",
+    "/System",
+    "/Library",
+    "/Applications",
+    "/private/etc",
+    "/private/var/db",
+];
+
 /// A writable root path accompanied by a list of subpaths that should remain
 /// read‑only even when the root is writable. This is primarily used to ensure
 /// top‑level VCS metadata directories (e.g. `.git`) under a writable root are
@@ -294,7 +358,10 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            readable_roots: vec![],
+            deny_read_paths: vec![],
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -304,20 +371,33 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            network_allowlist: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            readable_roots: vec![],
+            deny_read_paths: vec![],
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        match self {
+            SandboxPolicy::DangerFullAccess => true,
+            SandboxPolicy::ReadOnly {
+                readable_roots,
+                deny_read_paths,
+            }
+            | SandboxPolicy::WorkspaceWrite {
+                readable_roots,
+                deny_read_paths,
+                ..
+            } => readable_roots.is_empty() && deny_read_paths.is_empty(),
+        }
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -325,23 +405,111 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
 
+    /// Hosts reachable through the sandbox's filtering proxy. Empty unless the
+    /// policy restricts network access and configures an allowlist.
+    pub fn network_allowlist(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                network_allowlist,
+                ..
+            } => network_allowlist,
+            _ => &[],
+        }
+    }
+
+    /// Returns the folders that are readable from within the sandbox, or
+    /// `None` when the entire file-system is readable (minus
+    /// [`Self::deny_read_paths`]).
+    pub fn get_readable_roots_with_cwd(&self, cwd: &Path) -> Option<Vec<PathBuf>> {
+        let readable_roots = match self {
+            SandboxPolicy::DangerFullAccess => return None,
+            SandboxPolicy::ReadOnly { readable_roots, .. }
+            | SandboxPolicy::WorkspaceWrite { readable_roots, .. } => readable_roots,
+        };
+        if readable_roots.is_empty() {
+            return None;
+        }
+
+        let mut roots = readable_roots.clone();
+        roots.push(cwd.to_path_buf());
+        roots.extend(
+            self.get_writable_roots_with_cwd(cwd)
+                .into_iter()
+                .map(|writable_root| writable_root.root),
+        );
+        roots.extend(PLATFORM_READABLE_ROOTS.iter().map(PathBuf::from));
+        roots.dedup();
+        Some(roots)
+    }
+
+    /// Paths that must not be readable from within the sandbox.
+    pub fn deny_read_paths(&self) -> &[PathBuf] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly {
+                deny_read_paths, ..
+            }
+            | SandboxPolicy::WorkspaceWrite {
+                deny_read_paths, ..
+            } => deny_read_paths,
+        }
+    }
+
+    /// Returns this policy with the `readable_roots` and `deny_read_paths` of
+    /// `current`, so that switching to another policy mid-session does not
+    /// widen what the sandbox can read. `DangerFullAccess` is returned as is.
+    pub fn with_read_restrictions_of(mut self, current: &SandboxPolicy) -> Self {
+        let (current_readable, current_denied) = match current {
+            SandboxPolicy::DangerFullAccess => (&[][..], &[][..]),
+            SandboxPolicy::ReadOnly {
+                readable_roots,
+                deny_read_paths,
+            }
+            | SandboxPolicy::WorkspaceWrite {
+                readable_roots,
+                deny_read_paths,
+                ..
+            } => (readable_roots.as_slice(), deny_read_paths.as_slice()),
+        };
+        match &mut self {
+            SandboxPolicy::DangerFullAccess => {}
+            SandboxPolicy::ReadOnly {
+                readable_roots,
+                deny_read_paths,
+            }
+            | SandboxPolicy::WorkspaceWrite {
+                readable_roots,
+                deny_read_paths,
+                ..
+            } => {
+                *readable_roots = current_readable.to_vec();
+                *deny_read_paths = current_denied.to_vec();
+            }
+        }
+        self
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                network_allowlist: _,
+                readable_roots: _,
+                deny_read_paths: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -899,6 +1067,19 @@ impl InitialHistory {
         }
     }
 
+    /// The most recent recorded mode state, if any.
+    pub fn get_mode_state(&self) -> Option<ModeStateItem> {
+        let items = match self {
+            InitialHistory::New => return None,
+            InitialHistory::Resumed(resumed) => &resumed.history,
+            InitialHistory::Forked(items) => items,
+        };
+        items.iter().rev().find_map(|ri| match ri {
+            RolloutItem::ModeState(state) => Some(state.clone()),
+            _ => None,
+        })
+    }
+
     pub fn get_event_msgs(&self) -> Option<Vec<EventMsg>> {
         match self {
             InitialHistory::New => None,
@@ -979,6 +1160,7 @@ pub enum RolloutItem {
     ResponseItem(ResponseItem),
     Compacted(CompactedItem),
     TurnContext(TurnContextItem),
+    ModeState(ModeStateItem),
     EventMsg(EventMsg),
 }
 
@@ -1010,6 +1192,18 @@ pub struct TurnContextItem {
     pub summary: ReasoningSummaryConfig,
 }
 
+/// Custom modes enabled by the client at a point in the conversation. The
+/// latest item wins when the session is resumed or forked.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, TS)]
+pub struct ModeStateItem {
+    /// Enabled mode ids in enable order.
+    pub enabled: Vec<String>,
+    /// Explicit variable values by mode id, then variable name; `None` means
+    /// the value was reset to the mode's default.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, BTreeMap<String, Option<String>>>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RolloutLine {
     pub timestamp: String,
@@ -1110,6 +1304,24 @@ pub struct ExecCommandEndEvent {
     pub duration: Duration,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// Set when the command was stopped for exceeding a configured resource
+    /// limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_exceeded: Option<ResourceLimitKind>,
+}
+
+/// A per-command resource limit that a command can run into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Display, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceLimitKind {
+    #[strum(serialize = "CPU time")]
+    CpuTime,
+    #[strum(serialize = "memory")]
+    Memory,
+    #[strum(serialize = "file size")]
+    FileSize,
+    #[strum(serialize = "output size")]
+    OutputSize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1151,6 +1363,10 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// Exec policy rule that matched the command, if any, so approvers can
+    /// see why it was not auto-approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1216,6 +1432,10 @@ pub struct GetHistoryEntryResponseEvent {
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
+    /// Fully qualified names of the tools in `tools` that the session's tool
+    /// filter hides from the model, sorted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hidden_tools: Vec<String>,
 }
 
 /// Response payload for `Op::ListCustomPrompts`.
@@ -1247,6 +1467,10 @@ pub struct SessionConfiguredEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub initial_messages: Option<Vec<EventMsg>>,
 
+    /// Custom modes recorded in the rollout of a resumed or forked session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_state: Option<ModeStateItem>,
+
     pub rollout_path: PathBuf,
 }
 
@@ -1262,6 +1486,11 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants a persistent rule created so
+    /// that future commands with the same argv prefix are approved
+    /// automatically, in this project or everywhere depending on `scope`.
+    ApprovedRule { scope: ApprovalRuleScope },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -1272,6 +1501,19 @@ pub enum ReviewDecision {
     Abort,
 }
 
+/// Where a persistent approval rule created by
+/// [`ReviewDecision::ApprovedRule`] applies.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ApprovalRuleScope {
+    /// Only for commands run inside the current project (the git repository
+    /// root, or the working directory outside a repository).
+    Project,
+    /// For commands run in any directory.
+    Global,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum FileChange {
@@ -1330,6 +1572,7 @@ mod tests {
                 history_log_id: 0,
                 history_entry_count: 0,
                 initial_messages: None,
+                mode_state: None,
                 rollout_path: rollout_file.path().to_path_buf(),
             }),
         };
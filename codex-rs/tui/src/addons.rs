@@ -6,6 +6,7 @@ use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::InputResult;
 use crate::modes::PersistentModeState;
+use crate::modes::SessionDefaults;
 use crate::tui::FrameRequester;
 
 /// 在 BottomPane 中挂载的最小扩展接口。
@@ -56,9 +57,13 @@ pub struct ModeUiContext {
     pub base_user_instructions: String,
     pub current_user_instructions: Option<String>,
     pub persistent_mode_state: PersistentModeState,
+    /// 会话基线设置，用于撤销模式覆写（model/effort/sandbox/approval）。
+    pub session_defaults: Option<SessionDefaults>,
     pub on_update_summary: std::sync::Arc<dyn Fn(String) + Send + Sync + 'static>,
     pub on_update_persistent_state:
         std::sync::Arc<dyn Fn(PersistentModeState) + Send + Sync + 'static>,
+    /// 用户已确认的项目模式放宽项；其余放宽 sandbox/approval 的项目覆写在构建视图前去掉。
+    pub confirmed_mode_loosening: Vec<codex_modes::LooseningOverride>,
 }
 
 impl ModeUiContext {
@@ -71,6 +76,7 @@ impl ModeUiContext {
         base_user_instructions: String,
         current_user_instructions: Option<String>,
         persistent_mode_state: PersistentModeState,
+        session_defaults: Option<SessionDefaults>,
         on_update_summary: std::sync::Arc<dyn Fn(String) + Send + Sync + 'static>,
         on_update_persistent_state: std::sync::Arc<
             dyn Fn(PersistentModeState) + Send + Sync + 'static,
@@ -84,10 +90,20 @@ impl ModeUiContext {
             base_user_instructions,
             current_user_instructions,
             persistent_mode_state,
+            session_defaults,
             on_update_summary,
             on_update_persistent_state,
+            confirmed_mode_loosening: Vec::new(),
         }
     }
+
+    pub fn with_confirmed_mode_loosening(
+        mut self,
+        confirmed: Vec<codex_modes::LooseningOverride>,
+    ) -> Self {
+        self.confirmed_mode_loosening = confirmed;
+        self
+    }
 }
 
 /// 通用视图工厂：统一非模式与模式类视图的构建入口。
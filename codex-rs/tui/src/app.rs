@@ -630,6 +630,7 @@ mod tests {
                 history_log_id: 0,
                 history_entry_count: 0,
                 initial_messages: None,
+                mode_state: None,
                 rollout_path: PathBuf::new(),
             };
             Arc::new(new_session_info(
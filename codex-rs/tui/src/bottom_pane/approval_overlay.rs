@@ -17,6 +17,10 @@ use crate::render::highlight::highlight_bash_to_lines;
 use crate::render::renderable::ColumnRenderable;
 use crate::render::renderable::Renderable;
 use crate::text_formatting::truncate_text;
+use codex_core::approval_rules::RulePattern;
+use codex_core::approval_rules::describe_prefix;
+use codex_core::approval_rules::rule_pattern_for_command;
+use codex_core::protocol::ApprovalRuleScope;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
@@ -39,6 +43,8 @@ pub(crate) enum ApprovalRequest {
         id: String,
         command: Vec<String>,
         reason: Option<String>,
+        /// Exec policy rule that matched the command, if any.
+        matched_rule: Option<String>,
     },
     ApplyPatch {
         id: String,
@@ -95,8 +101,8 @@ impl ApprovalOverlay {
         header: Box<dyn Renderable>,
     ) -> (Vec<ApprovalOption>, SelectionViewParams) {
         let (options, title) = match &variant {
-            ApprovalVariant::Exec { .. } => (
-                exec_options(),
+            ApprovalVariant::Exec { command, .. } => (
+                exec_options(command),
                 "Would you like to run the following command?".to_string(),
             ),
             ApprovalVariant::ApplyPatch { .. } => (patch_options(), "Apply changes?".to_string()),
@@ -286,12 +292,20 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 id,
                 command,
                 reason,
+                matched_rule,
             } => {
                 let mut header: Vec<Line<'static>> = Vec::new();
                 if let Some(reason) = reason
                     && !reason.is_empty()
                 {
                     header.push(Line::from(vec!["Reason: ".into(), reason.italic()]));
+                }
+                if let Some(rule) = matched_rule
+                    && !rule.is_empty()
+                {
+                    header.push(Line::from(vec!["Policy: ".into(), rule.dim()]));
+                }
+                if !header.is_empty() {
                     header.push(Line::from(""));
                 }
                 let full_cmd = strip_bash_lc_and_escape(&command);
@@ -352,8 +366,8 @@ impl ApprovalOption {
     }
 }
 
-fn exec_options() -> Vec<ApprovalOption> {
-    vec![
+fn exec_options(command: &[String]) -> Vec<ApprovalOption> {
+    let mut options = vec![
         ApprovalOption {
             label: "Yes, proceed".to_string(),
             decision: ReviewDecision::Approved,
@@ -366,13 +380,37 @@ fn exec_options() -> Vec<ApprovalOption> {
             display_shortcut: None,
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
         },
-        ApprovalOption {
-            label: "No, and tell Codex what to do differently".to_string(),
-            decision: ReviewDecision::Abort,
-            display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
-            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
-        },
-    ]
+    ];
+    // Persistent rules are only offered when a safe pattern exists.
+    if let Some(pattern) = rule_pattern_for_command(command) {
+        let prefix = match &pattern {
+            RulePattern::Exact(argv) | RulePattern::Prefix(argv) => describe_prefix(argv),
+            RulePattern::Glob(glob) => glob.clone(),
+        };
+        options.push(ApprovalOption {
+            label: format!("Yes, and always allow `{prefix}` in this project"),
+            decision: ReviewDecision::ApprovedRule {
+                scope: ApprovalRuleScope::Project,
+            },
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
+        });
+        options.push(ApprovalOption {
+            label: format!("Yes, and always allow `{prefix}` everywhere"),
+            decision: ReviewDecision::ApprovedRule {
+                scope: ApprovalRuleScope::Global,
+            },
+            display_shortcut: None,
+            additional_shortcuts: Vec::new(),
+        });
+    }
+    options.push(ApprovalOption {
+        label: "No, and tell Codex what to do differently".to_string(),
+        decision: ReviewDecision::Abort,
+        display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
+        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+    });
+    options
 }
 
 fn patch_options() -> Vec<ApprovalOption> {
@@ -425,6 +463,23 @@ fn build_exec_history_lines(
                 ],
             )
         }
+        ApprovedRule { scope } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            let scope = match scope {
+                ApprovalRuleScope::Project => " in this project from now on",
+                ApprovalRuleScope::Global => " everywhere from now on",
+            };
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to run commands like ".into(),
+                    snippet,
+                    scope.bold(),
+                ],
+            )
+        }
         Denied => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -484,6 +539,7 @@ mod tests {
             id: "test".to_string(),
             command: vec!["echo".to_string(), "hi".to_string()],
             reason: Some("reason".to_string()),
+            matched_rule: None,
         }
     }
 
@@ -525,6 +581,7 @@ mod tests {
             id: "test".into(),
             command,
             reason: None,
+            matched_rule: None,
         };
 
         let view = ApprovalOverlay::new(exec_request, tx);
@@ -546,6 +603,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn header_includes_matched_policy_rule() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let exec_request = ApprovalRequest::Exec {
+            id: "test".into(),
+            command: vec!["cp".into(), "a".into(), "b".into()],
+            reason: None,
+            matched_rule: Some("`cp a b` matches exec policy but may write b".into()),
+        };
+
+        let view = ApprovalOverlay::new(exec_request, tx);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, view.desired_height(80)));
+        view.render(Rect::new(0, 0, 80, view.desired_height(80)), &mut buf);
+
+        let rendered: Vec<String> = (0..buf.area.height)
+            .map(|row| {
+                (0..buf.area.width)
+                    .map(|col| buf[(col, row)].symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(
+            rendered
+                .iter()
+                .any(|line| line.contains("Policy: `cp a b` matches exec policy but may write b")),
+            "expected header to include the matched rule, got {rendered:?}"
+        );
+    }
+
     #[test]
     fn enter_sets_last_selected_index_without_dismissing() {
         let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
@@ -568,4 +655,46 @@ mod tests {
         }
         assert_eq!(decision, Some(ReviewDecision::ApprovedForSession));
     }
+
+    #[test]
+    fn project_rule_shortcut_sends_approved_rule() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx);
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+
+        let mut decision = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision: d, .. }) = ev {
+                decision = Some(d);
+                break;
+            }
+        }
+        assert_eq!(
+            decision,
+            Some(ReviewDecision::ApprovedRule {
+                scope: ApprovalRuleScope::Project,
+            })
+        );
+    }
+
+    #[test]
+    fn rule_options_hidden_without_safe_prefix() {
+        let labels = |command: &[&str]| -> Vec<String> {
+            let command: Vec<String> = command.iter().map(ToString::to_string).collect();
+            exec_options(&command)
+                .into_iter()
+                .map(|option| option.label)
+                .collect()
+        };
+        assert!(
+            labels(&["cargo", "test", "--all"])
+                .contains(&"Yes, and always allow `cargo test` in this project".to_string())
+        );
+        assert!(
+            labels(&["rm", "-rf", "out"])
+                .contains(&"Yes, and always allow `rm -rf out` in this project".to_string())
+        );
+        assert_eq!(labels(&["bash", "-lc", "ls && rm -rf out"]).len(), 3);
+    }
 }
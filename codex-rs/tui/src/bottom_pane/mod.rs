@@ -668,6 +668,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".into(), "ok".into()],
             reason: None,
+            matched_rule: None,
         }
     }
 
@@ -29,6 +29,7 @@ use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::ModeStateItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
@@ -100,7 +101,10 @@ use crate::addons::AppLifecycleHook;
 use crate::addons::ModeUiContext;
 use crate::addons::UiViewFactory;
 use crate::addons::UiViewKind;
+use crate::modes::ModesWatcher;
+use crate::modes::OverrideFields;
 use crate::modes::PersistentModeState;
+use crate::modes::SessionDefaults;
 use crate::streaming::controller::StreamController;
 use chrono::Local;
 use codex_common::approval_presets::ApprovalPreset;
@@ -109,15 +113,18 @@ use codex_common::model_presets::ModelPreset;
 use codex_common::model_presets::builtin_model_presets;
 use codex_core::AuthManager;
 use codex_core::ConversationManager;
+use codex_core::SessionMetadata;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_core::update_session_metadata;
 use codex_file_search::FileMatch;
 use codex_git_tooling::CreateGhostCommitOptions;
 use codex_git_tooling::GhostCommit;
 use codex_git_tooling::GitToolingError;
 use codex_git_tooling::create_ghost_commit;
 use codex_git_tooling::restore_ghost_commit;
+use codex_modes::LooseningOverride;
 use strum::IntoEnumIterator;
 
 const MAX_TRACKED_GHOST_COMMITS: usize = 20;
@@ -240,6 +247,18 @@ pub(crate) struct ChatWidget {
     current_user_instructions: Option<String>,
     // Cached persistent mode enablement state for reopening ModeBar/Panel.
     persistent_mode_state: PersistentModeState,
+    // 上次扫描到的模式定义（热重载时据此计算新增/变更/删除）。
+    modes_snapshot: Vec<codex_modes::ModeDefinition>,
+    // 模式目录轮询任务；随 ChatWidget 一起销毁。
+    modes_watcher: Option<ModesWatcher>,
+    // 用户已确认的项目模式放宽项（sandbox/approval），本会话内有效。
+    confirmed_mode_loosening: Vec<LooseningOverride>,
+    // 已询问过的放宽项（无论是否同意），避免每次重扫重复弹窗。
+    asked_mode_loosening: Vec<LooseningOverride>,
+    // 最近一次扫描中加载失败的模式文件（已提示过的不再重复提示）。
+    mode_diagnostics: Vec<codex_modes::ModeDiagnostic>,
+    // 最近一次写入 rollout 的模式状态（未变化时不重复记录）。
+    recorded_mode_state: Option<ModeStateItem>,
     // 是否为恢复已有会话（而非全新会话）
     resumed_session: bool,
 
@@ -449,6 +468,7 @@ impl ChatWidget {
             .set_history_metadata(event.history_log_id, event.history_entry_count);
         self.conversation_id = Some(event.session_id);
         let initial_messages = event.initial_messages.clone();
+        let recorded_mode_state = event.mode_state.clone();
         let model_for_header = event.model.clone();
         self.session_header.set_model(&model_for_header);
         self.add_to_history(history_cell::new_session_info(
@@ -481,62 +501,82 @@ impl ChatWidget {
             use codex_modes::IndexMap as IMap;
             use codex_modes::ModeKind;
             use codex_modes::render_user_instructions;
-            use codex_modes::scan_modes;
-            if let Ok(defs) = scan_modes(&self.config.cwd, Some(&self.config.codex_home)) {
-                let mut enabled: Vec<EnabledMode> = Vec::new();
-                for def in &defs {
-                    if def.kind != ModeKind::Persistent || !def.default_enabled {
-                        continue;
-                    }
-                    let mut ok = true;
-                    let mut vars: IMap<&str, Option<String>> = IMap::new();
-                    for v in &def.variables {
-                        if v.required && v.default.is_none() {
-                            ok = false;
-                            break;
-                        }
-                        vars.insert(v.name.as_str(), None);
-                    }
-                    if ok {
-                        enabled.push(EnabledMode {
-                            id: &def.id,
-                            display_name: def.display_name.as_deref(),
-                            scope: &def.scope,
-                            variables: vars,
-                        });
-                    }
+            let (defs, stripped) = self.scan_mode_defs();
+            let mut enabled: Vec<EnabledMode> = Vec::new();
+            for def in &defs {
+                if def.kind != ModeKind::Persistent || !def.default_enabled {
+                    continue;
                 }
-                if !enabled.is_empty()
-                    && let Ok(rendered) = render_user_instructions(&base, &enabled, &defs)
-                {
-                    let mut state = PersistentModeState::default();
-                    for em in &enabled {
-                        let id = em.id.to_string();
-                        if state.enabled.insert(id.clone()) {
-                            state.enable_order.push(id);
-                        }
+                let mut ok = true;
+                let mut vars: IMap<&str, Option<String>> = IMap::new();
+                for v in &def.variables {
+                    if v.required && v.default.is_none() {
+                        ok = false;
+                        break;
                     }
-                    self.persistent_mode_state = state.clone();
-                    self.submit_op(Op::OverrideTurnContext {
-                        cwd: None,
-                        approval_policy: None,
-                        sandbox_policy: None,
-                        model: None,
-                        effort: None,
-                        summary: None,
-                        user_instructions: Some(rendered),
+                    vars.insert(v.name.as_str(), None);
+                }
+                if ok {
+                    enabled.push(EnabledMode {
+                        id: &def.id,
+                        display_name: def.display_name.as_deref(),
+                        scope: &def.scope,
+                        variables: vars,
                     });
-                    if let Ok(rendered_now) = render_user_instructions(&base, &enabled, &defs) {
-                        self.current_user_instructions = Some(rendered_now);
+                }
+            }
+            // 默认启用集合同样要满足 conflicts_with/requires，否则不自动应用
+            let errors = codex_modes::format_validation_errors(&codex_modes::validate_enabled(
+                &defs, &enabled,
+            ));
+            for msg in &errors {
+                self.add_to_history(history_cell::new_error_event(msg.clone()));
+            }
+            codex_modes::sort_by_priority(&defs, &mut enabled);
+            if errors.is_empty()
+                && !enabled.is_empty()
+                && let Ok(rendered) = render_user_instructions(&base, &enabled, &defs)
+            {
+                let mut state = PersistentModeState::default();
+                for em in &enabled {
+                    let id = em.id.to_string();
+                    if state.enabled.insert(id.clone()) {
+                        state.enable_order.push(id);
                     }
-                    // Update persistent mode summary silently（直接更新 BottomPane）
-                    let labels = codex_modes::enabled_labels(&enabled);
-                    let summary = codex_modes::format_mode_summary(&labels);
-                    self.set_mode_summary(summary);
                 }
+                let fields = self.default_mode_override_fields(&defs, &state.enable_order);
+                self.persistent_mode_state = state.clone();
+                self.record_mode_state();
+                self.submit_op(Op::OverrideTurnContext {
+                    cwd: None,
+                    approval_policy: fields.approval_policy,
+                    sandbox_policy: fields.sandbox_policy,
+                    model: fields.model,
+                    effort: fields.effort,
+                    summary: None,
+                    user_instructions: Some(rendered),
+                    tool_filter: fields.tool_filter,
+                });
+                if let Ok(rendered_now) = render_user_instructions(&base, &enabled, &defs) {
+                    self.current_user_instructions = Some(rendered_now);
+                }
+                // Update persistent mode summary silently（直接更新 BottomPane）
+                let labels = codex_modes::enabled_labels(&enabled);
+                let summary = codex_modes::format_mode_summary(&labels);
+                self.set_mode_summary(summary);
+                self.confirm_mode_loosening(stripped);
             }
         }
 
+        // 恢复/分叉会话：按当前定义还原 rollout 中记录的模式
+        if self.resumed_session
+            && let Some(item) = recorded_mode_state
+        {
+            self.restore_mode_state(item);
+        }
+
+        self.start_modes_watcher();
+
         // 尾部调用扩展生命周期钩子（无扩展时无效果）。
         for hook in self.lifecycle_hooks.iter_mut() {
             hook.on_session_configured();
@@ -896,6 +936,14 @@ impl ChatWidget {
             )));
         }
 
+        let mut stderr = ev.stderr.clone();
+        if let Some(limit) = ev.limit_exceeded {
+            if !stderr.is_empty() && !stderr.ends_with('\n') {
+                stderr.push('\n');
+            }
+            stderr.push_str(&format!("command exceeded the {limit} limit"));
+        }
+
         if let Some(cell) = self
             .active_cell
             .as_mut()
@@ -906,7 +954,7 @@ impl ChatWidget {
                 CommandOutput {
                     exit_code: ev.exit_code,
                     stdout: ev.stdout.clone(),
-                    stderr: ev.stderr.clone(),
+                    stderr,
                     formatted_output: ev.formatted_output.clone(),
                 },
                 ev.duration,
@@ -940,6 +988,7 @@ impl ChatWidget {
             id,
             command: ev.command,
             reason: ev.reason,
+            matched_rule: ev.matched_rule,
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -1122,6 +1171,12 @@ impl ChatWidget {
             base_user_instructions: None,
             current_user_instructions: None,
             persistent_mode_state: PersistentModeState::default(),
+            modes_snapshot: Vec::new(),
+            recorded_mode_state: None,
+            modes_watcher: None,
+            confirmed_mode_loosening: Vec::new(),
+            asked_mode_loosening: Vec::new(),
+            mode_diagnostics: Vec::new(),
             resumed_session: false,
             lifecycle_hooks: Vec::new(),
             ui_view_factories: Vec::new(),
@@ -1195,6 +1250,12 @@ impl ChatWidget {
             base_user_instructions: None,
             current_user_instructions: None,
             persistent_mode_state: PersistentModeState::default(),
+            modes_snapshot: Vec::new(),
+            recorded_mode_state: None,
+            modes_watcher: None,
+            confirmed_mode_loosening: Vec::new(),
+            asked_mode_loosening: Vec::new(),
+            mode_diagnostics: Vec::new(),
             resumed_session: true,
             lifecycle_hooks: Vec::new(),
             ui_view_factories: Vec::new(),
@@ -1364,8 +1425,7 @@ impl ChatWidget {
         use codex_modes::IndexMap as IMap;
         use codex_modes::ModeKind;
         use codex_modes::render_user_instructions;
-        use codex_modes::scan_modes;
-        let defs = scan_modes(&self.config.cwd, Some(&self.config.codex_home))?;
+        let (defs, stripped) = self.scan_mode_defs();
         if defs.is_empty() {
             self.add_to_history(history_cell::new_error_event(
                 "No modes found under .codex/modes or $CODEX_HOME/modes".to_string(),
@@ -1406,6 +1466,15 @@ impl ChatWidget {
             ));
             return Ok(());
         }
+        let errors =
+            codex_modes::format_validation_errors(&codex_modes::validate_enabled(&defs, &enabled));
+        if !errors.is_empty() {
+            for msg in errors {
+                self.add_to_history(history_cell::new_error_event(msg));
+            }
+            return Ok(());
+        }
+        codex_modes::sort_by_priority(&defs, &mut enabled);
         let mut state = PersistentModeState::default();
         for em in &enabled {
             let id = em.id.to_string();
@@ -1413,16 +1482,19 @@ impl ChatWidget {
                 state.enable_order.push(id);
             }
         }
+        let fields = self.default_mode_override_fields(&defs, &state.enable_order);
         self.persistent_mode_state = state.clone();
+        self.record_mode_state();
         let rendered = render_user_instructions(base, &enabled, &defs)?;
         self.submit_op(Op::OverrideTurnContext {
             cwd: None,
-            approval_policy: None,
-            sandbox_policy: None,
-            model: None,
-            effort: None,
+            approval_policy: fields.approval_policy,
+            sandbox_policy: fields.sandbox_policy,
+            model: fields.model,
+            effort: fields.effort,
             summary: None,
             user_instructions: Some(rendered),
+            tool_filter: fields.tool_filter,
         });
         // Track current applied content for equivalence checks
         let rendered_now = render_user_instructions(base, &enabled, &defs)?;
@@ -1440,9 +1512,277 @@ impl ChatWidget {
         ));
         let summary = codex_modes::format_mode_summary(&labels);
         self.set_mode_summary(summary);
+        self.confirm_mode_loosening(stripped);
         Ok(())
     }
 
+    /// 当前会话基线（模式覆写撤销时回落到这些值）。
+    fn mode_session_defaults(&self) -> SessionDefaults {
+        SessionDefaults {
+            model: self.config.model.clone(),
+            effort: self.config.model_reasoning_effort,
+            sandbox_policy: self.config.sandbox_policy.clone(),
+            approval_policy: self.config.approval_policy,
+            tool_filter: self.config.tool_filter.clone(),
+        }
+    }
+
+    /// 默认启用集合的覆写字段（相对会话基线）。
+    fn default_mode_override_fields(
+        &self,
+        defs: &[codex_modes::ModeDefinition],
+        enable_order: &[String],
+    ) -> OverrideFields {
+        let resolved = codex_modes::resolve_overrides(defs, enable_order);
+        OverrideFields::diff(
+            &codex_modes::ModeOverrides::default(),
+            &resolved.overrides,
+            Some(&self.mode_session_defaults()),
+        )
+    }
+
+    /// 将当前启用状态写入 rollout（与上次记录相同则跳过），供恢复/分叉还原。
+    /// 以 `modes_snapshot` 过滤 secret 变量。
+    fn record_mode_state(&mut self) {
+        let item = self.persistent_mode_state.to_item(&self.modes_snapshot);
+        if self.recorded_mode_state.as_ref() == Some(&item) {
+            return;
+        }
+        // 从未启用过模式的会话不写空记录
+        if self.recorded_mode_state.is_none() && item == ModeStateItem::default() {
+            return;
+        }
+        self.recorded_mode_state = Some(item.clone());
+        self.submit_op(Op::RecordModeState { mode_state: item });
+    }
+
+    /// 还原 rollout 中记录的模式：按当前定义修剪后重新渲染并下发（下一轮生效）。
+    /// 校验失败时保持会话基线指令，并提示错误。
+    fn restore_mode_state(&mut self, item: ModeStateItem) {
+        self.recorded_mode_state = Some(item.clone());
+        let (defs, stripped) = self.scan_mode_defs();
+        let base = self
+            .base_user_instructions
+            .clone()
+            .unwrap_or_else(|| self.compose_fallback_user_instructions_with_project_doc());
+        let restored = crate::modes::plan_reload(
+            &[],
+            &defs,
+            &PersistentModeState::from_item(item),
+            &base,
+            None,
+            Some(&self.mode_session_defaults()),
+        );
+        // record_mode_state 依据快照判定哪些变量可写入 rollout
+        self.modes_snapshot = defs;
+        if !restored.errors.is_empty() {
+            for msg in restored.errors {
+                self.add_to_history(history_cell::new_error_event(msg));
+            }
+            return;
+        }
+        self.persistent_mode_state = restored.state;
+        self.record_mode_state();
+        let fields = restored.overrides;
+        if restored.rendered.is_some() || !fields.is_empty() {
+            if let Some(rendered) = &restored.rendered {
+                self.current_user_instructions = Some(rendered.clone());
+            }
+            self.submit_op(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: fields.approval_policy,
+                sandbox_policy: fields.sandbox_policy,
+                model: fields.model,
+                effort: fields.effort,
+                summary: None,
+                user_instructions: restored.rendered,
+                tool_filter: fields.tool_filter,
+            });
+        }
+        if !restored.labels.is_empty() {
+            self.add_to_history(history_cell::new_info_event(
+                "Modes restored".to_string(),
+                Some(restored.labels.clone()),
+            ));
+        }
+        self.set_mode_summary(codex_modes::format_mode_summary(&restored.labels));
+        self.confirm_mode_loosening(stripped);
+    }
+
+    /// 启动模式目录轮询：文件变化（去抖后）回投 UI 线程执行 `reload_modes_from_disk`。
+    fn start_modes_watcher(&mut self) {
+        if self.modes_watcher.is_some() {
+            return;
+        }
+        self.modes_snapshot = self.scan_mode_defs().0;
+        let ui_tx = self.ui_tasks_tx.clone();
+        let frame_requester = self.frame_requester.clone();
+        self.modes_watcher = ModesWatcher::spawn(
+            self.config.cwd.clone(),
+            self.config.codex_home.clone(),
+            std::sync::Arc::new(move || {
+                let _ = ui_tx.send(Box::new(|cw: &mut ChatWidget| cw.reload_modes_from_disk()));
+                frame_requester.schedule_frame();
+            }),
+        );
+    }
+
+    /// 重扫模式定义：修剪已启用状态、重新渲染（下一轮生效），并提示新增/变更/删除。
+    /// 加载失败的模式文件沿用上次的定义并提示其错误；已启用模式校验失败时保持原有指令不变。
+    pub(crate) fn reload_modes_from_disk(&mut self) {
+        let (mut defs, stripped) = self.scan_mode_defs();
+        crate::modes::keep_failed_modes(&mut defs, &self.modes_snapshot, &self.mode_diagnostics);
+        let base = self
+            .base_user_instructions
+            .clone()
+            .unwrap_or_else(|| self.compose_fallback_user_instructions_with_project_doc());
+        let reload = crate::modes::plan_reload(
+            &self.modes_snapshot,
+            &defs,
+            &self.persistent_mode_state,
+            &base,
+            self.current_user_instructions.as_deref(),
+            Some(&self.mode_session_defaults()),
+        );
+        self.modes_snapshot = defs;
+        if reload.diff.is_empty() {
+            return;
+        }
+        self.add_to_history(history_cell::new_info_event(
+            "Modes reloaded".to_string(),
+            Some(reload.diff.describe()),
+        ));
+        if !reload.errors.is_empty() {
+            for msg in reload.errors {
+                self.add_to_history(history_cell::new_error_event(msg));
+            }
+            return;
+        }
+        self.persistent_mode_state = reload.state;
+        self.record_mode_state();
+        let fields = reload.overrides;
+        if reload.rendered.is_some() || !fields.is_empty() {
+            if let Some(rendered) = &reload.rendered {
+                self.current_user_instructions = Some(rendered.clone());
+            }
+            self.submit_op(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: fields.approval_policy,
+                sandbox_policy: fields.sandbox_policy,
+                model: fields.model,
+                effort: fields.effort,
+                summary: None,
+                user_instructions: reload.rendered,
+                tool_filter: fields.tool_filter,
+            });
+        }
+        self.set_mode_summary(codex_modes::format_mode_summary(&reload.labels));
+        self.confirm_mode_loosening(stripped);
+        self.request_redraw();
+    }
+
+    /// 扫描模式定义，并去掉未经确认、会放宽会话 sandbox/approval 的项目模式覆写。
+    /// 加载失败的模式文件逐个提示错误；同一问题只提示一次，修复后再次出错会重新提示。
+    fn scan_mode_defs(&mut self) -> (Vec<codex_modes::ModeDefinition>, Vec<LooseningOverride>) {
+        let scan = codex_modes::scan_modes(&self.config.cwd, Some(&self.config.codex_home));
+        for diagnostic in &scan.diagnostics {
+            if !self.mode_diagnostics.contains(diagnostic) {
+                self.add_to_history(history_cell::new_error_event(format!(
+                    "Failed to load mode {}: {}",
+                    diagnostic.id,
+                    diagnostic.message()
+                )));
+            }
+        }
+        self.mode_diagnostics = scan.diagnostics;
+        let mut defs = scan.defs;
+        let stripped = codex_modes::strip_loosening_overrides(
+            &mut defs,
+            &self.config.sandbox_policy,
+            self.config.approval_policy,
+            &self.confirmed_mode_loosening,
+        );
+        (defs, stripped)
+    }
+
+    /// 已启用的项目模式想放宽 sandbox/approval 时请求用户确认；每项只问一次。
+    fn confirm_mode_loosening(&mut self, stripped: Vec<LooseningOverride>) {
+        let pending: Vec<LooseningOverride> = stripped
+            .into_iter()
+            .filter(|l| {
+                self.persistent_mode_state.enabled.contains(&l.mode_id)
+                    && !self.asked_mode_loosening.contains(l)
+            })
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        self.asked_mode_loosening.extend(pending.iter().cloned());
+        let detail = pending
+            .iter()
+            .map(LooseningOverride::describe)
+            .collect::<Vec<_>>()
+            .join("; ");
+        let ui_tx = self.ui_tasks_tx.clone();
+        let frame_requester = self.frame_requester.clone();
+        let allow: SelectionAction = Box::new(move |_tx| {
+            let pending = pending.clone();
+            let _ = ui_tx.send(Box::new(move |cw: &mut ChatWidget| {
+                cw.allow_mode_loosening(pending.clone());
+            }));
+            frame_requester.schedule_frame();
+        });
+        let items = vec![
+            SelectionItem {
+                name: "Allow for this session".to_string(),
+                actions: vec![allow],
+                dismiss_on_select: true,
+                ..Default::default()
+            },
+            SelectionItem {
+                name: "Keep current settings".to_string(),
+                dismiss_on_select: true,
+                ..Default::default()
+            },
+        ];
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Project mode wants to loosen the sandbox or approvals".to_string()),
+            subtitle: Some(detail),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+        self.request_redraw();
+    }
+
+    /// 用户同意放宽后：按已确认项重扫，并下发启用集合因此变化的覆写。
+    fn allow_mode_loosening(&mut self, allowed: Vec<LooseningOverride>) {
+        self.confirmed_mode_loosening.extend(allowed);
+        let (defs, _) = self.scan_mode_defs();
+        let enable_order = &self.persistent_mode_state.enable_order;
+        let prev = codex_modes::resolve_overrides(&self.modes_snapshot, enable_order).overrides;
+        let next = codex_modes::resolve_overrides(&defs, enable_order).overrides;
+        let fields = OverrideFields::diff(&prev, &next, Some(&self.mode_session_defaults()));
+        self.modes_snapshot = defs;
+        if fields.is_empty() {
+            return;
+        }
+        self.submit_op(Op::OverrideTurnContext {
+            cwd: None,
+            approval_policy: fields.approval_policy,
+            sandbox_policy: fields.sandbox_policy,
+            model: fields.model,
+            effort: fields.effort,
+            summary: None,
+            user_instructions: None,
+            tool_filter: fields.tool_filter,
+        });
+        self.add_to_history(history_cell::new_info_event(
+            "Mode overrides applied".to_string(),
+            Some(next.describe()),
+        ));
+    }
+
     /// 打开模式面板（扫描与交互）。
     fn open_modes_panel(&mut self) {
         // 优先：通用工厂
@@ -1461,6 +1801,7 @@ impl ChatWidget {
                 base,
                 self.current_user_instructions.clone(),
                 self.persistent_mode_state.clone(),
+                Some(self.mode_session_defaults()),
                 std::sync::Arc::new(move |labels: String| {
                     let payload = labels;
                     let _ = ui_tx1.send(Box::new(move |cw| {
@@ -1478,7 +1819,8 @@ impl ChatWidget {
                         cw.set_persistent_mode_state(st.clone());
                     }));
                 }),
-            );
+            )
+            .with_confirmed_mode_loosening(self.confirmed_mode_loosening.clone());
             for f in &self.ui_view_factories {
                 if let Some(view) = f.make_view(UiViewKind::ModePanel, &ctx) {
                     self.bottom_pane.show_custom_view(view);
@@ -1511,6 +1853,7 @@ impl ChatWidget {
                 base,
                 self.current_user_instructions.clone(),
                 self.persistent_mode_state.clone(),
+                Some(self.mode_session_defaults()),
                 std::sync::Arc::new(move |labels: String| {
                     let payload = labels;
                     let _ = ui_tx1.send(Box::new(move |cw| {
@@ -1528,7 +1871,8 @@ impl ChatWidget {
                         cw.set_persistent_mode_state(st.clone());
                     }));
                 }),
-            );
+            )
+            .with_confirmed_mode_loosening(self.confirmed_mode_loosening.clone());
             for f in &self.ui_view_factories {
                 if let Some(view) = f.make_view(UiViewKind::ModeBar, &ctx) {
                     self.bottom_pane.show_custom_view(view);
@@ -1609,6 +1953,15 @@ impl ChatWidget {
             SlashCommand::Status => {
                 self.add_status_output();
             }
+            SlashCommand::Rename => {
+                self.show_rename_session_prompt();
+            }
+            SlashCommand::Tag => {
+                self.show_tag_session_prompt();
+            }
+            SlashCommand::Pin => {
+                self.toggle_session_pin();
+            }
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
@@ -2182,6 +2535,7 @@ impl ChatWidget {
                     effort: Some(effort_for_action),
                     summary: None,
                     user_instructions: None,
+                    tool_filter: None,
                 }));
                 tx.send(AppEvent::UpdateModel(model_for_action.clone()));
                 tx.send(AppEvent::UpdateReasoningEffort(effort_for_action));
@@ -2224,10 +2578,13 @@ impl ChatWidget {
         let mut items: Vec<SelectionItem> = Vec::new();
         let presets: Vec<ApprovalPreset> = builtin_approval_presets();
         for preset in presets.into_iter() {
-            let is_current =
-                current_approval == preset.approval && current_sandbox == preset.sandbox;
+            // Presets keep the configured readable_roots/deny_read_paths.
+            let sandbox = preset
+                .sandbox
+                .clone()
+                .with_read_restrictions_of(&current_sandbox);
+            let is_current = current_approval == preset.approval && current_sandbox == sandbox;
             let approval = preset.approval;
-            let sandbox = preset.sandbox.clone();
             let name = preset.label.to_string();
             let description = Some(preset.description.to_string());
             let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
@@ -2239,6 +2596,7 @@ impl ChatWidget {
                     effort: None,
                     summary: None,
                     user_instructions: None,
+                    tool_filter: None,
                 }));
                 tx.send(AppEvent::UpdateAskForApprovalPolicy(approval));
                 tx.send(AppEvent::UpdateSandboxPolicy(sandbox.clone()));
@@ -2389,7 +2747,11 @@ impl ChatWidget {
     }
 
     fn on_list_mcp_tools(&mut self, ev: McpListToolsResponseEvent) {
-        self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
+        self.add_to_history(history_cell::new_mcp_tools_output(
+            &self.config,
+            ev.tools,
+            &ev.hidden_tools,
+        ));
     }
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
@@ -2574,6 +2936,80 @@ impl ChatWidget {
         self.bottom_pane.show_view(Box::new(view));
     }
 
+    pub(crate) fn show_rename_session_prompt(&mut self) {
+        let Some(conversation_id) = self.conversation_id else {
+            self.add_error_message("No session to rename yet.".to_string());
+            return;
+        };
+        let tx = self.app_event_tx.clone();
+        let codex_home = self.config.codex_home.clone();
+        let view = CustomPromptView::new(
+            "Session title".to_string(),
+            "Type a title and press Enter".to_string(),
+            None,
+            Box::new(move |title: String| {
+                let title = title.trim().to_string();
+                if title.is_empty() {
+                    return;
+                }
+                update_session_metadata_in_background(
+                    tx.clone(),
+                    codex_home.clone(),
+                    conversation_id,
+                    move |metadata| metadata.set_title(&title),
+                );
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    pub(crate) fn show_tag_session_prompt(&mut self) {
+        let Some(conversation_id) = self.conversation_id else {
+            self.add_error_message("No session to tag yet.".to_string());
+            return;
+        };
+        let tx = self.app_event_tx.clone();
+        let codex_home = self.config.codex_home.clone();
+        let view = CustomPromptView::new(
+            "Tag session".to_string(),
+            "Type tags separated by spaces; prefix a tag with - to remove it".to_string(),
+            None,
+            Box::new(move |input: String| {
+                let words: Vec<String> = input.split_whitespace().map(str::to_string).collect();
+                if words.is_empty() {
+                    return;
+                }
+                update_session_metadata_in_background(
+                    tx.clone(),
+                    codex_home.clone(),
+                    conversation_id,
+                    move |metadata| {
+                        for word in &words {
+                            match word.strip_prefix('-') {
+                                Some(tag) => metadata.remove_tag(tag),
+                                None => metadata.add_tag(word),
+                            }
+                        }
+                    },
+                );
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    fn toggle_session_pin(&mut self) {
+        let Some(conversation_id) = self.conversation_id else {
+            self.add_error_message("No session to pin yet.".to_string());
+            return;
+        };
+        update_session_metadata_in_background(
+            self.app_event_tx.clone(),
+            self.config.codex_home.clone(),
+            conversation_id,
+            |metadata| metadata.pinned = !metadata.pinned,
+        );
+    }
+
     /// Programmatically submit a user text message as if typed in the
     /// composer. The text will be added to conversation history and sent to
     /// the agent.
@@ -2594,6 +3030,10 @@ impl ChatWidget {
 
     pub(crate) fn set_persistent_mode_state(&mut self, state: PersistentModeState) {
         self.persistent_mode_state = state;
+        self.record_mode_state();
+        // 面板/模式条中新启用的项目模式若想放宽 sandbox/approval，同样需确认
+        let (_, stripped) = self.scan_mode_defs();
+        self.confirm_mode_loosening(stripped);
     }
 
     pub(crate) fn token_usage(&self) -> TokenUsage {
@@ -2794,5 +3234,36 @@ fn extract_first_bold(s: &str) -> Option<String> {
     None
 }
 
+/// Saves a change to the current session's title, tags or pin state and
+/// reports the result in the transcript.
+fn update_session_metadata_in_background(
+    tx: AppEventSender,
+    codex_home: PathBuf,
+    conversation_id: ConversationId,
+    update: impl FnOnce(&mut SessionMetadata) + Send + 'static,
+) {
+    tokio::spawn(async move {
+        let cell = match update_session_metadata(&codex_home, conversation_id, update).await {
+            Ok(metadata) => {
+                let title = metadata.title.as_deref().unwrap_or("(untitled)");
+                let tags = if metadata.tags.is_empty() {
+                    "no tags".to_string()
+                } else {
+                    metadata
+                        .tags
+                        .iter()
+                        .map(|tag| format!("#{tag}"))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                let pinned = if metadata.pinned { ", pinned" } else { "" };
+                history_cell::new_info_event(format!("Session: {title} ({tags}{pinned})"), None)
+            }
+            Err(e) => history_cell::new_error_event(format!("Failed to update session: {e}")),
+        };
+        tx.send(AppEvent::InsertHistoryCell(Box::new(cell)));
+    });
+}
+
 #[cfg(test)]
 pub(crate) mod tests;
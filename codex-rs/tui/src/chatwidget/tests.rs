@@ -22,6 +22,7 @@ use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ResourceLimitKind;
 use codex_core::protocol::ReviewCodeLocation;
 use codex_core::protocol::ReviewFinding;
 use codex_core::protocol::ReviewLineRange;
@@ -161,6 +162,7 @@ fn resumed_initial_messages_render_history() {
                 message: "assistant reply".to_string(),
             }),
         ]),
+        mode_state: None,
         rollout_path: rollout_file.path().to_path_buf(),
     };
 
@@ -341,6 +343,12 @@ fn make_chatwidget_manual() -> (
         base_user_instructions: None,
         current_user_instructions: None,
         persistent_mode_state: crate::modes::PersistentModeState::default(),
+        modes_snapshot: Vec::new(),
+        recorded_mode_state: None,
+        modes_watcher: None,
+        confirmed_mode_loosening: Vec::new(),
+        asked_mode_loosening: Vec::new(),
+        mode_diagnostics: Vec::new(),
         resumed_session: false,
         lifecycle_hooks: Vec::new(),
         ui_view_factories: Vec::new(),
@@ -433,6 +441,7 @@ fn exec_approval_emits_proposed_command_and_decision_history() {
         reason: Some(
             "this is a test reason such as one that would be produced by the model".into(),
         ),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
@@ -471,6 +480,7 @@ fn exec_approval_decision_truncates_multiline_and_long_commands() {
         reason: Some(
             "this is a test reason such as one that would be produced by the model".into(),
         ),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
@@ -501,6 +511,7 @@ fn exec_approval_decision_truncates_multiline_and_long_commands() {
         command: vec!["bash".into(), "-lc".into(), long],
         cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         reason: None,
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
@@ -553,6 +564,7 @@ fn end_exec(chat: &mut ChatWidget, call_id: &str, stdout: &str, stderr: &str, ex
             exit_code,
             duration: std::time::Duration::from_millis(5),
             formatted_output: aggregated,
+            limit_exceeded: None,
         }),
     });
 }
@@ -688,6 +700,38 @@ fn exec_history_cell_shows_working_then_failed() {
     assert!(blob.to_lowercase().contains("bloop"), "expected error text");
 }
 
+#[test]
+fn exec_end_shows_exceeded_resource_limit() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    begin_exec(&mut chat, "call-cpu", "cargo build");
+    chat.handle_codex_event(Event {
+        id: "call-cpu".to_string(),
+        msg: EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+            call_id: "call-cpu".to_string(),
+            stdout: String::new(),
+            stderr: "Compiling codex-core".to_string(),
+            aggregated_output: "Compiling codex-core".to_string(),
+            exit_code: 152,
+            duration: std::time::Duration::from_secs(60),
+            formatted_output: "Compiling codex-core".to_string(),
+            limit_exceeded: Some(ResourceLimitKind::CpuTime),
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected finalized exec cell to flush");
+    let blob = lines_to_single_string(&cells[0]);
+    assert!(
+        blob.contains("Compiling codex-core"),
+        "expected stderr to be kept: {blob:?}"
+    );
+    assert!(
+        blob.contains("command exceeded the CPU time limit"),
+        "expected limit note: {blob:?}"
+    );
+}
+
 /// Selecting the custom prompt option from the review popup sends
 /// OpenReviewCustomPrompt to the app event channel.
 #[test]
@@ -1200,6 +1244,7 @@ fn approval_modal_exec_snapshot() {
         reason: Some(
             "this is a test reason such as one that would be produced by the model".into(),
         ),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -1228,6 +1273,7 @@ fn approval_modal_exec_without_reason_snapshot() {
         command: vec!["bash".into(), "-lc".into(), "echo hello world".into()],
         cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         reason: None,
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
@@ -1395,6 +1441,7 @@ fn status_widget_and_approval_modal_snapshot() {
         reason: Some(
             "this is a test reason such as one that would be produced by the model".into(),
         ),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -2058,6 +2105,7 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(16000),
             formatted_output: String::new(),
+            limit_exceeded: None,
         }),
     });
     chat.handle_codex_event(Event {
@@ -408,6 +408,7 @@ pub(crate) fn new_session_info(
         history_log_id: _,
         history_entry_count: _,
         initial_messages: _,
+        mode_state: _,
         rollout_path: _,
     } = event;
     if is_first_event {
@@ -877,9 +878,12 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
 }
 
 /// Render MCP tools grouped by connection using the fully-qualified tool names.
+/// `hidden_tools` are the fully-qualified names the tool filter keeps from the
+/// model; they are listed separately from the exposed tools.
 pub(crate) fn new_mcp_tools_output(
     config: &Config,
     tools: std::collections::HashMap<String, mcp_types::Tool>,
+    hidden_tools: &[String],
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = vec![
         "/mcp".magenta().into(),
@@ -898,10 +902,14 @@ pub(crate) fn new_mcp_tools_output(
         let prefix = format!("{server}__");
         let mut names: Vec<String> = tools
             .keys()
-            .filter(|k| k.starts_with(&prefix))
+            .filter(|k| k.starts_with(&prefix) && !hidden_tools.contains(k))
             .map(|k| k[prefix.len()..].to_string())
             .collect();
         names.sort();
+        let hidden: Vec<&str> = hidden_tools
+            .iter()
+            .filter_map(|k| k.strip_prefix(&prefix))
+            .collect();
 
         lines.push(vec!["  • Server: ".into(), server.clone().into()].into());
 
@@ -934,6 +942,9 @@ pub(crate) fn new_mcp_tools_output(
         } else {
             lines.push(vec!["    • Tools: ".into(), names.join(", ").into()].into());
         }
+        if !hidden.is_empty() {
+            lines.push(vec!["    • Hidden: ".into(), hidden.join(", ").dim()].into());
+        }
         lines.push(Line::from(""));
     }
 
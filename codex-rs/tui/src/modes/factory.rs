@@ -27,13 +27,27 @@ impl UiViewFactory for ModesUiDefaultFactory {
         ctx: &ModeUiContext,
     ) -> Option<Box<dyn crate::bottom_pane::BottomPaneView>> {
         use codex_modes::scan_modes;
-        let defs = match scan_modes(&ctx.cwd, Some(&ctx.codex_home)) {
-            Ok(d) => d,
-            Err(e) => {
-                Self::send_error(&ctx.app_event_tx, format!("Failed to scan modes: {e}"));
-                return None;
-            }
-        };
+        let scan = scan_modes(&ctx.cwd, Some(&ctx.codex_home));
+        // 加载失败的模式不出现在面板中，逐个提示原因
+        for diagnostic in &scan.diagnostics {
+            Self::send_error(
+                &ctx.app_event_tx,
+                format!(
+                    "Failed to load mode {}: {}",
+                    diagnostic.id,
+                    diagnostic.message()
+                ),
+            );
+        }
+        let mut defs = scan.defs;
+        if let Some(defaults) = &ctx.session_defaults {
+            codex_modes::strip_loosening_overrides(
+                &mut defs,
+                &defaults.sandbox_policy,
+                defaults.approval_policy,
+                &ctx.confirmed_mode_loosening,
+            );
+        }
         if defs.is_empty() {
             Self::send_error(
                 &ctx.app_event_tx,
@@ -52,7 +66,8 @@ impl UiViewFactory for ModesUiDefaultFactory {
                     ctx.app_event_tx.clone(),
                     ctx.on_update_summary.clone(),
                     ctx.on_update_persistent_state.clone(),
-                );
+                )
+                .with_session_defaults(ctx.session_defaults.clone());
                 Some(Box::new(panel))
             }
             UiViewKind::ModeBar => {
@@ -64,7 +79,8 @@ impl UiViewFactory for ModesUiDefaultFactory {
                     ctx.app_event_tx.clone(),
                     ctx.on_update_summary.clone(),
                     ctx.on_update_persistent_state.clone(),
-                );
+                )
+                .with_session_defaults(ctx.session_defaults.clone());
                 Some(Box::new(bar))
             }
         }
@@ -1,11 +1,17 @@
 mod factory;
 mod mode_bar;
 mod mode_panel;
+mod reload;
 mod state;
 
 pub(crate) use factory::ModesUiDefaultFactory;
 pub(crate) use mode_bar::ModeBarView;
 pub(crate) use mode_panel::ModePanelView;
+pub(crate) use reload::ModesWatcher;
+pub(crate) use reload::keep_failed_modes;
+pub(crate) use reload::plan_reload;
+pub(crate) use state::OverrideFields;
 pub(crate) use state::PersistentModeState;
+pub(crate) use state::SessionDefaults;
 
 // 等价规范化已迁移到 codex-modes::normalize_equiv。
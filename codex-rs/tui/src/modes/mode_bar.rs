@@ -11,13 +11,14 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
 use std::collections::HashMap;
 
+use super::OverrideFields;
 use super::PersistentModeState;
+use super::SessionDefaults;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::CancellationEvent;
 use crate::history_cell;
-use codex_modes::YamlValue as Value;
 use tokio::time::Duration as TokioDuration;
 use tokio::time::sleep;
 
@@ -47,6 +48,12 @@ pub(crate) struct ModeBarView {
     /// 直接更新持久状态（取代 AppEvent::UpdatePersistentModeState）
     on_update_persistent_state:
         std::sync::Arc<dyn Fn(super::PersistentModeState) + Send + Sync + 'static>,
+    /// 会话基线；模式覆写撤销时回落到这里（None 时不回落）
+    session_defaults: Option<SessionDefaults>,
+    /// 当前启用集合已生效的覆写（model/effort/sandbox/approval）
+    applied_overrides: codex_modes::ModeOverrides,
+    /// 尚未下发的覆写变化；去抖期间被取代的发送会累积到这里，由最新一次发送取走
+    pending_overrides: std::sync::Arc<std::sync::Mutex<OverrideFields>>,
 }
 
 impl ModeBarView {
@@ -73,6 +80,7 @@ impl ModeBarView {
         let initial_last = initial_rendered.or_else(|| {
             codex_modes::render_user_instructions(&base_user_instructions, &empty, &defs).ok()
         });
+        let applied_overrides = codex_modes::resolve_overrides(&defs, &enable_order).overrides;
 
         Self {
             title: "Mode".to_string(),
@@ -94,9 +102,25 @@ impl ModeBarView {
             expanded_details: false,
             on_update_summary,
             on_update_persistent_state,
+            session_defaults: None,
+            applied_overrides,
+            pending_overrides: Default::default(),
         }
     }
 
+    pub(crate) fn with_session_defaults(mut self, defaults: Option<SessionDefaults>) -> Self {
+        self.session_defaults = defaults;
+        self
+    }
+
+    /// 正在编辑的变量定义（非编辑态为 None）。
+    fn editing_var_def(&self) -> Option<&codex_modes::ModeVariableDefinition> {
+        let mode_id = self.editing_mode_id.as_ref()?;
+        let var_name = self.editing_var_name.as_ref()?;
+        let def = self.defs.iter().find(|d| &d.id == mode_id)?;
+        def.variables.iter().find(|v| &v.name == var_name)
+    }
+
     fn prev_mode(&mut self) {
         if self.defs.is_empty() {
             return;
@@ -145,12 +169,18 @@ impl ModeBarView {
         }
         let idx = self.selected_mode_idx.min(self.defs.len() - 1);
         let id = self.defs[idx].id.clone();
-        if self.enabled.contains(&id) {
-            self.enabled.swap_remove(&id);
-            self.enable_order.retain(|x| x != &id);
+        // 依赖/冲突联动：requires 自动启用，conflicts_with 自动停用
+        let effects = if self.enabled.contains(&id) {
+            codex_modes::disable_with_constraints(&self.defs, &mut self.enable_order, &id)
         } else {
-            self.enabled.insert(id.clone());
-            self.enable_order.push(id);
+            codex_modes::enable_with_constraints(&self.defs, &mut self.enable_order, &id)
+        };
+        self.enabled = self.enable_order.iter().cloned().collect();
+        if let Some(detail) = effects.describe() {
+            let cell =
+                history_cell::new_info_event("Mode constraints applied".to_string(), Some(detail));
+            self.app_event_tx
+                .send(AppEvent::InsertHistoryCell(Box::new(cell)));
         }
     }
 
@@ -196,6 +226,26 @@ impl ModeBarView {
                 .send(AppEvent::InsertHistoryCell(Box::new(cell)));
             return;
         }
+        // 冲突/依赖缺失（E3109/E3110）拦截发送
+        let mut blocked = false;
+        for e in codex_modes::validate_enabled(&self.defs, &enabled_list) {
+            if matches!(
+                e,
+                codex_modes::ValidationError::ModeConflict { .. }
+                    | codex_modes::ValidationError::RequiresMissing { .. }
+            ) && let Some(msg) = codex_modes::format_validation_error(&e)
+            {
+                let cell = history_cell::new_error_event(msg);
+                self.app_event_tx
+                    .send(AppEvent::InsertHistoryCell(Box::new(cell)));
+                blocked = true;
+            }
+        }
+        if blocked {
+            return;
+        }
+        // 按 priority 降序（同优先级保持启用顺序），与渲染顺序一致
+        codex_modes::sort_by_priority(&self.defs, &mut enabled_list);
         let rendered = match codex_modes::render_user_instructions(
             &self.base_user_instructions,
             &enabled_list,
@@ -215,11 +265,18 @@ impl ModeBarView {
             enable_order: self.enable_order.clone(),
             var_values: self.var_values.clone(),
         });
-        if self
-            .last_sent
-            .as_ref()
-            .map(|s| codex_modes::is_equivalent(s, &rendered))
-            .unwrap_or(false)
+        let resolved = codex_modes::resolve_overrides(&self.defs, &self.enable_order);
+        let fields = OverrideFields::diff(
+            &self.applied_overrides,
+            &resolved.overrides,
+            self.session_defaults.as_ref(),
+        );
+        if fields.is_empty()
+            && self
+                .last_sent
+                .as_ref()
+                .map(|s| codex_modes::is_equivalent(s, &rendered))
+                .unwrap_or(false)
         {
             return;
         }
@@ -235,25 +292,62 @@ impl ModeBarView {
             .join(", ");
         // 提前记录 last_sent，避免等价内容重复发送造成多条提示
         self.last_sent = Some(rendered.clone());
+        let has_pending = match self.pending_overrides.lock() {
+            Ok(mut pending) => {
+                pending.merge(fields);
+                !pending.is_empty()
+            }
+            Err(_) => false,
+        };
+        let overrides_hint = has_pending.then(|| {
+            let applied = resolved.overrides.describe();
+            if applied.is_empty() {
+                "reverted to session defaults".to_string()
+            } else {
+                applied
+            }
+        });
+        let conflicts_hint = resolved.describe_conflicts().filter(|_| has_pending);
+        self.applied_overrides = resolved.overrides;
         let rendered_cloned = rendered;
         let debouncer = self.debouncer.clone();
+        let pending_overrides = self.pending_overrides.clone();
         tokio::spawn(async move {
             sleep(TokioDuration::from_millis(Self::DEBOUNCE_DELAY_MS)).await;
             if !debouncer.is_latest(r#gen) {
                 return; // superseded by a newer change
             }
             {
+                let fields = pending_overrides
+                    .lock()
+                    .map(|mut p| std::mem::take(&mut *p))
+                    .unwrap_or_default();
                 tx.send(AppEvent::CodexOp(
                     codex_core::protocol::Op::OverrideTurnContext {
                         cwd: None,
-                        approval_policy: None,
-                        sandbox_policy: None,
-                        model: None,
-                        effort: None,
+                        approval_policy: fields.approval_policy,
+                        sandbox_policy: fields.sandbox_policy,
+                        model: fields.model,
+                        effort: fields.effort,
                         summary: None,
                         user_instructions: Some(rendered_cloned),
+                        tool_filter: fields.tool_filter,
                     },
                 ));
+                if let Some(hint) = overrides_hint {
+                    let info = history_cell::new_info_event(
+                        "Mode overrides applied".to_string(),
+                        Some(hint),
+                    );
+                    tx.send(AppEvent::InsertHistoryCell(Box::new(info)));
+                }
+                if let Some(hint) = conflicts_hint {
+                    let info = history_cell::new_info_event(
+                        "Mode overrides conflict".to_string(),
+                        Some(hint),
+                    );
+                    tx.send(AppEvent::InsertHistoryCell(Box::new(info)));
+                }
                 let count = labels
                     .split(',')
                     .map(str::trim)
@@ -359,27 +453,12 @@ impl BottomPaneView for ModeBarView {
                                     .send(AppEvent::InsertHistoryCell(Box::new(cell)));
                                 return;
                             }
-                            let vv = self.var_values.entry(mode_id).or_default();
-                            // 存储规范化：非枚举值 trim；布尔值统一小写 true/false
-                            if vdef.r#enum.is_some() {
-                                if self.editing_buffer.is_empty() {
-                                    vv.insert(var_name, None);
-                                } else {
-                                    vv.insert(var_name, Some(self.editing_buffer.clone()));
-                                }
-                            } else {
-                                let trimmed = self.editing_buffer.trim();
-                                if trimmed.is_empty() {
-                                    vv.insert(var_name, None);
-                                } else if matches!(
-                                    vdef.var_type,
-                                    Some(codex_modes::VarType::Boolean)
-                                ) {
-                                    vv.insert(var_name, Some(trimmed.to_lowercase()));
-                                } else {
-                                    vv.insert(var_name, Some(trimmed.to_string()));
-                                }
-                            }
+                            // 存储规范化（库层）：枚举原样；布尔小写；列表统一为 `a, b`；其余 trim
+                            let value = vdef.normalize_value(&self.editing_buffer);
+                            self.var_values
+                                .entry(mode_id)
+                                .or_default()
+                                .insert(var_name, value);
                         } else {
                             // 找不到 def/vdef 时回退到“是否空值”的旧逻辑
                             let vv = self.var_values.entry(mode_id).or_default();
@@ -397,6 +476,23 @@ impl BottomPaneView for ModeBarView {
                     self.render_and_maybe_send();
                     return;
                 }
+                // 多行变量：Shift+Enter / Ctrl+J 插入换行
+                KeyEvent {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::SHIFT,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('j'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                } if self
+                    .editing_var_def()
+                    .is_some_and(|v| v.var_type == Some(codex_modes::VarType::Multiline)) =>
+                {
+                    self.editing_buffer.push('\n');
+                    return;
+                }
                 KeyEvent {
                     code: KeyCode::Backspace,
                     ..
@@ -482,8 +578,8 @@ impl BottomPaneView for ModeBarView {
                     if let Some(options) = &var_def.r#enum {
                         if !cur.is_empty() {
                             self.editing_buffer = cur;
-                        } else if let Some(Value::String(s)) = &var_def.default {
-                            self.editing_buffer = s.clone();
+                        } else if let Some(s) = var_def.default_value() {
+                            self.editing_buffer = s;
                         } else if let Some(first) = options.first() {
                             self.editing_buffer = first.clone();
                         } else {
@@ -543,7 +639,7 @@ impl crate::render::renderable::Renderable for ModeBarView {
             let mut kvs: Vec<String> = Vec::new();
             for v in &def.variables {
                 if let Some(Some(val)) = vv.and_then(|m| m.get(&v.name)) {
-                    kvs.push(format!("{}={}", v.name, val));
+                    kvs.push(format!("{}={}", v.name, v.display_value(val)));
                 } else if v.default.is_some() {
                     kvs.push(format!("{}=(default)", v.name));
                 }
@@ -653,21 +749,22 @@ impl crate::render::renderable::Renderable for ModeBarView {
                 let vv = self.var_values.get(&def.id);
                 for (i, var) in def.variables.iter().enumerate() {
                     let explicit = vv.and_then(|m| m.get(&var.name).cloned()).flatten();
-                    // 默认值字符串（用于标签直观展示）
-                    let default_str: Option<String> = var.default.as_ref().and_then(|d| match d {
-                        Value::Null => None,
-                        Value::Bool(b) => Some(b.to_string()),
-                        Value::Number(n) => Some(n.to_string()),
-                        Value::String(s) => Some(s.clone()),
-                        _ => None,
-                    });
+                    // 默认值字符串（用于标签直观展示；secret 打码、多行折叠）
+                    let default_str: Option<String> =
+                        var.default_value().map(|d| var.display_value(&d));
                     let text = if self.editing
                         && self.editing_mode_id.as_deref() == Some(def.id.as_str())
                         && self.editing_var_name.as_deref() == Some(var.name.as_str())
                     {
-                        format!("[{}={}_]", var.name, self.editing_buffer)
+                        // secret 编辑时只显示已输入的字符数
+                        let buffer = if var.is_secret() {
+                            "•".repeat(self.editing_buffer.chars().count())
+                        } else {
+                            var.display_value(&self.editing_buffer)
+                        };
+                        format!("[{}={buffer}_]", var.name)
                     } else if let Some(val) = explicit.clone() {
-                        format!("[{}={}]", var.name, val)
+                        format!("[{}={}]", var.name, var.display_value(&val))
                     } else if var.required && var.default.is_none() {
                         format!("[{}=!]", var.name)
                     } else if let Some(dv) = default_str {
@@ -714,18 +811,14 @@ impl crate::render::renderable::Renderable for ModeBarView {
                 );
             }
             let hint = if self.editing {
-                // 若是枚举编辑，提供上下选择提示
-                let mut enum_editing = false;
-                if let (Some(mode_id), Some(var_name)) = (
-                    self.editing_mode_id.as_ref(),
-                    self.editing_var_name.as_ref(),
-                ) && let Some(def) = self.defs.iter().find(|d| &d.id == mode_id)
-                    && let Some(vdef) = def.variables.iter().find(|v| &v.name == var_name)
-                    && vdef.r#enum.is_some()
-                {
-                    enum_editing = true;
-                }
-                if enum_editing {
+                // 按变量类型给出编辑提示：枚举上下选择、多行换行、列表分隔符
+                let vdef = self.editing_var_def();
+                let var_type = vdef.and_then(|v| v.var_type);
+                if var_type == Some(codex_modes::VarType::Multiline) {
+                    "⇧⏎ Newline  ⏎ Apply  Esc Cancel".dim().to_string()
+                } else if var_type == Some(codex_modes::VarType::List) {
+                    "a, b, c  ⏎ Apply  Esc Cancel".dim().to_string()
+                } else if vdef.is_some_and(|v| v.r#enum.is_some()) {
                     "↑↓ Select  ⏎ Apply  Esc Cancel".dim().to_string()
                 } else {
                     "⏎ Apply  Esc Cancel".dim().to_string()
@@ -791,7 +884,7 @@ impl crate::render::renderable::Renderable for ModeBarView {
                 let mut kvs: Vec<String> = Vec::new();
                 for v in &def.variables {
                     if let Some(Some(val)) = vv.and_then(|m| m.get(&v.name)) {
-                        kvs.push(format!("{}={}", v.name, val));
+                        kvs.push(format!("{}={}", v.name, v.display_value(val)));
                     } else if v.default.is_some() {
                         kvs.push(format!("{}=(default)", v.name));
                     }
@@ -905,10 +998,18 @@ mod tests {
                     pattern: None,
                     inline_edit: None,
                     mode_scoped: None,
+                    default_from: None,
                 }],
                 scope: codex_modes::ModeScope::Project("demo".into()),
                 path: PathBuf::new(),
                 body: "Review with target={{target}}".into(),
+                extends: None,
+                includes: Vec::new(),
+                inherited: Vec::new(),
+                conflicts_with: Vec::new(),
+                requires: Vec::new(),
+                priority: 0,
+                overrides: Default::default(),
             },
             codex_modes::ModeDefinition {
                 id: "/qa".into(),
@@ -927,10 +1028,18 @@ mod tests {
                     pattern: None,
                     inline_edit: None,
                     mode_scoped: None,
+                    default_from: None,
                 }],
                 scope: codex_modes::ModeScope::Project("demo".into()),
                 path: PathBuf::new(),
                 body: "QA level={{level}}".into(),
+                extends: None,
+                includes: Vec::new(),
+                inherited: Vec::new(),
+                conflicts_with: Vec::new(),
+                requires: Vec::new(),
+                priority: 0,
+                overrides: Default::default(),
             },
         ]
     }
@@ -1100,10 +1209,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: codex_modes::ModeScope::Project("demo".into()),
             path: PathBuf::new(),
             body: "Body".into(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         }];
         let mut state = PersistentModeState::default();
         if state.enabled.insert("/enum".to_string()) {
@@ -1149,10 +1266,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: codex_modes::ModeScope::Project("demo".into()),
             path: PathBuf::new(),
             body: "Body".into(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         }];
         let mut state = PersistentModeState::default();
         if state.enabled.insert("/b".to_string()) {
@@ -1198,10 +1323,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: codex_modes::ModeScope::Project("demo".into()),
             path: PathBuf::new(),
             body: "Body".into(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         }];
         let mut state = PersistentModeState::default();
         if state.enabled.insert("/n".to_string()) {
@@ -1247,10 +1380,18 @@ mod tests {
                 pattern: None,
                 inline_edit: None,
                 mode_scoped: None,
+                default_from: None,
             }],
             scope: codex_modes::ModeScope::Project("demo".into()),
             path: PathBuf::new(),
             body: "Body".into(),
+            extends: None,
+            includes: Vec::new(),
+            inherited: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            priority: 0,
+            overrides: Default::default(),
         }];
         let mut state = PersistentModeState::default();
         if state.enabled.insert("/p".to_string()) {
@@ -1274,4 +1415,58 @@ mod tests {
         let last = msgs.last().cloned().unwrap_or_default();
         assert_snapshot!("modebar_error_path_invalid", last);
     }
+
+    #[tokio::test]
+    async fn multiline_edit_and_secret_masking() {
+        let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut defs = mk_defs();
+        let review = &mut defs[0];
+        review.variables[0].var_type = Some(codex_modes::VarType::Multiline);
+        let mut token = review.variables[0].clone();
+        token.name = "token".into();
+        token.var_type = Some(codex_modes::VarType::Secret);
+        review.variables.push(token);
+        let mut state = PersistentModeState::default();
+        if state.enabled.insert("/review".to_string()) {
+            state.enable_order.push("/review".to_string());
+        }
+        let mut vars = IndexMap::new();
+        vars.insert("token".to_string(), Some("hunter2".to_string()));
+        state.var_values.insert("/review".to_string(), vars);
+        let mut view = ModeBarView::new(
+            defs,
+            state,
+            "base".to_string(),
+            None,
+            tx,
+            std::sync::Arc::new(|_s: String| {}),
+            std::sync::Arc::new(|_st: PersistentModeState| {}),
+        );
+
+        // 编辑多行变量：Shift+Enter 换行，Enter 应用
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        for key in [
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE),
+        ] {
+            view.handle_key_event(key);
+        }
+        assert_eq!(view.editing_buffer, "a\nb");
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!view.editing);
+        assert_eq!(
+            view.var_values["/review"].get("target"),
+            Some(&Some("a\nb".to_string()))
+        );
+
+        let rendered = render_lines(&view, 64);
+        assert!(rendered.contains("[target=a\\nb]"), "{rendered}");
+        assert!(rendered.contains("[token=••••]"), "{rendered}");
+        assert!(!rendered.contains("hunter2"), "{rendered}");
+        view.expanded_details = true;
+        let details = render_lines(&view, 64);
+        assert!(!details.contains("hunter2"), "{details}");
+    }
 }
@@ -9,7 +9,9 @@ use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
 
+use super::OverrideFields;
 use super::PersistentModeState;
+use super::SessionDefaults;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
@@ -45,6 +47,10 @@ pub(crate) struct ModePanelView {
     /// 直接更新持久状态（取代 AppEvent::UpdatePersistentModeState）
     on_update_persistent_state:
         std::sync::Arc<dyn Fn(super::PersistentModeState) + Send + Sync + 'static>,
+    /// 会话基线；模式覆写撤销时回落到这里（None 时不回落）
+    session_defaults: Option<SessionDefaults>,
+    /// 当前启用集合已生效的覆写（model/effort/sandbox/approval）
+    applied_overrides: codex_modes::ModeOverrides,
 }
 
 impl ModePanelView {
@@ -66,6 +72,7 @@ impl ModePanelView {
             enable_order,
             var_values,
         } = initial_state.sanitize(&defs);
+        let applied_overrides = codex_modes::resolve_overrides(&defs, &enable_order).overrides;
         Self {
             title,
             defs,
@@ -79,21 +86,34 @@ impl ModePanelView {
             complete: false,
             on_update_summary,
             on_update_persistent_state,
+            session_defaults: None,
+            applied_overrides,
         }
     }
 
+    pub(crate) fn with_session_defaults(mut self, defaults: Option<SessionDefaults>) -> Self {
+        self.session_defaults = defaults;
+        self
+    }
+
     fn toggle_selected(&mut self) {
         if self.defs.is_empty() {
             return;
         }
         let idx = self.selected.min(self.defs.len() - 1);
         let id = self.defs[idx].id.clone();
-        if self.enabled.contains(&id) {
-            self.enabled.swap_remove(&id);
-            self.enable_order.retain(|x| x != &id);
+        // 依赖/冲突联动：requires 自动启用，conflicts_with 自动停用
+        let effects = if self.enabled.contains(&id) {
+            codex_modes::disable_with_constraints(&self.defs, &mut self.enable_order, &id)
         } else {
-            self.enabled.insert(id);
-            self.enable_order.push(self.defs[idx].id.clone());
+            codex_modes::enable_with_constraints(&self.defs, &mut self.enable_order, &id)
+        };
+        self.enabled = self.enable_order.iter().cloned().collect();
+        if let Some(detail) = effects.describe() {
+            let cell =
+                history_cell::new_info_event("Mode constraints applied".to_string(), Some(detail));
+            self.app_event_tx
+                .send(AppEvent::InsertHistoryCell(Box::new(cell)));
         }
     }
 
@@ -161,7 +181,27 @@ impl ModePanelView {
                     .send(AppEvent::InsertHistoryCell(Box::new(cell)));
                 return;
             }
+            // 冲突/依赖缺失（E3109/E3110）同样拦截发送
+            let mut blocked = false;
+            for e in &v_errs {
+                if matches!(
+                    e,
+                    codex_modes::ValidationError::ModeConflict { .. }
+                        | codex_modes::ValidationError::RequiresMissing { .. }
+                ) && let Some(msg) = codex_modes::format_validation_error(e)
+                {
+                    let cell = history_cell::new_error_event(msg);
+                    self.app_event_tx
+                        .send(AppEvent::InsertHistoryCell(Box::new(cell)));
+                    blocked = true;
+                }
+            }
+            if blocked {
+                return;
+            }
         }
+        // 按 priority 降序（同优先级保持启用顺序），与渲染顺序一致
+        codex_modes::sort_by_priority(&self.defs, &mut enabled_list);
         let rendered =
             match render_user_instructions(&self.base_user_instructions, &enabled_list, &self.defs)
             {
@@ -179,26 +219,54 @@ impl ModePanelView {
             enable_order: self.enable_order.clone(),
             var_values: self.var_values.clone(),
         });
-        if self
-            .last_sent
-            .as_ref()
-            .map(|s| is_equivalent(s, &rendered))
-            .unwrap_or(false)
+        let resolved = codex_modes::resolve_overrides(&self.defs, &self.enable_order);
+        let fields = OverrideFields::diff(
+            &self.applied_overrides,
+            &resolved.overrides,
+            self.session_defaults.as_ref(),
+        );
+        if fields.is_empty()
+            && self
+                .last_sent
+                .as_ref()
+                .map(|s| is_equivalent(s, &rendered))
+                .unwrap_or(false)
         {
             return;
         }
+        let overrides_changed = !fields.is_empty();
 
         self.app_event_tx.send(AppEvent::CodexOp(
             codex_core::protocol::Op::OverrideTurnContext {
                 cwd: None,
-                approval_policy: None,
-                sandbox_policy: None,
-                model: None,
-                effort: None,
+                approval_policy: fields.approval_policy,
+                sandbox_policy: fields.sandbox_policy,
+                model: fields.model,
+                effort: fields.effort,
                 summary: None,
                 user_instructions: Some(rendered.clone()),
+                tool_filter: fields.tool_filter,
             },
         ));
+        if overrides_changed {
+            let applied = resolved.overrides.describe();
+            let hint = if applied.is_empty() {
+                "reverted to session defaults".to_string()
+            } else {
+                applied
+            };
+            let info =
+                history_cell::new_info_event("Mode overrides applied".to_string(), Some(hint));
+            self.app_event_tx
+                .send(AppEvent::InsertHistoryCell(Box::new(info)));
+            if let Some(hint) = resolved.describe_conflicts() {
+                let info =
+                    history_cell::new_info_event("Mode overrides conflict".to_string(), Some(hint));
+                self.app_event_tx
+                    .send(AppEvent::InsertHistoryCell(Box::new(info)));
+            }
+        }
+        self.applied_overrides = resolved.overrides;
 
         // 同步一条简短的反馈信息到历史（库层统一文案）
         let labels_comma = enabled_list
@@ -381,6 +449,13 @@ mod tests {
                 scope: codex_modes::ModeScope::Project("demo".into()),
                 path: PathBuf::new(),
                 body: "Review".into(),
+                extends: None,
+                includes: Vec::new(),
+                inherited: Vec::new(),
+                conflicts_with: Vec::new(),
+                requires: Vec::new(),
+                priority: 0,
+                overrides: Default::default(),
             },
             codex_modes::ModeDefinition {
                 id: "/qa".into(),
@@ -399,10 +474,18 @@ mod tests {
                     pattern: None,
                     inline_edit: None,
                     mode_scoped: None,
+                    default_from: None,
                 }],
                 scope: codex_modes::ModeScope::Project("demo".into()),
                 path: PathBuf::new(),
                 body: "QA".into(),
+                extends: None,
+                includes: Vec::new(),
+                inherited: Vec::new(),
+                conflicts_with: Vec::new(),
+                requires: Vec::new(),
+                priority: 0,
+                overrides: Default::default(),
             },
         ]
     }
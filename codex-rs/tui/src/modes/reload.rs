@@ -0,0 +1,251 @@
+//! 模式热重载：后台轮询模式目录指纹，变化经 `DebounceGen` 去抖后回投 UI 线程重扫，
+//! 再据新定义修剪已启用状态并重新渲染 `<user_instructions>`（下一轮生效）。
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use codex_modes::DebounceGen;
+use codex_modes::EnabledMode;
+use codex_modes::IndexMap;
+use codex_modes::ModeDefinition;
+use codex_modes::ModeDiagnostic;
+use codex_modes::ModesDiff;
+
+use super::OverrideFields;
+use super::PersistentModeState;
+use super::SessionDefaults;
+
+/// 连续写入（编辑器保存、git checkout）合并为一次重扫的静默窗口。
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 后台轮询任务句柄；Drop 时停止轮询。
+pub(crate) struct ModesWatcher(tokio::task::JoinHandle<()>);
+
+impl Drop for ModesWatcher {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl ModesWatcher {
+    /// 启动轮询；变化静默 `DEBOUNCE` 后调用一次 `on_change`。
+    /// 无 tokio 运行时（如单元测试）时返回 None。
+    pub(crate) fn spawn(
+        cwd: PathBuf,
+        codex_home: PathBuf,
+        on_change: Arc<dyn Fn() + Send + Sync>,
+    ) -> Option<Self> {
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        let task = runtime.spawn(async move {
+            let debouncer = DebounceGen::new();
+            let mut last = codex_modes::modes_fingerprint(&cwd, Some(&codex_home));
+            loop {
+                tokio::time::sleep(codex_modes::MODES_POLL_INTERVAL).await;
+                let now = codex_modes::modes_fingerprint(&cwd, Some(&codex_home));
+                if now == last {
+                    continue;
+                }
+                last = now;
+                let r#gen = debouncer.next();
+                let debouncer = debouncer.clone();
+                let on_change = on_change.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(DEBOUNCE).await;
+                    if debouncer.is_latest(r#gen) {
+                        on_change();
+                    }
+                });
+            }
+        });
+        Some(Self(task))
+    }
+}
+
+/// 一次重扫的计算结果（不含副作用，由 ChatWidget 负责下发与提示）。
+#[derive(Debug)]
+pub(crate) struct ModesReload {
+    pub diff: ModesDiff,
+    /// 按新定义修剪后的启用状态；有 `errors` 时为原状态。
+    pub state: PersistentModeState,
+    /// 需下发的完整 `<user_instructions>`；None 表示与当前已生效内容等价。
+    pub rendered: Option<String>,
+    pub overrides: OverrideFields,
+    /// 摘要标签（如 "a · b"），为空表示无启用模式。
+    pub labels: String,
+    /// 校验或渲染失败（带错误码）；此时不下发、保持原有指令。
+    pub errors: Vec<String>,
+}
+
+/// 文件改坏（加载失败）的模式沿用上次成功加载的定义：不当作删除、不停用，
+/// 错误由扫描诊断提示；文件修好后按新定义重载。
+pub(crate) fn keep_failed_modes(
+    defs: &mut Vec<ModeDefinition>,
+    prev_defs: &[ModeDefinition],
+    diagnostics: &[ModeDiagnostic],
+) {
+    for diagnostic in diagnostics {
+        if defs.iter().any(|d| d.id == diagnostic.id) {
+            continue;
+        }
+        if let Some(prev) = prev_defs.iter().find(|d| d.id == diagnostic.id) {
+            defs.push(prev.clone());
+        }
+    }
+}
+
+/// 对比前后两次扫描，修剪 `state` 并重新渲染已启用模式。
+pub(crate) fn plan_reload(
+    prev_defs: &[ModeDefinition],
+    defs: &[ModeDefinition],
+    state: &PersistentModeState,
+    base_user_instructions: &str,
+    current_user_instructions: Option<&str>,
+    defaults: Option<&SessionDefaults>,
+) -> ModesReload {
+    let diff = codex_modes::diff_modes(prev_defs, defs);
+    let next = state.clone().sanitize(defs);
+    let mut enabled: Vec<EnabledMode> = next
+        .enable_order
+        .iter()
+        .filter_map(|id| defs.iter().find(|d| &d.id == id))
+        .map(|def| {
+            let values = next.var_values.get(&def.id);
+            let mut vars: IndexMap<&str, Option<String>> = IndexMap::new();
+            for v in &def.variables {
+                let val = values.and_then(|m| m.get(&v.name).cloned()).flatten();
+                vars.insert(v.name.as_str(), val);
+            }
+            EnabledMode {
+                id: &def.id,
+                display_name: def.display_name.as_deref(),
+                scope: &def.scope,
+                variables: vars,
+            }
+        })
+        .collect();
+    let unchanged = |errors: Vec<String>| ModesReload {
+        diff: diff.clone(),
+        state: state.clone(),
+        rendered: None,
+        overrides: OverrideFields::default(),
+        labels: String::new(),
+        errors,
+    };
+
+    let errors =
+        codex_modes::format_validation_errors(&codex_modes::validate_enabled(defs, &enabled));
+    if !errors.is_empty() {
+        return unchanged(errors);
+    }
+    codex_modes::sort_by_priority(defs, &mut enabled);
+    let rendered =
+        match codex_modes::render_user_instructions(base_user_instructions, &enabled, defs) {
+            Ok(rendered) => rendered,
+            Err(e) => return unchanged(vec![codex_modes::format_modes_error(&e)]),
+        };
+    // 从未启用过模式时无需下发基线文本
+    let rendered = match current_user_instructions {
+        Some(current) if codex_modes::is_equivalent(current, &rendered) => None,
+        None if enabled.is_empty() => None,
+        _ => Some(rendered),
+    };
+    let prev_overrides = codex_modes::resolve_overrides(prev_defs, &state.enable_order).overrides;
+    let next_overrides = codex_modes::resolve_overrides(defs, &next.enable_order).overrides;
+    let overrides = OverrideFields::diff(&prev_overrides, &next_overrides, defaults);
+    let labels = codex_modes::enabled_labels(&enabled);
+    ModesReload {
+        diff,
+        state: next,
+        rendered,
+        overrides,
+        labels,
+        errors: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn write(dir: &std::path::Path, name: &str, text: &str) {
+        let path = dir.join(".codex/modes").join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    fn enabled(ids: &[&str]) -> PersistentModeState {
+        let mut state = PersistentModeState::default();
+        for id in ids {
+            state.enabled.insert(id.to_string());
+            state.enable_order.push(id.to_string());
+        }
+        state
+    }
+
+    #[test]
+    fn reload_drops_removed_modes_and_rerenders_changed_bodies() {
+        let project = tempfile::tempdir().unwrap();
+        write(project.path(), "a.md", "alpha v1");
+        write(project.path(), "b.md", "beta");
+        let before = codex_modes::scan_modes(project.path(), None).defs;
+        let state = enabled(&["/a", "/b"]);
+
+        write(project.path(), "a.md", "alpha v2");
+        std::fs::remove_file(project.path().join(".codex/modes/b.md")).unwrap();
+        let after = codex_modes::scan_modes(project.path(), None).defs;
+        let reload = plan_reload(&before, &after, &state, "base", Some("old"), None);
+
+        assert_eq!(reload.diff.describe(), "changed /a; removed /b");
+        assert_eq!(reload.state.enable_order, vec!["/a".to_string()]);
+        assert_eq!(reload.labels, "a");
+        assert!(reload.errors.is_empty());
+        let rendered = reload.rendered.unwrap();
+        assert!(rendered.contains("alpha v2"), "{rendered}");
+        assert!(!rendered.contains("beta"), "{rendered}");
+    }
+
+    #[test]
+    fn reload_keeps_state_when_enabled_mode_becomes_invalid() {
+        let project = tempfile::tempdir().unwrap();
+        write(project.path(), "a.md", "alpha");
+        let before = codex_modes::scan_modes(project.path(), None).defs;
+        let state = enabled(&["/a"]);
+
+        write(
+            project.path(),
+            "a.md",
+            "---\nvariables:\n  - name: who\n    required: true\n---\nhi {{who}}",
+        );
+        let after = codex_modes::scan_modes(project.path(), None).defs;
+        let reload = plan_reload(&before, &after, &state, "base", Some("old"), None);
+
+        assert_eq!(
+            reload.errors,
+            vec!["E3101 RequiredMissing: /a/who".to_string()]
+        );
+        assert_eq!(reload.state.enable_order, state.enable_order);
+        assert_eq!(reload.rendered, None);
+    }
+
+    #[test]
+    fn reload_keeps_modes_whose_file_no_longer_loads() {
+        let project = tempfile::tempdir().unwrap();
+        write(project.path(), "a.md", "alpha");
+        write(project.path(), "b.md", "beta");
+        let before = codex_modes::scan_modes(project.path(), None).defs;
+        let state = enabled(&["/a", "/b"]);
+
+        write(project.path(), "a.md", "---\nkind: [\n---\nalpha");
+        let scan = codex_modes::scan_modes(project.path(), None);
+        assert_eq!(scan.diagnostics.len(), 1);
+        let mut after = scan.defs;
+        keep_failed_modes(&mut after, &before, &scan.diagnostics);
+        let reload = plan_reload(&before, &after, &state, "base", None, None);
+
+        assert!(reload.diff.is_empty(), "{}", reload.diff.describe());
+        assert_eq!(reload.state.enable_order, state.enable_order);
+        assert!(reload.errors.is_empty());
+    }
+}
@@ -1,6 +1,12 @@
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ModeStateItem;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_core::protocol_config_types::ToolFilter;
 use codex_modes::IndexMap;
 use codex_modes::IndexSet;
 use codex_modes::ModeDefinition;
+use codex_modes::ModeOverrides;
 use std::collections::HashMap;
 
 /// 当前会话中已启用的持久模式状态快照。
@@ -16,6 +22,40 @@ impl PersistentModeState {
         self.enabled.is_empty()
     }
 
+    /// 写入 rollout 的快照（变量按名称排序，便于比较与去重）。
+    /// secret 变量与 `defs` 中未声明的变量不写入。
+    pub(crate) fn to_item(&self, defs: &[ModeDefinition]) -> ModeStateItem {
+        ModeStateItem {
+            enabled: self.enable_order.clone(),
+            variables: self
+                .var_values
+                .iter()
+                .map(|(id, vars)| {
+                    let vars = vars
+                        .iter()
+                        .filter(|(k, _)| codex_modes::is_recordable_var(defs, id, k))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect();
+                    (id.clone(), vars)
+                })
+                .collect(),
+        }
+    }
+
+    /// 从 rollout 快照还原；调用方需再以当前定义 `sanitize`。
+    pub(crate) fn from_item(item: ModeStateItem) -> Self {
+        let mut state = Self::default();
+        for id in item.enabled {
+            if state.enabled.insert(id.clone()) {
+                state.enable_order.push(id);
+            }
+        }
+        for (id, vars) in item.variables {
+            state.var_values.insert(id, vars.into_iter().collect());
+        }
+        state
+    }
+
     pub(crate) fn sanitize(self, defs: &[ModeDefinition]) -> Self {
         let mut defs_by_id: HashMap<&str, &ModeDefinition> = HashMap::new();
         for def in defs {
@@ -57,3 +97,222 @@ impl PersistentModeState {
         }
     }
 }
+
+/// 会话基线设置（来自当前 Config）：模式覆写被撤销时回落到这些值。
+#[derive(Debug, Clone)]
+pub(crate) struct SessionDefaults {
+    pub model: String,
+    pub effort: Option<ReasoningEffortConfig>,
+    pub sandbox_policy: SandboxPolicy,
+    pub approval_policy: AskForApproval,
+    pub tool_filter: ToolFilter,
+}
+
+/// 模式覆写变化对应的 `Op::OverrideTurnContext` 字段；None 表示该字段不变。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct OverrideFields {
+    pub model: Option<String>,
+    pub effort: Option<Option<ReasoningEffortConfig>>,
+    pub sandbox_policy: Option<SandboxPolicy>,
+    pub approval_policy: Option<AskForApproval>,
+    pub tool_filter: Option<ToolFilter>,
+}
+
+impl OverrideFields {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.model.is_none()
+            && self.effort.is_none()
+            && self.sandbox_policy.is_none()
+            && self.approval_policy.is_none()
+            && self.tool_filter.is_none()
+    }
+
+    /// 合并更晚的变化：`later` 中已设置的字段覆盖当前值。
+    pub(crate) fn merge(&mut self, later: OverrideFields) {
+        if later.model.is_some() {
+            self.model = later.model;
+        }
+        if later.effort.is_some() {
+            self.effort = later.effort;
+        }
+        if later.sandbox_policy.is_some() {
+            self.sandbox_policy = later.sandbox_policy;
+        }
+        if later.approval_policy.is_some() {
+            self.approval_policy = later.approval_policy;
+        }
+        if later.tool_filter.is_some() {
+            self.tool_filter = later.tool_filter;
+        }
+    }
+
+    /// 计算已生效覆写 `prev` 到 `next` 的变化：新值直接下发；
+    /// 被撤销的字段回落到 `defaults`（缺少基线时保持不变）。
+    pub(crate) fn diff(
+        prev: &ModeOverrides,
+        next: &ModeOverrides,
+        defaults: Option<&SessionDefaults>,
+    ) -> Self {
+        let mut out = Self::default();
+        if prev.model != next.model {
+            out.model = match &next.model {
+                Some(m) => Some(m.clone()),
+                None => defaults.map(|d| d.model.clone()),
+            };
+        }
+        if prev.effort != next.effort {
+            out.effort = match next.effort.as_deref() {
+                Some(e) => codex_modes::parse_effort(e).map(Some),
+                None => defaults.map(|d| d.effort),
+            };
+        }
+        if prev.sandbox != next.sandbox {
+            out.sandbox_policy = match next.sandbox.as_deref() {
+                Some(s) => codex_modes::parse_sandbox(
+                    s,
+                    defaults.map_or(&SandboxPolicy::DangerFullAccess, |d| &d.sandbox_policy),
+                ),
+                None => defaults.map(|d| d.sandbox_policy.clone()),
+            };
+        }
+        if prev.approval_policy != next.approval_policy {
+            out.approval_policy = match next.approval_policy.as_deref() {
+                Some(a) => codex_modes::parse_approval(a),
+                None => defaults.map(|d| d.approval_policy),
+            };
+        }
+        if prev.tools != next.tools {
+            out.tool_filter = match &next.tools {
+                Some(t) => Some(t.clone()),
+                None => defaults.map(|d| d.tool_filter.clone()),
+            };
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn defaults() -> SessionDefaults {
+        SessionDefaults {
+            model: "gpt-5".to_string(),
+            effort: None,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            approval_policy: AskForApproval::OnRequest,
+            tool_filter: ToolFilter::default(),
+        }
+    }
+
+    #[test]
+    fn diff_applies_new_values_and_reverts_to_defaults() {
+        let deep = ModeOverrides {
+            effort: Some("high".to_string()),
+            sandbox: Some("read-only".to_string()),
+            tools: Some(ToolFilter {
+                include: Vec::new(),
+                exclude: vec!["apply_patch".to_string()],
+            }),
+            ..Default::default()
+        };
+        let applied = OverrideFields::diff(&ModeOverrides::default(), &deep, Some(&defaults()));
+        assert_eq!(
+            applied,
+            OverrideFields {
+                model: None,
+                effort: Some(Some(ReasoningEffortConfig::High)),
+                sandbox_policy: Some(SandboxPolicy::new_read_only_policy()),
+                approval_policy: None,
+                tool_filter: deep.tools.clone(),
+            }
+        );
+
+        let reverted = OverrideFields::diff(&deep, &ModeOverrides::default(), Some(&defaults()));
+        assert_eq!(
+            reverted,
+            OverrideFields {
+                model: None,
+                effort: Some(None),
+                sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+                approval_policy: None,
+                tool_filter: Some(ToolFilter::default()),
+            }
+        );
+
+        assert!(OverrideFields::diff(&deep, &deep, Some(&defaults())).is_empty());
+        assert!(OverrideFields::diff(&deep, &ModeOverrides::default(), None).is_empty());
+    }
+
+    #[test]
+    fn switching_to_read_only_keeps_deny_read_paths() {
+        let deny = vec![std::path::PathBuf::from("/home/me/.ssh")];
+        let defaults = SessionDefaults {
+            sandbox_policy: SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![],
+                network_access: false,
+                network_allowlist: vec![],
+                exclude_tmpdir_env_var: false,
+                exclude_slash_tmp: false,
+                readable_roots: vec![],
+                deny_read_paths: deny.clone(),
+            },
+            ..defaults()
+        };
+        let read_only = ModeOverrides {
+            sandbox: Some("read-only".to_string()),
+            ..Default::default()
+        };
+        let applied = OverrideFields::diff(&ModeOverrides::default(), &read_only, Some(&defaults));
+        assert_eq!(
+            applied.sandbox_policy,
+            Some(SandboxPolicy::ReadOnly {
+                readable_roots: vec![],
+                deny_read_paths: deny,
+            })
+        );
+    }
+
+    #[test]
+    fn mode_state_item_round_trips_and_sanitizes_against_current_defs() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path().join(".codex/modes");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("a.md"),
+            "---\nvariables:\n  - name: focus\n  - name: token\n    type: secret\n---\n{{focus}} {{token}}",
+        )
+        .unwrap();
+
+        let mut state = PersistentModeState::default();
+        for id in ["/a", "/gone"] {
+            state.enabled.insert(id.to_string());
+            state.enable_order.push(id.to_string());
+        }
+        let mut vars = IndexMap::new();
+        vars.insert("focus".to_string(), Some("tests".to_string()));
+        vars.insert("token".to_string(), Some("hunter2".to_string()));
+        vars.insert("stale".to_string(), Some("x".to_string()));
+        state.var_values.insert("/a".to_string(), vars);
+
+        let defs = codex_modes::scan_modes(project.path(), None).defs;
+        let item = state.to_item(&defs);
+        assert_eq!(item.enabled, vec!["/a".to_string(), "/gone".to_string()]);
+        // secret 与未声明变量不落盘
+        assert_eq!(
+            item.variables["/a"].keys().collect::<Vec<_>>(),
+            vec!["focus"]
+        );
+        let restored = PersistentModeState::from_item(item.clone());
+        assert_eq!(restored.to_item(&defs), item);
+
+        let sanitized = state.sanitize(&defs);
+        assert_eq!(sanitized.enable_order, vec!["/a".to_string()]);
+        assert_eq!(
+            sanitized.var_values["/a"].get("focus"),
+            Some(&Some("tests".to_string()))
+        );
+        assert_eq!(sanitized.var_values["/a"].get("stale"), None);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -10,6 +11,9 @@ use codex_core::ConversationsPage;
 use codex_core::Cursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::RolloutRecorder;
+use codex_core::SessionMetadata;
+use codex_core::load_session_metadata;
+use codex_core::normalize_tag;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -23,6 +27,7 @@ use ratatui::text::Span;
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::warn;
 use unicode_width::UnicodeWidthStr;
 
 use crate::key_hint;
@@ -30,6 +35,7 @@ use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
 use crate::tui::Tui;
 use crate::tui::TuiEvent;
+use codex_protocol::ConversationId;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::InputMessageKind;
@@ -37,6 +43,7 @@ use codex_protocol::protocol::USER_MESSAGE_BEGIN;
 
 const PAGE_SIZE: usize = 25;
 const LOAD_NEAR_THRESHOLD: usize = 5;
+const FULL_TEXT_RESULT_LIMIT: usize = 100;
 
 #[derive(Debug, Clone)]
 pub enum ResumeSelection {
@@ -55,12 +62,25 @@ struct PageLoadRequest {
 
 type PageLoader = Arc<dyn Fn(PageLoadRequest) + Send + Sync>;
 
+#[derive(Clone)]
+struct FullTextRequest {
+    codex_home: PathBuf,
+    query: String,
+}
+
+type FullTextLoader = Arc<dyn Fn(FullTextRequest) + Send + Sync>;
+
 enum BackgroundEvent {
     PageLoaded {
         request_token: usize,
         search_token: Option<usize>,
         page: std::io::Result<ConversationsPage>,
     },
+    /// Sessions whose recorded messages, commands or file paths match `query`.
+    FullTextResults {
+        query: String,
+        page: std::io::Result<ConversationsPage>,
+    },
 }
 
 /// Interactive session picker that lists recorded rollout files with simple
@@ -89,11 +109,47 @@ pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<Resum
         });
     });
 
+    let search_tx = bg_tx.clone();
+    let full_text_loader: FullTextLoader = Arc::new(move |request: FullTextRequest| {
+        let tx = search_tx.clone();
+        tokio::spawn(async move {
+            let (tags, text) = parse_query(&request.query);
+            let page = match (text.trim(), tags.first()) {
+                ("", Some(tag)) => {
+                    RolloutRecorder::list_tagged_conversations(
+                        &request.codex_home,
+                        tag,
+                        FULL_TEXT_RESULT_LIMIT,
+                        None,
+                        INTERACTIVE_SESSION_SOURCES,
+                    )
+                    .await
+                }
+                (text, tag) => {
+                    RolloutRecorder::search_conversations(
+                        &request.codex_home,
+                        text,
+                        tag.map(String::as_str),
+                        FULL_TEXT_RESULT_LIMIT,
+                        None,
+                        INTERACTIVE_SESSION_SOURCES,
+                    )
+                    .await
+                }
+            };
+            let _ = tx.send(BackgroundEvent::FullTextResults {
+                query: request.query,
+                page,
+            });
+        });
+    });
+
     let mut state = PickerState::new(
         codex_home.to_path_buf(),
         alt.tui.frame_requester(),
         page_loader,
-    );
+    )
+    .with_full_text_loader(full_text_loader);
     state.load_initial_page().await?;
     state.request_frame();
 
@@ -167,6 +223,12 @@ struct PickerState {
     next_search_token: usize,
     page_loader: PageLoader,
     view_rows: Option<usize>,
+    full_text_loader: Option<FullTextLoader>,
+    /// Paths of sessions whose full text matches the current query.
+    full_text_matches: HashSet<PathBuf>,
+    full_text_pending: bool,
+    /// Titles, tags and pin state of sessions, by conversation id.
+    metadata: HashMap<ConversationId, SessionMetadata>,
 }
 
 struct PaginationState {
@@ -221,11 +283,31 @@ impl SearchState {
 #[derive(Clone)]
 struct Row {
     path: PathBuf,
+    id: Option<ConversationId>,
     preview: String,
+    title: Option<String>,
+    tags: Vec<String>,
+    pinned: bool,
     created_at: Option<DateTime<Utc>>,
     updated_at: Option<DateTime<Utc>>,
 }
 
+impl Row {
+    /// The title if the session has one, otherwise the first message.
+    fn name(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.preview)
+    }
+
+    fn with_metadata(mut self, metadata: &HashMap<ConversationId, SessionMetadata>) -> Self {
+        if let Some(metadata) = self.id.and_then(|id| metadata.get(&id)) {
+            self.title = metadata.title.clone();
+            self.tags = metadata.tags.clone();
+            self.pinned = metadata.pinned;
+        }
+        self
+    }
+}
+
 impl PickerState {
     fn new(codex_home: PathBuf, requester: FrameRequester, page_loader: PageLoader) -> Self {
         Self {
@@ -248,9 +330,18 @@ impl PickerState {
             next_search_token: 0,
             page_loader,
             view_rows: None,
+            full_text_loader: None,
+            full_text_matches: HashSet::new(),
+            full_text_pending: false,
+            metadata: HashMap::new(),
         }
     }
 
+    fn with_full_text_loader(mut self, loader: FullTextLoader) -> Self {
+        self.full_text_loader = Some(loader);
+        self
+    }
+
     fn request_frame(&self) {
         self.requester.schedule_frame();
     }
@@ -326,6 +417,13 @@ impl PickerState {
     }
 
     async fn load_initial_page(&mut self) -> Result<()> {
+        // Without metadata, sessions are still listed by their first message.
+        self.metadata = load_session_metadata(&self.codex_home)
+            .await
+            .unwrap_or_else(|err| {
+                warn!("failed to load session metadata: {err}");
+                HashMap::new()
+            });
         let page = RolloutRecorder::list_conversations(
             &self.codex_home,
             PAGE_SIZE,
@@ -363,6 +461,19 @@ impl PickerState {
                 let completed_token = pending.search_token.or(search_token);
                 self.continue_search_if_token_matches(completed_token);
             }
+            BackgroundEvent::FullTextResults { query, page } => {
+                if query != self.query {
+                    return Ok(());
+                }
+                self.full_text_pending = false;
+                match page {
+                    Ok(page) => self.ingest_full_text_results(page),
+                    Err(err) => {
+                        warn!("full-text session search failed: {err}");
+                        self.request_frame();
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -391,22 +502,51 @@ impl PickerState {
         let rows = rows_from_items(page.items);
         for row in rows {
             if self.seen_paths.insert(row.path.clone()) {
-                self.all_rows.push(row);
+                self.all_rows.push(row.with_metadata(&self.metadata));
             }
         }
 
         self.apply_filter();
     }
 
+    fn ingest_full_text_results(&mut self, page: ConversationsPage) {
+        let mut inserted = false;
+        for row in rows_from_items(page.items) {
+            self.full_text_matches.insert(row.path.clone());
+            if self.seen_paths.insert(row.path.clone()) {
+                self.all_rows.push(row.with_metadata(&self.metadata));
+                inserted = true;
+            }
+        }
+        if inserted {
+            // Matches can be older than anything paged in so far.
+            self.all_rows
+                .sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        }
+        self.apply_filter();
+        if !self.filtered_rows.is_empty() {
+            self.search_state = SearchState::Idle;
+        }
+    }
+
     fn apply_filter(&mut self) {
         if self.query.is_empty() {
             self.filtered_rows = self.all_rows.clone();
         } else {
-            let q = self.query.to_lowercase();
+            let (tags, text) = parse_query(&self.query);
+            let q = text.to_lowercase();
             self.filtered_rows = self
                 .all_rows
                 .iter()
-                .filter(|r| r.preview.to_lowercase().contains(&q))
+                .filter(|r| {
+                    tags.iter().all(|tag| r.tags.contains(tag))
+                        && (q.trim().is_empty()
+                            || r.preview.to_lowercase().contains(&q)
+                            || r.title
+                                .as_ref()
+                                .is_some_and(|title| title.to_lowercase().contains(&q))
+                            || self.full_text_matches.contains(&r.path))
+                })
                 .cloned()
                 .collect();
         }
@@ -426,7 +566,9 @@ impl PickerState {
         }
         self.query = new_query;
         self.selected = 0;
+        self.full_text_matches.clear();
         self.apply_filter();
+        self.start_full_text_search();
         if self.query.is_empty() {
             self.search_state = SearchState::Idle;
             return;
@@ -444,6 +586,21 @@ impl PickerState {
         self.load_more_if_needed(LoadTrigger::Search { token });
     }
 
+    fn start_full_text_search(&mut self) {
+        self.full_text_pending = false;
+        let Some(loader) = self.full_text_loader.as_ref() else {
+            return;
+        };
+        if self.query.trim().is_empty() {
+            return;
+        }
+        self.full_text_pending = true;
+        loader(FullTextRequest {
+            codex_home: self.codex_home.clone(),
+            query: self.query.clone(),
+        });
+    }
+
     fn continue_search_if_needed(&mut self) {
         let Some(token) = self.search_state.active_token() else {
             return;
@@ -570,6 +727,20 @@ impl PickerState {
     }
 }
 
+/// Splits a picker query into `#tag` filters and the remaining text.
+fn parse_query(query: &str) -> (Vec<String>, String) {
+    let mut tags = Vec::new();
+    let mut text = Vec::new();
+    for word in query.split(' ') {
+        if word.starts_with('#') {
+            tags.extend(normalize_tag(word));
+        } else {
+            text.push(word);
+        }
+    }
+    (tags, text.join(" "))
+}
+
 fn rows_from_items(items: Vec<ConversationItem>) -> Vec<Row> {
     items.into_iter().map(|item| head_to_row(&item)).collect()
 }
@@ -591,9 +762,20 @@ fn head_to_row(item: &ConversationItem) -> Row {
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| String::from("(no message yet)"));
 
+    let id = item
+        .head
+        .first()
+        .and_then(|meta| meta.get("id"))
+        .and_then(|id| id.as_str())
+        .and_then(|id| ConversationId::from_string(id).ok());
+
     Row {
         path: item.path.clone(),
+        id,
         preview,
+        title: None,
+        tags: Vec::new(),
+        pinned: false,
         created_at,
         updated_at,
     }
@@ -673,7 +855,7 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
 
         // Search line
         let q = if state.query.is_empty() {
-            "Type to search".dim().to_string()
+            "Type to search, #tag to filter by tag".dim().to_string()
         } else {
             format!("Search: {}", state.query)
         };
@@ -762,7 +944,12 @@ fn render_list(
         if add_leading_gap {
             preview_width = preview_width.saturating_sub(2);
         }
-        let preview = truncate_text(&row.preview, preview_width);
+        let tags: String = row.tags.iter().map(|tag| format!("  #{tag}")).collect();
+        let pin = if row.pinned { "* " } else { "" };
+        let name_width = preview_width
+            .saturating_sub(UnicodeWidthStr::width(tags.as_str()))
+            .saturating_sub(pin.len());
+        let preview = truncate_text(row.name(), name_width);
         let mut spans: Vec<Span> = vec![marker];
         if let Some(created) = created_span {
             spans.push(created);
@@ -775,7 +962,13 @@ fn render_list(
         if add_leading_gap {
             spans.push("  ".into());
         }
+        if row.pinned {
+            spans.push(pin.cyan());
+        }
         spans.push(preview.into());
+        if !tags.is_empty() {
+            spans.push(Span::from(tags).dim());
+        }
 
         let line: Line = spans.into();
         let rect = Rect::new(area.x, y, area.width, 1);
@@ -793,6 +986,7 @@ fn render_list(
 fn render_empty_state_line(state: &PickerState) -> Line<'static> {
     if !state.query.is_empty() {
         if state.search_state.is_active()
+            || state.full_text_pending
             || (state.pagination.loading.is_pending() && state.pagination.next_cursor.is_some())
         {
             return vec!["Searching…".italic().dim()].into();
@@ -1086,19 +1280,31 @@ mod tests {
         let rows = vec![
             Row {
                 path: PathBuf::from("/tmp/a.jsonl"),
+                id: None,
                 preview: String::from("Fix resume picker timestamps"),
+                title: None,
+                tags: Vec::new(),
+                pinned: false,
                 created_at: Some(now - Duration::minutes(16)),
                 updated_at: Some(now - Duration::seconds(42)),
             },
             Row {
                 path: PathBuf::from("/tmp/b.jsonl"),
+                id: None,
                 preview: String::from("Investigate lazy pagination cap"),
+                title: None,
+                tags: Vec::new(),
+                pinned: false,
                 created_at: Some(now - Duration::hours(1)),
                 updated_at: Some(now - Duration::minutes(35)),
             },
             Row {
                 path: PathBuf::from("/tmp/c.jsonl"),
+                id: None,
                 preview: String::from("Explain the codebase"),
+                title: None,
+                tags: Vec::new(),
+                pinned: false,
                 created_at: Some(now - Duration::hours(2)),
                 updated_at: Some(now - Duration::hours(2)),
             },
@@ -1404,4 +1610,125 @@ mod tests {
         assert!(!state.search_state.is_active());
         assert!(state.pagination.reached_scan_cap);
     }
+
+    #[test]
+    fn full_text_results_extend_query_matches() {
+        let loader: PageLoader = Arc::new(|_| {});
+        let recorded_queries: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let query_sink = recorded_queries.clone();
+        let full_text_loader: FullTextLoader = Arc::new(move |req: FullTextRequest| {
+            query_sink.lock().unwrap().push(req.query);
+        });
+
+        let mut state =
+            PickerState::new(PathBuf::from("/tmp"), FrameRequester::test_dummy(), loader)
+                .with_full_text_loader(full_text_loader);
+        state.reset_pagination();
+        state.ingest_page(page(
+            vec![
+                make_item("/tmp/new.jsonl", "2025-01-03T00:00:00Z", "fix login"),
+                make_item("/tmp/mid.jsonl", "2025-01-02T00:00:00Z", "update docs"),
+            ],
+            None,
+            2,
+            false,
+        ));
+
+        state.set_query("cargo".to_string());
+        assert_eq!(*recorded_queries.lock().unwrap(), vec!["cargo".to_string()]);
+        assert!(state.filtered_rows.is_empty());
+        assert!(state.full_text_pending);
+
+        // Results for an outdated query are ignored.
+        state
+            .handle_background_event(BackgroundEvent::FullTextResults {
+                query: "carg".to_string(),
+                page: Ok(page(
+                    vec![make_item(
+                        "/tmp/mid.jsonl",
+                        "2025-01-02T00:00:00Z",
+                        "update docs",
+                    )],
+                    None,
+                    1,
+                    false,
+                )),
+            })
+            .unwrap();
+        assert!(state.filtered_rows.is_empty());
+
+        state
+            .handle_background_event(BackgroundEvent::FullTextResults {
+                query: "cargo".to_string(),
+                page: Ok(page(
+                    vec![
+                        make_item("/tmp/mid.jsonl", "2025-01-02T00:00:00Z", "update docs"),
+                        make_item("/tmp/old.jsonl", "2025-01-01T00:00:00Z", "bump deps"),
+                    ],
+                    None,
+                    2,
+                    false,
+                )),
+            })
+            .unwrap();
+        assert!(!state.full_text_pending);
+        let paths: Vec<_> = state
+            .filtered_rows
+            .iter()
+            .map(|row| row.path.clone())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/tmp/mid.jsonl"),
+                PathBuf::from("/tmp/old.jsonl")
+            ]
+        );
+
+        // Clearing the query shows every row, including the one found by search.
+        state.set_query(String::new());
+        assert_eq!(state.filtered_rows.len(), 3);
+        assert_eq!(recorded_queries.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn tag_queries_filter_rows_by_metadata() {
+        let loader: PageLoader = Arc::new(|_| {});
+        let mut state =
+            PickerState::new(PathBuf::from("/tmp"), FrameRequester::test_dummy(), loader);
+        let tagged_id =
+            ConversationId::from_string("00000000-0000-0000-0000-000000000001").expect("valid id");
+        state.metadata.insert(
+            tagged_id,
+            SessionMetadata {
+                title: Some("Login rework".to_string()),
+                tags: vec!["auth".to_string()],
+                pinned: true,
+            },
+        );
+        let mut tagged = make_item("/tmp/tagged.jsonl", "2025-01-02T00:00:00Z", "fix login");
+        tagged.head[0]["id"] = json!(tagged_id.to_string());
+        let other = make_item("/tmp/other.jsonl", "2025-01-01T00:00:00Z", "login docs");
+        state.reset_pagination();
+        state.ingest_page(page(vec![tagged, other], None, 2, false));
+
+        assert_eq!(state.all_rows[0].name(), "Login rework");
+        assert!(state.all_rows[0].pinned);
+        assert_eq!(state.all_rows[1].name(), "login docs");
+
+        state.set_query("#Auth".to_string());
+        let paths: Vec<_> = state
+            .filtered_rows
+            .iter()
+            .map(|row| row.path.clone())
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("/tmp/tagged.jsonl")]);
+
+        // Titles are searched along with the first message.
+        state.set_query("rework".to_string());
+        assert_eq!(state.filtered_rows.len(), 1);
+
+        state.set_query("#auth docs".to_string());
+        assert!(state.filtered_rows.is_empty());
+    }
 }
@@ -22,6 +22,9 @@ pub enum SlashCommand {
     Diff,
     Mention,
     Status,
+    Rename,
+    Tag,
+    Pin,
     Mcp,
     Logout,
     Quit,
@@ -42,6 +45,9 @@ impl SlashCommand {
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
+            SlashCommand::Rename => "give this session a title",
+            SlashCommand::Tag => "tag this session (prefix a tag with - to remove it)",
+            SlashCommand::Pin => "pin or unpin this session",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
             SlashCommand::Mcp => "list configured MCP tools",
@@ -71,6 +77,9 @@ impl SlashCommand {
             SlashCommand::Diff
             | SlashCommand::Mention
             | SlashCommand::Status
+            | SlashCommand::Rename
+            | SlashCommand::Tag
+            | SlashCommand::Pin
             | SlashCommand::Mcp
             | SlashCommand::Quit => true,
 
@@ -4,6 +4,7 @@ use crate::history_cell::PlainHistoryCell;
 use crate::history_cell::with_border_with_inner_width;
 use crate::version::CODEX_CLI_VERSION;
 use codex_common::create_config_summary_entries;
+use codex_common::summarize_read_access;
 use codex_core::config::Config;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TokenUsage;
@@ -21,6 +22,7 @@ use super::format::truncate_line_to_width;
 use super::helpers::compose_account_display;
 use super::helpers::compose_agents_summary;
 use super::helpers::compose_model_display;
+use super::helpers::compose_tool_filter_summary;
 use super::helpers::format_directory_display;
 use super::helpers::format_tokens_compact;
 use super::rate_limits::RateLimitSnapshotDisplay;
@@ -51,6 +53,7 @@ struct StatusHistoryCell {
     directory: PathBuf,
     approval: String,
     sandbox: String,
+    tools: Option<String>,
     agents_summary: String,
     account: Option<StatusAccountDisplay>,
     session_id: Option<String>,
@@ -86,11 +89,15 @@ impl StatusHistoryCell {
             .find(|(k, _)| *k == "approval")
             .map(|(_, v)| v.clone())
             .unwrap_or_else(|| "<unknown>".to_string());
-        let sandbox = match &config.sandbox_policy {
+        let mut sandbox = match &config.sandbox_policy {
             SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-            SandboxPolicy::ReadOnly => "read-only".to_string(),
+            SandboxPolicy::ReadOnly { .. } => "read-only".to_string(),
             SandboxPolicy::WorkspaceWrite { .. } => "workspace-write".to_string(),
         };
+        if let Some(read_access) = summarize_read_access(&config.sandbox_policy) {
+            sandbox.push_str(&format!(" ({read_access})"));
+        }
+        let tools = compose_tool_filter_summary(config);
         let agents_summary = compose_agents_summary(config);
         let account = compose_account_display(config);
         let session_id = session_id.as_ref().map(std::string::ToString::to_string);
@@ -116,6 +123,7 @@ impl StatusHistoryCell {
             directory: config.cwd.clone(),
             approval,
             sandbox,
+            tools,
             agents_summary,
             account,
             session_id,
@@ -263,6 +271,9 @@ impl HistoryCell for StatusHistoryCell {
                 .collect();
         let mut seen: BTreeSet<String> = labels.iter().cloned().collect();
 
+        if self.tools.is_some() {
+            push_label(&mut labels, &mut seen, "Tools");
+        }
         if account_value.is_some() {
             push_label(&mut labels, &mut seen, "Account");
         }
@@ -291,6 +302,9 @@ impl HistoryCell for StatusHistoryCell {
         lines.push(formatter.line("Directory", vec![Span::from(directory_value)]));
         lines.push(formatter.line("Approval", vec![Span::from(self.approval.clone())]));
         lines.push(formatter.line("Sandbox", vec![Span::from(self.sandbox.clone())]));
+        if let Some(tools) = self.tools.as_ref() {
+            lines.push(formatter.line("Tools", vec![Span::from(tools.clone())]));
+        }
         lines.push(formatter.line("Agents.md", vec![Span::from(self.agents_summary.clone())]));
 
         if let Some(account_value) = account_value {
@@ -103,6 +103,22 @@ pub(crate) fn compose_account_display(config: &Config) -> Option<StatusAccountDi
     None
 }
 
+/// Summarizes `tool_filter`, or `None` when every tool is exposed.
+pub(crate) fn compose_tool_filter_summary(config: &Config) -> Option<String> {
+    let filter = &config.tool_filter;
+    if filter.is_empty() {
+        return None;
+    }
+    let mut parts = Vec::new();
+    if !filter.include.is_empty() {
+        parts.push(format!("only {}", filter.include.join(", ")));
+    }
+    if !filter.exclude.is_empty() {
+        parts.push(format!("hiding {}", filter.exclude.join(", ")));
+    }
+    Some(parts.join("; "))
+}
+
 pub(crate) fn format_tokens_compact(value: u64) -> String {
     if value == 0 {
         return "0".to_string();
@@ -11,6 +11,7 @@ use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TokenUsage;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::config_types::ToolFilter;
 use insta::assert_snapshot;
 use ratatui::prelude::*;
 use std::path::PathBuf;
@@ -71,8 +72,11 @@ fn status_snapshot_includes_reasoning_details() {
     config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: Vec::new(),
         network_access: false,
+        network_allowlist: vec![],
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
+        readable_roots: vec![],
+        deny_read_paths: vec![],
     };
 
     config.cwd = PathBuf::from("/workspace/tests");
@@ -179,6 +183,52 @@ fn status_card_token_usage_excludes_cached_tokens() {
     );
 }
 
+#[test]
+fn status_card_shows_read_restrictions() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.model = "gpt-5-codex".to_string();
+    config.cwd = PathBuf::from("/workspace/tests");
+    config.sandbox_policy = SandboxPolicy::ReadOnly {
+        readable_roots: vec![PathBuf::from("/srv/data")],
+        deny_read_paths: vec![PathBuf::from("/home/dev/.ssh")],
+    };
+
+    let usage = TokenUsage::default();
+    let composite = new_status_output(&config, &usage, Some(&usage), &None, None);
+    let rendered = render_lines(&composite.display_lines(160));
+
+    assert!(
+        rendered
+            .iter()
+            .any(|line| line
+                .contains("read-only (read: workdir, /srv/data; deny read: /home/dev/.ssh)")),
+        "read restrictions should be displayed, got: {rendered:?}"
+    );
+}
+
+#[test]
+fn status_card_shows_tool_filter() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.model = "gpt-5-codex".to_string();
+    config.cwd = PathBuf::from("/workspace/tests");
+    config.tool_filter = ToolFilter {
+        include: vec!["shell".to_string(), "github/*".to_string()],
+        exclude: vec!["github/delete_*".to_string()],
+    };
+
+    let usage = TokenUsage::default();
+    let composite = new_status_output(&config, &usage, Some(&usage), &None, None);
+    let rendered = render_lines(&composite.display_lines(160));
+
+    assert!(
+        rendered.iter().any(|line| line.contains("Tools:")
+            && line.contains("only shell, github/*; hiding github/delete_*")),
+        "tool filter should be displayed, got: {rendered:?}"
+    );
+}
+
 #[test]
 fn status_snapshot_truncates_in_narrow_terminal() {
     let temp_home = TempDir::new().expect("temp home");